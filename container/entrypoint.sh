@@ -20,27 +20,31 @@ if [ "$(id -u)" = "0" ]; then
         -exec chown -R $USER_ID:$GROUP_ID {} \; 2>/dev/null || true
     
     # Ensure home directory itself has correct ownership
-    chown $USER_ID:$GROUP_ID /home/developer
+    # (fails harmlessly when the root filesystem is read-only)
+    chown $USER_ID:$GROUP_ID /home/developer 2>/dev/null || true
     
     # Setup Claude Code authentication using the refresh script
     /usr/local/bin/refresh-claude-auth.sh || echo "   (This is normal if Claude Code isn't configured on the host)"
     
-    # Start docker daemon in background
-    dockerd &
-    
-    # Wait for docker to be ready
-    echo "Waiting for Docker daemon..."
-    timeout=30
-    while ! docker info >/dev/null 2>&1; do
-        sleep 1
-        timeout=$((timeout - 1))
-        if [ $timeout -eq 0 ]; then
-            echo "Docker daemon failed to start"
-            exit 1
-        fi
-    done
-    
-    echo "Docker daemon ready"
+    # Docker-in-Docker needs a privileged container (crowdcontrol new --privileged)
+    if [ "$CROWDCONTROL_PRIVILEGED" = "1" ]; then
+        # Start docker daemon in background
+        dockerd &
+        
+        # Wait for docker to be ready
+        echo "Waiting for Docker daemon..."
+        timeout=30
+        while ! docker info >/dev/null 2>&1; do
+            sleep 1
+            timeout=$((timeout - 1))
+            if [ $timeout -eq 0 ]; then
+                echo "Docker daemon failed to start"
+                exit 1
+            fi
+        done
+        
+        echo "Docker daemon ready"
+    fi
     
     # Switch to developer user for the rest of the script
     exec su developer "$0" "$@"
@@ -48,8 +52,13 @@ fi
 
 # Now running as developer user
 
-# Find the repository directory (should be only subdirectory in /workspace)
-REPO_DIR=$(find /workspace -maxdepth 1 -type d ! -path /workspace | head -1)
+# Find the repository directory: /workspace itself with the flat layout,
+# otherwise its only non-hidden subdirectory (nested layout)
+if [ -d /workspace/.git ]; then
+    REPO_DIR=/workspace
+else
+    REPO_DIR=$(find /workspace -mindepth 1 -maxdepth 1 -type d ! -name '.*' | head -1)
+fi
 
 if [ -z "$REPO_DIR" ]; then
     echo "No repository directory found in /workspace"
@@ -69,8 +78,11 @@ export CROWDCONTROL_WORKSPACE="/workspace"
 # Set Claude config directory to home
 export CLAUDE_CONFIG_DIR="/home/developer"
 
-# Run repository-specific setup if it exists and hasn't been run
-if [ -f ".crowdcontrol/setup.sh" ] && [ ! -f ".crowdcontrol/.setup-complete" ]; then
+# Run repository-specific setup if it exists and hasn't been run. A read-only
+# workspace (new --readonly-workspace) can't record that setup ran, so it's skipped.
+if [ ! -w . ]; then
+    echo "Workspace is read-only, skipping repository setup"
+elif [ -f ".crowdcontrol/setup.sh" ] && [ ! -f ".crowdcontrol/.setup-complete" ]; then
     echo "Running repository setup for $REPO_NAME..."
     chmod +x .crowdcontrol/setup.sh
     ./.crowdcontrol/setup.sh
@@ -86,7 +98,7 @@ fi
 # Run repository-specific start script if it exists
 if [ -f ".crowdcontrol/start.sh" ]; then
     echo "Running repository start script for $REPO_NAME..."
-    chmod +x .crowdcontrol/start.sh
+    [ -x .crowdcontrol/start.sh ] || chmod +x .crowdcontrol/start.sh
     ./.crowdcontrol/start.sh
     if [ $? -eq 0 ]; then
         echo "Repository services started successfully"
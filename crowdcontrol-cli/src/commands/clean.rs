@@ -0,0 +1,80 @@
+use anyhow::{anyhow, Result};
+
+use crate::commands::switch::{git, running_container_id};
+use crate::commands::CleanArgs;
+use crate::utils::*;
+use crowdcontrol_core::load_agent_metadata;
+use crowdcontrol_core::{Config, DockerClient};
+
+pub async fn execute(config: Config, args: CleanArgs) -> Result<()> {
+    let agent = load_agent_metadata(&config, &args.name)?;
+    let docker = DockerClient::new(config.clone())?;
+    let container_id = running_container_id(&docker, &agent).await?;
+    let repo_dir = agent.container_repo_dir();
+
+    // Untracked files are what clean removes; changes to tracked files are work in progress
+    if !args.force && !args.dry_run {
+        let status = git(
+            &docker,
+            &container_id,
+            &repo_dir,
+            &["status", "--porcelain", "--untracked-files=no"],
+        )
+        .await?;
+        if !status.success() {
+            return Err(anyhow!(
+                "Failed to read git status in agent '{}': {}",
+                args.name,
+                status.stderr.trim()
+            ));
+        }
+        if !status.stdout.trim().is_empty() {
+            return Err(anyhow!(
+                "Agent '{}' has staged or uncommitted changes. Commit or stash them first, or rerun with --force",
+                args.name
+            ));
+        }
+    }
+
+    let clean = git(&docker, &container_id, &repo_dir, &clean_args(&args)).await?;
+    if !clean.success() {
+        return Err(anyhow!(
+            "git clean failed in agent '{}': {}",
+            args.name,
+            clean.stderr.trim()
+        ));
+    }
+
+    // git prints one "Removing ..." / "Would remove ..." line per path
+    let removed = clean.stdout.lines().count();
+    if !is_quiet() {
+        print!("{}", clean.stdout);
+    }
+    if removed == 0 {
+        print_info(&format!("Nothing to clean in agent '{}'", args.name));
+    } else if args.dry_run {
+        print_info(&format!(
+            "{} path(s) would be removed; rerun without --dry-run to remove them",
+            removed
+        ));
+    } else {
+        print_success(&format!(
+            "Removed {} path(s) from agent '{}'",
+            removed, args.name
+        ));
+    }
+
+    Ok(())
+}
+
+/// Arguments for `git clean`. The workspace's .crowdcontrol directory is always
+/// kept, since agent metadata lives there and is normally untracked.
+fn clean_args(args: &CleanArgs) -> Vec<&'static str> {
+    let mut clean = vec!["clean", "-d"];
+    clean.push(if args.dry_run { "-n" } else { "-f" });
+    if args.include_ignored {
+        clean.push("-x");
+    }
+    clean.extend(["-e", "/.crowdcontrol/"]);
+    clean
+}
@@ -0,0 +1,72 @@
+use anyhow::{anyhow, Result};
+
+use crate::commands::{new, CloneArgs, NewArgs, NewOutputFormat};
+use crowdcontrol_core::docker::DEFAULT_CREATE_TIMEOUT_SECS;
+use crowdcontrol_core::{
+    load_agent_metadata, validate_agent_name, CancellationToken, Config, CrowdControlError,
+};
+
+pub async fn execute(config: Config, args: CloneArgs, cancel: CancellationToken) -> Result<()> {
+    validate_agent_name(&args.new_name)?;
+    if config.agent_workspace_path(&args.new_name).exists() {
+        return Err(CrowdControlError::AgentAlreadyExists(args.new_name.clone()).into());
+    }
+
+    let source = load_agent_metadata(&config, &args.source)?;
+
+    // An agent working on a mounted local checkout is cloned by mounting the same checkout
+    let mount = source.local_mount.is_some();
+    if mount && args.branch.is_some() {
+        return Err(anyhow!(
+            "Agent '{}' mounts a local checkout; --branch can't be used when cloning it",
+            args.source
+        ));
+    }
+    let repository = (!mount).then_some(source.repository);
+
+    // Only the definition is copied: the new agent gets a fresh workspace and container
+    let new_args = NewArgs {
+        name: args.new_name,
+        repository,
+        from_local: source.local_mount,
+        mount,
+        branch: args.branch.or(source.branch),
+        create_branch: None,
+        force: false,
+        skip_verification: false,
+        require_crowdcontrol: false,
+        workspace_only: false,
+        run_setup: false,
+        skip_setup: false,
+        ignore_setup_errors: false,
+        ignore_lifecycle_errors: false,
+        ssh_key: args.ssh_key,
+        ssh_accept_new: args.ssh_accept_new,
+        memory: source.memory,
+        memory_swap: source.memory_swap,
+        memory_reservation: source.memory_reservation,
+        cpus: source.cpus,
+        healthcheck: None,
+        build: false,
+        tag: source.tags,
+        network: source.network,
+        privileged: source.privileged,
+        cap_add: source.cap_add,
+        read_only: source.read_only,
+        readonly_workspace: source.readonly_workspace,
+        no_mount_claude: source.no_mount_claude,
+        platform: source.platform,
+        create_timeout: DEFAULT_CREATE_TIMEOUT_SECS,
+        image: source.image,
+        restart: source.restart_policy,
+        gpus: source.gpus,
+        volume: source.volumes,
+        env_passthrough: Vec::new(),
+        idle_timeout: source.idle_timeout,
+        depends_on: source.depends_on,
+        template: None,
+        format: NewOutputFormat::Text,
+    };
+
+    new::execute(config, new_args, cancel).await
+}
@@ -1,13 +1,81 @@
 use anyhow::Result;
-use clap::CommandFactory;
-use clap_complete::generate;
-use std::io;
+use clap::{Command, CommandFactory};
+use clap_complete::{generate, Shell};
+use std::io::{self, Write};
 
 use crate::commands::CompletionsArgs;
-use crowdcontrol_core::Config;
+use crowdcontrol_core::{list_all_agents, Config};
+
+/// Bash wrapper that completes agent names for the first positional argument
+/// of commands taking an agent, and defers to the generated completion otherwise
+const BASH_AGENT_COMPLETION: &str = r#"
+_crowdcontrol_agents() {
+    local cur="${COMP_WORDS[COMP_CWORD]}"
+    case "${COMP_WORDS[1]}" in
+        start|stop|connect|logs|remove|refresh)
+            if [[ ${COMP_CWORD} -eq 2 && "${cur}" != -* ]]; then
+                COMPREPLY=($(compgen -W "$(crowdcontrol __complete_agents 2>/dev/null)" -- "${cur}"))
+                return 0
+            fi
+            ;;
+    esac
+    _crowdcontrol "$@"
+}
+
+complete -F _crowdcontrol_agents -o bashdefault -o default crowdcontrol
+"#;
+
+/// Zsh equivalent of `BASH_AGENT_COMPLETION`
+const ZSH_AGENT_COMPLETION: &str = r#"
+_crowdcontrol_agents() {
+    if (( CURRENT == 3 )) && [[ ${words[2]} == (start|stop|connect|logs|remove|refresh) && ${words[CURRENT]} != -* ]]; then
+        local -a agents
+        agents=(${(f)"$(crowdcontrol __complete_agents 2>/dev/null)"})
+        compadd -a agents
+        return
+    fi
+    _crowdcontrol "$@"
+}
+
+compdef _crowdcontrol_agents crowdcontrol
+"#;
 
 pub async fn execute(_config: Config, args: CompletionsArgs) -> Result<()> {
-    let mut cmd = crate::Cli::command();
-    generate(args.shell, &mut cmd, "crowdcontrol", &mut io::stdout());
+    let mut cmd = completion_command();
+    let mut stdout = io::stdout();
+    generate(args.shell, &mut cmd, "crowdcontrol", &mut stdout);
+
+    // Agent names are only known at runtime, so hook them in for shells that support it
+    match args.shell {
+        Shell::Bash => stdout.write_all(BASH_AGENT_COMPLETION.as_bytes())?,
+        Shell::Zsh => stdout.write_all(ZSH_AGENT_COMPLETION.as_bytes())?,
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// The CLI without its hidden internal commands, which completions shouldn't offer.
+/// Their `__` names also trip up clap_complete's bash generator, which splits on `__`.
+fn completion_command() -> Command {
+    let cli = crate::Cli::command();
+    Command::new("crowdcontrol")
+        .version(env!("CARGO_PKG_VERSION"))
+        .args(cli.get_arguments().cloned())
+        .subcommands(
+            cli.get_subcommands()
+                .filter(|subcommand| !subcommand.is_hide_set())
+                .cloned(),
+        )
+}
+
+/// Print agent names one per line for shell completion.
+/// Never fails: an empty or unreadable workspaces directory simply yields no names.
+pub async fn complete_agents(config: Config) -> Result<()> {
+    if let Ok(agents) = list_all_agents(&config) {
+        for agent in agents {
+            println!("{}", agent);
+        }
+    }
     Ok(())
 }
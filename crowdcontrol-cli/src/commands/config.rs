@@ -0,0 +1,144 @@
+use anyhow::Result;
+use std::path::Path;
+
+use crate::commands::{ConfigAction, ConfigArgs, ConfigFormat};
+use crate::utils::*;
+use crowdcontrol_core::settings::{config_dir, resolve_config_files, setting_sources};
+use crowdcontrol_core::{load_templates, templates_path, Config, Template};
+
+/// `config_file` is the file given with `--config`, if any
+pub async fn execute(
+    config: Config,
+    args: ConfigArgs,
+    config_file: Option<&Path>,
+    cli_keys: &[&str],
+) -> Result<()> {
+    match args.action {
+        ConfigAction::Show { format } => show(&config, format, config_file, cli_keys),
+        ConfigAction::Path => path(config_file),
+        ConfigAction::Templates => templates(),
+    }
+}
+
+fn show(
+    config: &Config,
+    format: ConfigFormat,
+    config_file: Option<&Path>,
+    cli_keys: &[&str],
+) -> Result<()> {
+    let values = serde_json::to_value(config)?;
+    let sources = setting_sources(&resolve_config_files(config_file)?, cli_keys)?;
+
+    match format {
+        ConfigFormat::Json => {
+            let entries: serde_json::Map<String, serde_json::Value> = sources
+                .iter()
+                .map(|(key, source)| {
+                    let entry = serde_json::json!({
+                        "value": values[*key],
+                        "source": source.to_string(),
+                    });
+                    (key.to_string(), entry)
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&entries)?);
+        }
+        ConfigFormat::Toml => {
+            for (key, source) in &sources {
+                match &values[*key] {
+                    // TOML has no null; show unset options as comments
+                    serde_json::Value::Null => println!("# {} is not set  # {}", key, source),
+                    value => println!("{} = {}  # {}", key, toml::Value::try_from(value)?, source),
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn path(config_file: Option<&Path>) -> Result<()> {
+    // With --config, that file is the only one loaded
+    if let Some(file) = config_file {
+        println!("{}", file.display());
+        return Ok(());
+    }
+
+    let Some(dir) = config_dir() else {
+        print_warning("Could not determine the config directory");
+        return Ok(());
+    };
+
+    let files = resolve_config_files(None)?;
+    if files.is_empty() {
+        print_info(&format!("No config file found in {}", dir.display()));
+    }
+
+    // Highest precedence first
+    for file in files {
+        println!("{}", file.display());
+    }
+
+    Ok(())
+}
+
+fn templates() -> Result<()> {
+    let Some(path) = templates_path() else {
+        print_warning("Could not determine the config directory");
+        return Ok(());
+    };
+
+    let templates = load_templates(&path)?;
+    if templates.is_empty() {
+        print_info(&format!("No templates defined in {}", path.display()));
+        return Ok(());
+    }
+
+    let name_width = templates.keys().map(String::len).max().unwrap_or(0);
+    for (name, template) in &templates {
+        println!(
+            "{:<name_width$}  {}",
+            name,
+            describe_template(template),
+            name_width = name_width
+        );
+    }
+
+    Ok(())
+}
+
+/// One-line summary of the options a template sets; environment values are left out
+fn describe_template(template: &Template) -> String {
+    let mut parts = Vec::new();
+    let values = [
+        ("memory", &template.memory),
+        ("cpus", &template.cpus),
+        ("network", &template.network),
+        ("platform", &template.platform),
+        ("idle_timeout", &template.idle_timeout),
+    ];
+    for (key, value) in values {
+        if let Some(value) = value {
+            parts.push(format!("{}={}", key, value));
+        }
+    }
+    if !template.tags.is_empty() {
+        parts.push(format!("tags={}", template.tags.join(",")));
+    }
+    if !template.env.is_empty() {
+        let keys: Vec<&str> = template.env.keys().map(String::as_str).collect();
+        parts.push(format!("env={}", keys.join(",")));
+    }
+    if !template.env_passthrough.is_empty() {
+        parts.push(format!(
+            "env_passthrough={}",
+            template.env_passthrough.join(",")
+        ));
+    }
+
+    if parts.is_empty() {
+        "(no options)".to_string()
+    } else {
+        parts.join(" ")
+    }
+}
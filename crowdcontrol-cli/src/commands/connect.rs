@@ -1,43 +1,85 @@
 use anyhow::{anyhow, Result};
-use std::process::{Command, Stdio};
 
-use crate::commands::ConnectArgs;
+use crate::commands::start::start_with_dependencies;
+use crate::commands::{ConnectArgs, StartArgs};
 use crate::utils::*;
-use crowdcontrol_core::load_agent_metadata;
 use crowdcontrol_core::Config;
-use crowdcontrol_core::{AgentStatus, DockerClient};
-pub async fn execute(config: Config, args: ConnectArgs) -> Result<()> {
+use crowdcontrol_core::{load_agent_metadata, record_agent_activity};
+use crowdcontrol_core::{run_until_cancelled, CancellationToken};
+use crowdcontrol_core::{AgentManager, AgentStatus, CrowdControlError, SessionEnd};
+pub async fn execute(config: Config, args: ConnectArgs, cancel: CancellationToken) -> Result<()> {
     // Load agent metadata
     let agent = load_agent_metadata(&config, &args.name)?;
 
-    // Create Docker client
-    let docker = DockerClient::new(config.clone())?;
+    let manager = AgentManager::new(config.clone());
+    let docker = manager.docker()?;
 
     // Check if container is running (validates container_id and gets live status)
-    let status = agent.compute_live_status(&docker).await?;
-    if status != AgentStatus::Running {
-        return Err(anyhow!(
-            "Agent '{}' is not running. Start it with: crowdcontrol start {}",
-            args.name,
-            args.name
-        ));
+    let status = agent.compute_live_status(docker).await?;
+    if status == AgentStatus::Paused {
+        return Err(CrowdControlError::AgentPaused(args.name.clone()).into());
+    }
+    let auto_start = args.auto_start || config.connect_auto_start;
+    if auto_start && matches!(status, AgentStatus::Created | AgentStatus::Stopped) {
+        // Started like `start --wait`; the agent keeps running after the session ends
+        let start_args = StartArgs {
+            name: Some(args.name.clone()),
+            all: false,
+            tag: None,
+            network: None,
+            readonly_workspace: false,
+            wait: true,
+            timeout: AUTO_START_TIMEOUT_SECS,
+            ready_file: None,
+            ignore_lifecycle_errors: false,
+        };
+        start_with_dependencies(&manager, &agent, status, &start_args).await?;
+    } else if !status.is_running() {
+        return Err(CrowdControlError::not_running(&args.name).into());
     }
 
     // Get container name
-    let container_name = format!("crowdcontrol-{}", args.name);
+    let container_name = config.container_name(&args.name);
+
+    // Keep idle reaping away from agents that are in use
+    mark_active(&config, &args.name);
 
     // Prepare command
-    let default_command = vec!["claude", "--dangerously-skip-permissions"];
-    let command_parts: Vec<&str> = if let Some(cmd) = &args.command {
-        cmd.split_whitespace().collect()
-    } else {
-        default_command
-    };
+    let command = config.connect_command(args.command.as_deref());
+    let command_parts: Vec<&str> = command.iter().map(String::as_str).collect();
 
-    if args.detach {
+    if args.shell {
+        // Interactive shell through the Docker API, with the exec TTY sized to this terminal
+        print_info(&format!("Opening shell in agent '{}'...", args.name));
+        let session = docker.exec_in_container_as_user(
+            &container_name,
+            vec!["/bin/bash", "-l"],
+            true,
+            Some("developer"),
+            &args.env,
+            Some(&args.detach_keys),
+        );
+        let result = run_until_cancelled(&cancel, session).await;
+        mark_active(&config, &args.name);
+        match result {
+            Some(result) => {
+                if result? == SessionEnd::Detached {
+                    print_detached(&args.name);
+                }
+            }
+            None => print_detached(&args.name),
+        }
+    } else if args.detach {
         // Run in background
         docker
-            .exec_in_container_as_user(&container_name, command_parts, false, Some("developer"))
+            .exec_in_container_as_user(
+                &container_name,
+                command_parts,
+                false,
+                Some("developer"),
+                &args.env,
+                None,
+            )
             .await?;
         print_success(&format!(
             "Command started in background in agent '{}'",
@@ -47,26 +89,48 @@ pub async fn execute(config: Config, args: ConnectArgs) -> Result<()> {
         // Interactive connection
         print_info(&format!("Connecting to agent '{}'...", args.name));
 
-        // Use docker exec directly for better TTY handling
-        let mut cmd = Command::new("docker")
-            .arg("exec")
-            .arg("-it")
-            .arg("-u")
-            .arg("developer")
-            .arg(&container_name)
-            .args(&command_parts)
-            .stdin(Stdio::inherit())
-            .stdout(Stdio::inherit())
-            .stderr(Stdio::inherit())
-            .spawn()
-            .map_err(|e| anyhow!("Failed to connect to agent: {}", e))?;
-
-        let status = cmd.wait().map_err(|e| anyhow!("Connection error: {}", e))?;
-
-        if !status.success() {
-            return Err(anyhow!("Connection to agent terminated with error"));
+        // Attach through the Docker API so terminal resizes are forwarded to the session
+        let session = docker.exec_in_container_as_user(
+            &container_name,
+            command_parts,
+            true,
+            Some("developer"),
+            &args.env,
+            Some(&args.detach_keys),
+        );
+        let result = run_until_cancelled(&cancel, session).await;
+        mark_active(&config, &args.name);
+        match result {
+            Some(result) => {
+                let end = result
+                    .map_err(|e| anyhow!("Connection to agent terminated with error: {}", e))?;
+                if end == SessionEnd::Detached {
+                    print_detached(&args.name);
+                }
+            }
+            None => print_detached(&args.name),
         }
     }
 
     Ok(())
 }
+
+/// How long `--auto-start` waits for the started agent to be ready
+const AUTO_START_TIMEOUT_SECS: u64 = 60;
+
+/// Typing the detach keys, or dropping the exec session on Ctrl-C, detaches from it;
+/// the container keeps running
+fn print_detached(name: &str) {
+    eprintln!();
+    print_info(&format!(
+        "Detached from agent '{}'; it is still running",
+        name
+    ));
+}
+
+/// Record activity for idle reaping; failing to do so shouldn't block the session
+fn mark_active(config: &Config, name: &str) {
+    if let Err(e) = record_agent_activity(config, name) {
+        print_warning(&format!("Failed to record agent activity: {}", e));
+    }
+}
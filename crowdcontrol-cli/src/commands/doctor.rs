@@ -1,7 +1,10 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use clap::Args;
 use colored::Colorize;
-use crowdcontrol_core::{Config, StateInconsistency, StateValidator};
+use crowdcontrol_core::{
+    check_workspaces_dir, docker_unavailable_hint, Config, CrowdControlError, DockerClient,
+    EnvironmentCheck, RepairOptions, StateInconsistency, StateValidator,
+};
 
 #[derive(Args, Debug)]
 pub struct DoctorCommand {
@@ -9,16 +12,84 @@ pub struct DoctorCommand {
     #[arg(long)]
     repair: bool,
 
-    /// Show detailed information about each check
-    #[arg(long)]
-    verbose: bool,
+    /// When repairing, recreate missing containers instead of marking agents stopped
+    #[arg(long, requires = "repair")]
+    recreate: bool,
+
+    /// When repairing, remove stopped containers that have no agent metadata
+    #[arg(long, requires = "repair")]
+    prune_orphans: bool,
+
+    /// With --prune-orphans, also remove orphaned containers that are still running
+    #[arg(long, requires = "prune_orphans")]
+    force: bool,
+
+    /// Only run the given check
+    #[arg(long, value_enum, conflicts_with = "repair")]
+    check: Option<DoctorCheck>,
+}
+
+/// Checks that can be run on their own with `doctor --check`
+#[derive(clap::ValueEnum, Clone, Debug)]
+enum DoctorCheck {
+    /// Docker daemon connectivity, API version and image availability
+    Docker,
+    /// Workspaces directory existence, permissions and free disk space
+    Workspaces,
 }
 
 pub async fn execute(config: Config, cmd: DoctorCommand) -> Result<()> {
     println!("{}", "Running system diagnostics...".bold());
 
+    // Agents can't be checked without a usable workspaces directory, or without
+    // the daemon, so check both first
+    let workspace_checks = match cmd.check {
+        None | Some(DoctorCheck::Workspaces) => check_workspaces_dir(&config.workspaces_dir),
+        Some(DoctorCheck::Docker) => Vec::new(),
+    };
+    for check in &workspace_checks {
+        println!("{}", format_check(check));
+    }
+
+    let docker_checks = match cmd.check {
+        None | Some(DoctorCheck::Docker) => check_docker(&config).await,
+        Some(DoctorCheck::Workspaces) => Vec::new(),
+    };
+    for check in &docker_checks {
+        println!("{}", format_check(check));
+    }
+    if docker_checks
+        .iter()
+        .any(|check| check.name == "Docker daemon" && !check.passed)
+    {
+        return Err(CrowdControlError::DockerUnavailable(format!(
+            "Docker daemon is not reachable. {}",
+            docker_unavailable_hint()
+        ))
+        .into());
+    }
+
+    let failed = workspace_checks
+        .iter()
+        .chain(&docker_checks)
+        .filter(|check| !check.passed)
+        .count();
+    if cmd.check.is_some() {
+        if failed > 0 {
+            return Err(anyhow!("{} check(s) failed", failed));
+        }
+        return Ok(());
+    }
+    if workspace_checks.iter().any(|check| !check.passed) {
+        return Err(anyhow!(
+            "The workspaces directory {} can't be used; fix the failed check(s) above first",
+            config.workspaces_dir.display()
+        ));
+    }
+    println!();
+
     // Create state validator
-    let validator = StateValidator::new(config)?;
+    let validator = StateValidator::new(config.clone())?;
 
     // Check for inconsistencies
     let inconsistencies = validator.validate_all().await?;
@@ -42,13 +113,24 @@ pub async fn execute(config: Config, cmd: DoctorCommand) -> Result<()> {
     );
 
     for (i, issue) in inconsistencies.iter().enumerate() {
-        println!("\n{}. {}", i + 1, format_issue(issue, cmd.verbose));
+        println!(
+            "\n{}. {}",
+            i + 1,
+            format_issue(issue, &config, config.verbose > 0)
+        );
     }
 
     // Repair if requested
     if cmd.repair {
         println!("\n{}", "Attempting to repair issues...".bold());
-        validator.repair_inconsistencies(inconsistencies).await?;
+        let options = RepairOptions {
+            recreate_containers: cmd.recreate,
+            prune_orphans: cmd.prune_orphans,
+            force: cmd.force,
+        };
+        validator
+            .repair_inconsistencies(inconsistencies, &options)
+            .await?;
 
         // Re-validate to show current state
         println!("\n{}", "Re-validating system state...".bold());
@@ -67,7 +149,7 @@ pub async fn execute(config: Config, cmd: DoctorCommand) -> Result<()> {
             );
 
             for issue in remaining_issues {
-                println!("  • {}", format_issue(&issue, false));
+                println!("  • {}", format_issue(&issue, &config, false));
             }
         }
     } else {
@@ -75,12 +157,52 @@ pub async fn execute(config: Config, cmd: DoctorCommand) -> Result<()> {
             "\n{}",
             "Run with --repair to attempt automatic fixes".dimmed()
         );
+        if inconsistencies
+            .iter()
+            .any(|issue| matches!(issue, StateInconsistency::MissingContainer { .. }))
+        {
+            println!(
+                "{}",
+                "Add --recreate to create new containers for agents whose container is missing"
+                    .dimmed()
+            );
+        }
+        if inconsistencies
+            .iter()
+            .any(|issue| matches!(issue, StateInconsistency::OrphanedContainer { .. }))
+        {
+            println!(
+                "{}",
+                "Add --prune-orphans to remove containers that have no agent".dimmed()
+            );
+        }
     }
 
     Ok(())
 }
 
-fn format_issue(issue: &StateInconsistency, verbose: bool) -> String {
+/// Run the Docker preflight checks; a client that can't even be created fails the daemon check
+async fn check_docker(config: &Config) -> Vec<EnvironmentCheck> {
+    match DockerClient::new(config.clone()) {
+        Ok(docker) => docker.check_environment().await,
+        Err(e) => vec![EnvironmentCheck::fail(
+            "Docker daemon",
+            format!("not reachable: {:#}", e),
+        )],
+    }
+}
+
+fn format_check(check: &EnvironmentCheck) -> String {
+    if !check.passed {
+        check.to_string().red().to_string()
+    } else if check.warning {
+        check.to_string().yellow().to_string()
+    } else {
+        check.to_string().green().to_string()
+    }
+}
+
+fn format_issue(issue: &StateInconsistency, config: &Config, verbose: bool) -> String {
     use StateInconsistency::*;
 
     match issue {
@@ -96,13 +218,19 @@ fn format_issue(issue: &StateInconsistency, verbose: bool) -> String {
             }
         }
 
-        OrphanedContainer { container_name } => {
-            let msg = format!(
-                "Orphaned container 'crowdcontrol-{}' has no metadata",
-                container_name.red()
+        OrphanedContainer {
+            container_name,
+            tags,
+        } => {
+            let mut msg = format!(
+                "Orphaned container '{}' has no metadata",
+                config.container_name(container_name).red()
             );
+            if !tags.is_empty() {
+                msg.push_str(&format!(" (tags: {})", tags.join(", ")));
+            }
             if verbose {
-                format!("{}\n    A Docker container exists but there's no corresponding agent metadata.\n    This may happen if metadata was manually deleted or corrupted.", msg)
+                format!("{}\n    A Docker container exists but there's no corresponding agent metadata.\n    This may happen if metadata was manually deleted or corrupted.\n    Run with --repair --prune-orphans to remove it.", msg)
             } else {
                 msg
             }
@@ -110,11 +238,11 @@ fn format_issue(issue: &StateInconsistency, verbose: bool) -> String {
 
         MissingContainer { agent_name } => {
             let msg = format!(
-                "Agent '{}' marked as running but container doesn't exist",
+                "Agent '{}' references a container that doesn't exist",
                 agent_name.yellow()
             );
             if verbose {
-                format!("{}\n    The metadata indicates the agent is running, but no Docker container was found.\n    This can happen if Docker was restarted or the container was manually removed.", msg)
+                format!("{}\n    The metadata references a container, but no Docker container was found.\n    This can happen if Docker was restarted or the container was manually removed.\n    Run with --repair --recreate to create a new container from the agent's settings.", msg)
             } else {
                 msg
             }
@@ -0,0 +1,16 @@
+use anyhow::Result;
+
+use crate::commands::{ExportArgs, SpecFormat};
+use crowdcontrol_core::{load_agent_metadata, AgentSpec, Config};
+
+pub async fn execute(config: Config, args: ExportArgs) -> Result<()> {
+    let agent = load_agent_metadata(&config, &args.name)?;
+    let spec = AgentSpec::from_agent(&agent);
+
+    match args.format {
+        SpecFormat::Json => println!("{}", serde_json::to_string_pretty(&spec)?),
+        SpecFormat::Yaml => print!("{}", serde_yaml::to_string(&spec)?),
+    }
+
+    Ok(())
+}
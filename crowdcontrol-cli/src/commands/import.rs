@@ -0,0 +1,71 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::io::{self, Read};
+use std::path::Path;
+
+use crate::commands::{new, ImportArgs, NewArgs, NewOutputFormat};
+use crowdcontrol_core::docker::DEFAULT_CREATE_TIMEOUT_SECS;
+use crowdcontrol_core::{AgentSpec, CancellationToken, Config};
+
+pub async fn execute(config: Config, args: ImportArgs, cancel: CancellationToken) -> Result<()> {
+    let document = match args.file.as_deref() {
+        Some(path) if path != Path::new("-") => fs::read_to_string(path)
+            .with_context(|| format!("Failed to read agent spec: {:?}", path))?,
+        _ => {
+            let mut document = String::new();
+            io::stdin()
+                .read_to_string(&mut document)
+                .context("Failed to read agent spec from stdin")?;
+            document
+        }
+    };
+
+    let spec = AgentSpec::parse(&document)?;
+
+    // Recreate the agent exactly as `new` would, re-cloning the repository
+    let new_args = NewArgs {
+        name: args.name.unwrap_or(spec.name),
+        repository: Some(spec.repository),
+        from_local: None,
+        mount: false,
+        branch: spec.branch,
+        create_branch: None,
+        force: false,
+        skip_verification: false,
+        require_crowdcontrol: false,
+        workspace_only: false,
+        run_setup: false,
+        skip_setup: false,
+        ignore_setup_errors: false,
+        ignore_lifecycle_errors: false,
+        ssh_key: args.ssh_key,
+        ssh_accept_new: args.ssh_accept_new,
+        memory: spec.memory,
+        memory_swap: spec.memory_swap,
+        memory_reservation: spec.memory_reservation,
+        cpus: spec.cpus,
+        healthcheck: None,
+        build: false,
+        tag: spec.tags,
+        network: spec.network,
+        privileged: spec.privileged,
+        cap_add: spec.cap_add,
+        read_only: spec.read_only,
+        readonly_workspace: spec.readonly_workspace,
+        no_mount_claude: spec.no_mount_claude,
+        platform: spec.platform,
+        create_timeout: DEFAULT_CREATE_TIMEOUT_SECS,
+        image: spec.image,
+        restart: spec.restart_policy,
+        gpus: spec.gpus,
+        // Host paths don't travel between machines
+        volume: Vec::new(),
+        env_passthrough: Vec::new(),
+        idle_timeout: spec.idle_timeout,
+        depends_on: spec.depends_on,
+        template: None,
+        format: NewOutputFormat::Text,
+    };
+
+    new::execute(config, new_args, cancel).await
+}
@@ -0,0 +1,18 @@
+use anyhow::Result;
+
+use crate::commands::InspectArgs;
+use crowdcontrol_core::{load_agent_metadata, AgentManager, Config};
+
+pub async fn execute(config: Config, args: InspectArgs) -> Result<()> {
+    // Fail with "not found" for unknown agents before asking Docker
+    let agent = load_agent_metadata(&config, &args.name)?;
+    let manager = AgentManager::new(config);
+
+    let info = manager
+        .docker()?
+        .inspect_agent_container(&agent.name)
+        .await?;
+    println!("{}", serde_json::to_string_pretty(&info)?);
+
+    Ok(())
+}
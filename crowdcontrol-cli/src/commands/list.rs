@@ -1,28 +1,136 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Local, SecondsFormat, Utc};
 use colored::*;
 use serde::Serialize;
 
-use crate::commands::{AgentStatusFilter, ListArgs, OutputFormat};
+use crate::commands::{ListArgs, OutputFormat, TimeFormat};
 use crate::utils::*;
 use crowdcontrol_core::Config;
-use crowdcontrol_core::{format_duration, list_all_agents, load_agent_metadata};
-use crowdcontrol_core::{AgentStatus, DockerClient};
+use crowdcontrol_core::{directory_size, format_bytes, format_duration};
+use crowdcontrol_core::{format_ports, short_container_id};
+use crowdcontrol_core::{AgentEntry, AgentManager, AgentStatus, DiskUsage};
+use std::io::{self, IsTerminal, Write};
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::task::JoinHandle;
 #[derive(Serialize)]
 struct AgentInfo {
     name: String,
     status: String,
     repository: String,
     branch: Option<String>,
-    created: String,
+    /// Serialized as RFC3339 whatever `--time-format` says
+    created: DateTime<Utc>,
+    /// `created` as shown in the table
+    #[serde(skip)]
+    created_display: String,
+    memory: Option<String>,
+    cpus: Option<String>,
+    tags: Vec<String>,
+    /// Workspace size in bytes, only computed with `--disk`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    disk_usage: Option<u64>,
+    #[serde(skip)]
+    workspace_path: PathBuf,
+    #[serde(skip)]
+    skipped_dirs: Vec<PathBuf>,
+    /// Container details shown by `--format wide`
+    #[serde(skip)]
+    container_id: Option<String>,
+    #[serde(skip)]
+    image: Option<String>,
+    #[serde(skip)]
+    ports: String,
 }
 
 pub async fn execute(config: Config, args: ListArgs) -> Result<()> {
-    let docker = DockerClient::new(config.clone())?;
-    let agents = list_all_agents(&config)?;
+    let format = if args.wide {
+        OutputFormat::Wide
+    } else {
+        args.format.clone()
+    };
+    let manager = AgentManager::new(config);
+
+    if let Some(interval) = args.watch {
+        // Redrawing only makes sense for a table; ndjson already streams
+        if !matches!(format, OutputFormat::Table | OutputFormat::Wide) {
+            return Err(anyhow!(
+                "--watch only works with the table and wide formats; for machine-readable \
+                 output, run `crowdcontrol list --format ndjson` periodically instead"
+            ));
+        }
+        return watch(&manager, &args, &format, Duration::from_secs(interval)).await;
+    }
+
+    list_agents(&manager, &args, &format).await
+}
+
+/// Redraw the list every `interval` until Ctrl-C, which main handles by exiting.
+/// On a terminal the screen is cleared first and a resize redraws right away; otherwise
+/// each listing is printed in full after the previous one.
+async fn watch(
+    manager: &AgentManager,
+    args: &ListArgs,
+    format: &OutputFormat,
+    interval: Duration,
+) -> Result<()> {
+    let terminal = io::stdout().is_terminal();
+    #[cfg(unix)]
+    let mut resizes = if terminal {
+        use tokio::signal::unix::{signal, SignalKind};
+        signal(SignalKind::window_change()).ok()
+    } else {
+        None
+    };
+
+    loop {
+        let mut stdout = io::stdout();
+        if terminal {
+            // Clear the screen and move the cursor to the top left
+            write!(stdout, "\x1b[2J\x1b[H")?;
+        } else {
+            writeln!(stdout)?;
+        }
+        writeln!(
+            stdout,
+            "{}",
+            format!(
+                "Every {}s: crowdcontrol list    {}",
+                interval.as_secs(),
+                Local::now().format("%Y-%m-%d %H:%M:%S")
+            )
+            .dimmed()
+        )?;
+        writeln!(stdout)?;
+
+        // A failed refresh (Docker restarting, say) shows up until the next one succeeds
+        if let Err(e) = list_agents(manager, args, format).await {
+            print_error(&format!("{:#}", e));
+        }
+        stdout.flush()?;
+
+        // A resized terminal is redrawn right away rather than at the next refresh
+        #[cfg(unix)]
+        if let Some(resizes) = resizes.as_mut() {
+            tokio::select! {
+                _ = tokio::time::sleep(interval) => {}
+                _ = resizes.recv() => {}
+            }
+            continue;
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
+
+async fn list_agents(manager: &AgentManager, args: &ListArgs, format: &OutputFormat) -> Result<()> {
+    // Statuses come from a single container listing rather than one or two Docker
+    // round-trips per agent
+    let agents = manager.list().await?;
 
     if agents.is_empty() {
-        match args.format {
+        match format {
             OutputFormat::Json => println!("[]"),
+            OutputFormat::Ndjson => {}
             _ => print_info("No agents found"),
         }
         return Ok(());
@@ -30,29 +138,29 @@ pub async fn execute(config: Config, args: ListArgs) -> Result<()> {
 
     let mut agent_infos = Vec::new();
 
-    for agent_name in agents {
-        // Load agent metadata
-        let agent = match load_agent_metadata(&config, &agent_name) {
-            Ok(a) => a,
-            Err(_) => continue,
-        };
+    for AgentEntry {
+        agent,
+        status,
+        container,
+    } in agents
+    {
+        // Apply tag filter if provided
+        if let Some(tag) = &args.tag {
+            if !agent.has_tag(tag) {
+                continue;
+            }
+        }
 
-        // Get current live status (this validates container_id and gets status from Docker)
-        let status = agent
-            .compute_live_status(&docker)
-            .await
-            .unwrap_or(AgentStatus::Error);
+        // Apply repository filter if provided
+        if let Some(repo) = &args.repo {
+            if !agent.repository_matches(repo) {
+                continue;
+            }
+        }
 
         // Apply status filter if provided
         if let Some(filter) = &args.status {
-            let matches = match filter {
-                AgentStatusFilter::Running => status == AgentStatus::Running,
-                AgentStatusFilter::Stopped => status == AgentStatus::Stopped,
-                AgentStatusFilter::Created => status == AgentStatus::Created,
-                AgentStatusFilter::Error => status == AgentStatus::Error,
-            };
-
-            if !matches {
+            if !filter.matches(&status) {
                 continue;
             }
         }
@@ -67,13 +175,32 @@ pub async fn execute(config: Config, args: ListArgs) -> Result<()> {
             status: format!("{:?}", status),
             repository: agent.repository.clone(),
             branch: agent.branch.clone(),
-            created: format_duration(agent.created_at),
+            created: agent.created_at,
+            created_display: format_created(agent.created_at, args.time_format),
+            memory: agent.memory.clone(),
+            cpus: agent.cpus.clone(),
+            tags: agent.tags.clone(),
+            disk_usage: None,
+            workspace_path: agent.workspace_path.clone(),
+            skipped_dirs: Vec::new(),
+            container_id: agent.container_id.clone(),
+            // Agents without a container show the image they'll be created from
+            image: container
+                .as_ref()
+                .and_then(|c| c.image.clone())
+                .or_else(|| agent.image.clone()),
+            ports: container
+                .as_ref()
+                .and_then(|c| c.ports.as_deref())
+                .map(format_ports)
+                .unwrap_or_default(),
         });
     }
 
     if agent_infos.is_empty() {
-        match args.format {
+        match format {
             OutputFormat::Json => println!("[]"),
+            OutputFormat::Ndjson => {}
             _ => {
                 if args.all {
                     print_info("No agents found");
@@ -85,8 +212,27 @@ pub async fn execute(config: Config, args: ListArgs) -> Result<()> {
         return Ok(());
     }
 
-    match args.format {
-        OutputFormat::Table => print_table(&agent_infos),
+    // Stream each agent as soon as its size is known instead of waiting for all of them
+    if matches!(format, OutputFormat::Ndjson) {
+        return print_ndjson(agent_infos, args.disk).await;
+    }
+
+    if args.disk {
+        compute_disk_usage(&mut agent_infos).await;
+    }
+
+    match format {
+        OutputFormat::Table | OutputFormat::Wide => {
+            let wide = matches!(format, OutputFormat::Wide);
+            print_table(&agent_infos, args.disk, wide);
+            for agent in agent_infos.iter().filter(|a| !a.skipped_dirs.is_empty()) {
+                print_warning(&format!(
+                    "Could not read {} director(ies) in '{}'; its size is a lower bound",
+                    agent.skipped_dirs.len(),
+                    agent.name
+                ));
+            }
+        }
         OutputFormat::Json => {
             let json = serde_json::to_string_pretty(&agent_infos)?;
             println!("{}", json);
@@ -95,12 +241,71 @@ pub async fn execute(config: Config, args: ListArgs) -> Result<()> {
             let yaml = serde_yaml::to_string(&agent_infos)?;
             print!("{}", yaml);
         }
+        OutputFormat::Ndjson => unreachable!("ndjson is streamed above"),
     }
 
     Ok(())
 }
 
-fn print_table(agents: &[AgentInfo]) {
+fn format_created(created_at: DateTime<Utc>, format: TimeFormat) -> String {
+    match format {
+        TimeFormat::Relative => format_duration(created_at),
+        TimeFormat::Absolute => created_at
+            .with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string(),
+        TimeFormat::Iso => created_at.to_rfc3339_opts(SecondsFormat::Secs, true),
+    }
+}
+
+/// Size every workspace on the blocking thread pool, one task per agent
+fn spawn_disk_usage(agents: &[AgentInfo]) -> Vec<JoinHandle<Result<DiskUsage>>> {
+    agents
+        .iter()
+        .map(|agent| {
+            let path = agent.workspace_path.clone();
+            tokio::task::spawn_blocking(move || directory_size(&path))
+        })
+        .collect()
+}
+
+async fn compute_disk_usage(agents: &mut [AgentInfo]) {
+    let tasks = spawn_disk_usage(agents);
+    for (agent, task) in agents.iter_mut().zip(tasks) {
+        apply_disk_usage(agent, task).await;
+    }
+}
+
+async fn apply_disk_usage(agent: &mut AgentInfo, task: JoinHandle<Result<DiskUsage>>) {
+    // A missing or unreadable workspace shows as "-"
+    if let Ok(Ok(DiskUsage { bytes, skipped })) = task.await {
+        agent.disk_usage = Some(bytes);
+        agent.skipped_dirs = skipped;
+    }
+}
+
+/// Print one compact JSON object per agent, flushing each line so consumers such as
+/// `jq` see agents as they arrive
+async fn print_ndjson(mut agents: Vec<AgentInfo>, disk: bool) -> Result<()> {
+    let mut tasks = if disk {
+        spawn_disk_usage(&agents).into_iter().map(Some).collect()
+    } else {
+        Vec::new()
+    };
+    tasks.resize_with(agents.len(), || None);
+
+    let mut stdout = io::stdout();
+    for (agent, task) in agents.iter_mut().zip(tasks) {
+        if let Some(task) = task {
+            apply_disk_usage(agent, task).await;
+        }
+        writeln!(stdout, "{}", serde_json::to_string(agent)?)?;
+        stdout.flush()?;
+    }
+    Ok(())
+}
+
+fn print_table(agents: &[AgentInfo], show_disk: bool, wide: bool) {
     // Calculate column widths
     let name_width = agents
         .iter()
@@ -110,17 +315,78 @@ fn print_table(agents: &[AgentInfo]) {
         .max(4);
 
     let status_width = 10;
-    let created_width = 10;
+    let created_width = agents
+        .iter()
+        .map(|a| a.created_display.len())
+        .max()
+        .unwrap_or(10)
+        .max(10);
     let repo_width = 30;
+    let disk_width = 12;
+
+    // Wide mode appends CONTAINER ID, IMAGE and PORTS, so BRANCH needs padding too
+    let branch_width = agents
+        .iter()
+        .map(|a| a.branch.as_deref().unwrap_or("-").len())
+        .max()
+        .unwrap_or(6)
+        .max(20);
+    let id_width = "CONTAINER ID".len();
+    let image_width = agents
+        .iter()
+        .map(|a| a.image.as_deref().unwrap_or("-").len())
+        .max()
+        .unwrap_or(5)
+        .max(5);
+
+    // The disk column sits between CREATED and REPOSITORY when requested
+    let disk_header = if show_disk {
+        format!("{:<disk_width$} ", "DISK".bold(), disk_width = disk_width)
+    } else {
+        String::new()
+    };
+    let disk_separator = if show_disk {
+        format!("{} ", "-".repeat(disk_width))
+    } else {
+        String::new()
+    };
+
+    let (branch_header, wide_header, wide_separator) = if wide {
+        (
+            format!(
+                "{:<branch_width$}",
+                "BRANCH".bold(),
+                branch_width = branch_width
+            ),
+            format!(
+                " {:<id_width$} {:<image_width$} {}",
+                "CONTAINER ID".bold(),
+                "IMAGE".bold(),
+                "PORTS".bold(),
+                id_width = id_width,
+                image_width = image_width,
+            ),
+            format!(
+                " {} {} {}",
+                "-".repeat(id_width),
+                "-".repeat(image_width),
+                "-".repeat(20)
+            ),
+        )
+    } else {
+        ("BRANCH".bold().to_string(), String::new(), String::new())
+    };
 
     // Print header
     println!(
-        "{:<name_width$} {:<status_width$} {:<created_width$} {:<repo_width$} {}",
+        "{:<name_width$} {:<status_width$} {:<created_width$} {}{:<repo_width$} {}{}",
         "NAME".bold(),
         "STATUS".bold(),
         "CREATED".bold(),
+        disk_header,
         "REPOSITORY".bold(),
-        "BRANCH".bold(),
+        branch_header,
+        wide_header,
         name_width = name_width,
         status_width = status_width,
         created_width = created_width,
@@ -129,18 +395,23 @@ fn print_table(agents: &[AgentInfo]) {
 
     // Print separator
     println!(
-        "{} {} {} {} {}",
+        "{} {} {} {}{} {}{}",
         "-".repeat(name_width),
         "-".repeat(status_width),
         "-".repeat(created_width),
+        disk_separator,
         "-".repeat(repo_width),
-        "-".repeat(20),
+        "-".repeat(if wide { branch_width } else { 20 }),
+        wide_separator,
     );
 
     // Print agents
     for agent in agents {
         let status_colored = match agent.status.as_str() {
             "Running" => agent.status.green(),
+            "Starting" => agent.status.bright_green(),
+            "Restarting" => agent.status.magenta(),
+            "Paused" => agent.status.cyan(),
             "Stopped" => agent.status.yellow(),
             "Created" => agent.status.white(),
             "Error" => agent.status.red(),
@@ -156,13 +427,51 @@ fn print_table(agents: &[AgentInfo]) {
             agent.repository.clone()
         };
 
+        let disk = if show_disk {
+            let size = agent.disk_usage.map(format_bytes);
+            format!(
+                "{:<disk_width$} ",
+                size.as_deref().unwrap_or("-"),
+                disk_width = disk_width
+            )
+        } else {
+            String::new()
+        };
+
+        let branch = agent.branch.as_deref().unwrap_or("-");
+        let (branch, wide_columns) = if wide {
+            let ports = if agent.ports.is_empty() {
+                "-"
+            } else {
+                agent.ports.as_str()
+            };
+            (
+                format!("{:<branch_width$}", branch, branch_width = branch_width),
+                format!(
+                    " {:<id_width$} {:<image_width$} {}",
+                    agent
+                        .container_id
+                        .as_deref()
+                        .map_or("-", short_container_id),
+                    agent.image.as_deref().unwrap_or("-"),
+                    ports,
+                    id_width = id_width,
+                    image_width = image_width,
+                ),
+            )
+        } else {
+            (branch.to_string(), String::new())
+        };
+
         println!(
-            "{:<name_width$} {:<status_width$} {:<created_width$} {:<repo_width$} {}",
+            "{:<name_width$} {:<status_width$} {:<created_width$} {}{:<repo_width$} {}{}",
             agent.name,
             status_colored,
-            agent.created,
+            agent.created_display,
+            disk,
             repo_short,
-            agent.branch.as_deref().unwrap_or("-"),
+            branch,
+            wide_columns,
             name_width = name_width,
             status_width = status_width,
             created_width = created_width,
@@ -1,28 +1,143 @@
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Context, Result};
+use chrono::Utc;
+use colored::*;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::time::Duration;
+use tokio::time::sleep;
 
 use crate::commands::LogsArgs;
-use crowdcontrol_core::{load_agent_metadata, Config, DockerClient};
-pub async fn execute(config: Config, args: LogsArgs) -> Result<()> {
-    // Load agent metadata
-    let agent = load_agent_metadata(&config, &args.name)?;
+use crowdcontrol_core::{follow_action, parse_time_spec};
+use crowdcontrol_core::{run_until_cancelled, CancellationToken};
+use crowdcontrol_core::{
+    AgentManager, Config, DockerClient, FollowAction, LineFilter, LogTail, LogsQuery,
+};
 
-    // Get container ID
-    let container_id = agent
-        .container_id
-        .ok_or_else(|| anyhow!("No container ID found for agent '{}'", args.name))?;
+/// Pause between checks for a stopped container to come back while following
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(1);
 
-    // Create Docker client
-    let docker = DockerClient::new(config)?;
+pub async fn execute(config: Config, args: LogsArgs, cancel: CancellationToken) -> Result<()> {
+    // Resolve the time range before touching Docker so bad input fails fast
+    let now = Utc::now();
+    let since = args
+        .since
+        .as_deref()
+        .map(|spec| parse_time_spec(spec, now))
+        .transpose()?;
+    let until = args
+        .until
+        .as_deref()
+        .map(|spec| parse_time_spec(spec, now))
+        .transpose()?;
+
+    if let (Some(since), Some(until)) = (since, until) {
+        if until < since {
+            return Err(anyhow!(
+                "--until ({}) is before --since ({})",
+                until.to_rfc3339(),
+                since.to_rfc3339()
+            ));
+        }
+    }
+
+    let grep = args
+        .grep
+        .as_deref()
+        .map(|pattern| LineFilter::new(pattern, args.invert_match))
+        .transpose()?;
+
+    // A time range usually means "everything in that window" unless a line count is given
+    let tail = LogTail::resolve(args.tail, since.is_some());
+
+    let manager = AgentManager::new(config);
+    let container_id = manager.container_id(&args.name)?;
+    let docker = manager.docker()?;
+
+    let query = LogsQuery {
+        follow: args.follow,
+        tail: Some(tail.to_docker_value()),
+        timestamps: args.timestamps,
+        since: since.map(|time| time.timestamp()),
+        until: until.map(|time| time.timestamp()),
+        grep,
+    };
 
     // Get logs
-    docker
-        .get_container_logs(
-            &container_id,
-            args.follow,
-            Some(args.tail.to_string()),
-            args.timestamps,
-        )
-        .await?;
-
-    Ok(())
+    let mut writer: Box<dyn Write> = match &args.output {
+        Some(path) => {
+            // Append while following so an existing capture keeps growing; otherwise start fresh
+            let file = OpenOptions::new()
+                .create(true)
+                .write(true)
+                .append(args.follow)
+                .truncate(!args.follow)
+                .open(path)
+                .with_context(|| format!("Failed to open log file: {:?}", path))?;
+            Box::new(file)
+        }
+        None => Box::new(io::stdout()),
+    };
+
+    let stream = async {
+        docker
+            .write_container_logs(&container_id, &query, &mut writer)
+            .await?;
+
+        // A followed stream also ends when the container stops or restarts; an --until
+        // stream ends by design
+        if args.follow && until.is_none() {
+            follow_across_restarts(docker, &args, query.clone(), &mut writer).await?;
+        }
+        Ok::<_, anyhow::Error>(())
+    };
+
+    // Ctrl-C just stops streaming; whatever was received so far is kept
+    let result = run_until_cancelled(&cancel, stream).await.unwrap_or(Ok(()));
+    writer.flush()?;
+    result
+}
+
+/// Keep following after the log stream ends, reopening it whenever the container is
+/// running again. Gives up after `--max-reconnects` consecutive checks find it not running.
+async fn follow_across_restarts(
+    docker: &DockerClient,
+    args: &LogsArgs,
+    mut query: LogsQuery,
+    writer: &mut impl Write,
+) -> Result<()> {
+    // Follow by name so a recreated container is picked up too
+    let container_name = docker.config().container_name(&args.name);
+    let mut failed_attempts = 0;
+
+    loop {
+        let status = docker.get_container_status(&args.name).await.ok();
+        match follow_action(status.as_ref(), failed_attempts, args.max_reconnects) {
+            FollowAction::Reconnect => {
+                failed_attempts = 0;
+                eprintln!("{}", "--- reconnected ---".dimmed());
+
+                // Only show what's logged from now on
+                query.since = Some(Utc::now().timestamp());
+                query.tail = Some("all".to_string());
+                docker
+                    .write_container_logs(&container_name, &query, writer)
+                    .await?;
+            }
+            FollowAction::Wait => {
+                failed_attempts += 1;
+                sleep(RECONNECT_INTERVAL).await;
+            }
+            FollowAction::GiveUp => {
+                eprintln!(
+                    "{}",
+                    format!(
+                        "--- agent '{}' is not running, stopped following ---",
+                        args.name
+                    )
+                    .dimmed()
+                );
+                return Ok(());
+            }
+        }
+    }
 }
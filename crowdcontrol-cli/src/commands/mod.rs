@@ -1,15 +1,37 @@
 use clap::Args;
+use crowdcontrol_core::docker::{DEFAULT_CREATE_TIMEOUT_SECS, DEFAULT_STOP_TIMEOUT_SECS};
+use crowdcontrol_core::terminal::{DetachKeys, DEFAULT_DETACH_KEYS};
+use crowdcontrol_core::{
+    parse_cpus, parse_gpus, parse_memory_limit, parse_restart_policy, validate_env_entry,
+    validate_platform, AgentStatus, LogTail, VolumeSpec,
+};
+use std::path::PathBuf;
 
+pub mod clean;
+pub mod clone;
 pub mod completions;
+pub mod config;
 pub mod connect;
 pub mod doctor;
+pub mod export;
+pub mod import;
+pub mod inspect;
 pub mod list;
 pub mod logs;
 pub mod new;
+pub mod pause;
+pub mod reap;
 pub mod refresh;
 pub mod remove;
+pub mod reset;
+pub mod schema;
 pub mod start;
 pub mod stop;
+pub mod switch;
+pub mod top;
+pub mod unpause;
+pub mod update;
+pub mod version;
 
 /// Arguments for the new command
 #[derive(Args)]
@@ -19,8 +41,28 @@ pub struct NewArgs {
     pub name: String,
 
     /// Git repository URL to clone
-    #[arg(help = "Git repository URL (ssh format: git@github.com:org/repo.git)")]
-    pub repository: String,
+    #[arg(
+        required_unless_present = "from_local",
+        help = "Git repository URL (ssh format: git@github.com:org/repo.git)"
+    )]
+    pub repository: Option<String>,
+
+    /// Use an existing local checkout instead of cloning
+    #[arg(
+        long,
+        value_name = "PATH",
+        conflicts_with_all = ["repository", "branch", "ssh_key", "ssh_accept_new"],
+        help = "Copy an existing local checkout (including uncommitted work) instead of cloning"
+    )]
+    pub from_local: Option<PathBuf>,
+
+    /// Bind-mount the local checkout instead of copying it
+    #[arg(
+        long,
+        requires = "from_local",
+        help = "With --from-local, mount the directory for live editing instead of copying it"
+    )]
+    pub mount: bool,
 
     /// Custom branch to checkout
     #[arg(
@@ -30,30 +72,391 @@ pub struct NewArgs {
     )]
     pub branch: Option<String>,
 
+    /// New branch to start work on
+    #[arg(
+        long,
+        value_name = "BRANCH",
+        conflicts_with = "mount",
+        help = "Create and check out a new branch after cloning (from --branch, or the default branch)"
+    )]
+    pub create_branch: Option<String>,
+
+    /// Recreate an existing branch
+    #[arg(
+        long,
+        requires = "create_branch",
+        help = "With --create-branch, reset the branch to the cloned HEAD if it already exists"
+    )]
+    pub force: bool,
+
     /// Skip repository verification
     #[arg(
         long,
+        visible_alias = "no-verify",
         help = "Skip checking if repository contains .crowdcontrol/ directory"
     )]
     pub skip_verification: bool,
 
+    /// Fail when the repository has no `.crowdcontrol/` directory
+    #[arg(
+        long,
+        conflicts_with = "skip_verification",
+        help = "Fail instead of warning when the repository has no .crowdcontrol/ directory \
+                (default from require_crowdcontrol in the config file)"
+    )]
+    pub require_crowdcontrol: bool,
+
+    /// Only prepare the workspace and metadata
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "build",
+            "healthcheck",
+            "run_setup",
+            "ignore_setup_errors",
+            "ignore_lifecycle_errors",
+        ],
+        help = "Clone the repository and save the agent without Docker; start creates the container"
+    )]
+    pub workspace_only: bool,
+
+    /// Run the repository setup script after creating the agent
+    #[arg(
+        long,
+        conflicts_with = "skip_setup",
+        help = "Start the agent and run .crowdcontrol/setup.sh in it (the default when the script exists)"
+    )]
+    pub run_setup: bool,
+
+    /// Don't run the repository setup script
+    #[arg(long, help = "Don't start the agent to run .crowdcontrol/setup.sh")]
+    pub skip_setup: bool,
+
+    /// Keep the agent when the setup script fails
+    #[arg(
+        long,
+        conflicts_with = "skip_setup",
+        help = "Warn instead of failing when .crowdcontrol/setup.sh exits with an error"
+    )]
+    pub ignore_setup_errors: bool,
+
+    /// Keep the agent when a lifecycle command fails
+    #[arg(
+        long,
+        help = "Warn instead of failing when the repository's post-create or post-start command exits with an error"
+    )]
+    pub ignore_lifecycle_errors: bool,
+
+    /// SSH key for cloning SSH repository URLs
+    #[arg(
+        long,
+        help = "Private SSH key used by git on the host to clone SSH URLs (defaults to ssh-agent/~/.ssh)"
+    )]
+    pub ssh_key: Option<PathBuf>,
+
+    /// Accept unknown SSH host keys on first connection
+    #[arg(
+        long,
+        help = "Trust unknown SSH host keys on first use (StrictHostKeyChecking=accept-new)"
+    )]
+    pub ssh_accept_new: bool,
+
     /// Custom resource limits
-    #[arg(long, help = "Memory limit (e.g., 2g, 1024m)")]
+    #[arg(long, value_parser = memory_value, help = "Memory limit (e.g., 2g, 1024m)")]
     pub memory: Option<String>,
 
-    #[arg(long, help = "CPU limit (e.g., 1.5, 2)")]
+    #[arg(
+        long,
+        value_parser = memory_value,
+        help = "Memory plus swap limit, at least --memory (e.g., 4g)"
+    )]
+    pub memory_swap: Option<String>,
+
+    #[arg(
+        long,
+        value_parser = memory_value,
+        help = "Soft memory limit enforced when the host runs low on memory (e.g., 1g)"
+    )]
+    pub memory_reservation: Option<String>,
+
+    #[arg(
+        long,
+        value_parser = cpus_value,
+        allow_negative_numbers = true,
+        help = "CPU limit (e.g., 1.5, 2)"
+    )]
     pub cpus: Option<String>,
+
+    /// Container health check command
+    #[arg(
+        long,
+        help = "Health check command run inside the container (overrides .crowdcontrol/config.toml)"
+    )]
+    pub healthcheck: Option<String>,
+
+    /// Build the image from a local Dockerfile if it can't be pulled
+    #[arg(
+        long,
+        help = "Build the image from container/Dockerfile without prompting if it can't be pulled"
+    )]
+    pub build: bool,
+
+    /// Tags for grouping agents
+    #[arg(
+        long,
+        value_name = "TAG",
+        help = "Tag the agent for grouping and filtering (repeatable)"
+    )]
+    pub tag: Vec<String>,
+
+    /// Docker network for the container
+    #[arg(
+        long,
+        value_name = "NETWORK",
+        help = "Network to attach the container to: none, bridge, host or a named Docker network"
+    )]
+    pub network: Option<String>,
+
+    /// Run the container in privileged mode
+    #[arg(
+        long,
+        help = "Run the container privileged (e.g. for Docker-in-Docker). Containers are no longer \
+                privileged by default; prefer --cap-add for narrower needs"
+    )]
+    pub privileged: bool,
+
+    /// Extra Linux capabilities
+    #[arg(
+        long,
+        value_name = "CAPABILITY",
+        help = "Add a Linux capability to the container, e.g. NET_ADMIN or SYS_PTRACE (repeatable)"
+    )]
+    pub cap_add: Vec<String>,
+
+    /// Read-only root filesystem
+    #[arg(
+        long,
+        help = "Mount the container's root filesystem read-only; /workspace, /tmp and ~/.claude stay writable"
+    )]
+    pub read_only: bool,
+
+    /// Read-only workspace
+    #[arg(
+        long,
+        help = "Mount /workspace read-only, for agents that only review code; edits and git commits will fail"
+    )]
+    pub readonly_workspace: bool,
+
+    /// Keep host Claude credentials out of the container
+    #[arg(
+        long,
+        help = "Don't mount ~/.claude and ~/.claude.json from the host (also set by CROWDCONTROL_NO_CLAUDE_MOUNT)"
+    )]
+    pub no_mount_claude: bool,
+
+    /// Platform of the container image
+    #[arg(
+        long,
+        value_name = "OS/ARCH",
+        value_parser = platform_value,
+        help = "Container platform, e.g. linux/amd64 to run amd64 images on Apple Silicon"
+    )]
+    pub platform: Option<String>,
+
+    /// Limit on pulling the image and on creating the container
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        default_value_t = DEFAULT_CREATE_TIMEOUT_SECS,
+        help = "Seconds to allow each of pulling the image and creating the container before giving up"
+    )]
+    pub create_timeout: u64,
+
+    /// Image for this agent. It shares its ID with the global `--image`, so clap uses
+    /// this argument for `new` and the two always hold the same value.
+    #[arg(
+        long,
+        env = "CROWDCONTROL_IMAGE",
+        help = "Docker image for this agent, recorded so its container is always recreated from it"
+    )]
+    pub image: Option<String>,
+
+    /// Docker restart policy for the container
+    #[arg(
+        long,
+        value_name = "POLICY",
+        value_parser = restart_value,
+        help = "Restart policy: no, on-failure, unless-stopped or always. With unless-stopped or always the agent comes back after a Docker daemon restart"
+    )]
+    pub restart: Option<String>,
+
+    /// NVIDIA GPUs to pass through to the container
+    #[arg(
+        long,
+        value_name = "GPUS",
+        value_parser = gpus_value,
+        help = "Pass NVIDIA GPUs through like docker run --gpus: all, a number of GPUs, or device=0,1 (needs the NVIDIA Container Toolkit)"
+    )]
+    pub gpus: Option<String>,
+
+    /// Extra bind mounts
+    #[arg(
+        long,
+        value_name = "HOST:CONTAINER[:ro]",
+        value_parser = volume_value,
+        help = "Bind-mount a host directory into the container, e.g. ~/.cargo:/home/developer/.cargo:ro (repeatable)"
+    )]
+    pub volume: Vec<String>,
+
+    /// Host environment variables to forward into the container
+    #[arg(
+        long,
+        value_name = "NAME",
+        help = "Copy an environment variable from this shell into the container; the value is never saved (repeatable)"
+    )]
+    pub env_passthrough: Vec<String>,
+
+    /// Stop the agent after a period without activity
+    #[arg(
+        long,
+        value_name = "DURATION",
+        help = "Let `crowdcontrol reap` stop the agent after this long without a connect session (e.g. 30m, 4h, 1d)"
+    )]
+    pub idle_timeout: Option<String>,
+
+    /// Agents this one needs running
+    #[arg(
+        long,
+        value_name = "AGENT",
+        help = "Start this agent after AGENT and stop it before AGENT (repeatable)"
+    )]
+    pub depends_on: Vec<String>,
+
+    /// Preset from templates.toml
+    #[arg(
+        long,
+        value_name = "NAME",
+        help = "Apply a preset from ~/.config/crowdcontrol/templates.toml; flags given here override it"
+    )]
+    pub template: Option<String>,
+
+    /// Output format
+    #[arg(
+        long,
+        value_enum,
+        default_value = "text",
+        help = "Output format (json prints a machine-readable summary)"
+    )]
+    pub format: NewOutputFormat,
+}
+
+/// Validate `--memory`, `--memory-swap` and `--memory-reservation` with the same rules used when creating the container
+fn memory_value(value: &str) -> Result<String, String> {
+    parse_memory_limit(value)
+        .map(|_| value.to_string())
+        .map_err(|e| e.to_string())
+}
+
+/// Validate `--cpus` with the same rules used when creating the container
+fn cpus_value(value: &str) -> Result<String, String> {
+    parse_cpus(value)
+        .map(|_| value.to_string())
+        .map_err(|e| e.to_string())
+}
+
+/// Validate `--platform` before any work is done
+fn platform_value(value: &str) -> Result<String, String> {
+    validate_platform(value)
+        .map(|_| value.to_string())
+        .map_err(|e| e.to_string())
+}
+
+/// Validate `--restart` before any work is done
+fn restart_value(value: &str) -> Result<String, String> {
+    parse_restart_policy(value)
+        .map(|_| value.to_string())
+        .map_err(|e| e.to_string())
+}
+
+/// Validate `--gpus` before any work is done
+fn gpus_value(value: &str) -> Result<String, String> {
+    parse_gpus(value)
+        .map(|_| value.to_string())
+        .map_err(|e| e.to_string())
+}
+
+/// Parse `connect --detach-keys` the way Docker does
+fn detach_keys_value(value: &str) -> Result<DetachKeys, String> {
+    value.parse().map_err(|e: anyhow::Error| e.to_string())
+}
+
+/// Validate `connect --env` before the session starts
+fn env_value(value: &str) -> Result<String, String> {
+    validate_env_entry(value)
+        .map(|_| value.to_string())
+        .map_err(|e| e.to_string())
+}
+
+/// Validate `list --watch`: a whole number of seconds, at least one
+fn watch_interval_value(value: &str) -> Result<u64, String> {
+    match value.parse::<u64>() {
+        Ok(0) => Err("the interval must be at least 1 second".to_string()),
+        Ok(secs) => Ok(secs),
+        Err(_) => Err(format!("expected a number of seconds, got '{}'", value)),
+    }
+}
+
+/// Validate `--volume` and resolve its host path, so the stored spec is absolute
+fn volume_value(value: &str) -> Result<String, String> {
+    VolumeSpec::parse(value)
+        .map(|volume| volume.to_string())
+        .map_err(|e| format!("{:#}", e))
 }
 
 /// Arguments for the start command
-#[derive(Args)]
+#[derive(Args, Clone)]
 pub struct StartArgs {
     /// Name of the agent to start
     #[arg(help = "Name of the agent to start")]
-    pub name: String,
+    pub name: Option<String>,
+
+    /// Start all agents that are not running
+    #[arg(
+        long,
+        conflicts_with = "name",
+        help = "Start all agents that are not running"
+    )]
+    pub all: bool,
+
+    /// Start all agents carrying a tag
+    #[arg(
+        long,
+        conflicts_with_all = ["name", "all"],
+        help = "Start all agents with this tag that are not running"
+    )]
+    pub tag: Option<String>,
+
+    /// Move the agent onto a different Docker network before starting
+    #[arg(
+        long,
+        value_name = "NETWORK",
+        help = "Switch the agent to this network (none, bridge or a named Docker network) and remember it"
+    )]
+    pub network: Option<String>,
+
+    /// Make the agent's workspace read-only from now on
+    #[arg(
+        long,
+        help = "Mount /workspace read-only from now on, replacing the stopped agent's container"
+    )]
+    pub readonly_workspace: bool,
 
     /// Wait for agent to be ready before returning
-    #[arg(short, long, help = "Wait for agent initialization to complete")]
+    #[arg(
+        short,
+        long,
+        help = "Wait for agent initialization to complete (healthy if a health check is configured)"
+    )]
     pub wait: bool,
 
     /// Timeout for waiting (in seconds)
@@ -64,6 +467,35 @@ pub struct StartArgs {
         help = "Timeout for wait operation"
     )]
     pub timeout: u64,
+
+    /// Wait in the background and record the outcome in this file
+    #[arg(
+        long,
+        value_name = "PATH",
+        requires = "wait",
+        conflicts_with_all = ["all", "tag"],
+        help = "Return once the agent is started and wait for it in the background, writing {\"ready\": true} or an error to this file"
+    )]
+    pub ready_file: Option<PathBuf>,
+
+    /// Keep going when a lifecycle command fails
+    #[arg(
+        long,
+        help = "Warn instead of failing when the repository's post-create or post-start command exits with an error"
+    )]
+    pub ignore_lifecycle_errors: bool,
+}
+
+/// Arguments for the hidden command that performs a background `start --wait --ready-file`
+#[derive(Args)]
+pub struct WaitReadyArgs {
+    pub name: String,
+
+    #[arg(long)]
+    pub ready_file: PathBuf,
+
+    #[arg(long)]
+    pub timeout: u64,
 }
 
 /// Arguments for the stop command
@@ -77,9 +509,52 @@ pub struct StopArgs {
     #[arg(long, conflicts_with = "name", help = "Stop all running agents")]
     pub all: bool,
 
+    /// Stop all running agents carrying a tag
+    #[arg(
+        long,
+        conflicts_with = "name",
+        help = "Stop all running agents with this tag (on its own or with --all)"
+    )]
+    pub tag: Option<String>,
+
+    /// Only bulk-stop agents with a status
+    #[arg(
+        long,
+        value_enum,
+        requires = "all",
+        help = "With --all, only stop agents with this status, e.g. error"
+    )]
+    pub status: Option<AgentStatusFilter>,
+
     /// Force stop (kill instead of graceful shutdown)
     #[arg(short, long, help = "Force stop the agent (SIGKILL)")]
     pub force: bool,
+
+    /// Grace period before the container is killed
+    #[arg(
+        short,
+        long,
+        default_value_t = DEFAULT_STOP_TIMEOUT_SECS,
+        conflicts_with = "force",
+        help = "Seconds to wait for a graceful shutdown before killing (cannot be combined with --force)"
+    )]
+    pub timeout: u64,
+}
+
+/// Arguments for the pause command
+#[derive(Args)]
+pub struct PauseArgs {
+    /// Name of the agent to pause
+    #[arg(help = "Name of the agent to pause")]
+    pub name: String,
+}
+
+/// Arguments for the unpause command
+#[derive(Args)]
+pub struct UnpauseArgs {
+    /// Name of the agent to resume
+    #[arg(help = "Name of the agent to resume")]
+    pub name: String,
 }
 
 /// Arguments for the connect command
@@ -93,13 +568,132 @@ pub struct ConnectArgs {
     #[arg(
         short,
         long,
-        help = "Custom command to run (defaults to 'claude --dangerously-skip-permissions')"
+        help = "Custom command to run (defaults to connect_default_command, or 'claude --dangerously-skip-permissions')"
     )]
     pub command: Option<String>,
 
+    /// Open an interactive login shell instead of Claude Code
+    #[arg(
+        long,
+        conflicts_with_all = ["command", "detach"],
+        help = "Open an interactive bash login shell as the developer user"
+    )]
+    pub shell: bool,
+
     /// Run command in the background
     #[arg(short, long, help = "Run command in background and return immediately")]
     pub detach: bool,
+
+    /// Environment variables for this session only
+    #[arg(
+        short,
+        long,
+        value_name = "KEY=VALUE",
+        value_parser = env_value,
+        help = "Set an environment variable for this session only; it is not saved (repeatable)"
+    )]
+    pub env: Vec<String>,
+
+    /// Start the agent first if it isn't running
+    #[arg(
+        long,
+        help = "Start the agent (creating its container if needed) when it is created or stopped, and leave it running afterwards (default from connect_auto_start in the config file)"
+    )]
+    pub auto_start: bool,
+
+    /// Key sequence that leaves an interactive session running and returns
+    #[arg(
+        long,
+        value_name = "KEYS",
+        default_value = DEFAULT_DETACH_KEYS,
+        value_parser = detach_keys_value,
+        conflicts_with = "detach",
+        help = "Keys that detach from the session, leaving it running in the agent: comma-separated characters or ctrl-<key>, \"\" to turn off. Ctrl-C is passed to the command"
+    )]
+    pub detach_keys: DetachKeys,
+}
+
+/// Arguments for the switch command
+#[derive(Args)]
+pub struct SwitchArgs {
+    /// Name of the agent
+    #[arg(help = "Name of the agent whose workspace should switch branches")]
+    pub name: String,
+
+    /// Branch to check out
+    #[arg(help = "Branch to check out, local or on origin")]
+    pub branch: String,
+
+    /// Stash uncommitted changes before switching
+    #[arg(
+        long,
+        help = "Stash uncommitted changes (including untracked files) before switching"
+    )]
+    pub force: bool,
+}
+
+/// Arguments for the update command
+#[derive(Args)]
+pub struct UpdateArgs {
+    /// Name of the agent
+    #[arg(help = "Name of the agent whose workspace should pull upstream changes")]
+    pub name: String,
+
+    /// Rebase local commits instead of only fast-forwarding
+    #[arg(
+        long,
+        help = "Rebase local commits onto upstream (git pull --rebase) instead of only fast-forwarding"
+    )]
+    pub rebase: bool,
+
+    /// Stash uncommitted changes before pulling
+    #[arg(
+        long,
+        help = "Stash uncommitted changes (including untracked files) before pulling"
+    )]
+    pub stash: bool,
+}
+
+/// Arguments for the reset command
+#[derive(Args)]
+pub struct ResetArgs {
+    /// Name of the agent
+    #[arg(help = "Name of the agent whose container should be replaced")]
+    pub name: String,
+}
+
+/// Arguments for the clean command
+#[derive(Args)]
+pub struct CleanArgs {
+    /// Name of the agent
+    #[arg(help = "Name of the agent whose workspace should be cleaned")]
+    pub name: String,
+
+    /// Also remove ignored files
+    #[arg(
+        long,
+        help = "Also remove files matched by .gitignore, such as node_modules or target"
+    )]
+    pub include_ignored: bool,
+
+    /// Only list what would be removed
+    #[arg(long, help = "Show what would be removed without removing anything")]
+    pub dry_run: bool,
+
+    /// Clean even with uncommitted changes
+    #[arg(
+        long,
+        help = "Clean even if tracked files have staged or uncommitted changes"
+    )]
+    pub force: bool,
+}
+
+/// Arguments for the top command
+#[derive(Args)]
+pub struct TopArgs {
+    /// Keep refreshing
+    #[arg(long, help = "Keep refreshing the table until interrupted")]
+    pub watch: bool,
 }
 
 /// Arguments for the list command
@@ -113,25 +707,105 @@ pub struct ListArgs {
     #[arg(long, value_enum, default_value = "table", help = "Output format")]
     pub format: OutputFormat,
 
+    /// Shorthand for `--format wide`
+    #[arg(
+        long,
+        conflicts_with = "format",
+        help = "Also show container ID, image and ports (same as --format wide)"
+    )]
+    pub wide: bool,
+
     /// Filter agents by status
     #[arg(long, value_enum, help = "Filter agents by status")]
     pub status: Option<AgentStatusFilter>,
+
+    /// Filter agents by tag
+    #[arg(long, help = "Only show agents with this tag")]
+    pub tag: Option<String>,
+
+    /// Filter agents by repository
+    #[arg(
+        long,
+        value_name = "SUBSTRING",
+        help = "Only show agents whose repository contains this text (case-insensitive)"
+    )]
+    pub repo: Option<String>,
+
+    /// Show how much disk each workspace uses
+    #[arg(
+        long,
+        help = "Show the disk space used by each agent's workspace (walks every workspace, can be slow)"
+    )]
+    pub disk: bool,
+
+    /// How to show when each agent was created
+    #[arg(
+        long,
+        value_enum,
+        default_value = "relative",
+        help = "How the CREATED column is shown; JSON and YAML always use RFC3339"
+    )]
+    pub time_format: TimeFormat,
+
+    /// Redraw the table every few seconds
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        num_args = 0..=1,
+        default_missing_value = "2",
+        value_parser = watch_interval_value,
+        help = "Redraw the table every SECONDS seconds (default 2) until Ctrl-C; table and wide formats only"
+    )]
+    pub watch: Option<u64>,
 }
 
 /// Arguments for the remove command
 #[derive(Args)]
 pub struct RemoveArgs {
     /// Name of the agent to remove
-    #[arg(help = "Name of the agent to remove")]
-    pub name: String,
+    #[arg(required_unless_present = "all", help = "Name of the agent to remove")]
+    pub name: Option<String>,
+
+    /// Remove every agent
+    #[arg(
+        long,
+        conflicts_with = "name",
+        help = "Remove all agents (requires --force or typing YES at the prompt)"
+    )]
+    pub all: bool,
 
-    /// Remove without confirmation
-    #[arg(short, long, help = "Remove without confirmation prompt")]
+    /// Only bulk-remove agents with a status
+    #[arg(
+        long,
+        value_enum,
+        requires = "all",
+        help = "With --all, only remove agents with this status"
+    )]
+    pub status: Option<AgentStatusFilter>,
+
+    /// Only bulk-remove agents carrying a tag
+    #[arg(
+        long,
+        requires = "all",
+        help = "With --all, only remove agents with this tag"
+    )]
+    pub tag: Option<String>,
+
+    /// Remove without confirmation, stopping running agents first
+    #[arg(
+        short,
+        long,
+        help = "Remove without confirmation prompt, stopping the agent first if it is running"
+    )]
     pub force: bool,
 
     /// Keep workspace directory
     #[arg(long, help = "Keep the workspace directory (only remove container)")]
     pub keep_workspace: bool,
+
+    /// Show what would be removed without removing anything
+    #[arg(long, help = "Print what would be removed without changing anything")]
+    pub dry_run: bool,
 }
 
 /// Arguments for the logs command
@@ -145,18 +819,229 @@ pub struct LogsArgs {
     #[arg(short, long, help = "Follow log output (like tail -f)")]
     pub follow: bool,
 
+    /// How long to keep waiting for a stopped container while following
+    #[arg(
+        long,
+        default_value = "10",
+        requires = "follow",
+        help = "Consecutive failed reconnect attempts (one per second) before --follow gives up"
+    )]
+    pub max_reconnects: u32,
+
     /// Number of lines to show
     #[arg(
         short = 'n',
         long,
-        default_value = "50",
-        help = "Number of lines to show from the end"
+        value_name = "N|all",
+        help = "Number of lines to show from the end, or 'all'; 0 with --follow shows only new output [default: 50, or all with --since]"
     )]
-    pub tail: u32,
+    pub tail: Option<LogTail>,
+
+    /// Show logs since a point in time
+    #[arg(
+        long,
+        value_name = "TIME",
+        help = "Show logs since a relative time (e.g. 10m, 2h) or RFC3339 timestamp"
+    )]
+    pub since: Option<String>,
+
+    /// Show logs until a point in time
+    #[arg(
+        long,
+        value_name = "TIME",
+        help = "Show logs before a relative time (e.g. 10m, 2h) or RFC3339 timestamp"
+    )]
+    pub until: Option<String>,
 
     /// Show timestamps
     #[arg(short, long, help = "Show timestamps")]
     pub timestamps: bool,
+
+    /// Only show lines matching a regex
+    #[arg(
+        long,
+        value_name = "REGEX",
+        help = "Only show lines matching this regular expression (the timestamp is not matched)"
+    )]
+    pub grep: Option<String>,
+
+    /// Show the lines --grep would hide
+    #[arg(
+        long,
+        requires = "grep",
+        help = "Only show lines that don't match --grep"
+    )]
+    pub invert_match: bool,
+
+    /// Write logs to a file instead of stdout
+    #[arg(
+        short,
+        long,
+        value_name = "PATH",
+        help = "Write logs to a file instead of the terminal (appends when following)"
+    )]
+    pub output: Option<PathBuf>,
+}
+
+/// Arguments for the reap command
+#[derive(Args)]
+pub struct ReapArgs {
+    /// Only report idle agents
+    #[arg(
+        long,
+        help = "Show which agents would be stopped without stopping them"
+    )]
+    pub dry_run: bool,
+}
+
+/// Arguments for the inspect command
+#[derive(Args)]
+pub struct InspectArgs {
+    /// Name of the agent to inspect
+    #[arg(help = "Name of the agent whose container to inspect")]
+    pub name: String,
+}
+
+/// Arguments for the export command
+#[derive(Args)]
+pub struct ExportArgs {
+    /// Name of the agent to export
+    #[arg(help = "Name of the agent to export")]
+    pub name: String,
+
+    /// Document format
+    #[arg(
+        long,
+        value_enum,
+        default_value = "json",
+        help = "Format of the exported agent spec"
+    )]
+    pub format: SpecFormat,
+}
+
+/// Arguments for the schema command
+#[derive(Args)]
+pub struct SchemaArgs {
+    /// File to describe
+    #[arg(value_enum, help = "File to print the JSON Schema of")]
+    pub kind: SchemaKind,
+}
+
+/// Files `schema` can describe
+#[derive(clap::ValueEnum, Clone)]
+pub enum SchemaKind {
+    /// An agent's .crowdcontrol/metadata.json
+    Metadata,
+    /// The settings file, config.toml
+    Config,
+}
+
+/// Arguments for the import command
+#[derive(Args)]
+pub struct ImportArgs {
+    /// Agent spec to import
+    #[arg(
+        value_name = "FILE",
+        help = "Agent spec produced by `crowdcontrol export` (JSON or YAML; reads stdin if omitted or -)"
+    )]
+    pub file: Option<PathBuf>,
+
+    /// Create the agent under a different name
+    #[arg(
+        long,
+        help = "Name for the imported agent (defaults to the name in the spec)"
+    )]
+    pub name: Option<String>,
+
+    /// SSH key for cloning SSH repository URLs
+    #[arg(
+        long,
+        help = "Private SSH key used by git on the host to clone SSH URLs (defaults to ssh-agent/~/.ssh)"
+    )]
+    pub ssh_key: Option<PathBuf>,
+
+    /// Accept unknown SSH host keys on first connection
+    #[arg(
+        long,
+        help = "Trust unknown SSH host keys on first use (StrictHostKeyChecking=accept-new)"
+    )]
+    pub ssh_accept_new: bool,
+}
+
+/// Arguments for the clone command
+#[derive(Args)]
+pub struct CloneArgs {
+    /// Agent to copy
+    #[arg(help = "Name of the agent to copy")]
+    pub source: String,
+
+    /// Name for the new agent
+    #[arg(help = "Unique name for the new agent")]
+    pub new_name: String,
+
+    /// Check out a different branch in the new agent
+    #[arg(
+        short,
+        long,
+        help = "Git branch for the new agent (defaults to the source agent's branch)"
+    )]
+    pub branch: Option<String>,
+
+    /// SSH key for cloning SSH repository URLs
+    #[arg(
+        long,
+        help = "Private SSH key used by git on the host to clone SSH URLs (defaults to ssh-agent/~/.ssh)"
+    )]
+    pub ssh_key: Option<PathBuf>,
+
+    /// Accept unknown SSH host keys on first connection
+    #[arg(
+        long,
+        help = "Trust unknown SSH host keys on first use (StrictHostKeyChecking=accept-new)"
+    )]
+    pub ssh_accept_new: bool,
+}
+
+/// Arguments for the config command
+#[derive(Args)]
+pub struct ConfigArgs {
+    #[command(subcommand)]
+    pub action: ConfigAction,
+}
+
+/// Config subcommands
+#[derive(clap::Subcommand)]
+pub enum ConfigAction {
+    /// Print the effective configuration and where each value came from
+    Show {
+        /// Output format
+        #[arg(long, value_enum, default_value = "toml", help = "Output format")]
+        format: ConfigFormat,
+    },
+
+    /// Print the config files that are loaded
+    Path,
+
+    /// List the presets available to `new --template`
+    Templates,
+}
+
+/// Output formats for `config show`
+#[derive(clap::ValueEnum, Clone)]
+pub enum ConfigFormat {
+    Toml,
+    Json,
+}
+
+/// Arguments for the version command
+#[derive(Args)]
+pub struct VersionArgs {
+    /// Include details for bug reports
+    #[arg(
+        long,
+        help = "Also show the platform, Docker versions and image, for bug reports (same as --verbose)"
+    )]
+    pub long: bool,
 }
 
 /// Arguments for the completions command
@@ -171,18 +1056,71 @@ pub struct CompletionsArgs {
 #[derive(Args)]
 pub struct RefreshArgs {
     /// Name of the agent to refresh Claude Code authentication for
-    #[arg(help = "Name of the agent to refresh Claude Code authentication for")]
-    pub name: String,
-    
+    #[arg(
+        required_unless_present = "all",
+        help = "Name of the agent to refresh Claude Code authentication for"
+    )]
+    pub name: Option<String>,
+
+    /// Refresh every running agent
+    #[arg(
+        long,
+        conflicts_with = "name",
+        help = "Refresh all running agents, skipping the others"
+    )]
+    pub all: bool,
+
     /// Extract credentials from macOS keychain
-    #[arg(long, help = "Extract Claude Code credentials from macOS keychain (macOS only)")]
+    #[arg(
+        long,
+        help = "Extract Claude Code credentials from macOS keychain (macOS only)"
+    )]
     pub extract_keychain: bool,
+
+    /// Keychain service to read credentials from
+    #[arg(
+        long,
+        value_name = "SERVICE",
+        requires = "extract_keychain",
+        help = "With --extract-keychain, the keychain service holding the credentials \
+                (default from keychain_service in the config file, \"Claude Code-credentials\")"
+    )]
+    pub keychain_service: Option<String>,
 }
 
 /// Output format options
 #[derive(clap::ValueEnum, Clone)]
 pub enum OutputFormat {
     Table,
+    /// Table with container ID, image and ports columns
+    Wide,
+    Json,
+    Yaml,
+    /// One compact JSON object per line, printed as each agent is ready
+    Ndjson,
+}
+
+/// How timestamps are shown in tables
+#[derive(clap::ValueEnum, Clone, Copy)]
+pub enum TimeFormat {
+    /// Time elapsed, e.g. "3h ago"
+    Relative,
+    /// Local date and time
+    Absolute,
+    /// RFC3339 timestamp in UTC
+    Iso,
+}
+
+/// Output format options for commands that report a single result
+#[derive(clap::ValueEnum, Clone)]
+pub enum NewOutputFormat {
+    Text,
+    Json,
+}
+
+/// Document formats for exported agent specs
+#[derive(clap::ValueEnum, Clone)]
+pub enum SpecFormat {
     Json,
     Yaml,
 }
@@ -191,7 +1129,39 @@ pub enum OutputFormat {
 #[derive(clap::ValueEnum, Clone)]
 pub enum AgentStatusFilter {
     Running,
+    Paused,
     Stopped,
     Created,
     Error,
+    Starting,
+    Restarting,
+}
+
+impl AgentStatusFilter {
+    /// The agent status this filter selects
+    pub fn status(&self) -> AgentStatus {
+        match self {
+            AgentStatusFilter::Running => AgentStatus::Running,
+            AgentStatusFilter::Paused => AgentStatus::Paused,
+            AgentStatusFilter::Stopped => AgentStatus::Stopped,
+            AgentStatusFilter::Created => AgentStatus::Created,
+            AgentStatusFilter::Error => AgentStatus::Error,
+            AgentStatusFilter::Starting => AgentStatus::Starting,
+            AgentStatusFilter::Restarting => AgentStatus::Restarting,
+        }
+    }
+
+    /// Whether an agent's live status passes this filter
+    pub fn matches(&self, status: &AgentStatus) -> bool {
+        matches!(
+            (self, status),
+            (AgentStatusFilter::Running, AgentStatus::Running)
+                | (AgentStatusFilter::Paused, AgentStatus::Paused)
+                | (AgentStatusFilter::Stopped, AgentStatus::Stopped)
+                | (AgentStatusFilter::Created, AgentStatus::Created)
+                | (AgentStatusFilter::Error, AgentStatus::Error)
+                | (AgentStatusFilter::Starting, AgentStatus::Starting)
+                | (AgentStatusFilter::Restarting, AgentStatus::Restarting)
+        )
+    }
 }
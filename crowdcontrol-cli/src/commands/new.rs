@@ -1,109 +1,685 @@
 use anyhow::{anyhow, Context, Result};
 use chrono::Utc;
+use colored::Colorize;
+use dialoguer::Confirm;
+use indicatif::ProgressBar;
+use serde::Serialize;
+use std::env;
 use std::fs;
+use std::io::{self, Write};
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
-use crate::commands::NewArgs;
+use crate::commands::start::run_lifecycle_commands;
+use crate::commands::{NewArgs, NewOutputFormat};
 use crate::utils::*;
 use crowdcontrol_core::{
-    clone_repository, save_agent_metadata, validate_agent_name, verify_repository_setup, Agent,
-    AgentStatus, Config, DockerClient,
+    clone_repository_with_ssh, copy_local_repository, create_branch, detect_current_branch,
+    parse_duration, resolve_env_passthrough, save_agent_metadata, validate_agent_name,
+    validate_memory_swap, validate_platform, validate_repository_url, verify_repository_setup,
+    with_timeout, Agent, AgentManager, AgentStatus, Config, ContainerOptions, CrowdControlError,
+    DockerClient, RepoConfig, RepositorySetupReport, SshOptions, REPOSITORY_SETUP_FILES,
 };
+use crowdcontrol_core::{find_template, templates_path, Template};
+use crowdcontrol_core::{run_until_cancelled, CancellationToken};
+
+/// Repository setup script, relative to the repository root
+const SETUP_SCRIPT: &str = ".crowdcontrol/setup.sh";
+
+/// Marker the container entrypoint checks before running the setup script itself
+const SETUP_COMPLETE_MARKER: &str = ".crowdcontrol/.setup-complete";
+
+/// Summary of a newly created agent, emitted with `--format json`
+#[derive(Serialize)]
+struct NewAgentOutput {
+    name: String,
+    workspace_path: PathBuf,
+    container_id: Option<String>,
+    repository: String,
+    branch: Option<String>,
+    has_crowdcontrol: bool,
+    /// The files of `.crowdcontrol/` the repository has, e.g. `setup.sh`
+    setup_files: Vec<&'static str>,
+}
+
+pub async fn execute(config: Config, args: NewArgs, cancel: CancellationToken) -> Result<()> {
+    match args.format {
+        NewOutputFormat::Text => create_agent(config, args, false, &cancel).await.map(|_| ()),
+        NewOutputFormat::Json => match create_agent(config, args, true, &cancel).await {
+            Ok(output) => {
+                println!("{}", serde_json::to_string_pretty(&output)?);
+                Ok(())
+            }
+            Err(e) => {
+                // Emit the error as JSON and exit without the default human error message
+                eprintln!("{}", serde_json::json!({ "error": format!("{:#}", e) }));
+                std::process::exit(crate::exit_code(&e));
+            }
+        },
+    }
+}
+
+async fn create_agent(
+    config: Config,
+    mut args: NewArgs,
+    quiet: bool,
+    cancel: &CancellationToken,
+) -> Result<NewAgentOutput> {
+    let progress = |message: &str| {
+        if quiet {
+            ProgressBar::hidden()
+        } else {
+            create_progress_bar(message)
+        }
+    };
 
-pub async fn execute(config: Config, args: NewArgs) -> Result<()> {
     // Validate agent name
     validate_agent_name(&args.name)?;
 
+    for dependency in &args.depends_on {
+        validate_agent_name(dependency)?;
+        if *dependency == args.name {
+            return Err(anyhow!("Agent '{}' can't depend on itself", args.name));
+        }
+        // The dependency may be created later; start only warns while it's missing
+        if !quiet && !config.agent_workspace_path(dependency).exists() {
+            print_warning(&format!("Agent '{}' doesn't exist yet", dependency));
+        }
+    }
+
+    // Fill in options from the template; flags given on the command line win
+    let template_env = match args.template.clone() {
+        Some(name) => apply_template(&mut args, &name)?,
+        None => Vec::new(),
+    };
+
+    if let Some(idle_timeout) = &args.idle_timeout {
+        parse_duration(idle_timeout)?;
+    }
+
+    // The flag is checked by clap; a default from the config file is checked here
+    if let Some(platform) = &config.default_platform {
+        validate_platform(platform)?;
+    }
+
+    // Docker rejects a swap limit below the memory limit; catch it before cloning
+    validate_memory_swap(
+        args.memory.as_deref().or(config.default_memory.as_deref()),
+        args.memory_swap.as_deref(),
+    )?;
+
+    // Resolve where the code comes from: a remote to clone or a local checkout
+    let local_source = match &args.from_local {
+        Some(path) => Some(
+            path.canonicalize()
+                .with_context(|| format!("Local repository not found: {:?}", path))?,
+        ),
+        None => None,
+    };
+    let (repository, branch) = match (&local_source, &args.repository) {
+        (Some(path), _) => (path.display().to_string(), detect_current_branch(path)),
+        // Reject URLs git can't clone before creating anything
+        (None, Some(repository)) => (validate_repository_url(repository)?, args.branch.clone()),
+        (None, None) => return Err(anyhow!("A repository URL or --from-local path is required")),
+    };
+
+    // With --mount the container works directly on the local checkout; the agent
+    // workspace then only holds CrowdControl's metadata
+    let local_mount = local_source.clone().filter(|_| args.mount);
+
     // Check if agent already exists
     let workspace_path = config.agent_workspace_path(&args.name);
+    // Where the repository goes in the workspace, depending on the layout
+    let repo_path = config.agent_repo_path(&args.name);
     if workspace_path.exists() {
-        return Err(anyhow!("Agent '{}' already exists", args.name));
+        return Err(CrowdControlError::AgentAlreadyExists(args.name.clone()).into());
     }
 
-    print_info(&format!("Creating new agent: {}", args.name));
+    if !quiet {
+        print_info(&format!("Creating new agent: {}", args.name));
+    }
 
     // Create workspace directory
     fs::create_dir_all(&workspace_path)
         .with_context(|| format!("Failed to create workspace directory: {:?}", workspace_path))?;
 
-    // Clone repository directly to workspace root
-    let pb = create_progress_bar("Cloning repository...");
+    // Any failure from here on removes the workspace, and the container once creating
+    // it has been attempted, so a failed `new` can simply be retried. Ctrl-C counts as
+    // a failure. A failing setup script or lifecycle command leaves a complete agent,
+    // which is kept so the failure can be fixed in place.
+    let name = args.name.clone();
+    let created_workspace = workspace_path.clone();
+    let setup_marker = local_mount
+        .clone()
+        .unwrap_or_else(|| repo_path.clone())
+        .join(SETUP_COMPLETE_MARKER);
+    let mut creating_container = false;
+    let mut keep_agent = false;
+    let mut running_setup = false;
+    let work = async {
+        // Clone the repository into the workspace (or copy the local checkout there)
+        let pb = progress(match &local_source {
+            Some(_) if local_mount.is_some() => "Preparing workspace...",
+            Some(_) => "Copying local repository...",
+            None => "Cloning repository...",
+        });
 
-    // Wrap clone operation in a closure that handles cleanup on failure
-    let clone_result =
-        (|| clone_repository(&args.repository, &workspace_path, args.branch.as_deref()))();
+        // Clone the repository and check out the branch to create, if any
+        let ssh = SshOptions {
+            key: args.ssh_key.clone(),
+            accept_new_host_keys: args.ssh_accept_new,
+        };
+        let clone_result = (|| {
+            match &local_source {
+                Some(_) if local_mount.is_some() => Ok(()),
+                Some(source) => copy_local_repository(source, &repo_path),
+                None => clone_repository_with_ssh(&repository, &repo_path, branch.as_deref(), &ssh),
+            }?;
+            match &args.create_branch {
+                Some(new_branch) => create_branch(&repo_path, new_branch, args.force),
+                None => Ok(()),
+            }
+        })();
 
-    pb.finish_and_clear();
+        pb.finish_and_clear();
 
-    // If clone failed, cleanup workspace directory and return the error
-    if let Err(e) = clone_result {
-        // Cleanup the workspace directory we created
-        if let Err(cleanup_err) = fs::remove_dir_all(&workspace_path) {
-            eprintln!(
-                "Warning: Failed to cleanup workspace directory after clone failure: {}",
-                cleanup_err
-            );
+        // The clone itself can't be interrupted, so check for Ctrl-C once it's done
+        if cancel.is_cancelled() {
+            return Err(CrowdControlError::Interrupted.into());
+        }
+        clone_result?;
+
+        if !quiet {
+            match &local_source {
+                Some(source) if local_mount.is_some() => print_success(&format!(
+                    "Local repository {} will be mounted at /workspace",
+                    source.display()
+                )),
+                Some(_) => print_success("Repository copied successfully"),
+                None => print_success("Repository cloned successfully"),
+            }
+            if let Some(new_branch) = &args.create_branch {
+                print_success(&format!("Created branch '{}'", new_branch));
+            }
         }
-        return Err(e);
-    }
 
-    print_success("Repository cloned successfully");
+        // The agent works on the branch it created
+        let branch = args.create_branch.clone().or(branch);
 
-    // Verify repository setup if not skipped
-    if !args.skip_verification {
-        let has_crowdcontrol = verify_repository_setup(&workspace_path)?;
-        if !has_crowdcontrol {
-            print_warning("Repository does not contain .crowdcontrol/ directory");
-            print_info(
-                "The container will start but repository-specific setup scripts will not run",
-            );
+        // The repository the container works on
+        let repo_root = local_mount.clone().unwrap_or_else(|| repo_path.clone());
+
+        // Verify repository setup if not skipped
+        let setup = verify_repository_setup(&repo_root)?;
+        if !args.skip_verification {
+            if !setup.has_crowdcontrol_dir {
+                if args.require_crowdcontrol || config.require_crowdcontrol {
+                    return Err(anyhow!(
+                        "Repository does not contain .crowdcontrol/ directory, which is required \
+                         here (require_crowdcontrol). Use --skip-verification to create the \
+                         agent anyway"
+                    ));
+                }
+                if !quiet {
+                    print_warning("Repository does not contain .crowdcontrol/ directory");
+                    print_info(
+                        "The container will start but repository-specific setup scripts will \
+                         not run",
+                    );
+                }
+            } else if !quiet {
+                print_repository_setup(&setup);
+            }
         }
-    }
 
-    // Create Docker client
-    let docker = DockerClient::new(config.clone())?;
+        // Setup runs whenever the repository has a script; --run-setup insists on one
+        let has_setup_script = repo_root.join(SETUP_SCRIPT).is_file();
+        if args.run_setup && !has_setup_script {
+            return Err(anyhow!(
+                "--run-setup was given but the repository has no {}",
+                SETUP_SCRIPT
+            ));
+        }
+        let run_setup = has_setup_script && !args.skip_setup;
 
-    // Check if container already exists
-    if docker
-        .container_exists(&format!("crowdcontrol-{}", args.name))
-        .await?
-    {
-        print_warning(&format!(
-            "Container crowdcontrol-{} already exists",
-            args.name
-        ));
-    } else {
-        // Pull image if needed
-        docker.pull_image().await?;
+        if args.network.as_deref() == Some("none") && !quiet {
+            warn_network_disabled();
+        }
+        if args.readonly_workspace && !quiet {
+            warn_readonly_workspace();
+        }
 
-        // Create container with defaults from config if not specified
-        let pb = create_progress_bar("Creating container...");
+        // Defaults from config apply where no flag was given
         let memory = args.memory.or(config.default_memory.clone());
         let cpus = args.cpus.or(config.default_cpus.clone());
-        let container_id = docker
-            .create_container(&args.name, &workspace_path, memory, cpus)
-            .await?;
-        pb.finish_and_clear();
-        print_success("Container created successfully");
-
-        // Save agent metadata
+        let no_mount_claude = args.no_mount_claude || !config.mount_claude_config;
+        let platform = args.platform.clone().or(config.default_platform.clone());
         let agent = Agent {
             name: args.name.clone(),
             status: AgentStatus::Created,
-            container_id: Some(container_id),
-            repository: args.repository.clone(),
-            branch: args.branch.clone(),
+            container_id: None,
+            repository: repository.clone(),
+            branch: branch.clone(),
             created_at: Utc::now(),
             workspace_path: workspace_path.clone(),
+            memory,
+            memory_swap: args.memory_swap.clone(),
+            memory_reservation: args.memory_reservation.clone(),
+            cpus,
+            tags: args.tag.clone(),
+            local_mount,
+            network: args.network.clone(),
+            privileged: args.privileged,
+            cap_add: args.cap_add.clone(),
+            read_only: args.read_only,
+            readonly_workspace: args.readonly_workspace,
+            no_mount_claude,
+            platform,
+            // The image the container is created from, so recreating it uses the same one
+            image: Some(args.image.clone().unwrap_or_else(|| config.image.clone())),
+            restart_policy: args.restart.clone(),
+            gpus: args.gpus.clone(),
+            volumes: args.volume.clone(),
+            idle_timeout: args.idle_timeout.clone(),
+            post_create_done: false,
+            depends_on: args.depends_on.clone(),
+            last_activity: None,
+            workspace_layout: config.workspace_layout,
+        };
+
+        let manager = AgentManager::new(config.clone());
+        let mut container_id = None;
+        let mut started = false;
+
+        if args.workspace_only {
+            // Environment variables aren't part of the metadata, so `start` can't apply them
+            if !quiet && (!template_env.is_empty() || !args.env_passthrough.is_empty()) {
+                print_warning(
+                    "Environment variables from --env-passthrough or the template are only set on \
+                 containers created by new, not by start",
+                );
+            }
+            save_agent_metadata(&config, &agent)?;
+        } else if manager
+            .docker()?
+            .container_exists(&config.container_name(&args.name))
+            .await?
+        {
+            if !quiet {
+                print_warning(&format!(
+                    "Container {} already exists",
+                    config.container_name(&args.name)
+                ));
+            }
+        } else {
+            let docker = manager.docker()?;
+
+            // Pull image if needed, falling back to building it from a local Dockerfile
+            let passthrough =
+                resolve_env_passthrough(&args.env_passthrough, |name| env::var(name).ok());
+            if !quiet {
+                for name in &passthrough.missing {
+                    print_warning(&format!(
+                        "Environment variable {} is not set, not passing it through",
+                        name
+                    ));
+                }
+            }
+
+            // A pull or create that hangs (e.g. emulating another platform) is abandoned
+            let create_timeout = Duration::from_secs(args.create_timeout);
+            let image = agent.image.as_deref().unwrap_or(&config.image);
+            let pulled = with_timeout(
+                &format!("image {} to be pulled", image),
+                create_timeout,
+                docker.pull_image_for_platform(
+                    image,
+                    !quiet && !is_quiet(),
+                    agent.platform.as_deref(),
+                ),
+            )
+            .await;
+            match pulled {
+                Ok(()) => {}
+                // Building instead would only hide a stalled pull
+                Err(e) if timed_out(&e) => return Err(e),
+                Err(pull_err) => {
+                    ensure_image_built(docker, image, args.build, quiet, pull_err).await?
+                }
+            }
+
+            // Repository config may define a health check; the CLI flag takes precedence
+            let repo_config = RepoConfig::load(&repo_root)?;
+            let has_lifecycle_commands = repo_config.has_lifecycle_commands();
+
+            // Create container
+            let pb = progress("Creating container...");
+            let options = ContainerOptions {
+                healthcheck: args.healthcheck.or(repo_config.healthcheck),
+                env: template_env.into_iter().chain(passthrough.vars).collect(),
+                ..agent.container_options()
+            };
+
+            // Creates the container and saves the agent's metadata
+            creating_container = true;
+            let registered = with_timeout(
+                &format!(
+                    "container {} to be created",
+                    config.container_name(&args.name)
+                ),
+                create_timeout,
+                manager.register(agent, &options),
+            )
+            .await;
+            pb.finish_and_clear();
+            let agent = registered?;
+            if !quiet {
+                print_success("Container created successfully");
+            }
+
+            if run_setup {
+                started = true;
+                keep_agent = true;
+                running_setup = true;
+                run_setup_script(&manager, &agent, args.ignore_setup_errors, quiet).await?;
+                running_setup = false;
+            }
+
+            // Lifecycle commands run in the container, so the agent starts with them
+            if has_lifecycle_commands {
+                if !started {
+                    started = true;
+                    let warnings = manager.start_agent(&agent, &AgentStatus::Created).await?;
+                    if !quiet {
+                        for warning in &warnings {
+                            print_warning(warning);
+                        }
+                    }
+                }
+                keep_agent = true;
+                run_lifecycle_commands(&manager, &agent, args.ignore_lifecycle_errors, quiet)
+                    .await?;
+            }
+            container_id = agent.container_id;
+        }
+
+        if !quiet {
+            print_success(&format!("Agent '{}' setup complete!", args.name));
+            if started {
+                print_info(&format!(
+                    "Connect to the agent with: crowdcontrol connect {}",
+                    args.name
+                ));
+            } else {
+                print_info(&format!(
+                    "Start the agent with: crowdcontrol start {}",
+                    args.name
+                ));
+            }
+        }
+
+        Ok::<_, anyhow::Error>(NewAgentOutput {
+            name: args.name,
+            workspace_path,
+            container_id,
+            repository,
+            branch,
+            has_crowdcontrol: setup.has_crowdcontrol_dir,
+            setup_files: setup
+                .files
+                .iter()
+                .filter(|(_, present)| *present)
+                .map(|(name, _)| *name)
+                .collect(),
+        })
+    };
+
+    // Ctrl-C drops the work at its next await, leaving it to be rolled back here
+    let result = match run_until_cancelled(cancel, work).await {
+        Some(result) => result,
+        None => {
+            if running_setup {
+                // Let the entrypoint run the interrupted setup script on the next start
+                let _ = fs::remove_file(&setup_marker);
+            }
+            if keep_agent && !quiet {
+                print_warning(&format!(
+                    "Agent '{}' was already created when interrupted and has been kept",
+                    name
+                ));
+            }
+            Err(CrowdControlError::Interrupted.into())
+        }
+    };
+
+    if result.is_err() && !keep_agent {
+        roll_back_new_agent(&config, &name, &created_workspace, creating_container).await;
+    }
+    result
+}
+
+/// Start a newly created agent and run the repository setup script in it as the
+/// developer user, streaming its output. With `--format json` the output goes to
+/// stderr so stdout stays parseable.
+async fn run_setup_script(
+    manager: &AgentManager,
+    agent: &Agent,
+    ignore_errors: bool,
+    quiet: bool,
+) -> Result<()> {
+    let container_id = agent
+        .container_id
+        .as_deref()
+        .ok_or_else(|| anyhow!("No container ID found for agent '{}'", agent.name))?;
+
+    // Mark setup as done up front so the entrypoint doesn't run the script at the same time
+    let marker = agent.repo_path().join(SETUP_COMPLETE_MARKER);
+    fs::write(&marker, "")
+        .with_context(|| format!("Failed to write setup marker: {:?}", marker))?;
+
+    let warnings = manager.start_agent(agent, &AgentStatus::Created).await?;
+    if !quiet {
+        for warning in &warnings {
+            print_warning(warning);
+        }
+        print_info(&format!("Running {}...", SETUP_SCRIPT));
+    }
+
+    let mut output: Box<dyn Write> = if quiet {
+        Box::new(io::stderr())
+    } else if is_quiet() {
+        Box::new(io::sink())
+    } else {
+        Box::new(io::stdout())
+    };
+    let script = format!("cd {} && bash {}", agent.container_repo_dir(), SETUP_SCRIPT);
+    let exit_code = manager
+        .docker()?
+        .exec_streaming_as_user(
+            container_id,
+            vec!["bash", "-c", &script],
+            Some("developer"),
+            &mut output,
+        )
+        .await?;
+
+    if exit_code == 0 {
+        if !quiet {
+            print_success("Setup script completed");
+        }
+        return Ok(());
+    }
+
+    let message = format!("{} failed with exit code {}", SETUP_SCRIPT, exit_code);
+    if ignore_errors {
+        if !quiet {
+            print_warning(&message);
+        }
+        return Ok(());
+    }
+
+    // Let the entrypoint retry the script the next time the agent starts
+    let _ = fs::remove_file(&marker);
+    Err(anyhow!(
+        "{}. The agent '{}' was created and is running; fix the script and rerun it from \
+         `crowdcontrol connect {} --shell`, or pass --ignore-setup-errors",
+        message,
+        agent.name,
+        agent.name
+    ))
+}
+
+/// Checklist of the `.crowdcontrol/` files the repository provides
+fn print_repository_setup(setup: &RepositorySetupReport) {
+    if is_quiet() {
+        return;
+    }
+    print_info("Repository setup (.crowdcontrol/):");
+    for ((name, present), (_, description)) in setup.files.iter().zip(REPOSITORY_SETUP_FILES) {
+        let mark = if *present {
+            "✓".green()
+        } else {
+            "✗".dimmed()
         };
+        println!("  {} {:<12} {}", mark, name, description.dimmed());
+    }
+}
 
-        save_agent_metadata(&config, &agent)?;
+/// Merge the named template into `args` and return the environment variables it sets
+fn apply_template(args: &mut NewArgs, name: &str) -> Result<Vec<String>> {
+    let path = templates_path()
+        .ok_or_else(|| anyhow!("Could not determine the config directory for templates"))?;
+    let template = find_template(&path, name)?;
+
+    let explicit = Template {
+        memory: args.memory.take(),
+        cpus: args.cpus.take(),
+        network: args.network.take(),
+        platform: args.platform.take(),
+        idle_timeout: args.idle_timeout.take(),
+        tags: mem::take(&mut args.tag),
+        env: Default::default(),
+        env_passthrough: mem::take(&mut args.env_passthrough),
+    };
+    let merged = template.with_overrides(&explicit);
+
+    args.memory = merged.memory.clone();
+    args.cpus = merged.cpus.clone();
+    args.network = merged.network.clone();
+    args.platform = merged.platform.clone();
+    args.idle_timeout = merged.idle_timeout.clone();
+    args.tag = merged.tags.clone();
+    args.env_passthrough = merged.env_passthrough.clone();
+
+    Ok(merged.env_vars())
+}
+
+fn timed_out(error: &anyhow::Error) -> bool {
+    matches!(
+        CrowdControlError::find(error),
+        Some(CrowdControlError::Timeout { .. })
+    )
+}
+
+/// Undo a failed `new`: remove the workspace it created and, if it got as far as
+/// creating one, the agent's container. The container is looked up by name, since a
+/// create that timed out or failed afterwards never reported its ID. Cleanup failures
+/// are reported but don't replace the original error.
+async fn roll_back_new_agent(
+    config: &Config,
+    name: &str,
+    workspace_path: &Path,
+    remove_container: bool,
+) {
+    if remove_container {
+        let container_name = config.container_name(name);
+        let removed = async {
+            let docker = DockerClient::new(config.clone())?;
+            if docker.container_exists(&container_name).await? {
+                docker.remove_container(&container_name).await?;
+            }
+            Ok::<_, anyhow::Error>(())
+        }
+        .await;
+        if let Err(e) = removed {
+            eprintln!(
+                "Warning: Failed to remove container {} after the error: {:#}",
+                container_name, e
+            );
+        }
     }
 
-    print_success(&format!("Agent '{}' setup complete!", args.name));
-    print_info(&format!(
-        "Start the agent with: crowdcontrol start {}",
-        args.name
-    ));
+    if let Err(e) = fs::remove_dir_all(workspace_path) {
+        eprintln!(
+            "Warning: Failed to cleanup workspace directory {:?} after the error: {}",
+            workspace_path, e
+        );
+    }
+}
+
+/// Build the agent's image when it couldn't be pulled, if a Dockerfile is available.
+/// Prompts unless `build` is set; in quiet mode (no prompt possible) `build` is required.
+async fn ensure_image_built(
+    docker: &DockerClient,
+    image: &str,
+    build: bool,
+    quiet: bool,
+    pull_err: anyhow::Error,
+) -> Result<()> {
+    let Some(context_dir) = find_build_context() else {
+        return Err(anyhow!(
+            "Image '{}' was not found locally and could not be pulled: {:#}\n\
+             No container/Dockerfile was found to build it from. Build the image manually \
+             or choose another with --image.",
+            image,
+            pull_err
+        ));
+    };
+
+    let confirmed = build
+        || (!quiet
+            && Confirm::new()
+                .with_prompt(format!(
+                    "Image '{}' could not be pulled. Build it from {}?",
+                    image,
+                    context_dir.join("Dockerfile").display()
+                ))
+                .default(true)
+                .interact()?);
+
+    if !confirmed {
+        return Err(anyhow!(
+            "Image '{}' was not found locally and could not be pulled: {:#}\n\
+             Rerun with --build to build it from {}",
+            image,
+            pull_err,
+            context_dir.join("Dockerfile").display()
+        ));
+    }
+
+    if !quiet {
+        print_info(&format!("Building image {}...", image));
+    }
+    docker
+        .build_image(image, &context_dir, !quiet && !is_quiet())
+        .await?;
+    if !quiet {
+        print_success(&format!("Built image {}", image));
+    }
 
     Ok(())
 }
+
+/// Find a `container/` directory with a Dockerfile, looking upwards from the
+/// current directory and from the location of the running binary
+fn find_build_context() -> Option<PathBuf> {
+    let starts = [env::current_dir().ok(), env::current_exe().ok()];
+
+    starts
+        .iter()
+        .flatten()
+        .flat_map(|start| start.ancestors())
+        .map(|dir| dir.join("container"))
+        .find(|dir| dir.join("Dockerfile").is_file())
+}
@@ -0,0 +1,37 @@
+use anyhow::{anyhow, Result};
+
+use crate::commands::PauseArgs;
+use crate::utils::*;
+use crowdcontrol_core::load_agent_metadata;
+use crowdcontrol_core::Config;
+use crowdcontrol_core::{AgentStatus, CrowdControlError, DockerClient};
+pub async fn execute(config: Config, args: PauseArgs) -> Result<()> {
+    let agent = load_agent_metadata(&config, &args.name)?;
+    let docker = DockerClient::new(config.clone())?;
+
+    // Check current status (validates container_id and gets live status)
+    let status = agent.compute_live_status(&docker).await?;
+    match status {
+        AgentStatus::Running | AgentStatus::Starting => {}
+        AgentStatus::Paused => {
+            print_info(&format!("Agent '{}' is already paused", args.name));
+            return Ok(());
+        }
+        _ => return Err(CrowdControlError::not_running(&args.name).into()),
+    }
+
+    let container_id = agent
+        .container_id
+        .as_ref()
+        .ok_or_else(|| anyhow!("No container ID found for agent '{}'", args.name))?;
+
+    docker.pause_container(container_id).await?;
+
+    print_success(&format!("Agent '{}' paused", args.name));
+    print_info(&format!(
+        "Resume it with: crowdcontrol unpause {}",
+        args.name
+    ));
+
+    Ok(())
+}
@@ -0,0 +1,78 @@
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+
+use crate::commands::stop::stop_agent;
+use crate::commands::ReapArgs;
+use crate::utils::*;
+use crowdcontrol_core::docker::DEFAULT_STOP_TIMEOUT_SECS;
+use crowdcontrol_core::{
+    format_duration, list_all_agents, load_agent_metadata, select_agents_to_reap, AgentManager,
+    Config,
+};
+
+/// Stop running agents whose idle timeout has passed. Non-interactive, so it can run from cron;
+/// exits non-zero only if an agent couldn't be checked or stopped.
+pub async fn execute(config: Config, args: ReapArgs) -> Result<()> {
+    let manager = AgentManager::new(config.clone());
+    let docker = manager.docker()?;
+    let mut error_count = 0;
+
+    let mut agents = Vec::new();
+    for agent_name in list_all_agents(&config)? {
+        let result = match load_agent_metadata(&config, &agent_name) {
+            Ok(agent) => agent
+                .compute_live_status(docker)
+                .await
+                .map(|status| (agent, status)),
+            Err(e) => Err(e),
+        };
+
+        match result {
+            Ok(entry) => agents.push(entry),
+            Err(e) => {
+                print_error(&format!("Failed to check {}: {}", agent_name, e));
+                error_count += 1;
+            }
+        }
+    }
+
+    let idle_agents = select_agents_to_reap(agents, Utc::now());
+    let mut stopped_count = 0;
+
+    for agent in &idle_agents {
+        let last_active = format_duration(agent.last_active_at());
+
+        if args.dry_run {
+            print_info(&format!(
+                "Would stop '{}' (last active {})",
+                agent.name, last_active
+            ));
+            continue;
+        }
+
+        print_info(&format!(
+            "Stopping idle agent '{}' (last active {})",
+            agent.name, last_active
+        ));
+        match stop_agent(&manager, &agent.name, false, DEFAULT_STOP_TIMEOUT_SECS).await {
+            Ok(true) => stopped_count += 1,
+            Ok(false) => {} // Stopped since we checked
+            Err(e) => {
+                print_error(&format!("Failed to stop {}: {}", agent.name, e));
+                error_count += 1;
+            }
+        }
+    }
+
+    if idle_agents.is_empty() {
+        print_info("No idle agents to stop");
+    } else if stopped_count > 0 {
+        print_success(&format!("Stopped {} idle agent(s)", stopped_count));
+    }
+
+    if error_count > 0 {
+        return Err(anyhow!("Failed to reap {} agent(s)", error_count));
+    }
+
+    Ok(())
+}
@@ -1,17 +1,41 @@
 use anyhow::{anyhow, Context, Result};
-use std::process::Command;
 
 use crate::commands::RefreshArgs;
 use crate::utils::*;
-use crowdcontrol_core::{Config, DockerClient, load_agent_metadata};
+use crowdcontrol_core::{
+    list_all_agents, load_agent_metadata, select_agents_to_refresh, Config, CrowdControlError,
+    DockerClient,
+};
+
+/// Script in the agent image that installs fresh Claude Code credentials
+const REFRESH_SCRIPT: &str = "/usr/local/bin/refresh-claude-auth.sh";
 
 pub async fn execute(config: Config, args: RefreshArgs) -> Result<()> {
+    if args.extract_keychain && !cfg!(target_os = "macos") {
+        print_warning("--extract-keychain flag is only supported on macOS");
+        return Ok(());
+    }
+
+    // The keychain service to read credentials from, with --extract-keychain
+    let keychain_service = args.extract_keychain.then(|| {
+        args.keychain_service
+            .unwrap_or_else(|| config.keychain_service.clone())
+    });
+
+    if args.all {
+        return refresh_all(&config, keychain_service.as_deref()).await;
+    }
+
+    let name = args
+        .name
+        .ok_or_else(|| anyhow!("Agent name required when not using --all"))?;
+
     // Load agent metadata
-    let agent = load_agent_metadata(&config, &args.name)?;
+    let agent = load_agent_metadata(&config, &name)?;
 
     print_info(&format!(
         "Refreshing Claude Code authentication for agent: {}",
-        args.name
+        name
     ));
 
     // Create Docker client
@@ -19,83 +43,157 @@ pub async fn execute(config: Config, args: RefreshArgs) -> Result<()> {
 
     // Get container status (validates container_id and gets live status)
     let status = agent.compute_live_status(&docker).await?;
-    if !matches!(status, crowdcontrol_core::AgentStatus::Running) {
-        return Err(anyhow!(
-            "Agent '{}' must be running to refresh configs. Start it first with: crowdcontrol start {}",
-            args.name, args.name
-        ));
+    if !status.is_running() {
+        return Err(CrowdControlError::not_running_to(&name, "refresh configs").into());
     }
 
-    // Get container name for exec operations
-    let container_name = format!("crowdcontrol-{}", args.name);
-
-    // Run the refresh script, optionally with keychain credentials
-    if args.extract_keychain {
-        if cfg!(target_os = "macos") {
-            let credentials = extract_keychain_credentials()?;
-            let cmd = vec!["/usr/local/bin/refresh-claude-auth.sh", &credentials];
-            docker
-                .exec_in_container(&container_name, cmd, false)
-                .await
-                .context("Failed to refresh Claude Code authentication with keychain credentials")?;
-        } else {
-            print_warning("--extract-keychain flag is only supported on macOS");
-            return Ok(());
-        }
-    } else {
-        // Run the standard refresh script in the container
-        let cmd = vec!["/usr/local/bin/refresh-claude-auth.sh"];
-        docker
-            .exec_in_container(&container_name, cmd, false)
-            .await
-            .context("Failed to refresh Claude Code authentication")?;
-    }
+    let credentials = match &keychain_service {
+        Some(service) => Some(extract_keychain_credentials(service)?),
+        None => None,
+    };
+    refresh_agent(&config, &docker, &name, credentials.as_deref()).await?;
 
     print_success(&format!(
         "Claude Code authentication refreshed successfully for agent '{}'",
-        args.name
+        name
     ));
     print_info("You can now use Claude Code with the updated authentication");
 
     Ok(())
 }
 
-#[cfg(target_os = "macos")]
-fn extract_keychain_credentials() -> Result<String> {
-    print_info("Extracting Claude Code credentials from macOS keychain...");
-    
-    // Try to extract credentials from keychain
-    let output = Command::new("security")
-        .args(&[
-            "find-generic-password",
-            "-s", "Claude Code-credentials",
-            "-a", &whoami::username(),
-            "-w"
-        ])
-        .output()
-        .context("Failed to execute security command")?;
-
-    if !output.status.success() {
-        return Err(anyhow!(
-            "No Claude Code credentials found in keychain. Make sure you're logged in to Claude Code."
+/// Refresh every running agent, skipping the others. Credentials from `keychain_service`
+/// are extracted once and passed to each agent.
+async fn refresh_all(config: &Config, keychain_service: Option<&str>) -> Result<()> {
+    let docker = DockerClient::new(config.clone())?;
+    let mut refreshed_count = 0;
+    let mut error_count = 0;
+
+    let mut agents = Vec::new();
+    for agent_name in list_all_agents(config)? {
+        let status = match load_agent_metadata(config, &agent_name) {
+            Ok(agent) => agent
+                .compute_live_status(&docker)
+                .await
+                .map(|status| (agent, status)),
+            Err(e) => Err(e),
+        };
+        match status {
+            Ok(entry) => agents.push(entry),
+            Err(e) => {
+                print_error(&format!("Failed to refresh {}: {}", agent_name, e));
+                error_count += 1;
+            }
+        }
+    }
+
+    let (agents, skipped) = select_agents_to_refresh(agents);
+    for (agent, status) in &skipped {
+        print_info(&format!(
+            "Skipping agent '{}': {:?}, not running",
+            agent.name, status
         ));
     }
 
-    let credentials = String::from_utf8(output.stdout)
-        .context("Invalid UTF-8 in keychain credentials")?
-        .trim()
-        .to_string();
+    if !agents.is_empty() {
+        let credentials = match keychain_service {
+            Some(service) => Some(extract_keychain_credentials(service)?),
+            None => None,
+        };
+
+        for agent in &agents {
+            let pb = create_progress_bar(&format!("Refreshing agent '{}'...", agent.name));
+            let result = refresh_agent(config, &docker, &agent.name, credentials.as_deref()).await;
+            pb.finish_and_clear();
+
+            match result {
+                Ok(()) => {
+                    print_success(&format!("Refreshed agent '{}'", agent.name));
+                    refreshed_count += 1;
+                }
+                Err(e) => {
+                    print_error(&format!("Failed to refresh {}: {:#}", agent.name, e));
+                    error_count += 1;
+                }
+            }
+        }
+    }
+
+    print_info(&format!(
+        "Refreshed {} agent(s), {} failed, {} skipped",
+        refreshed_count,
+        error_count,
+        skipped.len()
+    ));
+
+    if error_count > 0 {
+        return Err(anyhow!("Failed to refresh {} agent(s)", error_count));
+    }
+
+    Ok(())
+}
+
+/// Run the refresh script in a running agent's container, with keychain
+/// credentials if given
+async fn refresh_agent(
+    config: &Config,
+    docker: &DockerClient,
+    name: &str,
+    credentials: Option<&str>,
+) -> Result<()> {
+    // Get container name for exec operations
+    let container_name = config.container_name(name);
 
-    if credentials.is_empty() {
-        return Err(anyhow!("Empty credentials returned from keychain"));
+    match credentials {
+        Some(credentials) => docker
+            .exec_in_container(&container_name, vec![REFRESH_SCRIPT, credentials], false)
+            .await
+            .context("Failed to refresh Claude Code authentication with keychain credentials"),
+        None => docker
+            .exec_in_container(&container_name, vec![REFRESH_SCRIPT], false)
+            .await
+            .context("Failed to refresh Claude Code authentication"),
     }
+}
+
+#[cfg(target_os = "macos")]
+fn extract_keychain_credentials(service: &str) -> Result<String> {
+    print_info("Extracting Claude Code credentials from macOS keychain...");
+
+    // Try the configured service, then the names Claude Code has used before
+    let found = crowdcontrol_core::find_keychain_credentials(service, |service| {
+        let output = std::process::Command::new("security")
+            .args([
+                "find-generic-password",
+                "-s",
+                service,
+                "-a",
+                &whoami::username(),
+                "-w",
+            ])
+            .output()
+            .context("Failed to execute security command")?;
+
+        if !output.status.success() {
+            return Ok(None);
+        }
+
+        let credentials = String::from_utf8(output.stdout)
+            .context("Invalid UTF-8 in keychain credentials")?
+            .trim()
+            .to_string();
+        Ok(Some(credentials).filter(|credentials| !credentials.is_empty()))
+    })?;
+
+    print_success(&format!(
+        "Keychain credentials extracted from service '{}'",
+        found.service
+    ));
 
-    print_success("Keychain credentials extracted successfully");
-    
-    Ok(credentials)
+    Ok(found.credentials)
 }
 
 #[cfg(not(target_os = "macos"))]
-fn extract_keychain_credentials() -> Result<String> {
+fn extract_keychain_credentials(_service: &str) -> Result<String> {
     Err(anyhow!("Keychain extraction is only supported on macOS"))
 }
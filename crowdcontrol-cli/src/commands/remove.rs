@@ -1,27 +1,50 @@
-use anyhow::Result;
-use dialoguer::Confirm;
-use std::fs;
+use anyhow::{anyhow, Result};
+use dialoguer::{Confirm, Input};
+use std::io::IsTerminal;
 
+use crate::commands::stop::stop_agent;
 use crate::commands::RemoveArgs;
 use crate::utils::*;
-use crowdcontrol_core::load_agent_metadata;
+use crowdcontrol_core::docker::DEFAULT_STOP_TIMEOUT_SECS;
 use crowdcontrol_core::Config;
-use crowdcontrol_core::DockerClient;
+use crowdcontrol_core::{list_all_agents, load_agent_metadata};
+use crowdcontrol_core::{Agent, AgentManager, AgentStatus};
 pub async fn execute(config: Config, args: RemoveArgs) -> Result<()> {
+    if args.all {
+        return remove_all(&config, &args).await;
+    }
+
+    let name = args
+        .name
+        .clone()
+        .ok_or_else(|| anyhow!("Agent name required when not using --all"))?;
+
     // Load agent metadata
-    let agent = load_agent_metadata(&config, &args.name)?;
+    let agent = load_agent_metadata(&config, &name)?;
+
+    // Describe the removal without touching anything
+    if args.dry_run {
+        print_dry_run(&config, &agent, args.keep_workspace);
+        return Ok(());
+    }
+
+    let manager = AgentManager::new(config);
 
     // Confirm removal if not forced
     if !args.force {
+        // Refuse before asking, so a yes doesn't lead to an error
+        ensure_not_running(&manager, &agent).await?;
+
         let prompt = if args.keep_workspace {
             format!(
                 "Are you sure you want to remove the container for agent '{}'?",
-                args.name
+                name
             )
         } else {
             format!(
-                "Are you sure you want to remove agent '{}' and its workspace?",
-                args.name
+                "Are you sure you want to remove agent '{}' and delete its workspace {}?",
+                name,
+                agent.workspace_path.display()
             )
         };
 
@@ -36,40 +59,193 @@ pub async fn execute(config: Config, args: RemoveArgs) -> Result<()> {
         }
     }
 
-    // Create Docker client
-    let docker = DockerClient::new(config.clone())?;
+    remove_agent(&manager, &agent, args.keep_workspace, args.force).await
+}
+
+/// Remove every agent matching the `--status`/`--tag` filters, continuing past
+/// failures and reporting them at the end
+async fn remove_all(config: &Config, args: &RemoveArgs) -> Result<()> {
+    let manager = AgentManager::new(config.clone());
 
-    // Remove container if it exists
-    if let Some(container_id) = &agent.container_id {
-        let pb = create_progress_bar("Removing container...");
-        match docker.remove_container(container_id).await {
-            Ok(_) => {
-                pb.finish_and_clear();
-                print_success("Container removed successfully");
+    let mut agents = Vec::new();
+    for agent_name in list_all_agents(config)? {
+        let agent = match load_agent_metadata(config, &agent_name) {
+            Ok(agent) => agent,
+            Err(e) => {
+                print_warning(&format!("Skipping {}: {}", agent_name, e));
+                continue;
+            }
+        };
+
+        if let Some(tag) = &args.tag {
+            if !agent.has_tag(tag) {
+                continue;
             }
+        }
+
+        // A status we can't determine aborts the whole removal rather than guessing
+        if let Some(filter) = &args.status {
+            let status = agent.compute_live_status(manager.docker()?).await?;
+            if !filter.matches(&status) {
+                continue;
+            }
+        }
+
+        agents.push(agent);
+    }
+
+    if agents.is_empty() {
+        print_info("No agents to remove");
+        return Ok(());
+    }
+
+    if args.dry_run {
+        for agent in &agents {
+            print_dry_run(config, agent, args.keep_workspace);
+        }
+        return Ok(());
+    }
+
+    if !args.force && !confirm_remove_all(&agents, args.keep_workspace)? {
+        print_info("Removal cancelled");
+        return Ok(());
+    }
+
+    let mut removed_count = 0;
+    let mut error_count = 0;
+
+    for agent in &agents {
+        match remove_agent(&manager, agent, args.keep_workspace, args.force).await {
+            Ok(()) => removed_count += 1,
             Err(e) => {
-                pb.finish_and_clear();
-                print_warning(&format!("Failed to remove container: {}", e));
+                print_error(&format!("Failed to remove {}: {}", agent.name, e));
+                error_count += 1;
             }
         }
     }
 
-    // Remove workspace directory if requested
-    if !args.keep_workspace {
-        let pb = create_progress_bar("Removing workspace directory...");
-        fs::remove_dir_all(&agent.workspace_path)?;
-        pb.finish_and_clear();
-        print_success("Workspace directory removed successfully");
+    if removed_count > 0 {
+        print_success(&format!("Removed {} agent(s)", removed_count));
+    }
+
+    if error_count > 0 {
+        return Err(anyhow!("Failed to remove {} agent(s)", error_count));
+    }
+
+    Ok(())
+}
+
+/// Ask the user to type YES before a bulk removal. Without a terminal to ask on,
+/// refuse instead of deleting anything.
+fn confirm_remove_all(agents: &[Agent], keep_workspace: bool) -> Result<bool> {
+    if !std::io::stdin().is_terminal() {
+        return Err(anyhow!(
+            "Refusing to remove {} agent(s) without --force when not running interactively",
+            agents.len()
+        ));
+    }
+
+    if keep_workspace {
+        println!("The containers for these agents will be removed:");
     } else {
-        // Remove metadata file only
-        let metadata_path = agent.workspace_path.join(".crowdcontrol-metadata.json");
-        if metadata_path.exists() {
-            fs::remove_file(metadata_path)?;
+        println!("These agents and their workspaces will be deleted:");
+    }
+    for agent in agents {
+        println!("  {}", agent.name);
+    }
+
+    let answer: String = Input::new()
+        .with_prompt("Type YES to continue")
+        .allow_empty(true)
+        .interact_text()?;
+
+    Ok(answer == "YES")
+}
+
+/// Fail if the agent is running, since removing it would kill whatever is executing
+/// inside. Agents without a container are never running, so Docker isn't needed.
+async fn ensure_not_running(manager: &AgentManager, agent: &Agent) -> Result<()> {
+    if agent.container_id.is_none() {
+        return Ok(());
+    }
+
+    let status = agent.compute_live_status(manager.docker()?).await?;
+    if status.is_running() || status == AgentStatus::Restarting {
+        return Err(anyhow!(
+            "Agent '{}' is running; stop it first or use --force",
+            agent.name
+        ));
+    }
+    Ok(())
+}
+
+/// Remove an agent. Running agents are stopped first with `force`, and refused without it.
+async fn remove_agent(
+    manager: &AgentManager,
+    agent: &Agent,
+    keep_workspace: bool,
+    force: bool,
+) -> Result<()> {
+    if force {
+        // Give processes inside a chance to exit cleanly before the container goes.
+        // If Docker can't be reached the removal below reports it.
+        if agent.container_id.is_some() {
+            if let Ok(docker) = manager.docker() {
+                let status = agent.compute_live_status(docker).await.ok();
+                if status
+                    .is_some_and(|status| status.is_running() || status == AgentStatus::Restarting)
+                {
+                    stop_agent(manager, &agent.name, false, DEFAULT_STOP_TIMEOUT_SECS).await?;
+                }
+            }
         }
+    } else {
+        ensure_not_running(manager, agent).await?;
+    }
+
+    let pb = create_progress_bar("Removing agent...");
+    let warnings = manager.remove_agent(agent, keep_workspace).await;
+    pb.finish_and_clear();
+
+    let warnings = warnings?;
+    for warning in &warnings {
+        print_warning(warning);
+    }
+    if agent.container_id.is_some() && warnings.is_empty() {
+        print_success("Container removed successfully");
+    }
+    if keep_workspace {
         print_info("Workspace directory kept");
+    } else {
+        print_success("Workspace directory removed successfully");
     }
 
-    print_success(&format!("Agent '{}' removed successfully", args.name));
+    print_success(&format!("Agent '{}' removed successfully", agent.name));
 
     Ok(())
 }
+
+fn print_dry_run(config: &Config, agent: &Agent, keep_workspace: bool) {
+    println!("Dry run: no changes will be made");
+
+    match &agent.container_id {
+        Some(container_id) => println!(
+            "Would remove container: {} ({})",
+            config.container_name(&agent.name),
+            container_id
+        ),
+        None => println!("No container to remove"),
+    }
+
+    if keep_workspace {
+        println!(
+            "Would keep workspace directory: {}",
+            agent.workspace_path.display()
+        );
+    } else {
+        println!(
+            "Would delete workspace directory: {}",
+            agent.workspace_path.display()
+        );
+    }
+}
@@ -0,0 +1,66 @@
+use anyhow::Result;
+
+use crate::commands::start::run_lifecycle_commands;
+use crate::commands::ResetArgs;
+use crate::utils::*;
+use crowdcontrol_core::load_agent_metadata;
+use crowdcontrol_core::{
+    run_until_cancelled, AgentManager, AgentStatus, CancellationToken, Config, CrowdControlError,
+};
+
+pub async fn execute(config: Config, args: ResetArgs, cancel: CancellationToken) -> Result<()> {
+    let manager = AgentManager::new(config);
+    let agent = load_agent_metadata(manager.config(), &args.name)?;
+
+    // A running agent is started again once its container is replaced
+    let was_running = agent
+        .compute_live_status(manager.docker()?)
+        .await?
+        .is_running();
+    if cancel.is_cancelled() {
+        return Err(CrowdControlError::Interrupted.into());
+    }
+
+    // Replacing the container always runs to completion, even through Ctrl-C
+    let pb = create_progress_bar(&format!(
+        "Recreating container for agent '{}'...",
+        args.name
+    ));
+    let reset = manager.reset_container(agent).await;
+    pb.finish_and_clear();
+    let agent = reset?;
+    print_success(&format!(
+        "Replaced the container of agent '{}'; its workspace is unchanged",
+        args.name
+    ));
+
+    // If interrupted, the agent is left stopped with its new container
+    let interrupted = cancel.is_cancelled();
+    if !was_running || interrupted {
+        print_info(&format!(
+            "Start the agent with: crowdcontrol start {}",
+            args.name
+        ));
+        if interrupted {
+            return Err(CrowdControlError::Interrupted.into());
+        }
+        return Ok(());
+    }
+
+    let pb = create_progress_bar(&format!("Starting agent '{}'...", args.name));
+    let warnings = manager.start_agent(&agent, &AgentStatus::Created).await;
+    pb.finish_and_clear();
+    for warning in &warnings? {
+        print_warning(warning);
+    }
+    print_success(&format!("Agent '{}' started successfully", args.name));
+
+    // The new container hasn't run the repository's post-create command yet. It's only
+    // recorded as done once it finishes, so an interrupted run is repeated on next start.
+    run_until_cancelled(
+        &cancel,
+        run_lifecycle_commands(&manager, &agent, false, false),
+    )
+    .await
+    .unwrap_or_else(|| Err(CrowdControlError::Interrupted.into()))
+}
@@ -0,0 +1,13 @@
+use anyhow::Result;
+
+use crate::commands::{SchemaArgs, SchemaKind};
+use crowdcontrol_core::{metadata_schema, settings_schema};
+
+pub async fn execute(args: SchemaArgs) -> Result<()> {
+    let schema = match args.kind {
+        SchemaKind::Metadata => metadata_schema()?,
+        SchemaKind::Config => settings_schema()?,
+    };
+    println!("{}", serde_json::to_string_pretty(&schema)?);
+    Ok(())
+}
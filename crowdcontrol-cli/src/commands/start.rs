@@ -1,84 +1,532 @@
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Context, Result};
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
 use std::time::Duration;
-use tokio::time::sleep;
 
-use crate::commands::StartArgs;
+use crate::commands::{StartArgs, WaitReadyArgs};
 use crate::utils::*;
 use crowdcontrol_core::Config;
-use crowdcontrol_core::{load_agent_metadata};
-use crowdcontrol_core::{AgentStatus, DockerClient};
-pub async fn execute(config: Config, args: StartArgs) -> Result<()> {
-    // Load agent metadata
-    let agent = load_agent_metadata(&config, &args.name)?;
+use crowdcontrol_core::{
+    list_all_agents, load_agent_metadata, order_by_dependencies, select_agents_to_start,
+    update_agent_metadata, write_ready_file,
+};
+use crowdcontrol_core::{
+    Agent, AgentManager, AgentStatus, CancellationToken, ContainerHealth, CrowdControlError,
+    DockerClient, ReadyStatus, RepoConfig, STARTUP_CHECK_SECS,
+};
+pub async fn execute(config: Config, args: StartArgs, cancel: CancellationToken) -> Result<()> {
+    if args.network.as_deref() == Some("none") {
+        warn_network_disabled();
+    }
+
+    let manager = AgentManager::new(config);
+
+    if args.all || args.tag.is_some() {
+        return start_all(&manager, &args, &cancel).await;
+    }
+
+    let name = args
+        .name
+        .clone()
+        .ok_or_else(|| anyhow!("Agent name required when not using --all or --tag"))?;
 
-    // Create Docker client
-    let docker = DockerClient::new(config.clone())?;
+    // Load agent metadata
+    let agent = load_agent_metadata(manager.config(), &name)?;
 
     // Check current status (validates container_id and gets live status)
-    let status = agent.compute_live_status(&docker).await?;
+    let status = agent.compute_live_status(manager.docker()?).await?;
 
-    match status {
-        AgentStatus::Running => {
-            print_info(&format!("Agent '{}' is already running", args.name));
-            return Ok(());
+    if status.is_running() {
+        print_info(&format!("Agent '{}' is already running", name));
+        return Ok(());
+    }
+
+    start_with_dependencies(&manager, &agent, status, &args).await?;
+
+    print_info(&format!(
+        "Connect to the agent with: crowdcontrol connect {}",
+        name
+    ));
+
+    Ok(())
+}
+
+/// Start an agent that isn't running, after the agents it depends on
+pub(crate) async fn start_with_dependencies(
+    manager: &AgentManager,
+    agent: &Agent,
+    status: AgentStatus,
+    args: &StartArgs,
+) -> Result<()> {
+    // Dependencies start first, without this agent's --network or --readonly-workspace,
+    // and are waited for in the foreground so the agent itself starts once they're ready
+    let dependency_args = StartArgs {
+        name: None,
+        network: None,
+        readonly_workspace: false,
+        ready_file: None,
+        ..args.clone()
+    };
+    for dependency in load_dependencies(manager.config(), agent)? {
+        let status = dependency.compute_live_status(manager.docker()?).await?;
+        if !status.is_running() {
+            start_agent(manager, &dependency, status, &dependency_args)
+                .await
+                .with_context(|| format!("Failed to start dependency '{}'", dependency.name))?;
         }
-        AgentStatus::Error => {
-            return Err(anyhow!(
-                "Agent '{}' is in error state. Please remove and recreate it.",
-                args.name
-            ));
+    }
+
+    start_agent(manager, agent, status, args).await
+}
+
+/// Start every agent that isn't running (optionally only those with a tag),
+/// continuing past failures and reporting them at the end. Ctrl-C lets the agent
+/// being started finish, then stops before the next one.
+async fn start_all(
+    manager: &AgentManager,
+    args: &StartArgs,
+    cancel: &CancellationToken,
+) -> Result<()> {
+    let config = manager.config();
+    let docker = manager.docker()?;
+    let mut error_count = 0;
+
+    let mut agents = Vec::new();
+    for agent_name in list_all_agents(config)? {
+        let result = match load_agent_metadata(config, &agent_name) {
+            Ok(agent) => agent
+                .compute_live_status(docker)
+                .await
+                .map(|status| (agent, status)),
+            Err(e) => Err(e),
+        };
+
+        match result {
+            Ok(entry) => agents.push(entry),
+            Err(e) => {
+                print_error(&format!("Failed to start {}: {}", agent_name, e));
+                error_count += 1;
+            }
         }
-        _ => {}
     }
 
-    // Get container ID
-    let container_id = agent
-        .container_id
-        .as_ref()
-        .ok_or_else(|| anyhow!("No container ID found for agent '{}'", args.name))?;
+    let mut started_count = 0;
+    // Dependencies start before the agents that need them
+    let selected = select_agents_to_start(agents, args.tag.as_deref());
+    let mut interrupted = false;
+    for (agent, status) in order_by_dependencies(selected, |(agent, _)| agent)? {
+        if cancel.is_cancelled() {
+            interrupted = true;
+            break;
+        }
+        match start_agent(manager, &agent, status, args).await {
+            Ok(()) => started_count += 1,
+            Err(e) => {
+                print_error(&format!("Failed to start {}: {}", agent.name, e));
+                error_count += 1;
+            }
+        }
+    }
+
+    if started_count > 0 {
+        print_success(&format!("Started {} agent(s)", started_count));
+    }
+
+    if interrupted {
+        return Err(CrowdControlError::Interrupted.into());
+    }
+
+    if error_count > 0 {
+        return Err(anyhow!("Failed to start {} agent(s)", error_count));
+    }
+
+    if started_count == 0 {
+        print_info("No stopped agents to start");
+    }
+
+    Ok(())
+}
+
+/// The agents `agent` depends on, directly or not, in the order they should start.
+/// Dependencies that don't exist are skipped with a warning.
+fn load_dependencies(config: &Config, agent: &Agent) -> Result<Vec<Agent>> {
+    let mut seen = HashSet::from([agent.name.clone()]);
+    let mut pending: Vec<(String, String)> = agent
+        .depends_on
+        .iter()
+        .map(|dependency| (agent.name.clone(), dependency.clone()))
+        .collect();
+    let mut dependencies = Vec::new();
+
+    while let Some((dependent, name)) = pending.pop() {
+        if !seen.insert(name.clone()) {
+            continue;
+        }
+        match load_agent_metadata(config, &name) {
+            Ok(dependency) => {
+                pending.extend(
+                    dependency
+                        .depends_on
+                        .iter()
+                        .map(|next| (name.clone(), next.clone())),
+                );
+                dependencies.push(dependency);
+            }
+            Err(e)
+                if matches!(
+                    CrowdControlError::find(&e),
+                    Some(CrowdControlError::AgentNotFound(_))
+                ) =>
+            {
+                print_warning(&format!(
+                    "Agent '{}' depends on '{}', which doesn't exist",
+                    dependent, name
+                ));
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    // Order with the agent itself included so a cycle back to it is caught; it
+    // depends on everything else, so it comes out last
+    dependencies.push(agent.clone());
+    let mut ordered = order_by_dependencies(dependencies, |agent| agent)?;
+    ordered.pop();
+    Ok(ordered)
+}
+
+async fn start_agent(
+    manager: &AgentManager,
+    agent: &Agent,
+    status: AgentStatus,
+    args: &StartArgs,
+) -> Result<()> {
+    let docker = manager.docker()?;
+
+    // The workspace mount of an existing container can't change, so a stopped agent
+    // made read-only gets a new container. Paused and errored agents are left for the
+    // manager to reject.
+    let mut status = status;
+    let switched;
+    let agent = if args.readonly_workspace
+        && !agent.readonly_workspace
+        && !matches!(status, AgentStatus::Error | AgentStatus::Paused)
+    {
+        if let Some(container_id) = agent.container_id.as_deref() {
+            if !manager.needs_container(agent).await? {
+                docker.remove_container(container_id).await?;
+            }
+        }
+        update_agent_metadata(manager.config(), &agent.name, |agent| {
+            agent.readonly_workspace = true;
+            agent.container_id = None;
+            Ok(())
+        })?;
+        switched = Agent {
+            readonly_workspace: true,
+            container_id: None,
+            ..agent.clone()
+        };
+        status = AgentStatus::Created;
+        &switched
+    } else {
+        agent
+    };
+
+    // Agents created with `new --workspace-only`, or whose container was removed
+    // outside crowdcontrol, get a new container
+    let created;
+    let agent = if status == AgentStatus::Created && manager.needs_container(agent).await? {
+        let pb = create_progress_bar(&format!("Creating container for agent '{}'...", agent.name));
+        let result = manager.ensure_container(agent.clone()).await;
+        pb.finish_and_clear();
+        created = result?;
+        print_success(&format!("Created container for agent '{}'", agent.name));
+        &created
+    } else {
+        agent
+    };
+
+    // Switch networks while the container is stopped and remember the choice.
+    // Paused and errored agents are left for the manager to reject.
+    if let Some(network) = &args.network {
+        let startable = !matches!(status, AgentStatus::Error | AgentStatus::Paused);
+        if startable && agent.network.as_ref() != Some(network) {
+            let container_id = agent
+                .container_id
+                .as_deref()
+                .ok_or_else(|| anyhow!("No container ID found for agent '{}'", agent.name))?;
+            docker.set_container_network(container_id, network).await?;
+            update_agent_metadata(manager.config(), &agent.name, |agent| {
+                agent.network = Some(network.clone());
+                Ok(())
+            })?;
+        }
+    }
 
     // Start container
-    let pb = create_progress_bar(&format!("Starting agent '{}'...", args.name));
-    docker.start_container(container_id).await?;
+    let pb = create_progress_bar(&format!("Starting agent '{}'...", agent.name));
+    let warnings = manager.start_agent(agent, &status).await;
     pb.finish_and_clear();
 
-    print_success(&format!("Agent '{}' started successfully", args.name));
+    let warnings = warnings?;
+
+    // An agent whose entrypoint dies right away (a failing setup script, say) isn't
+    // started; report why instead
+    if let Some(container_id) = agent.container_id.as_deref() {
+        let pb = create_progress_bar(&format!("Checking that agent '{}' stays up...", agent.name));
+        let checked = docker
+            .check_started(container_id, Duration::from_secs(STARTUP_CHECK_SECS))
+            .await;
+        pb.finish_and_clear();
+        checked.with_context(|| format!("Agent '{}' failed to start", agent.name))?;
+    }
+
+    print_success(&format!("Agent '{}' started successfully", agent.name));
+    for warning in &warnings {
+        print_warning(warning);
+    }
+    if agent.readonly_workspace {
+        warn_readonly_workspace();
+    }
+
+    run_lifecycle_commands(manager, agent, args.ignore_lifecycle_errors, false).await?;
 
     // Wait for initialization if requested
     if args.wait {
-        let pb = create_progress_bar("Waiting for agent initialization...");
-        let timeout_duration = Duration::from_secs(args.timeout);
-        let start_time = std::time::Instant::now();
-
-        loop {
-            if start_time.elapsed() > timeout_duration {
-                pb.finish_and_clear();
-                print_warning("Timeout waiting for agent initialization");
-                break;
-            }
+        if let Some(ready_file) = &args.ready_file {
+            spawn_ready_waiter(manager.config(), &agent.name, ready_file, args.timeout)?;
+            print_info(&format!(
+                "Waiting for agent '{}' in the background; the result will be written to {}",
+                agent.name,
+                ready_file.display()
+            ));
+            return Ok(());
+        }
 
-            // Check if container is still running
-            let status = agent.compute_live_status(&docker).await?;
-            if status != AgentStatus::Running {
-                pb.finish_and_clear();
-                return Err(anyhow!("Agent stopped unexpectedly during initialization"));
-            }
+        let container_id = agent.container_id.as_deref().unwrap_or_default();
+        let pb = create_progress_bar("Waiting for agent initialization...");
+        let waited = wait_until_ready(docker, container_id, args.timeout).await;
+        pb.finish_and_clear();
 
-            // TODO: Add actual readiness check (e.g., check if Docker daemon is ready inside container)
-            // For now, just wait a fixed amount of time
-            sleep(Duration::from_secs(2)).await;
-            pb.finish_and_clear();
+        let has_health_check = waited
+            .with_context(|| format!("Agent '{}' did not finish initializing", agent.name))?;
+        if has_health_check {
+            print_success("Agent is healthy");
+        } else {
             print_success("Agent initialization complete");
-            break;
         }
     }
 
     // Note: Agent status is now computed live from Docker, no need to save it
 
-    print_info(&format!(
-        "Connect to the agent with: crowdcontrol connect {}",
-        args.name
-    ));
+    Ok(())
+}
+
+/// Run the repository's lifecycle commands in a started agent as the developer user,
+/// streaming their output: `post_create_command` once per container, then
+/// `post_start_command`. With `quiet` the output goes to stderr so stdout stays parseable.
+pub(crate) async fn run_lifecycle_commands(
+    manager: &AgentManager,
+    agent: &Agent,
+    ignore_errors: bool,
+    quiet: bool,
+) -> Result<()> {
+    let repo_config = RepoConfig::load(&agent.repo_path())?;
+    if !repo_config.has_lifecycle_commands() {
+        return Ok(());
+    }
+
+    let container_id = agent
+        .container_id
+        .as_deref()
+        .ok_or_else(|| anyhow!("No container ID found for agent '{}'", agent.name))?;
+    let mut output: Box<dyn Write> = if quiet {
+        Box::new(io::stderr())
+    } else if is_quiet() {
+        Box::new(io::sink())
+    } else {
+        Box::new(io::stdout())
+    };
+    // Commands run from the repository root
+    let script = |command: &str| format!("cd {} && {}", agent.container_repo_dir(), command);
+
+    if !agent.post_create_done {
+        if let Some(command) = &repo_config.post_create_command {
+            let result = run_lifecycle_command(
+                manager,
+                container_id,
+                "post-create",
+                &script(command),
+                ignore_errors,
+                quiet,
+                &mut output,
+            )
+            .await;
+            // A failure that stops the agent here leaves the flag unset so the
+            // command is retried on the next start
+            if result.is_ok() {
+                update_agent_metadata(manager.config(), &agent.name, |agent| {
+                    agent.post_create_done = true;
+                    Ok(())
+                })?;
+            }
+            result?;
+        }
+    }
+
+    if let Some(command) = &repo_config.post_start_command {
+        run_lifecycle_command(
+            manager,
+            container_id,
+            "post-start",
+            &script(command),
+            ignore_errors,
+            quiet,
+            &mut output,
+        )
+        .await?;
+    }
 
     Ok(())
 }
+
+async fn run_lifecycle_command(
+    manager: &AgentManager,
+    container_id: &str,
+    kind: &str,
+    script: &str,
+    ignore_errors: bool,
+    quiet: bool,
+    output: &mut impl Write,
+) -> Result<()> {
+    if !quiet {
+        print_info(&format!("Running {} command...", kind));
+    }
+
+    let exit_code = manager
+        .docker()?
+        .exec_streaming_as_user(
+            container_id,
+            vec!["bash", "-lc", script],
+            Some("developer"),
+            output,
+        )
+        .await?;
+
+    if exit_code == 0 {
+        return Ok(());
+    }
+
+    let message = format!("The {} command failed with exit code {}", kind, exit_code);
+    if ignore_errors {
+        if !quiet {
+            print_warning(&message);
+        }
+        return Ok(());
+    }
+
+    Err(anyhow!(
+        "{}. The agent is running; fix the command in .crowdcontrol/config.toml or pass \
+         --ignore-lifecycle-errors",
+        message
+    ))
+}
+
+/// Wait for a started container to become healthy, or just running if it has no health
+/// check. Returns whether a health check was waited on.
+async fn wait_until_ready(docker: &DockerClient, container_id: &str, timeout: u64) -> Result<bool> {
+    let timeout = Duration::from_secs(timeout);
+
+    // Without a health check configured, fall back to the running-state wait
+    let has_health_check = docker.get_container_health(container_id).await?.is_some();
+    let waited = if has_health_check {
+        docker.wait_for_healthy(container_id, timeout).await
+    } else {
+        docker
+            .wait_for_container_running(container_id, timeout)
+            .await
+    };
+
+    if let Err(e) = waited {
+        // The container died after the startup check; its logs say more than the wait
+        if let Ok(Some(exited)) = docker.startup_failure(container_id).await {
+            return Err(exited.into());
+        }
+        if let Ok(Some(ContainerHealth {
+            last_output: Some(output),
+            ..
+        })) = docker.get_container_health(container_id).await
+        {
+            return Err(e.context(format!("Last health check output: {}", output)));
+        }
+        return Err(e);
+    }
+
+    Ok(has_health_check)
+}
+
+/// Run the readiness wait in a detached `crowdcontrol __wait_ready` process, which
+/// outlives this one and writes the ready file when it's done
+fn spawn_ready_waiter(config: &Config, name: &str, ready_file: &Path, timeout: u64) -> Result<()> {
+    // Don't let a watcher mistake the previous run's result for this one
+    match fs::remove_file(ready_file) {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => {
+            return Err(e).with_context(|| format!("Failed to remove ready file: {:?}", ready_file))
+        }
+    }
+
+    let exe = env::current_exe().context("Failed to locate the crowdcontrol executable")?;
+    let mut command = Command::new(exe);
+    command
+        .arg("--workspaces-dir")
+        .arg(&config.workspaces_dir)
+        .arg("__wait_ready")
+        .arg(name)
+        .arg("--ready-file")
+        .arg(ready_file)
+        .arg("--timeout")
+        .arg(timeout.to_string())
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+
+    // A process group of its own keeps Ctrl-C in the terminal from reaching it
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+
+    command
+        .spawn()
+        .context("Failed to start the background readiness wait")?;
+    Ok(())
+}
+
+/// Entry point of the hidden `__wait_ready` command: wait for the agent and record the
+/// outcome, failures included, in the ready file
+pub async fn wait_ready(config: Config, args: WaitReadyArgs) -> Result<()> {
+    let manager = AgentManager::new(config);
+    let result = async {
+        let agent = load_agent_metadata(manager.config(), &args.name)?;
+        let container_id = agent
+            .container_id
+            .as_deref()
+            .ok_or_else(|| anyhow!("No container ID found for agent '{}'", agent.name))?;
+        wait_until_ready(manager.docker()?, container_id, args.timeout)
+            .await
+            .with_context(|| format!("Agent '{}' did not finish initializing", agent.name))?;
+        Ok(())
+    }
+    .await;
+
+    write_ready_file(&args.ready_file, &ReadyStatus::from_result(&result))?;
+    result
+}
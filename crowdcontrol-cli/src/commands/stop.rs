@@ -1,21 +1,50 @@
 use anyhow::{anyhow, Result};
 
-use crate::commands::StopArgs;
+use crate::commands::{AgentStatusFilter, StopArgs};
 use crate::utils::*;
 use crowdcontrol_core::Config;
-use crowdcontrol_core::{list_all_agents, load_agent_metadata, save_agent_metadata};
-use crowdcontrol_core::{AgentStatus, DockerClient};
+use crowdcontrol_core::{
+    list_all_agents, load_agent_metadata, order_by_dependencies, select_agents_to_stop,
+    AgentManager,
+};
 pub async fn execute(config: Config, args: StopArgs) -> Result<()> {
-    let docker = DockerClient::new(config.clone())?;
+    let manager = AgentManager::new(config.clone());
 
-    if args.all {
-        // Stop all running agents
-        let agents = list_all_agents(&config)?;
+    if args.all || args.tag.is_some() {
         let mut stopped_count = 0;
         let mut error_count = 0;
 
-        for agent_name in agents {
-            match stop_agent(&docker, &config, &agent_name, args.force).await {
+        // Stop all running agents, or only those matching the requested tag and status.
+        // Docker is only connected to once there's an agent to ask about.
+        let mut agents = Vec::new();
+        for agent_name in list_all_agents(&config)? {
+            match load_agent_metadata(&config, &agent_name) {
+                // Don't ask Docker about agents the tag rules out
+                Ok(agent) if args.tag.as_ref().is_some_and(|tag| !agent.has_tag(tag)) => {}
+                Ok(agent) => match agent.compute_live_status(manager.docker()?).await {
+                    Ok(status) => agents.push((agent, status)),
+                    Err(e) => {
+                        print_error(&format!("Failed to stop {}: {}", agent_name, e));
+                        error_count += 1;
+                    }
+                },
+                Err(e) if args.tag.is_none() => {
+                    print_error(&format!("Failed to stop {}: {}", agent_name, e));
+                    error_count += 1;
+                }
+                Err(_) => {}
+            }
+        }
+        let status = args.status.as_ref().map(AgentStatusFilter::status);
+        let agents = select_agents_to_stop(agents, args.tag.as_deref(), status.as_ref());
+
+        // Agents stop before the agents they depend on
+        let mut agents = order_by_dependencies(agents, |agent| agent)?;
+        agents.reverse();
+
+        for agent in agents {
+            let agent_name = agent.name;
+            match stop_agent(&manager, &agent_name, args.force, args.timeout).await {
                 Ok(true) => stopped_count += 1,
                 Ok(false) => {} // Agent was not running
                 Err(e) => {
@@ -40,8 +69,8 @@ pub async fn execute(config: Config, args: StopArgs) -> Result<()> {
         // Stop specific agent
         let name = args
             .name
-            .ok_or_else(|| anyhow!("Agent name required when not using --all"))?;
-        let stopped = stop_agent(&docker, &config, &name, args.force).await?;
+            .ok_or_else(|| anyhow!("Agent name required when not using --all or --tag"))?;
+        let stopped = stop_agent(&manager, &name, args.force, args.timeout).await?;
 
         if !stopped {
             print_info(&format!("Agent '{}' is not running", name));
@@ -51,38 +80,19 @@ pub async fn execute(config: Config, args: StopArgs) -> Result<()> {
     Ok(())
 }
 
-async fn stop_agent(
-    docker: &DockerClient,
-    config: &Config,
+pub(crate) async fn stop_agent(
+    manager: &AgentManager,
     name: &str,
     force: bool,
+    timeout: u64,
 ) -> Result<bool> {
-    // Load agent metadata
-    let mut agent = load_agent_metadata(config, name)?;
-
-    // Check current status (validates container_id and gets live status)
-    let status = agent.compute_live_status(docker).await?;
-
-    if status != AgentStatus::Running {
-        return Ok(false);
-    }
-
-    // Get container ID
-    let container_id = agent
-        .container_id
-        .as_ref()
-        .ok_or_else(|| anyhow!("No container ID found for agent '{}'", name))?;
-
-    // Stop container
     let pb = create_progress_bar(&format!("Stopping agent '{}'...", name));
-    docker.stop_container(container_id, force).await?;
+    let stopped = manager.stop(name, force, timeout).await;
     pb.finish_and_clear();
 
-    print_success(&format!("Agent '{}' stopped successfully", name));
-
-    // Clear container ID since container is now stopped
-    agent.container_id = None;
-    save_agent_metadata(config, &agent)?;
-
-    Ok(true)
+    if stopped? {
+        print_success(&format!("Agent '{}' stopped successfully", name));
+        return Ok(true);
+    }
+    Ok(false)
 }
@@ -0,0 +1,151 @@
+use anyhow::{anyhow, Result};
+
+use crate::commands::SwitchArgs;
+use crate::utils::*;
+use crowdcontrol_core::{load_agent_metadata, update_agent_metadata};
+use crowdcontrol_core::{Agent, AgentStatus, Config, CrowdControlError, DockerClient, ExecOutput};
+
+pub async fn execute(config: Config, args: SwitchArgs) -> Result<()> {
+    let agent = load_agent_metadata(&config, &args.name)?;
+    let docker = DockerClient::new(config.clone())?;
+
+    // Keep the branch from being parsed as a git option
+    if args.branch.starts_with('-') {
+        return Err(anyhow!("Invalid branch name '{}'", args.branch));
+    }
+
+    let container_id = running_container_id(&docker, &agent).await?;
+    let repo_dir = agent.container_repo_dir();
+
+    let status = git(
+        &docker,
+        &container_id,
+        &repo_dir,
+        &["status", "--porcelain"],
+    )
+    .await?;
+    if !status.success() {
+        return Err(anyhow!(
+            "Failed to read git status in agent '{}': {}",
+            args.name,
+            status.stderr.trim()
+        ));
+    }
+    if !status.stdout.trim().is_empty() {
+        if !args.force {
+            return Err(anyhow!(
+                "Agent '{}' has uncommitted changes. Commit or stash them first, or rerun with --force to stash them",
+                args.name
+            ));
+        }
+
+        let message = format!("crowdcontrol switch to {}", args.branch);
+        let stash = git(
+            &docker,
+            &container_id,
+            &repo_dir,
+            &["stash", "push", "--include-untracked", "-m", &message],
+        )
+        .await?;
+        if !stash.success() {
+            return Err(anyhow!(
+                "Failed to stash uncommitted changes: {}",
+                stash.stderr.trim()
+            ));
+        }
+        print_info("Stashed uncommitted changes; restore them with `git stash pop`");
+    }
+
+    // Agents without network access (or whose origin is a host path) can still
+    // switch between branches they already know about
+    let fetch = git(&docker, &container_id, &repo_dir, &["fetch", "origin"]).await?;
+    if !fetch.success() {
+        print_warning(&format!(
+            "Could not fetch from origin, using branches already in the workspace: {}",
+            fetch.stderr.trim()
+        ));
+    }
+
+    let local_ref = format!("refs/heads/{}", args.branch);
+    let remote_ref = format!("refs/remotes/origin/{}", args.branch);
+    let mut exists = false;
+    for reference in [&local_ref, &remote_ref] {
+        let verify = git(
+            &docker,
+            &container_id,
+            &repo_dir,
+            &["rev-parse", "--verify", "--quiet", reference],
+        )
+        .await?;
+        if verify.success() {
+            exists = true;
+            break;
+        }
+    }
+    if !exists {
+        return Err(anyhow!(
+            "Branch '{}' does not exist locally or on origin",
+            args.branch
+        ));
+    }
+
+    // A remote-only branch is checked out as a new tracking branch
+    let checkout = git(
+        &docker,
+        &container_id,
+        &repo_dir,
+        &["checkout", &args.branch],
+    )
+    .await?;
+    if !checkout.success() {
+        return Err(anyhow!(
+            "Failed to check out '{}': {}",
+            args.branch,
+            checkout.stderr.trim()
+        ));
+    }
+
+    update_agent_metadata(&config, &args.name, |agent| {
+        agent.branch = Some(args.branch.clone());
+        Ok(())
+    })?;
+
+    print_success(&format!(
+        "Agent '{}' switched to branch '{}'",
+        args.name, args.branch
+    ));
+
+    Ok(())
+}
+
+/// Container ID of an agent whose workspace git commands can run in, or an error
+/// saying how to get it running
+pub(crate) async fn running_container_id(docker: &DockerClient, agent: &Agent) -> Result<String> {
+    // Check current status (validates container_id and gets live status)
+    match agent.compute_live_status(docker).await? {
+        AgentStatus::Running | AgentStatus::Starting => {}
+        AgentStatus::Paused => {
+            return Err(CrowdControlError::AgentPaused(agent.name.clone()).into())
+        }
+        _ => return Err(CrowdControlError::not_running(&agent.name).into()),
+    }
+
+    agent
+        .container_id
+        .clone()
+        .ok_or_else(|| anyhow!("No container ID found for agent '{}'", agent.name))
+}
+
+/// Run git in the repository at `repo_dir` in the container as the developer user
+pub(crate) async fn git(
+    docker: &DockerClient,
+    container_id: &str,
+    repo_dir: &str,
+    args: &[&str],
+) -> Result<ExecOutput> {
+    let mut cmd = vec!["git", "-C", repo_dir];
+    cmd.extend_from_slice(args);
+    docker
+        .exec_capture_as_user(container_id, cmd, Some("developer"))
+        .await
+}
@@ -0,0 +1,87 @@
+use anyhow::Result;
+use colored::*;
+use std::io::{self, Write};
+use std::time::Duration;
+use tokio::time::sleep;
+
+use crate::commands::TopArgs;
+use crate::utils::*;
+use crowdcontrol_core::{format_bytes, Config, ContainerStats, DockerClient};
+
+/// Pause between refreshes with `--watch`; taking a sample already takes about a second
+const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+pub async fn execute(config: Config, args: TopArgs) -> Result<()> {
+    let docker = DockerClient::new(config)?;
+
+    loop {
+        let stats = docker.running_agent_stats().await?;
+
+        if args.watch {
+            // Clear the screen and move the cursor home before redrawing
+            print!("\x1B[2J\x1B[H");
+        }
+        if stats.is_empty() {
+            print_info("No running agents found");
+        } else {
+            print_table(&stats);
+        }
+
+        if !args.watch {
+            return Ok(());
+        }
+        io::stdout().flush()?;
+        sleep(REFRESH_INTERVAL).await;
+    }
+}
+
+fn print_table(stats: &[(String, ContainerStats)]) {
+    let name_width = stats
+        .iter()
+        .map(|(name, _)| name.len())
+        .max()
+        .unwrap_or(4)
+        .max(4);
+    let cpu_width = 8;
+    let mem_width = 24;
+    let mem_percent_width = 8;
+
+    println!(
+        "{:<name_width$} {:>cpu_width$} {:<mem_width$} {:>mem_percent_width$} {}",
+        "NAME".bold(),
+        "CPU %".bold(),
+        "MEM USAGE / LIMIT".bold(),
+        "MEM %".bold(),
+        "NET I/O".bold(),
+        name_width = name_width,
+        cpu_width = cpu_width,
+        mem_width = mem_width,
+        mem_percent_width = mem_percent_width,
+    );
+
+    for (name, sample) in stats {
+        let memory = format!(
+            "{} / {}",
+            format_bytes(sample.memory_usage),
+            format_bytes(sample.memory_limit)
+        );
+        let network = format!(
+            "{} / {}",
+            format_bytes(sample.net_rx_bytes),
+            format_bytes(sample.net_tx_bytes)
+        );
+
+        println!(
+            "{:<name_width$} {:>cpu_width$} {:<mem_width$} {:>mem_percent_width$} {}",
+            name,
+            format!("{:.2}%", sample.cpu_percent),
+            memory,
+            format!("{:.2}%", sample.memory_percent()),
+            network,
+            name_width = name_width,
+            cpu_width = cpu_width,
+            mem_width = mem_width,
+            mem_percent_width = mem_percent_width,
+        );
+    }
+}
@@ -0,0 +1,39 @@
+use anyhow::{anyhow, Result};
+
+use crate::commands::UnpauseArgs;
+use crate::utils::*;
+use crowdcontrol_core::load_agent_metadata;
+use crowdcontrol_core::Config;
+use crowdcontrol_core::{AgentStatus, DockerClient};
+pub async fn execute(config: Config, args: UnpauseArgs) -> Result<()> {
+    let agent = load_agent_metadata(&config, &args.name)?;
+    let docker = DockerClient::new(config.clone())?;
+
+    // Check current status (validates container_id and gets live status)
+    let status = agent.compute_live_status(&docker).await?;
+    match status {
+        AgentStatus::Paused => {}
+        AgentStatus::Running | AgentStatus::Starting => {
+            print_info(&format!("Agent '{}' is not paused", args.name));
+            return Ok(());
+        }
+        _ => {
+            return Err(anyhow!(
+                "Agent '{}' is not paused. Start it with: crowdcontrol start {}",
+                args.name,
+                args.name
+            ));
+        }
+    }
+
+    let container_id = agent
+        .container_id
+        .as_ref()
+        .ok_or_else(|| anyhow!("No container ID found for agent '{}'", args.name))?;
+
+    docker.unpause_container(container_id).await?;
+
+    print_success(&format!("Agent '{}' resumed", args.name));
+
+    Ok(())
+}
@@ -0,0 +1,127 @@
+use anyhow::{anyhow, Result};
+
+use crate::commands::switch::{git, running_container_id};
+use crate::commands::UpdateArgs;
+use crate::utils::*;
+use crowdcontrol_core::load_agent_metadata;
+use crowdcontrol_core::{Config, DockerClient};
+
+pub async fn execute(config: Config, args: UpdateArgs) -> Result<()> {
+    let agent = load_agent_metadata(&config, &args.name)?;
+    let docker = DockerClient::new(config.clone())?;
+    let container_id = running_container_id(&docker, &agent).await?;
+    let repo_dir = agent.container_repo_dir();
+
+    let status = git(
+        &docker,
+        &container_id,
+        &repo_dir,
+        &["status", "--porcelain"],
+    )
+    .await?;
+    if !status.success() {
+        return Err(anyhow!(
+            "Failed to read git status in agent '{}': {}",
+            args.name,
+            status.stderr.trim()
+        ));
+    }
+    if !status.stdout.trim().is_empty() {
+        if !args.stash {
+            return Err(anyhow!(
+                "Agent '{}' has uncommitted changes. Commit or stash them first, or rerun with --stash",
+                args.name
+            ));
+        }
+
+        let stash = git(
+            &docker,
+            &container_id,
+            &repo_dir,
+            &[
+                "stash",
+                "push",
+                "--include-untracked",
+                "-m",
+                "crowdcontrol update",
+            ],
+        )
+        .await?;
+        if !stash.success() {
+            return Err(anyhow!(
+                "Failed to stash uncommitted changes: {}",
+                stash.stderr.trim()
+            ));
+        }
+        print_info("Stashed uncommitted changes; restore them with `git stash pop`");
+    }
+
+    let before = head(&docker, &container_id, &repo_dir).await?;
+
+    let pull_args: &[&str] = if args.rebase {
+        &["pull", "--rebase"]
+    } else {
+        &["pull", "--ff-only"]
+    };
+    let pb = create_progress_bar(&format!("Updating agent '{}'...", args.name));
+    let pull = git(&docker, &container_id, &repo_dir, pull_args).await;
+    pb.finish_and_clear();
+    let pull = pull?;
+
+    if !pull.success() {
+        let stderr = pull.stderr.trim();
+        if args.rebase {
+            // Leave the workspace as it was rather than in the middle of a rebase
+            let abort = git(&docker, &container_id, &repo_dir, &["rebase", "--abort"]).await?;
+            if abort.success() {
+                return Err(anyhow!(
+                    "Could not rebase agent '{}' onto upstream, most likely because of \
+                     conflicts; the rebase was aborted and the workspace is unchanged: {}",
+                    args.name,
+                    stderr
+                ));
+            }
+        } else if stderr.contains("fast-forward") {
+            return Err(anyhow!(
+                "Agent '{}' has commits that aren't upstream, so it can't be fast-forwarded. \
+                 Rerun with --rebase to replay them on top of upstream",
+                args.name
+            ));
+        }
+        return Err(anyhow!(
+            "Failed to update agent '{}': {}",
+            args.name,
+            stderr
+        ));
+    }
+
+    let after = head(&docker, &container_id, &repo_dir).await?;
+    if before == after {
+        print_info(&format!("Agent '{}' is already up to date", args.name));
+    } else {
+        print_success(&format!(
+            "Agent '{}' updated from {} to {}",
+            args.name, before, after
+        ));
+    }
+
+    Ok(())
+}
+
+/// Abbreviated commit the workspace has checked out
+async fn head(docker: &DockerClient, container_id: &str, repo_dir: &str) -> Result<String> {
+    let output = git(
+        docker,
+        container_id,
+        repo_dir,
+        &["rev-parse", "--short", "HEAD"],
+    )
+    .await?;
+    if !output.success() {
+        return Err(anyhow!(
+            "Failed to read the current commit: {}",
+            output.stderr.trim()
+        ));
+    }
+    Ok(output.stdout.trim().to_string())
+}
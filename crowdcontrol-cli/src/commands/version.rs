@@ -0,0 +1,48 @@
+use anyhow::Result;
+use std::env;
+
+use crate::commands::VersionArgs;
+use crowdcontrol_core::{Config, DockerClient};
+
+pub async fn execute(config: Config, args: VersionArgs) -> Result<()> {
+    println!("crowdcontrol {}", env!("CARGO_PKG_VERSION"));
+    if !args.long && config.verbose == 0 {
+        return Ok(());
+    }
+
+    println!("OS/arch: {}/{}", env::consts::OS, env::consts::ARCH);
+
+    // Meant for bug reports, so a missing daemon is reported rather than an error
+    let docker = match DockerClient::new(config.clone()) {
+        Ok(docker) => docker,
+        Err(e) => {
+            print_docker_unavailable(&config, &e);
+            return Ok(());
+        }
+    };
+    let (version, api_version) = match docker.daemon_version().await {
+        Ok(versions) => versions,
+        Err(e) => {
+            print_docker_unavailable(&config, &e);
+            return Ok(());
+        }
+    };
+
+    println!("Docker: {} (API {})", version, api_version);
+    println!(
+        "Docker client API: {} (negotiated with the daemon)",
+        docker.client_api_version().await
+    );
+    match docker.image_exists(&config.image).await {
+        Ok(true) => println!("Image: {} (present locally)", config.image),
+        Ok(false) => println!("Image: {} (not present locally)", config.image),
+        Err(e) => println!("Image: {} (unknown: {:#})", config.image, e),
+    }
+
+    Ok(())
+}
+
+fn print_docker_unavailable(config: &Config, error: &anyhow::Error) {
+    println!("Docker: unavailable ({:#})", error);
+    println!("Image: {} (unknown, Docker unavailable)", config.image);
+}
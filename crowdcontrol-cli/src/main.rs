@@ -1,11 +1,38 @@
 use clap::{Parser, Subcommand};
-use std::path::PathBuf;
+use std::env;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
 
 mod commands;
 mod utils;
 
 use commands::*;
-use crowdcontrol_core::{init_logger, Config, Settings};
+use crowdcontrol_core::{
+    cancel_on_ctrl_c, explain_docker_unavailable, init_logger, run_until_cancelled,
+    CancellationToken, Config, CrowdControlError, Settings,
+};
+
+/// Process exit codes, listed in `--help`
+mod exit_codes {
+    pub const GENERIC: i32 = 1;
+    // 2 is left to clap for invalid arguments
+    pub const NOT_FOUND: i32 = 3;
+    pub const ALREADY_EXISTS: i32 = 4;
+    pub const DOCKER_UNAVAILABLE: i32 = 5;
+    pub const NOT_RUNNING: i32 = 6;
+    pub const INTERRUPTED: i32 = 130;
+}
+
+const EXIT_CODES_HELP: &str = "\
+Exit codes:
+  0    Success
+  1    Error not covered below
+  2    Invalid arguments
+  3    Agent not found
+  4    Agent already exists
+  5    Docker is not available
+  6    Agent is not running (stopped or paused)
+  130  Interrupted with Ctrl-C";
 
 /// CrowdControl: Containerized development environments with Claude Code
 #[derive(Parser)]
@@ -14,7 +41,8 @@ use crowdcontrol_core::{init_logger, Config, Settings};
     version,
     about = "Manage containerized development environments with Claude Code integration",
     long_about = "CrowdControl enables parallel development across multiple repositories using \
-                  isolated Docker containers with Claude Code as an AI coding assistant."
+                  isolated Docker containers with Claude Code as an AI coding assistant.",
+    after_long_help = EXIT_CODES_HELP
 )]
 pub struct Cli {
     /// Global configuration options
@@ -29,6 +57,15 @@ pub struct Cli {
 /// Global configuration options available to all commands
 #[derive(Parser, Clone)]
 pub struct GlobalOptions {
+    /// Config file to use instead of the standard locations
+    #[arg(
+        long,
+        value_name = "PATH",
+        global = true,
+        help = "Read settings from this config file instead of searching ~/.config/crowdcontrol"
+    )]
+    pub config: Option<PathBuf>,
+
     /// Custom workspaces directory
     #[arg(
         long,
@@ -60,9 +97,20 @@ pub struct GlobalOptions {
     /// Disable colored output
     #[arg(long, env = "NO_COLOR", global = true, help = "Disable colored output")]
     pub no_color: bool,
+
+    /// Suppress status output
+    #[arg(
+        short,
+        long,
+        global = true,
+        help = "Only print errors and command results (no status messages or progress)"
+    )]
+    pub quiet: bool,
 }
 
 /// Available subcommands
+// Parsed once per run, so the size of the largest arguments doesn't matter
+#[allow(clippy::large_enum_variant)]
 #[derive(Subcommand)]
 enum Commands {
     /// Create a new agent from a git repository
@@ -74,30 +122,126 @@ enum Commands {
     /// Stop a running agent
     Stop(StopArgs),
 
+    /// Freeze a running agent without stopping its container
+    Pause(PauseArgs),
+
+    /// Resume a paused agent
+    Unpause(UnpauseArgs),
+
     /// Connect to a running agent with Claude Code
     Connect(ConnectArgs),
 
     /// List all agents and their status
     List(ListArgs),
 
+    /// Show CPU, memory and network usage of running agents
+    Top(TopArgs),
+
+    /// Check out a different branch in a running agent's workspace
+    Switch(SwitchArgs),
+
+    /// Pull upstream changes into a running agent's workspace
+    Update(UpdateArgs),
+
+    /// Remove untracked files, and optionally build artifacts, from a running agent's workspace
+    Clean(CleanArgs),
+
+    /// Replace an agent's container with a fresh one, keeping its workspace and settings
+    Reset(ResetArgs),
+
     /// Remove an agent and its workspace
     Remove(RemoveArgs),
 
     /// Show agent logs
     Logs(LogsArgs),
 
+    /// Print Docker's inspect output for an agent's container as JSON
+    Inspect(InspectArgs),
+
     /// Refresh Claude Code authentication for an agent
     Refresh(RefreshArgs),
 
+    /// Stop running agents that have exceeded their idle timeout
+    Reap(ReapArgs),
+
+    /// Print an agent's portable definition
+    Export(ExportArgs),
+
+    /// Create an agent from an exported definition
+    Import(ImportArgs),
+
+    /// Create a new agent with the same settings as an existing one
+    Clone(CloneArgs),
+
     /// Generate shell completions
     Completions(CompletionsArgs),
 
+    /// Inspect the effective configuration
+    Config(ConfigArgs),
+
+    /// Print a JSON Schema for agent metadata or the config file, for editor validation
+    Schema(SchemaArgs),
+
     /// Check and repair system state inconsistencies
     Doctor(doctor::DoctorCommand),
+
+    /// Print the version, with --long or --verbose also Docker and image details
+    Version(VersionArgs),
+
+    /// Print agent names for shell completion
+    #[command(name = "__complete_agents", hide = true)]
+    CompleteAgents,
+
+    /// Wait for a started agent and write its ready file
+    #[command(name = "__wait_ready", hide = true)]
+    WaitReady(WaitReadyArgs),
+}
+
+impl Commands {
+    /// Commands that watch the cancellation token themselves and wind down on Ctrl-C
+    /// (stop following logs, detach from an exec, roll back a half-created agent, finish
+    /// replacing a container) instead of being dropped mid-flight
+    fn handles_cancellation(&self) -> bool {
+        match self {
+            Commands::Logs(_)
+            | Commands::Connect(_)
+            | Commands::New(_)
+            | Commands::Import(_)
+            | Commands::Clone(_)
+            | Commands::Reset(_) => true,
+            Commands::Start(args) => args.all || args.tag.is_some(),
+            _ => false,
+        }
+    }
 }
 
 #[tokio::main]
-async fn main() -> anyhow::Result<()> {
+async fn main() {
+    if let Err(error) = try_main().await {
+        // Connection failures surface wherever Docker is first used; explain them once here
+        let error = explain_docker_unavailable(error);
+        eprintln!("Error: {:?}", error);
+        let _ = io::stdout().flush();
+        std::process::exit(exit_code(&error));
+    }
+}
+
+/// Exit code for a failed command, from the `CrowdControlError` it carries if any
+pub(crate) fn exit_code(error: &anyhow::Error) -> i32 {
+    match CrowdControlError::find(error) {
+        Some(CrowdControlError::AgentNotFound(_)) => exit_codes::NOT_FOUND,
+        Some(CrowdControlError::AgentAlreadyExists(_)) => exit_codes::ALREADY_EXISTS,
+        Some(CrowdControlError::DockerUnavailable(_)) => exit_codes::DOCKER_UNAVAILABLE,
+        Some(CrowdControlError::AgentNotRunning { .. } | CrowdControlError::AgentPaused(_)) => {
+            exit_codes::NOT_RUNNING
+        }
+        Some(CrowdControlError::Interrupted) => exit_codes::INTERRUPTED,
+        Some(CrowdControlError::Timeout { .. } | CrowdControlError::ContainerExited { .. })
+        | None => exit_codes::GENERIC,
+    }
+}
+
+async fn try_main() -> anyhow::Result<()> {
     let cli = Cli::parse();
 
     // Initialize logger based on verbosity level
@@ -107,13 +251,19 @@ async fn main() -> anyhow::Result<()> {
         crowdcontrol_core::logger::init_env_logger(cli.global.verbose);
     }
 
-    // Set up colored output early
+    // Set up output options early
     if cli.global.no_color {
         colored::control::set_override(false);
     }
+    utils::set_quiet(cli.global.quiet);
+
+    // Remember which settings were given as flags, for `config show`
+    let cli_keys = cli_setting_keys(&cli.global);
 
     // Load settings with CLI overrides
+    let config_file = cli.global.config.clone();
     let settings = Settings::with_overrides(
+        config_file.as_deref(),
         cli.global.workspaces_dir,
         cli.global.image,
         cli.global.verbose,
@@ -122,17 +272,99 @@ async fn main() -> anyhow::Result<()> {
     // Create config from settings
     let config = Config::from_settings(settings)?;
 
-    // Execute the appropriate command
-    match cli.command {
-        Commands::New(args) => new::execute(config, args).await,
-        Commands::Start(args) => start::execute(config, args).await,
+    // Ctrl-C cancels this token rather than killing the process outright
+    let cancel = CancellationToken::new();
+    cancel_on_ctrl_c(cancel.clone());
+
+    let result = if cli.command.handles_cancellation() {
+        run(
+            cli.command,
+            config,
+            config_file.as_deref(),
+            &cli_keys,
+            cancel,
+        )
+        .await
+    } else {
+        let command = run(
+            cli.command,
+            config,
+            config_file.as_deref(),
+            &cli_keys,
+            cancel.clone(),
+        );
+        match run_until_cancelled(&cancel, command).await {
+            Some(result) => result,
+            None => {
+                // The command's futures have been dropped; exit like an interrupted process
+                let _ = io::stdout().flush();
+                std::process::exit(exit_codes::INTERRUPTED);
+            }
+        }
+    };
+
+    let _ = io::stdout().flush();
+    result
+}
+
+/// Execute the appropriate command
+async fn run(
+    command: Commands,
+    config: Config,
+    config_file: Option<&Path>,
+    cli_keys: &[&'static str],
+    cancel: CancellationToken,
+) -> anyhow::Result<()> {
+    match command {
+        Commands::New(args) => new::execute(config, args, cancel).await,
+        Commands::Start(args) => start::execute(config, args, cancel).await,
         Commands::Stop(args) => stop::execute(config, args).await,
-        Commands::Connect(args) => connect::execute(config, args).await,
+        Commands::Pause(args) => pause::execute(config, args).await,
+        Commands::Unpause(args) => unpause::execute(config, args).await,
+        Commands::Connect(args) => connect::execute(config, args, cancel).await,
         Commands::List(args) => list::execute(config, args).await,
+        Commands::Top(args) => top::execute(config, args).await,
+        Commands::Switch(args) => switch::execute(config, args).await,
+        Commands::Update(args) => update::execute(config, args).await,
+        Commands::Clean(args) => clean::execute(config, args).await,
         Commands::Remove(args) => remove::execute(config, args).await,
-        Commands::Logs(args) => logs::execute(config, args).await,
+        Commands::Reset(args) => reset::execute(config, args, cancel).await,
+        Commands::Logs(args) => logs::execute(config, args, cancel).await,
+        Commands::Inspect(args) => inspect::execute(config, args).await,
         Commands::Refresh(args) => refresh::execute(config, args).await,
+        Commands::Reap(args) => reap::execute(config, args).await,
+        Commands::Export(args) => export::execute(config, args).await,
+        Commands::Import(args) => import::execute(config, args, cancel).await,
+        Commands::Clone(args) => clone::execute(config, args, cancel).await,
         Commands::Completions(args) => completions::execute(config, args).await,
+        Commands::Config(args) => config::execute(config, args, config_file, cli_keys).await,
+        Commands::Schema(args) => schema::execute(args).await,
         Commands::Doctor(args) => doctor::execute(config, args).await,
+        Commands::Version(args) => version::execute(config, args).await,
+        Commands::CompleteAgents => completions::complete_agents(config).await,
+        Commands::WaitReady(args) => start::wait_ready(config, args).await,
+    }
+}
+
+/// Settings passed as command-line flags. Clap also fills `--workspaces-dir` and `--image`
+/// from their environment variables; values matching the variable are treated as coming from it.
+fn cli_setting_keys(global: &GlobalOptions) -> Vec<&'static str> {
+    let from_flag = |value: Option<&Path>, var: &str| {
+        value.is_some_and(|value| env::var_os(var).as_deref() != Some(value.as_os_str()))
+    };
+
+    let mut keys = Vec::new();
+    if from_flag(
+        global.workspaces_dir.as_deref(),
+        "CROWDCONTROL_WORKSPACES_DIR",
+    ) {
+        keys.push("workspaces_dir");
+    }
+    if from_flag(global.image.as_deref().map(Path::new), "CROWDCONTROL_IMAGE") {
+        keys.push("image");
+    }
+    if global.verbose > 0 {
+        keys.push("verbose");
     }
+    keys
 }
@@ -1,9 +1,23 @@
 use colored::*;
 use indicatif::{ProgressBar, ProgressStyle};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
 
+/// Set by `--quiet`: status messages and spinners are suppressed, errors are not
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
+
+pub fn is_quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
 pub fn print_success(message: &str) {
-    println!("{} {}", "✓".green(), message);
+    if !is_quiet() {
+        println!("{} {}", "✓".green(), message);
+    }
 }
 
 pub fn print_error(message: &str) {
@@ -11,14 +25,36 @@ pub fn print_error(message: &str) {
 }
 
 pub fn print_warning(message: &str) {
-    println!("{} {}", "!".yellow(), message);
+    if !is_quiet() {
+        println!("{} {}", "!".yellow(), message);
+    }
 }
 
 pub fn print_info(message: &str) {
-    println!("{} {}", "ℹ".blue(), message);
+    if !is_quiet() {
+        println!("{} {}", "ℹ".blue(), message);
+    }
+}
+
+/// Explain the consequences of `--network none`
+pub fn warn_network_disabled() {
+    print_warning(
+        "Networking is disabled: git fetch/push and Claude auth refresh will fail inside this agent",
+    );
+}
+
+pub fn warn_readonly_workspace() {
+    print_warning(
+        "The workspace is read-only: edits won't persist and git operations that write \
+         (commit, checkout, pull) will fail inside this agent",
+    );
 }
 
 pub fn create_progress_bar(message: &str) -> ProgressBar {
+    if is_quiet() {
+        return ProgressBar::hidden();
+    }
+
     let pb = ProgressBar::new_spinner();
     pb.set_style(
         ProgressStyle::default_spinner()
@@ -47,7 +47,7 @@ fn test_claude_auth_end_to_end() {
     // Test Claude Code authentication (run as developer user)
     let container_name = format!("crowdcontrol-{}", agent_name);
     let output = StdCommand::new("docker")
-        .args(&["exec", "-u", "developer", &container_name, "claude", "echo 'test'"])
+        .args(["exec", "-u", "developer", &container_name, "claude", "echo 'test'"])
         .output()
         .expect("Failed to execute docker command");
     
@@ -124,7 +124,7 @@ fn test_claude_files_mounted_correctly() {
     
     // Check /mnt/claude-config exists
     let output = StdCommand::new("docker")
-        .args(&["exec", &container_name, "test", "-d", "/mnt/claude-config"])
+        .args(["exec", &container_name, "test", "-d", "/mnt/claude-config"])
         .output()
         .expect("Failed to execute docker command");
     
@@ -132,7 +132,7 @@ fn test_claude_files_mounted_correctly() {
     
     // Check what's mounted (if host has Claude config)
     let ls_output = StdCommand::new("docker")
-        .args(&["exec", &container_name, "ls", "-la", "/mnt/claude-config/"])
+        .args(["exec", &container_name, "ls", "-la", "/mnt/claude-config/"])
         .output()
         .expect("Failed to execute docker command");
     
@@ -149,7 +149,7 @@ fn test_claude_files_mounted_correctly() {
     
     // Check if refresh script exists and is executable
     let script_check = StdCommand::new("docker")
-        .args(&["exec", &container_name, "test", "-x", "/usr/local/bin/refresh-claude-auth.sh"])
+        .args(["exec", &container_name, "test", "-x", "/usr/local/bin/refresh-claude-auth.sh"])
         .output()
         .expect("Failed to execute docker command");
     
@@ -157,7 +157,7 @@ fn test_claude_files_mounted_correctly() {
     
     // Verify Claude Code is installed
     let claude_version = StdCommand::new("docker")
-        .args(&["exec", &container_name, "claude", "--version"])
+        .args(["exec", &container_name, "claude", "--version"])
         .output()
         .expect("Failed to execute docker command");
     
@@ -172,7 +172,7 @@ fn test_claude_files_mounted_correctly() {
     if home_dir.join(".claude").exists() || home_dir.join(".claude.json").exists() {
         // Check if files were copied to user home
         let output = StdCommand::new("docker")
-            .args(&["exec", &container_name, "bash", "-c", "ls -la /home/developer/.claude* 2>/dev/null || echo 'No files'"])
+            .args(["exec", &container_name, "bash", "-c", "ls -la /home/developer/.claude* 2>/dev/null || echo 'No files'"])
             .output()
             .expect("Failed to execute docker command");
         
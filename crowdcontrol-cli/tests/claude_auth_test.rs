@@ -13,6 +13,31 @@ fn test_refresh_command_validation() {
         .stderr(predicate::str::contains("required arguments were not provided"));
 }
 
+#[test]
+fn test_refresh_all_conflicts_with_name() {
+    let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
+    cmd.arg("refresh")
+        .arg("my-agent")
+        .arg("--all")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+#[test]
+fn test_refresh_all_without_agents() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
+    cmd.env("DOCKER_HOST", "unix:///nonexistent/docker.sock")
+        .arg("--workspaces-dir")
+        .arg(temp_dir.path())
+        .arg("refresh")
+        .arg("--all")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Refreshed 0 agent(s), 0 failed, 0 skipped"));
+}
+
 #[test]
 fn test_refresh_nonexistent_agent() {
     let temp_dir = TempDir::new().unwrap();
@@ -24,7 +49,7 @@ fn test_refresh_nonexistent_agent() {
         .arg("nonexistent-agent")
         .assert()
         .failure()
-        .stderr(predicate::str::contains("Agent 'nonexistent-agent' does not exist"));
+        .stderr(predicate::str::contains("Agent 'nonexistent-agent' not found"));
 }
 
 #[test]
@@ -35,6 +35,12 @@ fn test_subcommand_help() {
         "logs",
         "connect",
         "completions",
+        "top",
+        "switch",
+        "update",
+        "clean",
+        "inspect",
+        "version",
     ];
 
     for subcommand in subcommands {
@@ -135,13 +141,219 @@ fn test_new_invalid_agent_names() {
         cmd.arg("--workspaces-dir")
             .arg(temp_dir.path())
             .arg("new")
-            .arg(&name)
+            .arg(name)
             .arg("https://github.com/test/repo.git")
             .assert()
             .failure();
     }
 }
 
+/// Run crowdcontrol with a `git` whose clone takes long enough to be interrupted,
+/// and send it Ctrl-C (SIGINT) once the clone has started
+#[cfg(unix)]
+fn interrupt_during_clone(temp_dir: &TempDir, args: &[&str]) -> std::process::Output {
+    use std::os::unix::fs::PermissionsExt;
+    use std::time::{Duration, Instant};
+
+    let bin_dir = temp_dir.path().join("bin");
+    let cloning = temp_dir.path().join("cloning");
+    fs::create_dir_all(&bin_dir).unwrap();
+
+    let git = bin_dir.join("git");
+    fs::write(
+        &git,
+        format!("#!/bin/sh\ntouch {}\nsleep 2\nexit 1\n", cloning.display()),
+    )
+    .unwrap();
+    fs::set_permissions(&git, fs::Permissions::from_mode(0o755)).unwrap();
+    let path = format!(
+        "{}:{}",
+        bin_dir.display(),
+        std::env::var("PATH").unwrap_or_default()
+    );
+
+    let child = std::process::Command::new(assert_cmd::cargo::cargo_bin("crowdcontrol"))
+        .env("PATH", path)
+        .env("DOCKER_HOST", "unix:///nonexistent/docker.sock")
+        .args(args)
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while !cloning.exists() {
+        assert!(Instant::now() < deadline, "clone never started");
+        std::thread::sleep(Duration::from_millis(20));
+    }
+    let status = std::process::Command::new("kill")
+        .arg("-INT")
+        .arg(child.id().to_string())
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    child.wait_with_output().unwrap()
+}
+
+#[cfg(unix)]
+#[test]
+fn test_new_interrupted_removes_workspace() {
+    let temp_dir = TempDir::new().unwrap();
+    let workspaces_dir = temp_dir.path().join("workspaces");
+
+    let output = interrupt_during_clone(
+        &temp_dir,
+        &[
+            "--workspaces-dir",
+            workspaces_dir.to_str().unwrap(),
+            "new",
+            "interrupted",
+            "https://github.com/test/repo.git",
+        ],
+    );
+
+    // Ctrl-C rolls the agent back and exits like an interrupted process
+    assert_eq!(output.status.code(), Some(130));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Interrupted"));
+    assert!(!workspaces_dir.join("interrupted").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_clone_and_import_interrupted_remove_workspace() {
+    let temp_dir = TempDir::new().unwrap();
+    let workspaces_dir = temp_dir.path().join("workspaces");
+    seed_agent(&workspaces_dir, "source", None);
+
+    let output = interrupt_during_clone(
+        &temp_dir,
+        &[
+            "--workspaces-dir",
+            workspaces_dir.to_str().unwrap(),
+            "clone",
+            "source",
+            "cloned",
+        ],
+    );
+    assert_eq!(output.status.code(), Some(130));
+    assert!(!workspaces_dir.join("cloned").exists());
+
+    let spec = temp_dir.path().join("source.json");
+    let exported = Command::cargo_bin("crowdcontrol")
+        .unwrap()
+        .arg("--workspaces-dir")
+        .arg(&workspaces_dir)
+        .arg("export")
+        .arg("source")
+        .output()
+        .unwrap();
+    fs::write(&spec, exported.stdout).unwrap();
+    fs::remove_file(temp_dir.path().join("cloning")).unwrap();
+
+    let output = interrupt_during_clone(
+        &temp_dir,
+        &[
+            "--workspaces-dir",
+            workspaces_dir.to_str().unwrap(),
+            "import",
+            spec.to_str().unwrap(),
+            "--name",
+            "imported",
+        ],
+    );
+    assert_eq!(output.status.code(), Some(130));
+    assert!(!workspaces_dir.join("imported").exists());
+}
+
+#[test]
+fn test_doctor_check_docker_reports_unreachable_daemon() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
+    cmd.env("DOCKER_HOST", "unix:///nonexistent/docker.sock")
+        .arg("--workspaces-dir")
+        .arg(temp_dir.path())
+        .arg("doctor")
+        .arg("--check")
+        .arg("docker")
+        .assert()
+        .code(5)
+        .stdout(predicates::str::contains("✗ Docker daemon: not reachable"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_doctor_check_workspaces_reports_unusable_dir() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = TempDir::new().unwrap();
+    let workspaces_dir = temp_dir.path().join("workspaces");
+    fs::create_dir(&workspaces_dir).unwrap();
+    fs::set_permissions(&workspaces_dir, fs::Permissions::from_mode(0o000)).unwrap();
+
+    // Checked without Docker, and reported rather than failing opaquely
+    let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
+    let assert = cmd
+        .env("DOCKER_HOST", "unix:///nonexistent/docker.sock")
+        .arg("--workspaces-dir")
+        .arg(&workspaces_dir)
+        .arg("doctor")
+        .arg("--check")
+        .arg("workspaces")
+        .assert();
+
+    fs::set_permissions(&workspaces_dir, fs::Permissions::from_mode(0o755)).unwrap();
+    assert
+        .code(1)
+        .stdout(predicates::str::contains("✓ Workspaces directory"))
+        .stdout(predicates::str::contains(
+            "✗ Workspaces permissions: mode 000",
+        ))
+        .stderr(predicates::str::contains("panicked").not());
+}
+
+#[test]
+fn test_version_verbose_without_docker() {
+    let temp_dir = TempDir::new().unwrap();
+
+    // Docker being down is reported rather than failing the command
+    let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
+    cmd.env("DOCKER_HOST", "unix:///nonexistent/docker.sock")
+        .arg("--workspaces-dir")
+        .arg(temp_dir.path())
+        .arg("version")
+        .arg("--verbose")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(format!(
+            "crowdcontrol {}",
+            env!("CARGO_PKG_VERSION")
+        )))
+        .stdout(predicates::str::contains("Docker: unavailable"))
+        .stdout(predicates::str::contains("OS/arch:"));
+}
+
+#[test]
+fn test_unreachable_docker_explains_how_to_fix_it() {
+    let temp_dir = TempDir::new().unwrap();
+    seed_agent(temp_dir.path(), "offline", Some("abc123"));
+
+    // With nothing on the PATH, Docker looks uninstalled
+    let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
+    cmd.env("DOCKER_HOST", "unix:///nonexistent/docker.sock")
+        .env("PATH", "")
+        .arg("--workspaces-dir")
+        .arg(temp_dir.path())
+        .arg("inspect")
+        .arg("offline")
+        .assert()
+        .code(5)
+        .stderr(predicates::str::contains(
+            "Docker doesn't appear to be installed",
+        ));
+}
+
 #[test]
 fn test_start_nonexistent_agent() {
     let temp_dir = TempDir::new().unwrap();
@@ -156,6 +368,62 @@ fn test_start_nonexistent_agent() {
         .stderr(predicates::str::contains("not found"));
 }
 
+#[test]
+fn test_exit_codes() {
+    let temp_dir = TempDir::new().unwrap();
+    seed_agent(temp_dir.path(), "taken", None);
+
+    let crowdcontrol = || {
+        let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
+        cmd.env("DOCKER_HOST", "unix:///nonexistent/docker.sock")
+            .arg("--workspaces-dir")
+            .arg(temp_dir.path());
+        cmd
+    };
+
+    // Agent not found
+    crowdcontrol()
+        .arg("start")
+        .arg("does-not-exist")
+        .assert()
+        .code(3)
+        .stderr(predicates::str::contains("not found"));
+
+    // Agent already exists
+    crowdcontrol()
+        .arg("new")
+        .arg("taken")
+        .arg("https://github.com/test/repo.git")
+        .assert()
+        .code(4);
+
+    // Invalid arguments keep clap's code
+    crowdcontrol().arg("start").arg("--bogus").assert().code(2);
+
+    // Nothing to stop is not an error, and doesn't need Docker at all
+    let empty_dir = TempDir::new().unwrap();
+    Command::cargo_bin("crowdcontrol")
+        .unwrap()
+        .env_remove("DOCKER_HOST")
+        .arg("--workspaces-dir")
+        .arg(empty_dir.path())
+        .arg("stop")
+        .arg("--all")
+        .assert()
+        .code(0)
+        .stdout(predicates::str::contains("No running agents to stop"));
+}
+
+#[test]
+fn test_help_documents_exit_codes() {
+    let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
+    cmd.arg("--help")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Exit codes:"))
+        .stdout(predicates::str::contains("3    Agent not found"));
+}
+
 #[test]
 fn test_stop_nonexistent_agent() {
     let temp_dir = TempDir::new().unwrap();
@@ -184,6 +452,67 @@ fn test_logs_nonexistent_agent() {
         .stderr(predicates::str::contains("not found"));
 }
 
+#[test]
+fn test_logs_rejects_until_before_since() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
+    cmd.arg("--workspaces-dir")
+        .arg(temp_dir.path())
+        .arg("logs")
+        .arg("test-agent")
+        .arg("--since")
+        .arg("10m")
+        .arg("--until")
+        .arg("1h")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("is before --since"));
+}
+
+#[test]
+fn test_logs_rejects_invalid_grep_pattern() {
+    let temp_dir = TempDir::new().unwrap();
+
+    // The pattern is checked before the agent is looked up
+    let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
+    cmd.arg("--workspaces-dir")
+        .arg(temp_dir.path())
+        .arg("logs")
+        .arg("test-agent")
+        .arg("--grep")
+        .arg("(unclosed")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Invalid --grep pattern"));
+}
+
+#[test]
+fn test_quiet_list_prints_nothing() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
+    cmd.arg("-q")
+        .arg("--workspaces-dir")
+        .arg(temp_dir.path())
+        .arg("list")
+        .assert()
+        .success()
+        .stdout(predicates::str::is_empty());
+
+    // Errors are still reported
+    let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
+    cmd.arg("--quiet")
+        .arg("--no-color")
+        .arg("--workspaces-dir")
+        .arg(temp_dir.path())
+        .arg("logs")
+        .arg("does-not-exist")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("not found"));
+}
+
 #[test]
 fn test_remove_nonexistent_agent() {
     let temp_dir = TempDir::new().unwrap();
@@ -265,10 +594,6 @@ fn test_config_file_loading() {
     let config_dir = TempDir::new().unwrap();
     let workspaces_dir = TempDir::new().unwrap();
 
-    // Create config directory structure
-    let crowdcontrol_config_dir = config_dir.path().join(".config").join("crowdcontrol");
-    fs::create_dir_all(&crowdcontrol_config_dir).unwrap();
-
     // Write config file
     let config_content = format!(
         r#"
@@ -280,15 +605,20 @@ verbose = 1
 "#,
         workspaces_dir.path().display()
     );
+    let config_file = config_dir.path().join("config.toml");
+    fs::write(&config_file, config_content).unwrap();
 
-    fs::write(crowdcontrol_config_dir.join("config.toml"), config_content).unwrap();
-
-    // Test that config file is loaded - we'll test with help to avoid Docker requirement
+    // Test that config file is loaded - `config show` doesn't need Docker
     let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
-    cmd.env("HOME", config_dir.path())
-        .arg("--help")
+    cmd.env_remove("CROWDCONTROL_IMAGE")
+        .env_remove("CROWDCONTROL_DEFAULT_MEMORY")
+        .arg("--config")
+        .arg(&config_file)
+        .args(["config", "show"])
         .assert()
-        .success();
+        .success()
+        .stdout(predicate::str::contains("image = \"test:image\""))
+        .stdout(predicate::str::contains("default_memory = \"1g\""));
 }
 
 #[test]
@@ -342,6 +672,57 @@ fn test_new_with_resource_limits() {
         .failure(); // Will fail due to git clone, but arguments are parsed correctly
 }
 
+#[test]
+fn test_new_rejects_invalid_resource_limits() {
+    let temp_dir = TempDir::new().unwrap();
+
+    for (flag, value, message) in [
+        ("--memory", "notaunit", "Invalid memory value 'notaunit'"),
+        ("--cpus", "-1", "Invalid CPU value '-1'"),
+        ("--platform", "amd64", "Invalid platform 'amd64'"),
+        (
+            "--volume",
+            "/nonexistent/cache:/cache",
+            "Volume host path does not exist",
+        ),
+    ] {
+        let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
+        cmd.arg("--workspaces-dir")
+            .arg(temp_dir.path())
+            .arg("new")
+            .arg("test-agent")
+            .arg("https://github.com/test/repo.git")
+            .arg(flag)
+            .arg(value)
+            .assert()
+            .code(2)
+            .stderr(predicates::str::contains(message))
+            .stdout(predicates::str::contains("Cloning").not());
+
+        // Rejected before any workspace is created or clone attempted
+        assert!(!temp_dir.path().join("test-agent").exists());
+    }
+}
+
+#[test]
+fn test_new_rejects_swap_below_memory() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
+    cmd.arg("--workspaces-dir")
+        .arg(temp_dir.path())
+        .args(["new", "test-agent", "https://github.com/test/repo.git"])
+        .args(["--memory", "2g", "--memory-swap", "1g"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "Memory swap limit '1g' is smaller than the memory limit '2g'",
+        ))
+        .stdout(predicates::str::contains("Cloning").not());
+
+    assert!(!temp_dir.path().join("test-agent").exists());
+}
+
 #[test]
 fn test_logs_with_options() {
     let temp_dir = TempDir::new().unwrap();
@@ -368,6 +749,50 @@ fn test_logs_with_options() {
         .failure(); // Will fail due to missing agent
 }
 
+#[test]
+fn test_logs_rejects_invalid_tail() {
+    let temp_dir = TempDir::new().unwrap();
+
+    for (tail, message) in [("-1", "can't be negative"), ("lots", "'all'")] {
+        let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
+        cmd.arg("--workspaces-dir")
+            .arg(temp_dir.path())
+            .arg("logs")
+            .arg("test-agent")
+            .arg(format!("--tail={}", tail))
+            .assert()
+            .failure()
+            .stderr(predicates::str::contains(message));
+    }
+
+    // "all" is accepted; this fails only because the agent doesn't exist
+    let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
+    cmd.arg("--workspaces-dir")
+        .arg(temp_dir.path())
+        .arg("logs")
+        .arg("test-agent")
+        .arg("--tail")
+        .arg("all")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("not found"));
+}
+
+#[test]
+fn test_inspect_nonexistent_agent() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
+    cmd.env("DOCKER_HOST", "unix:///nonexistent/docker.sock")
+        .arg("--workspaces-dir")
+        .arg(temp_dir.path())
+        .arg("inspect")
+        .arg("does-not-exist")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("not found"));
+}
+
 #[test]
 fn test_remove_with_force() {
     let temp_dir = TempDir::new().unwrap();
@@ -392,7 +817,8 @@ fn test_stop_all_agents() {
         .arg("stop")
         .arg("--all")
         .assert()
-        .success(); // Should succeed even with no agents
+        .code(0) // Should succeed even with no agents, without Docker
+        .stdout(predicates::str::contains("No running agents to stop"));
 }
 
 #[test]
@@ -409,3 +835,534 @@ fn test_connect_with_custom_command() {
         .assert()
         .failure(); // Will fail due to missing agent
 }
+
+#[test]
+fn test_stop_timeout_conflicts_with_force() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
+    cmd.arg("--workspaces-dir")
+        .arg(temp_dir.path())
+        .arg("stop")
+        .arg("--all")
+        .arg("--force")
+        .arg("--timeout")
+        .arg("30")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("cannot be used with"));
+}
+
+/// Write agent metadata directly so commands can be exercised without Docker
+fn seed_agent(workspaces_dir: &std::path::Path, name: &str, container_id: Option<&str>) {
+    seed_agent_with_tags(workspaces_dir, name, container_id, &[]);
+}
+
+fn seed_agent_with_tags(
+    workspaces_dir: &std::path::Path,
+    name: &str,
+    container_id: Option<&str>,
+    tags: &[&str],
+) {
+    let metadata_dir = workspaces_dir.join(name).join(".crowdcontrol");
+    fs::create_dir_all(&metadata_dir).unwrap();
+
+    let metadata = serde_json::json!({
+        "_comment": "This file is auto-generated by CrowdControl. Do not edit manually.",
+        "name": name,
+        "repository": "https://github.com/test/repo.git",
+        "branch": null,
+        "created_at": "2024-01-01T00:00:00Z",
+        "container_id": container_id,
+        "tags": tags,
+    });
+    fs::write(
+        metadata_dir.join("metadata.json"),
+        serde_json::to_string_pretty(&metadata).unwrap(),
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_export_omits_machine_specific_fields() {
+    let temp_dir = TempDir::new().unwrap();
+    seed_agent_with_tags(
+        temp_dir.path(),
+        "export-agent",
+        Some("abc123"),
+        &["backend"],
+    );
+
+    let output = Command::cargo_bin("crowdcontrol")
+        .unwrap()
+        .arg("--workspaces-dir")
+        .arg(temp_dir.path())
+        .arg("export")
+        .arg("export-agent")
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    let spec: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(spec["name"], "export-agent");
+    assert_eq!(spec["repository"], "https://github.com/test/repo.git");
+    assert_eq!(spec["tags"], serde_json::json!(["backend"]));
+    assert!(spec.get("container_id").is_none());
+    assert!(spec.get("workspace_path").is_none());
+
+    let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
+    cmd.arg("--workspaces-dir")
+        .arg(temp_dir.path())
+        .arg("export")
+        .arg("export-agent")
+        .arg("--format")
+        .arg("yaml")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("name: export-agent"));
+}
+
+#[test]
+fn test_schema_describes_metadata_and_config() {
+    let schema = |kind: &str| -> serde_json::Value {
+        let output = Command::cargo_bin("crowdcontrol")
+            .unwrap()
+            .arg("schema")
+            .arg(kind)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        serde_json::from_slice(&output.stdout).unwrap()
+    };
+
+    let metadata = schema("metadata");
+    assert!(metadata["properties"]["_comment"].is_object());
+    assert!(metadata["properties"]["container_id"].is_object());
+    // Only what's written to metadata.json; the workspace path is derived from its location
+    assert!(metadata["properties"].get("workspace_path").is_none());
+
+    let config = schema("config");
+    assert!(config["properties"]["workspaces_dir"].is_object());
+    assert!(config["properties"]["image"].is_object());
+}
+
+#[test]
+fn test_import_rejects_invalid_spec() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
+    cmd.arg("--workspaces-dir")
+        .arg(temp_dir.path())
+        .arg("import")
+        .write_stdin("name: [not, a, spec")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Failed to parse agent spec"));
+}
+
+#[test]
+fn test_remove_dry_run_does_not_delete() {
+    let temp_dir = TempDir::new().unwrap();
+    seed_agent(temp_dir.path(), "dry-run-agent", Some("abc123"));
+    let workspace = temp_dir.path().join("dry-run-agent");
+
+    // --force together with --dry-run must still only print
+    let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
+    cmd.arg("--workspaces-dir")
+        .arg(temp_dir.path())
+        .arg("remove")
+        .arg("dry-run-agent")
+        .arg("--dry-run")
+        .arg("--force")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "Would remove container: crowdcontrol-dry-run-agent (abc123)",
+        ))
+        .stdout(predicates::str::contains(format!(
+            "Would delete workspace directory: {}",
+            workspace.display()
+        )));
+
+    assert!(workspace.exists());
+
+    let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
+    cmd.arg("--workspaces-dir")
+        .arg(temp_dir.path())
+        .arg("remove")
+        .arg("dry-run-agent")
+        .arg("--dry-run")
+        .arg("--keep-workspace")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Would keep workspace directory"));
+
+    assert!(workspace.exists());
+}
+
+#[test]
+fn test_remove_all_without_force_or_tty_deletes_nothing() {
+    let temp_dir = TempDir::new().unwrap();
+    seed_agent(temp_dir.path(), "bulk-one", None);
+    seed_agent(temp_dir.path(), "bulk-two", Some("abc123"));
+
+    // assert_cmd doesn't attach a terminal, so there is nobody to type YES
+    let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
+    cmd.env("DOCKER_HOST", "unix:///nonexistent/docker.sock")
+        .arg("--workspaces-dir")
+        .arg(temp_dir.path())
+        .arg("remove")
+        .arg("--all")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "Refusing to remove 2 agent(s) without --force",
+        ));
+
+    assert!(temp_dir.path().join("bulk-one").exists());
+    assert!(temp_dir.path().join("bulk-two").exists());
+}
+
+#[test]
+fn test_clone_refuses_existing_name() {
+    let temp_dir = TempDir::new().unwrap();
+    seed_agent(temp_dir.path(), "original", None);
+    seed_agent(temp_dir.path(), "taken", None);
+
+    let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
+    cmd.arg("--workspaces-dir")
+        .arg(temp_dir.path())
+        .arg("clone")
+        .arg("original")
+        .arg("taken")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Agent 'taken' already exists"));
+}
+
+#[test]
+fn test_list_filters_by_tag() {
+    let temp_dir = TempDir::new().unwrap();
+    seed_agent_with_tags(temp_dir.path(), "web", None, &["frontend"]);
+    seed_agent_with_tags(temp_dir.path(), "api", None, &["backend"]);
+
+    // Point at a socket that doesn't exist so statuses resolve without a daemon
+    let output = Command::cargo_bin("crowdcontrol")
+        .unwrap()
+        .env("DOCKER_HOST", "unix:///nonexistent/docker.sock")
+        .arg("--workspaces-dir")
+        .arg(temp_dir.path())
+        .arg("list")
+        .arg("--all")
+        .arg("--format")
+        .arg("json")
+        .arg("--tag")
+        .arg("frontend")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let agents: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let agents = agents.as_array().unwrap();
+    assert_eq!(agents.len(), 1);
+    assert_eq!(agents[0]["name"], "web");
+    assert_eq!(agents[0]["tags"], serde_json::json!(["frontend"]));
+}
+
+#[test]
+fn test_list_ndjson_prints_one_object_per_line() {
+    let temp_dir = TempDir::new().unwrap();
+    seed_agent_with_tags(temp_dir.path(), "web", None, &["frontend"]);
+    seed_agent_with_tags(temp_dir.path(), "api", None, &["backend"]);
+
+    let output = Command::cargo_bin("crowdcontrol")
+        .unwrap()
+        .env("DOCKER_HOST", "unix:///nonexistent/docker.sock")
+        .arg("--workspaces-dir")
+        .arg(temp_dir.path())
+        .arg("list")
+        .arg("--all")
+        .arg("--disk")
+        .arg("--format")
+        .arg("ndjson")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let mut names: Vec<String> = stdout
+        .lines()
+        .map(|line| {
+            let agent: serde_json::Value = serde_json::from_str(line).unwrap();
+            assert!(agent.is_object());
+            agent["name"].as_str().unwrap().to_string()
+        })
+        .collect();
+    names.sort();
+    assert_eq!(names, ["api", "web"]);
+}
+
+#[test]
+fn test_list_wide_shows_short_container_id() {
+    let temp_dir = TempDir::new().unwrap();
+    seed_agent(
+        temp_dir.path(),
+        "wide-agent",
+        Some("0123456789abcdef0123456789abcdef"),
+    );
+
+    let output = Command::cargo_bin("crowdcontrol")
+        .unwrap()
+        .env("DOCKER_HOST", "unix:///nonexistent/docker.sock")
+        .arg("--workspaces-dir")
+        .arg(temp_dir.path())
+        .arg("list")
+        .arg("--all")
+        .arg("--wide")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("CONTAINER ID"));
+    assert!(stdout.contains("0123456789ab"));
+    assert!(!stdout.contains("0123456789abc"));
+}
+
+#[test]
+fn test_list_created_time_formats() {
+    let temp_dir = TempDir::new().unwrap();
+    seed_agent(temp_dir.path(), "time-agent", None);
+
+    let list = |args: &[&str]| {
+        let output = Command::cargo_bin("crowdcontrol")
+            .unwrap()
+            .env("DOCKER_HOST", "unix:///nonexistent/docker.sock")
+            .arg("--workspaces-dir")
+            .arg(temp_dir.path())
+            .arg("list")
+            .arg("--all")
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+
+    // JSON always carries the RFC3339 timestamp, even with a table time format set
+    let json = list(&["--format", "json", "--time-format", "relative"]);
+    let agents: serde_json::Value = serde_json::from_str(&json).unwrap();
+    let created = agents[0]["created"].as_str().unwrap();
+    let parsed = chrono::DateTime::parse_from_rfc3339(created).unwrap();
+    assert_eq!(parsed.timestamp(), 1704067200);
+
+    let relative = list(&[]);
+    assert!(!relative.contains("2024-01-01T00:00:00Z"));
+
+    let iso = list(&["--time-format", "iso"]);
+    assert!(iso.contains("2024-01-01T00:00:00Z"));
+}
+
+#[test]
+fn test_list_watch_interval_parsing() {
+    let temp_dir = TempDir::new().unwrap();
+
+    for (interval, message) in [("0", "at least 1 second"), ("soon", "number of seconds")] {
+        Command::cargo_bin("crowdcontrol")
+            .unwrap()
+            .arg("--workspaces-dir")
+            .arg(temp_dir.path())
+            .arg("list")
+            .arg("--watch")
+            .arg(interval)
+            .assert()
+            .code(2)
+            .stderr(predicates::str::contains(message));
+    }
+}
+
+#[test]
+fn test_list_watch_conflicts_with_machine_formats() {
+    let temp_dir = TempDir::new().unwrap();
+
+    for format in ["json", "yaml", "ndjson"] {
+        Command::cargo_bin("crowdcontrol")
+            .unwrap()
+            .env("DOCKER_HOST", "unix:///nonexistent/docker.sock")
+            .arg("--workspaces-dir")
+            .arg(temp_dir.path())
+            .arg("list")
+            .arg("--watch")
+            .arg("--format")
+            .arg(format)
+            .assert()
+            .failure()
+            .stderr(predicates::str::contains(
+                "--watch only works with the table and wide formats",
+            ));
+    }
+}
+
+#[test]
+fn test_list_watch_reprints_without_terminal() {
+    let temp_dir = TempDir::new().unwrap();
+    seed_agent(temp_dir.path(), "watched-agent", None);
+
+    // Killed by the timeout; without a terminal each refresh is printed in full
+    let output = Command::cargo_bin("crowdcontrol")
+        .unwrap()
+        .env("DOCKER_HOST", "unix:///nonexistent/docker.sock")
+        .arg("--workspaces-dir")
+        .arg(temp_dir.path())
+        .arg("list")
+        .arg("--all")
+        .arg("--watch")
+        .arg("1")
+        .timeout(std::time::Duration::from_millis(3500))
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.matches("Every 1s: crowdcontrol list").count() >= 2);
+    assert!(stdout.matches("watched-agent").count() >= 2);
+    assert!(!stdout.contains("\x1b[2J"));
+}
+
+#[test]
+fn test_stop_tag_conflicts_with_name() {
+    let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
+    cmd.arg("stop")
+        .arg("my-agent")
+        .arg("--tag")
+        .arg("frontend")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("cannot be used with"));
+}
+
+#[test]
+fn test_completions_reference_agent_completion() {
+    for shell in ["bash", "zsh"] {
+        let output = Command::cargo_bin("crowdcontrol")
+            .unwrap()
+            .arg("completions")
+            .arg(shell)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("_crowdcontrol_agents"));
+        assert!(stdout.contains("crowdcontrol __complete_agents"));
+    }
+}
+
+#[test]
+fn test_completions_omit_hidden_commands() {
+    for shell in ["bash", "zsh", "fish"] {
+        let output = Command::cargo_bin("crowdcontrol")
+            .unwrap()
+            .arg("completions")
+            .arg(shell)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "completions {} failed", shell);
+
+        // Internal commands are never offered as subcommands
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("start"));
+        assert!(!stdout.contains("__wait_ready"));
+    }
+}
+
+#[test]
+fn test_complete_agents_lists_names() {
+    let temp_dir = TempDir::new().unwrap();
+
+    // Empty workspaces directory prints nothing
+    let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
+    cmd.arg("--workspaces-dir")
+        .arg(temp_dir.path())
+        .arg("__complete_agents")
+        .assert()
+        .success()
+        .stdout(predicates::str::is_empty());
+
+    seed_agent(temp_dir.path(), "beta", None);
+    seed_agent(temp_dir.path(), "alpha", None);
+
+    let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
+    cmd.arg("--workspaces-dir")
+        .arg(temp_dir.path())
+        .arg("__complete_agents")
+        .assert()
+        .success()
+        .stdout("alpha\nbeta\n");
+}
+
+#[test]
+fn test_connect_shell_conflicts() {
+    for conflicting in [["--command", "ls"].as_slice(), ["--detach"].as_slice()] {
+        let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
+        cmd.arg("connect")
+            .arg("my-agent")
+            .arg("--shell")
+            .args(conflicting)
+            .assert()
+            .failure()
+            .stderr(predicates::str::contains("cannot be used with"));
+    }
+}
+
+#[test]
+fn test_start_all_conflicts_with_name() {
+    let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
+    cmd.arg("start")
+        .arg("my-agent")
+        .arg("--all")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("cannot be used with"));
+}
+
+#[test]
+fn test_start_ready_file_requires_wait() {
+    let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
+    cmd.arg("start")
+        .arg("my-agent")
+        .arg("--ready-file")
+        .arg("my-agent.ready")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("--wait"));
+}
+
+#[test]
+fn test_new_run_setup_conflicts_with_skip_setup() {
+    let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
+    cmd.arg("new")
+        .arg("my-agent")
+        .arg("https://github.com/user/repo.git")
+        .arg("--run-setup")
+        .arg("--skip-setup")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("cannot be used with"));
+}
+
+#[test]
+fn test_new_rejects_self_dependency() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
+    cmd.arg("--workspaces-dir")
+        .arg(temp_dir.path())
+        .arg("new")
+        .arg("api")
+        .arg("https://github.com/user/repo.git")
+        .arg("--depends-on")
+        .arg("api")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("can't depend on itself"));
+}
@@ -1,8 +1,39 @@
 use assert_cmd::Command;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tempfile::TempDir;
 
+/// Run `config show --format json` against `config_file` with the given environment
+/// and extra arguments, returning the parsed output
+fn show_config(config_file: &Path, env: &[(&str, &Path)], args: &[&str]) -> serde_json::Value {
+    let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
+    for key in [
+        "CROWDCONTROL_WORKSPACES_DIR",
+        "CROWDCONTROL_IMAGE",
+        "CROWDCONTROL_DEFAULT_MEMORY",
+        "CROWDCONTROL_DEFAULT_CPUS",
+    ] {
+        cmd.env_remove(key);
+    }
+    for (key, value) in env {
+        cmd.env(key, value);
+    }
+
+    let output = cmd
+        .arg("--config")
+        .arg(config_file)
+        .args(args)
+        .args(["config", "show", "--format", "json"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
 /// Test the configuration system's layered approach
 #[test]
 fn test_config_layering_priority() {
@@ -11,10 +42,6 @@ fn test_config_layering_priority() {
     let workspace2 = TempDir::new().unwrap();
     let workspace3 = TempDir::new().unwrap();
 
-    // Setup: Create config file
-    let config_path = config_dir.path().join(".config").join("crowdcontrol");
-    fs::create_dir_all(&config_path).unwrap();
-
     let config_content = format!(
         r#"
 workspaces_dir = "{}"
@@ -25,61 +52,63 @@ verbose = 1
 "#,
         workspace1.path().display()
     );
-
-    fs::write(config_path.join("config.toml"), config_content).unwrap();
+    let config_file = config_dir.path().join("config.toml");
+    fs::write(&config_file, config_content).unwrap();
 
     // Test 1: Config file values are used when no overrides
-    let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
-    cmd.env("HOME", config_dir.path())
-        .env_clear() // Clear any existing CROWDCONTROL_ env vars
-        .env("HOME", config_dir.path())
-        .arg("--help")
-        .assert()
-        .success();
+    let config = show_config(&config_file, &[], &[]);
+    assert_eq!(
+        config["workspaces_dir"]["value"],
+        workspace1.path().display().to_string()
+    );
+    assert_eq!(config["image"]["value"], "from-config:latest");
+    assert_eq!(config["default_memory"]["value"], "2g");
 
     // Test 2: Environment variables override config file
-    let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
-    cmd.env("HOME", config_dir.path())
-        .env("CROWDCONTROL_WORKSPACES_DIR", workspace2.path())
-        .env("CROWDCONTROL_IMAGE", "from-env:latest")
-        .arg("--help")
-        .assert()
-        .success();
+    let env = [("CROWDCONTROL_WORKSPACES_DIR", workspace2.path())];
+    let config = show_config(&config_file, &env, &[]);
+    assert_eq!(
+        config["workspaces_dir"]["value"],
+        workspace2.path().display().to_string()
+    );
+    assert_eq!(config["workspaces_dir"]["source"], "env");
 
     // Test 3: CLI arguments override both env vars and config
-    let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
-    cmd.env("HOME", config_dir.path())
-        .env("CROWDCONTROL_WORKSPACES_DIR", workspace2.path())
-        .env("CROWDCONTROL_IMAGE", "from-env:latest")
-        .arg("--workspaces-dir")
-        .arg(workspace3.path())
-        .arg("--image")
-        .arg("from-cli:latest")
-        .arg("--help")
-        .assert()
-        .success();
+    let workspace3_arg = workspace3.path().display().to_string();
+    let config = show_config(
+        &config_file,
+        &env,
+        &[
+            "--workspaces-dir",
+            &workspace3_arg,
+            "--image",
+            "from-cli:latest",
+        ],
+    );
+    assert_eq!(config["workspaces_dir"]["value"], workspace3_arg);
+    assert_eq!(config["image"]["value"], "from-cli:latest");
+    assert_eq!(config["image"]["source"], "cli");
+    assert_eq!(config["default_memory"]["value"], "2g");
 }
 
 #[test]
 fn test_invalid_config_file_handling() {
     let config_dir = TempDir::new().unwrap();
-    let config_path = config_dir.path().join(".config").join("crowdcontrol");
-    fs::create_dir_all(&config_path).unwrap();
-
-    // Test 1: Invalid TOML syntax - the config crate seems to handle errors gracefully
-    // so we'll skip this test as it doesn't fail as expected
+    let config_file = config_dir.path().join("config.toml");
 
-    // fs::write(config_path.join("config.toml"), "invalid toml {").unwrap();
-    // let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
-    // cmd.env("HOME", config_dir.path())
-    //     .arg("--help")
-    //     .assert()
-    //     .failure()
-    //     .stderr(predicates::str::contains("Failed to load configuration"));
+    // Test 1: Invalid TOML syntax is reported rather than ignored
+    fs::write(&config_file, "invalid toml {").unwrap();
+    Command::cargo_bin("crowdcontrol")
+        .unwrap()
+        .arg("--config")
+        .arg(&config_file)
+        .args(["config", "show"])
+        .assert()
+        .failure();
 
-    // Test 2: Valid config to ensure test passes
+    // Test 2: Valid config loads
     fs::write(
-        config_path.join("config.toml"),
+        &config_file,
         r#"
 workspaces_dir = "/tmp/test"
 verbose = 1
@@ -87,19 +116,14 @@ verbose = 1
     )
     .unwrap();
 
-    let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
-    cmd.env("HOME", config_dir.path())
-        .arg("--help")
-        .assert()
-        .success();
+    let config = show_config(&config_file, &[], &[]);
+    assert_eq!(config["workspaces_dir"]["value"], "/tmp/test");
 }
 
 #[test]
 fn test_partial_config_with_defaults() {
     let config_dir = TempDir::new().unwrap();
     let workspace_dir = TempDir::new().unwrap();
-    let config_path = config_dir.path().join(".config").join("crowdcontrol");
-    fs::create_dir_all(&config_path).unwrap();
 
     // Only specify some fields in config
     let config_content = format!(
@@ -110,15 +134,17 @@ workspaces_dir = "{}"
 "#,
         workspace_dir.path().display()
     );
-
-    fs::write(config_path.join("config.toml"), config_content).unwrap();
-
-    // Should succeed with defaults for unspecified fields
-    let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
-    cmd.env("HOME", config_dir.path())
-        .arg("--help")
-        .assert()
-        .success();
+    let config_file = config_dir.path().join("config.toml");
+    fs::write(&config_file, config_content).unwrap();
+
+    // Defaults fill in the unspecified fields
+    let config = show_config(&config_file, &[], &[]);
+    assert_eq!(
+        config["workspaces_dir"]["source"],
+        format!("file ({})", config_file.display())
+    );
+    assert_eq!(config["image"]["source"], "default");
+    assert_eq!(config["verbose"]["value"], 0);
 }
 
 #[test]
@@ -146,8 +172,6 @@ image = "test:latest"
 fn test_verbose_accumulation() {
     let temp_dir = TempDir::new().unwrap();
     let config_dir = TempDir::new().unwrap();
-    let config_path = config_dir.path().join(".config").join("crowdcontrol");
-    fs::create_dir_all(&config_path).unwrap();
 
     // Config file sets verbose = 1
     let config_content = format!(
@@ -157,16 +181,16 @@ verbose = 1
 "#,
         temp_dir.path().display()
     );
+    let config_file = config_dir.path().join("config.toml");
+    fs::write(&config_file, config_content).unwrap();
 
-    fs::write(config_path.join("config.toml"), config_content).unwrap();
+    let config = show_config(&config_file, &[], &[]);
+    assert_eq!(config["verbose"]["value"], 1);
 
-    // Test: -vv on CLI should result in verbose level 3 (1 from config + 2 from CLI)
-    let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
-    cmd.env("HOME", config_dir.path())
-        .arg("-vv")
-        .arg("--help")
-        .assert()
-        .success();
+    // -vv on the CLI takes precedence over the config file
+    let config = show_config(&config_file, &[], &["-vv"]);
+    assert_eq!(config["verbose"]["value"], 2);
+    assert_eq!(config["verbose"]["source"], "cli");
 }
 
 #[test]
@@ -178,22 +202,84 @@ fn test_example_config_validity() {
         .join("config.example.toml");
 
     if example_path.exists() {
-        let config_dir = TempDir::new().unwrap();
-        let config_path = config_dir.path().join(".config").join("crowdcontrol");
-        fs::create_dir_all(&config_path).unwrap();
-
-        // Copy example config
-        fs::copy(&example_path, config_path.join("config.toml")).unwrap();
-
         // Should be able to load it
-        let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
-        cmd.env("HOME", config_dir.path())
-            .arg("--help")
-            .assert()
-            .success();
+        show_config(&example_path, &[], &[]);
     }
 }
 
+#[test]
+fn test_config_flag_loads_only_that_file() {
+    let dir = TempDir::new().unwrap();
+    let workspaces = dir.path().join("workspaces");
+    fs::write(
+        dir.path().join("my.toml"),
+        format!(
+            "workspaces_dir = {:?}\nimage = \"from-flag:latest\"\ndefault_memory = \"3g\"\n",
+            workspaces
+        ),
+    )
+    .unwrap();
+
+    // A config file in the usual location is not read alongside it
+    let home_dir = TempDir::new().unwrap();
+    let config_path = home_dir.path().join(".config").join("crowdcontrol");
+    fs::create_dir_all(&config_path).unwrap();
+    fs::write(config_path.join("config.toml"), "default_cpus = \"4\"\n").unwrap();
+
+    let output = Command::cargo_bin("crowdcontrol")
+        .unwrap()
+        .current_dir(dir.path())
+        .env_remove("CROWDCONTROL_WORKSPACES_DIR")
+        .env_remove("CROWDCONTROL_IMAGE")
+        .env_remove("CROWDCONTROL_DEFAULT_MEMORY")
+        .env_remove("CROWDCONTROL_DEFAULT_CPUS")
+        .env_remove("XDG_CONFIG_HOME")
+        .env("HOME", home_dir.path())
+        .args(["--config", "./my.toml", "--image", "from-cli:latest"])
+        .args(["config", "show", "--format", "json"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    let config: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        config["workspaces_dir"]["value"],
+        workspaces.display().to_string()
+    );
+    assert_eq!(config["default_memory"]["value"], "3g");
+    assert_eq!(config["default_memory"]["source"], "file (./my.toml)");
+    assert_eq!(config["default_cpus"]["source"], "default");
+    // Flags still override the file
+    assert_eq!(config["image"]["value"], "from-cli:latest");
+    assert_eq!(config["image"]["source"], "cli");
+
+    Command::cargo_bin("crowdcontrol")
+        .unwrap()
+        .current_dir(dir.path())
+        .args(["--config", "./my.toml", "config", "path"])
+        .assert()
+        .success()
+        .stdout("./my.toml\n");
+}
+
+#[test]
+fn test_config_flag_missing_file_fails() {
+    let dir = TempDir::new().unwrap();
+    let missing = dir.path().join("missing.toml");
+
+    Command::cargo_bin("crowdcontrol")
+        .unwrap()
+        .arg("--config")
+        .arg(&missing)
+        .arg("list")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(format!(
+            "Config file {} does not exist",
+            missing.display()
+        )));
+}
+
 #[test]
 fn test_memory_and_cpu_format_validation() {
     let temp_dir = TempDir::new().unwrap();
@@ -300,3 +386,120 @@ fn test_no_color_flag_vs_env() {
         .assert()
         .success();
 }
+
+#[test]
+fn test_config_show_reports_sources() {
+    let home_dir = TempDir::new().unwrap();
+    let workspaces = TempDir::new().unwrap();
+    let config_path = home_dir.path().join(".config").join("crowdcontrol");
+    fs::create_dir_all(&config_path).unwrap();
+    fs::write(config_path.join("config.yaml"), "default_memory: 2g\n").unwrap();
+
+    let output = Command::cargo_bin("crowdcontrol")
+        .unwrap()
+        .env_remove("CROWDCONTROL_WORKSPACES_DIR")
+        .env_remove("CROWDCONTROL_DEFAULT_CPUS")
+        .env_remove("XDG_CONFIG_HOME")
+        .env("HOME", home_dir.path())
+        .env("CROWDCONTROL_IMAGE", "from-env:latest")
+        .arg("--workspaces-dir")
+        .arg(workspaces.path())
+        .args(["config", "show"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("image = \"from-env:latest\"  # env"));
+    assert!(stdout.contains("default_memory = \"2g\"  # file ("));
+    assert!(stdout.contains("# default_cpus is not set  # default"));
+    assert!(stdout.contains("workspaces_dir = ") && stdout.contains("# cli"));
+
+    // JSON output carries the same provenance
+    let output = Command::cargo_bin("crowdcontrol")
+        .unwrap()
+        .env_remove("XDG_CONFIG_HOME")
+        .env("HOME", home_dir.path())
+        .env("CROWDCONTROL_IMAGE", "from-env:latest")
+        .arg("--workspaces-dir")
+        .arg(workspaces.path())
+        .args(["config", "show", "--format", "json"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    let config: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(config["image"]["value"], "from-env:latest");
+    assert_eq!(config["image"]["source"], "env");
+}
+
+#[test]
+fn test_config_path_lists_loaded_files() {
+    let home_dir = TempDir::new().unwrap();
+    let workspaces = TempDir::new().unwrap();
+    let config_path = home_dir.path().join(".config").join("crowdcontrol");
+    fs::create_dir_all(&config_path).unwrap();
+    fs::write(config_path.join("config.json"), "{}").unwrap();
+
+    Command::cargo_bin("crowdcontrol")
+        .unwrap()
+        .env_remove("XDG_CONFIG_HOME")
+        .env("HOME", home_dir.path())
+        .arg("--workspaces-dir")
+        .arg(workspaces.path())
+        .args(["config", "path"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("config.json"));
+}
+
+#[test]
+fn test_config_templates_lists_presets() {
+    let xdg_dir = TempDir::new().unwrap();
+    let workspaces = TempDir::new().unwrap();
+    let config_path = xdg_dir.path().join("crowdcontrol");
+    fs::create_dir_all(&config_path).unwrap();
+    fs::write(
+        config_path.join("templates.toml"),
+        "[backend]\nmemory = \"4g\"\nenv = { RUST_LOG = \"debug\" }\n\n[frontend]\ncpus = \"1\"\n",
+    )
+    .unwrap();
+
+    Command::cargo_bin("crowdcontrol")
+        .unwrap()
+        .env("XDG_CONFIG_HOME", xdg_dir.path())
+        .arg("--workspaces-dir")
+        .arg(workspaces.path())
+        .args(["config", "templates"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("backend"))
+        .stdout(predicates::str::contains("memory=4g"))
+        .stdout(predicates::str::contains("env=RUST_LOG"))
+        .stdout(predicates::str::contains("frontend"));
+}
+
+#[test]
+fn test_new_with_unknown_template_fails() {
+    let xdg_dir = TempDir::new().unwrap();
+    let workspaces = TempDir::new().unwrap();
+
+    Command::cargo_bin("crowdcontrol")
+        .unwrap()
+        .env("XDG_CONFIG_HOME", xdg_dir.path())
+        .arg("--workspaces-dir")
+        .arg(workspaces.path())
+        .args([
+            "new",
+            "templated",
+            "https://github.com/test/repo.git",
+            "--template",
+            "missing",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Unknown template 'missing'"));
+
+    // Nothing was created for the agent
+    assert!(!workspaces.path().join("templated").exists());
+}
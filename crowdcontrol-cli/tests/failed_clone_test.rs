@@ -91,3 +91,42 @@ fn test_failed_clone_with_valid_repo_invalid_branch() {
     cmd2.assert()
         .stderr(predicate::str::contains("already exists").not());
 }
+
+#[test]
+fn test_ssh_clone_without_credentials_is_actionable() {
+    let temp_workspace = TempDir::new().expect("Failed to create temp directory");
+    let empty_home = TempDir::new().expect("Failed to create temp directory");
+
+    // No ssh-agent and no keys in ~/.ssh
+    let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
+    cmd.env("CROWDCONTROL_WORKSPACES_DIR", temp_workspace.path())
+        .env("HOME", empty_home.path())
+        .env_remove("SSH_AUTH_SOCK")
+        .arg("new")
+        .arg("test-ssh-no-key")
+        .arg("git@github.com:nonexistent/repo.git");
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("No SSH credentials found"))
+        .stderr(predicate::str::contains("--ssh-key"));
+
+    assert!(!temp_workspace.path().join("test-ssh-no-key").exists());
+}
+
+#[test]
+fn test_ssh_clone_with_missing_key_file() {
+    let temp_workspace = TempDir::new().expect("Failed to create temp directory");
+
+    let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
+    cmd.env("CROWDCONTROL_WORKSPACES_DIR", temp_workspace.path())
+        .arg("new")
+        .arg("test-ssh-missing-key")
+        .arg("git@github.com:nonexistent/repo.git")
+        .arg("--ssh-key")
+        .arg(temp_workspace.path().join("no-such-key"));
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("SSH key not found"));
+}
@@ -0,0 +1,4 @@
+#!/bin/bash
+# Setup script used by test_new_runs_setup_script; runs from /workspace
+set -e
+touch setup-ran
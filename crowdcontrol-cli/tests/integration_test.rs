@@ -49,7 +49,7 @@ fn ensure_docker_image_built() {
 
         // Check if image exists
         let check = StdCommand::new("docker")
-            .args(&["image", "inspect", "crowdcontrol:latest"])
+            .args(["image", "inspect", "crowdcontrol:latest"])
             .output()
             .expect("Failed to check docker image");
 
@@ -63,7 +63,7 @@ fn ensure_docker_image_built() {
                 .join("container");
 
             let output = StdCommand::new("docker")
-                .args(&["build", "-t", "crowdcontrol:latest", "."])
+                .args(["build", "-t", "crowdcontrol:latest", "."])
                 .current_dir(&container_dir)
                 .output()
                 .expect("Failed to build docker image");
@@ -90,7 +90,7 @@ fn test_full_agent_lifecycle() {
     // This test requires Docker to be running
     let test_agent_name = format!(
         "test-agent-{}",
-        uuid::Uuid::new_v4().to_string()[0..8].to_string()
+        &uuid::Uuid::new_v4().to_string()[0..8]
     );
 
     // Get the absolute path to the fixture repo
@@ -112,19 +112,19 @@ fn test_full_agent_lifecycle() {
         println!("Initializing fixture as git repo...");
         StdCommand::new("git")
             .current_dir(&fixture_path)
-            .args(&["init"])
+            .args(["init"])
             .status()
             .expect("Failed to init git repo");
 
         StdCommand::new("git")
             .current_dir(&fixture_path)
-            .args(&["add", "."])
+            .args(["add", "."])
             .status()
             .expect("Failed to add files");
 
         StdCommand::new("git")
             .current_dir(&fixture_path)
-            .args(&["commit", "-m", "Initial commit"])
+            .args(["commit", "-m", "Initial commit"])
             .status()
             .expect("Failed to commit");
     }
@@ -159,14 +159,12 @@ fn test_full_agent_lifecycle() {
     let mut cmd = crowdcontrol_cmd();
     cmd.arg("start")
         .arg(&test_agent_name)
+        .arg("--wait")
         .timeout(Duration::from_secs(60))
         .assert()
         .success()
         .stdout(predicate::str::contains("started successfully"));
 
-    // Give container time to fully initialize
-    std::thread::sleep(Duration::from_secs(20));
-
     // Test 4: List should show agent as running
     println!("Verifying agent is running...");
     let mut cmd = crowdcontrol_cmd();
@@ -235,11 +233,11 @@ fn test_multiple_agents() {
 
     let agent1 = format!(
         "test-multi-1-{}",
-        uuid::Uuid::new_v4().to_string()[0..8].to_string()
+        &uuid::Uuid::new_v4().to_string()[0..8]
     );
     let agent2 = format!(
         "test-multi-2-{}",
-        uuid::Uuid::new_v4().to_string()[0..8].to_string()
+        &uuid::Uuid::new_v4().to_string()[0..8]
     );
 
     let manifest_dir = env::var("CARGO_MANIFEST_DIR").expect("CARGO_MANIFEST_DIR not set");
@@ -301,7 +299,7 @@ fn test_resource_limits() {
 
     let test_agent = format!(
         "test-limits-{}",
-        uuid::Uuid::new_v4().to_string()[0..8].to_string()
+        &uuid::Uuid::new_v4().to_string()[0..8]
     );
 
     let manifest_dir = env::var("CARGO_MANIFEST_DIR").expect("CARGO_MANIFEST_DIR not set");
@@ -334,7 +332,7 @@ fn test_agent_start_and_connect_issue() {
     // This test reproduces the issue where agent starts but shows as not running
     let test_agent_name = format!(
         "test-issue-{}",
-        uuid::Uuid::new_v4().to_string()[0..8].to_string()
+        &uuid::Uuid::new_v4().to_string()[0..8]
     );
 
     let manifest_dir = env::var("CARGO_MANIFEST_DIR").expect("CARGO_MANIFEST_DIR not set");
@@ -376,7 +374,7 @@ fn test_agent_start_and_connect_issue() {
 
     // Check container logs to debug any startup issues
     let output = StdCommand::new("docker")
-        .args(&["logs", &format!("crowdcontrol-{}", test_agent_name)])
+        .args(["logs", &format!("crowdcontrol-{}", test_agent_name)])
         .output()
         .expect("Failed to get container logs");
 
@@ -427,7 +425,7 @@ fn test_refresh_claude_credentials() {
 
     let test_agent_name = format!(
         "test-refresh-{}",
-        uuid::Uuid::new_v4().to_string()[0..8].to_string()
+        &uuid::Uuid::new_v4().to_string()[0..8]
     );
 
     let manifest_dir = env::var("CARGO_MANIFEST_DIR").expect("CARGO_MANIFEST_DIR not set");
@@ -501,7 +499,7 @@ fn test_refresh_claude_credentials() {
 
     // Verify credentials were copied into container
     let output = StdCommand::new("docker")
-        .args(&[
+        .args([
             "exec",
             &format!("crowdcontrol-{}", test_agent_name),
             "cat",
@@ -535,7 +533,7 @@ fn test_refresh_command_requires_running_agent() {
 
     let test_agent_name = format!(
         "test-refresh-stopped-{}",
-        uuid::Uuid::new_v4().to_string()[0..8].to_string()
+        &uuid::Uuid::new_v4().to_string()[0..8]
     );
 
     let manifest_dir = env::var("CARGO_MANIFEST_DIR").expect("CARGO_MANIFEST_DIR not set");
@@ -17,7 +17,7 @@ fn test_repo_cloned_to_workspace_root() {
     
     // Initialize git repo
     std::process::Command::new("git")
-        .args(&["init"])
+        .args(["init"])
         .current_dir(&repo_dir)
         .output()
         .expect("Failed to init git repo");
@@ -27,26 +27,26 @@ fn test_repo_cloned_to_workspace_root() {
     
     // Configure git user for the test
     std::process::Command::new("git")
-        .args(&["config", "user.email", "test@example.com"])
+        .args(["config", "user.email", "test@example.com"])
         .current_dir(&repo_dir)
         .output()
         .unwrap();
     
     std::process::Command::new("git")
-        .args(&["config", "user.name", "Test User"])
+        .args(["config", "user.name", "Test User"])
         .current_dir(&repo_dir)
         .output()
         .unwrap();
     
     // Add and commit the file
     std::process::Command::new("git")
-        .args(&["add", "."])
+        .args(["add", "."])
         .current_dir(&repo_dir)
         .output()
         .unwrap();
     
     std::process::Command::new("git")
-        .args(&["commit", "-m", "Initial commit"])
+        .args(["commit", "-m", "Initial commit"])
         .current_dir(&repo_dir)
         .output()
         .unwrap();
@@ -104,7 +104,7 @@ fn test_claude_json_transformations() {
     fs::write(temp_file.path(), original_config.to_string()).unwrap();
     
     let output = std::process::Command::new("jq")
-        .args(&[".autoUpdates = false | .projects = {} | .mode = \"global\""])
+        .args([".autoUpdates = false | .projects = {} | .mode = \"global\""])
         .arg(temp_file.path())
         .output()
         .expect("Failed to run jq command");
@@ -143,7 +143,7 @@ fn test_refresh_script_jq_transformations() {
     
     // Simulate the jq transformation from the refresh script
     let output = std::process::Command::new("jq")
-        .args(&[".autoUpdates = false | .projects = {} | .mode = \"global\""])
+        .args([".autoUpdates = false | .projects = {} | .mode = \"global\""])
         .arg(&claude_file)
         .output()
         .expect("Failed to run jq transformation");
@@ -161,4 +161,1440 @@ fn test_refresh_script_jq_transformations() {
     assert_eq!(transformed["mode"], "global");
     assert_eq!(transformed["projects"], json!({}));
     assert_eq!(transformed["numStartups"], 10); // Should be preserved
-}
\ No newline at end of file
+}
+/// Create a git repository with a single commit to use as a clone source
+fn create_local_git_repo(repo_dir: &std::path::Path) {
+    fs::create_dir_all(repo_dir).unwrap();
+    fs::write(repo_dir.join("README.md"), "# Test Repository").unwrap();
+
+    for args in [
+        vec!["init"],
+        vec!["config", "user.email", "test@example.com"],
+        vec!["config", "user.name", "Test User"],
+        vec!["add", "."],
+        vec!["commit", "-m", "Initial commit"],
+    ] {
+        std::process::Command::new("git")
+            .args(&args)
+            .current_dir(repo_dir)
+            .output()
+            .expect("Failed to run git");
+    }
+}
+
+#[test]
+#[ignore = "requires Docker"]
+fn test_new_json_output_contains_container_id() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo_dir = temp_dir.path().join("json-repo");
+    create_local_git_repo(&repo_dir);
+
+    let workspaces_dir = temp_dir.path().join("workspaces");
+    let output = Command::cargo_bin("crowdcontrol")
+        .unwrap()
+        .arg("--workspaces-dir")
+        .arg(&workspaces_dir)
+        .arg("new")
+        .arg("test-json-output")
+        .arg(&repo_dir)
+        .arg("--format")
+        .arg("json")
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(result["name"], json!("test-json-output"));
+    assert!(result["container_id"].is_string());
+    assert_eq!(result["has_crowdcontrol"], json!(false));
+
+    // Cleanup
+    Command::cargo_bin("crowdcontrol")
+        .unwrap()
+        .arg("--workspaces-dir")
+        .arg(&workspaces_dir)
+        .arg("remove")
+        .arg("test-json-output")
+        .arg("--force")
+        .assert()
+        .success();
+}
+
+#[test]
+#[ignore = "requires Docker"]
+fn test_switch_updates_branch_in_metadata() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo_dir = temp_dir.path().join("switch-repo");
+    create_local_git_repo(&repo_dir);
+    std::process::Command::new("git")
+        .args(["branch", "feature"])
+        .current_dir(&repo_dir)
+        .output()
+        .expect("Failed to run git");
+
+    let workspaces_dir = temp_dir.path().join("workspaces");
+    let agent_name = "test-switch-branch";
+    let crowdcontrol = || {
+        let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
+        cmd.arg("--workspaces-dir").arg(&workspaces_dir);
+        cmd
+    };
+
+    crowdcontrol()
+        .arg("new")
+        .arg(agent_name)
+        .arg(&repo_dir)
+        .assert()
+        .success();
+    crowdcontrol()
+        .arg("start")
+        .arg(agent_name)
+        .assert()
+        .success();
+
+    crowdcontrol()
+        .arg("switch")
+        .arg(agent_name)
+        .arg("feature")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("switched to branch 'feature'"));
+
+    let metadata: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(
+            workspaces_dir
+                .join(agent_name)
+                .join(".crowdcontrol")
+                .join("metadata.json"),
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(metadata["branch"], json!("feature"));
+
+    // Unknown branches are rejected and leave the metadata alone
+    crowdcontrol()
+        .arg("switch")
+        .arg(agent_name)
+        .arg("no-such-branch")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("does not exist"));
+
+    // Cleanup
+    crowdcontrol()
+        .arg("remove")
+        .arg(agent_name)
+        .arg("--force")
+        .assert()
+        .success();
+}
+
+#[test]
+#[ignore = "requires Docker"]
+fn test_update_pulls_new_upstream_commit() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo_dir = temp_dir.path().join("update-repo");
+    create_local_git_repo(&repo_dir);
+
+    let workspaces_dir = temp_dir.path().join("workspaces");
+    let agent_name = "test-update";
+    let crowdcontrol = || {
+        let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
+        cmd.arg("--workspaces-dir").arg(&workspaces_dir);
+        cmd
+    };
+    let git = |dir: &std::path::Path, args: &[&str]| {
+        let output = std::process::Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .expect("Failed to run git");
+        assert!(output.status.success(), "git {:?} failed", args);
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    };
+
+    crowdcontrol()
+        .arg("new")
+        .arg(agent_name)
+        .arg(&repo_dir)
+        .assert()
+        .success();
+    crowdcontrol()
+        .arg("start")
+        .arg(agent_name)
+        .assert()
+        .success();
+
+    // The host path origin isn't visible in the container, so serve upstream from a
+    // bare repository inside the workspace's .git directory instead
+    let workspace = workspaces_dir.join(agent_name);
+    let upstream = workspace.join(".git/upstream.git");
+    git(
+        temp_dir.path(),
+        &[
+            "clone",
+            "--bare",
+            repo_dir.to_str().unwrap(),
+            upstream.to_str().unwrap(),
+        ],
+    );
+    git(
+        &workspace,
+        &[
+            "remote",
+            "set-url",
+            "origin",
+            "/workspace/.git/upstream.git",
+        ],
+    );
+
+    // Nothing new upstream yet
+    crowdcontrol()
+        .arg("update")
+        .arg(agent_name)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("already up to date"));
+
+    let branch = git(&repo_dir, &["rev-parse", "--abbrev-ref", "HEAD"]);
+    fs::write(repo_dir.join("CHANGELOG.md"), "Upstream change").unwrap();
+    git(&repo_dir, &["add", "CHANGELOG.md"]);
+    git(&repo_dir, &["commit", "-m", "Upstream change"]);
+    git(
+        &repo_dir,
+        &[
+            "push",
+            upstream.to_str().unwrap(),
+            &format!("HEAD:{}", branch),
+        ],
+    );
+
+    crowdcontrol()
+        .arg("update")
+        .arg(agent_name)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("updated from"));
+    assert_eq!(
+        git(&workspace, &["log", "-1", "--format=%s"]),
+        "Upstream change"
+    );
+    assert!(workspace.join("CHANGELOG.md").exists());
+
+    // Uncommitted work is only stashed on request
+    fs::write(workspace.join("README.md"), "# Local edit").unwrap();
+    crowdcontrol()
+        .arg("update")
+        .arg(agent_name)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("uncommitted changes"));
+    crowdcontrol()
+        .arg("update")
+        .arg(agent_name)
+        .arg("--stash")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Stashed uncommitted changes"));
+
+    // Cleanup
+    crowdcontrol()
+        .arg("remove")
+        .arg(agent_name)
+        .arg("--force")
+        .assert()
+        .success();
+}
+
+#[test]
+#[ignore = "requires Docker"]
+fn test_clean_removes_untracked_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo_dir = temp_dir.path().join("clean-repo");
+    create_local_git_repo(&repo_dir);
+
+    let workspaces_dir = temp_dir.path().join("workspaces");
+    let agent_name = "test-clean";
+    let crowdcontrol = || {
+        let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
+        cmd.arg("--workspaces-dir").arg(&workspaces_dir);
+        cmd
+    };
+
+    crowdcontrol()
+        .arg("new")
+        .arg(agent_name)
+        .arg(&repo_dir)
+        .assert()
+        .success();
+    crowdcontrol()
+        .arg("start")
+        .arg(agent_name)
+        .assert()
+        .success();
+
+    let workspace = workspaces_dir.join(agent_name);
+    let untracked = workspace.join("scratch.txt");
+    fs::write(&untracked, "leftover").unwrap();
+
+    // A dry run only reports the file
+    crowdcontrol()
+        .arg("clean")
+        .arg(agent_name)
+        .arg("--dry-run")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Would remove scratch.txt"));
+    assert!(untracked.exists());
+
+    crowdcontrol()
+        .arg("clean")
+        .arg(agent_name)
+        .assert()
+        .success();
+    assert!(!untracked.exists());
+
+    // Agent metadata is never cleaned away
+    assert!(workspace.join(".crowdcontrol/metadata.json").exists());
+
+    // Cleanup
+    crowdcontrol()
+        .arg("remove")
+        .arg(agent_name)
+        .arg("--force")
+        .assert()
+        .success();
+}
+
+#[test]
+#[ignore = "requires Docker"]
+fn test_new_runs_setup_script() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo_dir = temp_dir.path().join("setup-repo");
+    let fixture = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/setup-script/setup.sh");
+    fs::create_dir_all(repo_dir.join(".crowdcontrol")).unwrap();
+    fs::copy(&fixture, repo_dir.join(".crowdcontrol/setup.sh")).unwrap();
+    create_local_git_repo(&repo_dir);
+
+    let workspaces_dir = temp_dir.path().join("workspaces");
+    let agent_name = "test-setup-script";
+    let crowdcontrol = || {
+        let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
+        cmd.arg("--workspaces-dir").arg(&workspaces_dir);
+        cmd
+    };
+
+    // The script exists, so setup runs without --run-setup
+    crowdcontrol()
+        .arg("new")
+        .arg(agent_name)
+        .arg(&repo_dir)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Setup script completed"));
+
+    let workspace = workspaces_dir.join(agent_name);
+    assert!(workspace.join("setup-ran").exists());
+    assert!(workspace.join(".crowdcontrol/.setup-complete").exists());
+
+    // Cleanup
+    crowdcontrol()
+        .arg("remove")
+        .arg(agent_name)
+        .arg("--force")
+        .assert()
+        .success();
+}
+
+#[test]
+#[ignore = "requires Docker"]
+fn test_lifecycle_commands_run_on_create_and_start() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo_dir = temp_dir.path().join("lifecycle-repo");
+    let fixture = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/lifecycle/config.toml");
+    fs::create_dir_all(repo_dir.join(".crowdcontrol")).unwrap();
+    fs::copy(&fixture, repo_dir.join(".crowdcontrol/config.toml")).unwrap();
+    create_local_git_repo(&repo_dir);
+
+    let workspaces_dir = temp_dir.path().join("workspaces");
+    let agent_name = "test-lifecycle";
+    let crowdcontrol = || {
+        let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
+        cmd.arg("--workspaces-dir").arg(&workspaces_dir);
+        cmd
+    };
+
+    crowdcontrol()
+        .arg("new")
+        .arg(agent_name)
+        .arg(&repo_dir)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Running post-create command"));
+
+    crowdcontrol()
+        .arg("stop")
+        .arg(agent_name)
+        .assert()
+        .success();
+    crowdcontrol()
+        .arg("start")
+        .arg(agent_name)
+        .assert()
+        .success();
+
+    // The post-create command ran once, the post-start command on each start
+    let workspace = workspaces_dir.join(agent_name);
+    let post_create = fs::read_to_string(workspace.join("post-create.log")).unwrap();
+    let post_start = fs::read_to_string(workspace.join("post-start.log")).unwrap();
+    assert_eq!(post_create.lines().count(), 1);
+    assert_eq!(post_start.lines().count(), 2);
+
+    // Cleanup
+    crowdcontrol()
+        .arg("remove")
+        .arg(agent_name)
+        .arg("--force")
+        .assert()
+        .success();
+}
+
+#[test]
+#[ignore = "requires Docker"]
+fn test_remove_running_agent_requires_force() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo_dir = temp_dir.path().join("running-repo");
+    create_local_git_repo(&repo_dir);
+
+    let workspaces_dir = temp_dir.path().join("workspaces");
+    let agent_name = "test-remove-running";
+    let crowdcontrol = || {
+        let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
+        cmd.arg("--workspaces-dir").arg(&workspaces_dir);
+        cmd
+    };
+
+    crowdcontrol()
+        .arg("new")
+        .arg(agent_name)
+        .arg(&repo_dir)
+        .assert()
+        .success();
+    crowdcontrol()
+        .arg("start")
+        .arg(agent_name)
+        .assert()
+        .success();
+
+    crowdcontrol()
+        .arg("remove")
+        .arg(agent_name)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "is running; stop it first or use --force",
+        ));
+    let workspace = workspaces_dir.join(agent_name);
+    assert!(workspace.exists());
+
+    crowdcontrol()
+        .arg("remove")
+        .arg(agent_name)
+        .arg("--force")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("stopped successfully"));
+    assert!(!workspace.exists());
+}
+
+#[test]
+#[ignore = "requires Docker"]
+fn test_inspect_prints_container_json() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo_dir = temp_dir.path().join("inspect-repo");
+    create_local_git_repo(&repo_dir);
+
+    let workspaces_dir = temp_dir.path().join("workspaces");
+    let agent_name = "test-inspect";
+    let crowdcontrol = || {
+        let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
+        cmd.arg("--workspaces-dir").arg(&workspaces_dir);
+        cmd
+    };
+
+    crowdcontrol()
+        .arg("new")
+        .arg(agent_name)
+        .arg(&repo_dir)
+        .assert()
+        .success();
+
+    let output = crowdcontrol()
+        .arg("inspect")
+        .arg(agent_name)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let info: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(info["Name"], "/crowdcontrol-test-inspect");
+
+    // Cleanup
+    crowdcontrol()
+        .arg("remove")
+        .arg(agent_name)
+        .arg("--force")
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_new_run_setup_requires_setup_script() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo_dir = temp_dir.path().join("no-setup-repo");
+    create_local_git_repo(&repo_dir);
+
+    Command::cargo_bin("crowdcontrol")
+        .unwrap()
+        .env("DOCKER_HOST", "unix:///nonexistent/docker.sock")
+        .arg("--workspaces-dir")
+        .arg(temp_dir.path().join("workspaces"))
+        .arg("new")
+        .arg("test-no-setup")
+        .arg(&repo_dir)
+        .arg("--run-setup")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("has no .crowdcontrol/setup.sh"));
+}
+
+#[test]
+fn test_new_failure_after_clone_removes_workspace() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo_dir = temp_dir.path().join("rollback-repo");
+    create_local_git_repo(&repo_dir);
+
+    let workspaces_dir = temp_dir.path().join("workspaces");
+    let agent_name = "test-rollback";
+    let crowdcontrol = || {
+        let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
+        cmd.env("DOCKER_HOST", "unix:///nonexistent/docker.sock")
+            .arg("--workspaces-dir")
+            .arg(&workspaces_dir);
+        cmd
+    };
+
+    // The missing setup script is only noticed once the repository has been cloned
+    crowdcontrol()
+        .arg("new")
+        .arg(agent_name)
+        .arg(&repo_dir)
+        .arg("--run-setup")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("has no .crowdcontrol/setup.sh"));
+    assert!(!workspaces_dir.join(agent_name).exists());
+
+    // Nothing is left behind to stop the same name being used again
+    crowdcontrol()
+        .arg("new")
+        .arg(agent_name)
+        .arg(&repo_dir)
+        .arg("--workspace-only")
+        .assert()
+        .success();
+}
+
+#[test]
+#[ignore = "requires Docker"]
+fn test_new_setup_failure_keeps_agent() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo_dir = temp_dir.path().join("failing-setup-repo");
+    fs::create_dir_all(repo_dir.join(".crowdcontrol")).unwrap();
+    fs::write(repo_dir.join(".crowdcontrol/setup.sh"), "#!/bin/bash\nexit 1\n").unwrap();
+    create_local_git_repo(&repo_dir);
+
+    let workspaces_dir = temp_dir.path().join("workspaces");
+    let agent_name = "test-failing-setup";
+    let crowdcontrol = || {
+        let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
+        cmd.arg("--workspaces-dir").arg(&workspaces_dir);
+        cmd
+    };
+
+    let new = crowdcontrol()
+        .arg("new")
+        .arg(agent_name)
+        .arg(&repo_dir)
+        .output()
+        .unwrap();
+    let workspace_kept = workspaces_dir
+        .join(agent_name)
+        .join(".crowdcontrol/metadata.json")
+        .exists();
+
+    // Cleanup
+    crowdcontrol()
+        .arg("remove")
+        .arg(agent_name)
+        .arg("--force")
+        .assert()
+        .success();
+
+    // The advice in the error points at an agent that still exists
+    assert!(!new.status.success());
+    assert!(String::from_utf8_lossy(&new.stderr).contains("was created and is running"));
+    assert!(workspace_kept);
+}
+
+#[test]
+fn test_new_require_crowdcontrol() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo_dir = temp_dir.path().join("no-setup-repo");
+    create_local_git_repo(&repo_dir);
+
+    let workspaces_dir = temp_dir.path().join("workspaces");
+    let agent_name = "test-require-crowdcontrol";
+    let crowdcontrol = || {
+        let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
+        cmd.env("DOCKER_HOST", "unix:///nonexistent/docker.sock")
+            .env_remove("CROWDCONTROL_REQUIRE_CROWDCONTROL")
+            .arg("--workspaces-dir")
+            .arg(&workspaces_dir);
+        cmd
+    };
+
+    crowdcontrol()
+        .arg("new")
+        .arg(agent_name)
+        .arg(&repo_dir)
+        .arg("--require-crowdcontrol")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "does not contain .crowdcontrol/ directory",
+        ));
+    assert!(!workspaces_dir.join(agent_name).exists());
+
+    // The same from the config, unless verification is skipped
+    crowdcontrol()
+        .env("CROWDCONTROL_REQUIRE_CROWDCONTROL", "true")
+        .arg("new")
+        .arg(agent_name)
+        .arg(&repo_dir)
+        .arg("--workspace-only")
+        .assert()
+        .failure();
+    crowdcontrol()
+        .env("CROWDCONTROL_REQUIRE_CROWDCONTROL", "true")
+        .arg("new")
+        .arg(agent_name)
+        .arg(&repo_dir)
+        .arg("--workspace-only")
+        .arg("--skip-verification")
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_new_workspace_only_skips_docker() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo_dir = temp_dir.path().join("workspace-only-repo");
+    create_local_git_repo(&repo_dir);
+
+    let workspaces_dir = temp_dir.path().join("workspaces");
+    let agent_name = "test-workspace-only";
+
+    // No Docker daemon is reachable, and none is needed
+    Command::cargo_bin("crowdcontrol")
+        .unwrap()
+        .env("DOCKER_HOST", "unix:///nonexistent/docker.sock")
+        .arg("--workspaces-dir")
+        .arg(&workspaces_dir)
+        .arg("new")
+        .arg(agent_name)
+        .arg(&repo_dir)
+        .arg("--workspace-only")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Start the agent with"));
+
+    let workspace = workspaces_dir.join(agent_name);
+    assert!(workspace.join("README.md").exists());
+
+    let metadata: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(workspace.join(".crowdcontrol/metadata.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(metadata["name"], agent_name);
+    assert_eq!(metadata["container_id"], serde_json::Value::Null);
+}
+
+#[test]
+fn test_new_follows_workspace_layout() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo_dir = temp_dir.path().join("layout-repo");
+    create_local_git_repo(&repo_dir);
+
+    let workspaces_dir = temp_dir.path().join("workspaces");
+    let new_agent = |name: &str, layout: &str| {
+        Command::cargo_bin("crowdcontrol")
+            .unwrap()
+            .env("DOCKER_HOST", "unix:///nonexistent/docker.sock")
+            .env("CROWDCONTROL_WORKSPACE_LAYOUT", layout)
+            .arg("--workspaces-dir")
+            .arg(&workspaces_dir)
+            .arg("new")
+            .arg(name)
+            .arg(&repo_dir)
+            .arg("--workspace-only")
+            .assert()
+            .success();
+
+        let workspace = workspaces_dir.join(name);
+        let metadata: serde_json::Value = serde_json::from_str(
+            &fs::read_to_string(workspace.join(".crowdcontrol/metadata.json")).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(metadata["workspace_layout"], layout);
+        workspace
+    };
+
+    let flat = new_agent("flat-agent", "flat");
+    assert!(flat.join("README.md").exists());
+    assert!(flat.join(".git").exists());
+    assert!(!flat.join("flat-agent").exists());
+
+    let nested = new_agent("nested-agent", "nested");
+    assert!(nested.join("nested-agent/README.md").exists());
+    assert!(nested.join("nested-agent/.git").exists());
+    assert!(!nested.join("README.md").exists());
+}
+
+#[test]
+fn test_new_records_agent_image() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo_dir = temp_dir.path().join("image-repo");
+    create_local_git_repo(&repo_dir);
+
+    let workspaces_dir = temp_dir.path().join("workspaces");
+    let new_agent = |name: &str, global_args: &[&str], new_args: &[&str]| {
+        let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
+        cmd.env("DOCKER_HOST", "unix:///nonexistent/docker.sock")
+            .env("CROWDCONTROL_IMAGE", "crowdcontrol:from-env")
+            .arg("--workspaces-dir")
+            .arg(&workspaces_dir)
+            .args(global_args)
+            .arg("new")
+            .arg(name)
+            .arg(&repo_dir)
+            .arg("--workspace-only")
+            .args(new_args)
+            .assert()
+            .success();
+
+        let metadata_path = workspaces_dir
+            .join(name)
+            .join(".crowdcontrol/metadata.json");
+        let metadata: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(metadata_path).unwrap()).unwrap();
+        metadata["image"].clone()
+    };
+
+    assert_eq!(
+        new_agent("with-image", &[], &["--image", "crowdcontrol:tools"]),
+        "crowdcontrol:tools"
+    );
+    assert_eq!(
+        new_agent("global-image", &["--image", "crowdcontrol:global"], &[]),
+        "crowdcontrol:global"
+    );
+    // An image from the environment is recorded too, so recreating the container
+    // doesn't switch the agent to another image
+    assert_eq!(new_agent("default-image", &[], &[]), "crowdcontrol:from-env");
+}
+
+#[test]
+fn test_new_create_branch_checks_out_new_branch() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo_dir = temp_dir.path().join("branch-repo");
+    create_local_git_repo(&repo_dir);
+
+    let workspaces_dir = temp_dir.path().join("workspaces");
+    Command::cargo_bin("crowdcontrol")
+        .unwrap()
+        .env("DOCKER_HOST", "unix:///nonexistent/docker.sock")
+        .arg("--workspaces-dir")
+        .arg(&workspaces_dir)
+        .arg("new")
+        .arg("test-create-branch")
+        .arg(&repo_dir)
+        .arg("--create-branch")
+        .arg("feature-x")
+        .arg("--workspace-only")
+        .assert()
+        .success();
+
+    let workspace = workspaces_dir.join("test-create-branch");
+    let head = std::process::Command::new("git")
+        .args(["rev-parse", "--abbrev-ref", "HEAD"])
+        .current_dir(&workspace)
+        .output()
+        .unwrap();
+    assert_eq!(String::from_utf8_lossy(&head.stdout).trim(), "feature-x");
+
+    let metadata: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(workspace.join(".crowdcontrol/metadata.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(metadata["branch"], "feature-x");
+}
+
+#[test]
+#[ignore = "requires Docker"]
+fn test_start_recreates_missing_container() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo_dir = temp_dir.path().join("recreate-repo");
+    create_local_git_repo(&repo_dir);
+
+    let workspaces_dir = temp_dir.path().join("workspaces");
+    let agent_name = "test-recreate-container";
+    let crowdcontrol = || {
+        let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
+        cmd.arg("--workspaces-dir").arg(&workspaces_dir);
+        cmd
+    };
+
+    crowdcontrol()
+        .arg("new")
+        .arg(agent_name)
+        .arg(&repo_dir)
+        .assert()
+        .success();
+
+    // Lose both the container and its recorded ID, as after a prune
+    let metadata_path = workspaces_dir
+        .join(agent_name)
+        .join(".crowdcontrol/metadata.json");
+    let mut metadata: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&metadata_path).unwrap()).unwrap();
+    let old_id = metadata["container_id"].as_str().unwrap().to_string();
+    std::process::Command::new("docker")
+        .args(["rm", "-f", &old_id])
+        .output()
+        .expect("Failed to run docker");
+    metadata["container_id"] = serde_json::Value::Null;
+    fs::write(&metadata_path, metadata.to_string()).unwrap();
+
+    crowdcontrol()
+        .arg("start")
+        .arg(agent_name)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Created container"));
+
+    let metadata: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&metadata_path).unwrap()).unwrap();
+    let new_id = metadata["container_id"].as_str().unwrap().to_string();
+    assert_ne!(new_id, old_id);
+
+    let inspect = std::process::Command::new("docker")
+        .args(["inspect", "--format", "{{.State.Running}}", &new_id])
+        .output()
+        .expect("Failed to run docker");
+
+    // Cleanup
+    crowdcontrol()
+        .arg("remove")
+        .arg(agent_name)
+        .arg("--force")
+        .assert()
+        .success();
+
+    assert_eq!(String::from_utf8_lossy(&inspect.stdout).trim(), "true");
+}
+
+#[test]
+#[ignore = "requires Docker"]
+fn test_start_after_stop_reuses_container() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo_dir = temp_dir.path().join("restart-repo");
+    create_local_git_repo(&repo_dir);
+
+    let workspaces_dir = temp_dir.path().join("workspaces");
+    let agent_name = "test-stop-start";
+    let crowdcontrol = || {
+        let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
+        cmd.arg("--workspaces-dir").arg(&workspaces_dir);
+        cmd
+    };
+
+    crowdcontrol()
+        .arg("new")
+        .arg(agent_name)
+        .arg(&repo_dir)
+        .assert()
+        .success();
+
+    let metadata_path = workspaces_dir
+        .join(agent_name)
+        .join(".crowdcontrol/metadata.json");
+    let read_metadata = || -> serde_json::Value {
+        serde_json::from_str(&fs::read_to_string(&metadata_path).unwrap()).unwrap()
+    };
+    let created_id = read_metadata()["container_id"].as_str().unwrap().to_string();
+
+    crowdcontrol().arg("stop").arg(agent_name).assert().success();
+    let after_stop = read_metadata()["container_id"].clone();
+    let restart = crowdcontrol().arg("start").arg(agent_name).output().unwrap();
+    let after_start = read_metadata()["container_id"].clone();
+
+    // A container whose ID went missing, as older versions cleared it on stop, is
+    // taken over rather than reported as a conflict
+    crowdcontrol().arg("stop").arg(agent_name).assert().success();
+    let mut metadata = read_metadata();
+    metadata["container_id"] = serde_json::Value::Null;
+    fs::write(&metadata_path, metadata.to_string()).unwrap();
+    let adopt = crowdcontrol().arg("start").arg(agent_name).output().unwrap();
+    let after_adopt = read_metadata()["container_id"].clone();
+
+    let inspect = std::process::Command::new("docker")
+        .args(["inspect", "--format", "{{.State.Running}}", &created_id])
+        .output()
+        .expect("Failed to run docker");
+
+    // Cleanup
+    crowdcontrol()
+        .arg("remove")
+        .arg(agent_name)
+        .arg("--force")
+        .assert()
+        .success();
+
+    assert_eq!(after_stop, created_id.as_str());
+    assert!(
+        restart.status.success(),
+        "start failed: {}",
+        String::from_utf8_lossy(&restart.stderr)
+    );
+    assert_eq!(after_start, created_id.as_str());
+    assert!(
+        adopt.status.success(),
+        "start failed: {}",
+        String::from_utf8_lossy(&adopt.stderr)
+    );
+    assert_eq!(after_adopt, created_id.as_str());
+    assert_eq!(String::from_utf8_lossy(&inspect.stdout).trim(), "true");
+}
+
+#[test]
+#[ignore = "requires Docker"]
+fn test_start_all_after_stop_all() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo_dir = temp_dir.path().join("start-all-repo");
+    create_local_git_repo(&repo_dir);
+
+    let workspaces_dir = temp_dir.path().join("workspaces");
+    let agent_names = ["test-start-all-a", "test-start-all-b"];
+    let crowdcontrol = || {
+        let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
+        cmd.arg("--workspaces-dir").arg(&workspaces_dir);
+        cmd
+    };
+    let container_id = |name: &str| {
+        let metadata_path = workspaces_dir.join(name).join(".crowdcontrol/metadata.json");
+        let metadata: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(metadata_path).unwrap()).unwrap();
+        metadata["container_id"].as_str().unwrap().to_string()
+    };
+
+    for name in agent_names {
+        crowdcontrol()
+            .arg("new")
+            .arg(name)
+            .arg(&repo_dir)
+            .assert()
+            .success();
+    }
+    let created_ids: Vec<String> = agent_names.iter().map(|name| container_id(name)).collect();
+
+    crowdcontrol().args(["stop", "--all"]).assert().success();
+    let start_all = crowdcontrol().args(["start", "--all"]).output().unwrap();
+
+    let running: Vec<String> = created_ids
+        .iter()
+        .map(|id| {
+            let inspect = std::process::Command::new("docker")
+                .args(["inspect", "--format", "{{.State.Running}}", id])
+                .output()
+                .expect("Failed to run docker");
+            String::from_utf8_lossy(&inspect.stdout).trim().to_string()
+        })
+        .collect();
+    let started_ids: Vec<String> = agent_names.iter().map(|name| container_id(name)).collect();
+
+    // Cleanup
+    for name in agent_names {
+        crowdcontrol()
+            .arg("remove")
+            .arg(name)
+            .arg("--force")
+            .assert()
+            .success();
+    }
+
+    assert!(
+        start_all.status.success(),
+        "start --all failed: {}",
+        String::from_utf8_lossy(&start_all.stderr)
+    );
+    // Both agents resume their stopped containers
+    assert_eq!(started_ids, created_ids);
+    assert_eq!(running, vec!["true", "true"]);
+}
+
+#[test]
+#[ignore = "requires Docker"]
+fn test_reset_replaces_container_and_keeps_workspace() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo_dir = temp_dir.path().join("reset-repo");
+    create_local_git_repo(&repo_dir);
+
+    let workspaces_dir = temp_dir.path().join("workspaces");
+    let agent_name = "test-reset-container";
+    let crowdcontrol = || {
+        let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
+        cmd.arg("--workspaces-dir").arg(&workspaces_dir);
+        cmd
+    };
+
+    crowdcontrol()
+        .arg("new")
+        .arg(agent_name)
+        .arg(&repo_dir)
+        .assert()
+        .success();
+
+    let workspace = workspaces_dir.join(agent_name);
+    fs::write(workspace.join("notes.txt"), "work in progress").unwrap();
+    let metadata_path = workspace.join(".crowdcontrol/metadata.json");
+    let container_id = || {
+        let metadata: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&metadata_path).unwrap()).unwrap();
+        metadata["container_id"].as_str().unwrap().to_string()
+    };
+    let old_id = container_id();
+
+    crowdcontrol()
+        .arg("reset")
+        .arg(agent_name)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Replaced the container"));
+
+    let new_id = container_id();
+    let old_exists = std::process::Command::new("docker")
+        .args(["inspect", &old_id])
+        .output()
+        .expect("Failed to run docker")
+        .status
+        .success();
+    let notes = fs::read_to_string(workspace.join("notes.txt")).unwrap_or_default();
+
+    // Cleanup
+    crowdcontrol()
+        .arg("remove")
+        .arg(agent_name)
+        .arg("--force")
+        .assert()
+        .success();
+
+    assert_ne!(new_id, old_id);
+    assert!(!old_exists, "The old container should be removed");
+    assert_eq!(notes, "work in progress");
+}
+
+#[test]
+#[ignore = "requires Docker"]
+fn test_connect_env_is_passed_to_session() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo_dir = temp_dir.path().join("connect-env-repo");
+    create_local_git_repo(&repo_dir);
+
+    let workspaces_dir = temp_dir.path().join("workspaces");
+    let agent_name = "test-connect-env";
+    let crowdcontrol = || {
+        let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
+        cmd.arg("--workspaces-dir").arg(&workspaces_dir);
+        cmd
+    };
+
+    crowdcontrol()
+        .arg("new")
+        .arg(agent_name)
+        .arg(&repo_dir)
+        .assert()
+        .success();
+    crowdcontrol()
+        .arg("start")
+        .arg(agent_name)
+        .arg("--wait")
+        .assert()
+        .success();
+
+    // --command is split on whitespace, so print the variable without a shell
+    let connect = crowdcontrol()
+        .arg("connect")
+        .arg(agent_name)
+        .arg("--env")
+        .arg("FOO=bar")
+        .arg("--command")
+        .arg("printenv FOO")
+        .output()
+        .unwrap();
+
+    // Cleanup
+    crowdcontrol()
+        .arg("remove")
+        .arg(agent_name)
+        .arg("--force")
+        .assert()
+        .success();
+
+    assert!(connect.status.success());
+    assert!(String::from_utf8_lossy(&connect.stdout).contains("bar"));
+}
+
+#[test]
+#[ignore = "requires Docker"]
+fn test_connect_auto_start_starts_stopped_agent() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo_dir = temp_dir.path().join("connect-auto-start-repo");
+    create_local_git_repo(&repo_dir);
+
+    let workspaces_dir = temp_dir.path().join("workspaces");
+    let agent_name = "test-connect-auto-start";
+    let crowdcontrol = || {
+        let mut cmd = Command::cargo_bin("crowdcontrol").unwrap();
+        cmd.arg("--workspaces-dir").arg(&workspaces_dir);
+        cmd
+    };
+
+    crowdcontrol()
+        .arg("new")
+        .arg(agent_name)
+        .arg(&repo_dir)
+        .assert()
+        .success();
+    let metadata_path = workspaces_dir
+        .join(agent_name)
+        .join(".crowdcontrol/metadata.json");
+    let container_id = || {
+        let metadata: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&metadata_path).unwrap()).unwrap();
+        metadata["container_id"].as_str().map(str::to_string)
+    };
+    let created_id = container_id().unwrap();
+    crowdcontrol().arg("stop").arg(agent_name).assert().success();
+
+    let connect = crowdcontrol()
+        .arg("connect")
+        .arg(agent_name)
+        .arg("--auto-start")
+        .arg("--command")
+        .arg("echo hi")
+        .arg("--detach")
+        .output()
+        .unwrap();
+
+    let started_id = container_id();
+    let inspect = std::process::Command::new("docker")
+        .args(["inspect", "--format", "{{.State.Running}}", &created_id])
+        .output()
+        .expect("Failed to run docker");
+
+    // Cleanup
+    crowdcontrol()
+        .arg("remove")
+        .arg(agent_name)
+        .arg("--force")
+        .assert()
+        .success();
+
+    assert!(
+        connect.status.success(),
+        "connect failed: {}",
+        String::from_utf8_lossy(&connect.stderr)
+    );
+    // The stopped container is started again rather than replaced
+    assert_eq!(started_id.as_deref(), Some(created_id.as_str()));
+    assert_eq!(String::from_utf8_lossy(&inspect.stdout).trim(), "true");
+}
+
+#[test]
+fn test_connect_rejects_malformed_env() {
+    let temp_dir = TempDir::new().unwrap();
+
+    Command::cargo_bin("crowdcontrol")
+        .unwrap()
+        .arg("--workspaces-dir")
+        .arg(temp_dir.path())
+        .arg("connect")
+        .arg("any-agent")
+        .arg("--env")
+        .arg("NO_VALUE")
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("expected KEY=VALUE"));
+}
+
+#[test]
+fn test_new_json_output_reports_errors_as_json() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let output = Command::cargo_bin("crowdcontrol")
+        .unwrap()
+        .arg("--workspaces-dir")
+        .arg(temp_dir.path())
+        .arg("new")
+        .arg("test-json-error")
+        .arg(temp_dir.path().join("does-not-exist"))
+        .arg("--format")
+        .arg("json")
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+    let error: serde_json::Value = serde_json::from_slice(&output.stderr).unwrap();
+    assert!(error["error"]
+        .as_str()
+        .unwrap()
+        .contains("Invalid repository URL"));
+}
+
+#[test]
+#[ignore = "requires Docker"]
+fn test_new_from_local_copies_checkout() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo_dir = temp_dir.path().join("local-repo");
+    create_local_git_repo(&repo_dir);
+    fs::write(repo_dir.join("uncommitted.txt"), "work in progress").unwrap();
+
+    let workspaces_dir = temp_dir.path().join("workspaces");
+    Command::cargo_bin("crowdcontrol")
+        .unwrap()
+        .arg("--workspaces-dir")
+        .arg(&workspaces_dir)
+        .arg("new")
+        .arg("test-from-local")
+        .arg("--from-local")
+        .arg(&repo_dir)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Repository copied successfully"));
+
+    let workspace = workspaces_dir.join("test-from-local");
+    assert!(workspace.join("README.md").exists());
+    assert!(workspace.join("uncommitted.txt").exists());
+
+    let metadata: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(workspace.join(".crowdcontrol").join("metadata.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        metadata["repository"],
+        json!(repo_dir.canonicalize().unwrap().display().to_string())
+    );
+    assert!(metadata["branch"].is_string());
+
+    // Cleanup
+    Command::cargo_bin("crowdcontrol")
+        .unwrap()
+        .arg("--workspaces-dir")
+        .arg(&workspaces_dir)
+        .arg("remove")
+        .arg("test-from-local")
+        .arg("--force")
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_from_local_conflicts_with_repository() {
+    let temp_dir = TempDir::new().unwrap();
+
+    Command::cargo_bin("crowdcontrol")
+        .unwrap()
+        .arg("new")
+        .arg("test-conflict")
+        .arg("https://github.com/test/repo.git")
+        .arg("--from-local")
+        .arg(temp_dir.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+
+    // --mount only makes sense for a local checkout
+    Command::cargo_bin("crowdcontrol")
+        .unwrap()
+        .arg("new")
+        .arg("test-conflict")
+        .arg("https://github.com/test/repo.git")
+        .arg("--mount")
+        .assert()
+        .failure();
+}
+
+#[test]
+#[ignore = "requires Docker"]
+fn test_export_then_import_into_fresh_workspaces_dir() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo_dir = temp_dir.path().join("portable-repo");
+    create_local_git_repo(&repo_dir);
+
+    let source_dir = temp_dir.path().join("source-workspaces");
+    let target_dir = temp_dir.path().join("target-workspaces");
+
+    Command::cargo_bin("crowdcontrol")
+        .unwrap()
+        .arg("--workspaces-dir")
+        .arg(&source_dir)
+        .arg("new")
+        .arg("test-portable")
+        .arg(&repo_dir)
+        .arg("--memory")
+        .arg("1g")
+        .arg("--tag")
+        .arg("exported")
+        .assert()
+        .success();
+
+    let exported = Command::cargo_bin("crowdcontrol")
+        .unwrap()
+        .arg("--workspaces-dir")
+        .arg(&source_dir)
+        .arg("export")
+        .arg("test-portable")
+        .arg("--format")
+        .arg("yaml")
+        .output()
+        .unwrap();
+    assert!(exported.status.success());
+
+    // Container names are global, so the copy gets a new name
+    Command::cargo_bin("crowdcontrol")
+        .unwrap()
+        .arg("--workspaces-dir")
+        .arg(&target_dir)
+        .arg("import")
+        .arg("--name")
+        .arg("test-portable-copy")
+        .write_stdin(exported.stdout)
+        .assert()
+        .success();
+
+    assert!(target_dir.join("test-portable-copy/README.md").exists());
+
+    let reexported = Command::cargo_bin("crowdcontrol")
+        .unwrap()
+        .arg("--workspaces-dir")
+        .arg(&target_dir)
+        .arg("export")
+        .arg("test-portable-copy")
+        .output()
+        .unwrap();
+    let spec: serde_json::Value = serde_json::from_slice(&reexported.stdout).unwrap();
+    assert_eq!(spec["repository"], json!(repo_dir.display().to_string()));
+    assert_eq!(spec["memory"], json!("1g"));
+    assert_eq!(spec["tags"], json!(["exported"]));
+
+    // Cleanup
+    for (dir, name) in [
+        (&source_dir, "test-portable"),
+        (&target_dir, "test-portable-copy"),
+    ] {
+        Command::cargo_bin("crowdcontrol")
+            .unwrap()
+            .arg("--workspaces-dir")
+            .arg(dir)
+            .arg("remove")
+            .arg(name)
+            .arg("--force")
+            .assert()
+            .success();
+    }
+}
+
+#[test]
+#[ignore = "requires Docker"]
+fn test_clone_copies_agent_definition() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo_dir = temp_dir.path().join("clone-source-repo");
+    create_local_git_repo(&repo_dir);
+    let workspaces_dir = temp_dir.path().join("workspaces");
+
+    Command::cargo_bin("crowdcontrol")
+        .unwrap()
+        .arg("--workspaces-dir")
+        .arg(&workspaces_dir)
+        .arg("new")
+        .arg("test-clone-source")
+        .arg(&repo_dir)
+        .arg("--memory")
+        .arg("1g")
+        .arg("--cpus")
+        .arg("1.5")
+        .arg("--tag")
+        .arg("parallel")
+        .assert()
+        .success();
+
+    Command::cargo_bin("crowdcontrol")
+        .unwrap()
+        .arg("--workspaces-dir")
+        .arg(&workspaces_dir)
+        .arg("clone")
+        .arg("test-clone-source")
+        .arg("test-clone-copy")
+        .assert()
+        .success();
+
+    let read_metadata = |name: &str| -> serde_json::Value {
+        let path = workspaces_dir
+            .join(name)
+            .join(".crowdcontrol/metadata.json");
+        serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+    };
+    let mut source = read_metadata("test-clone-source");
+    let mut copy = read_metadata("test-clone-copy");
+
+    // The copy has its own container, which is never the source's
+    assert!(copy["container_id"].is_string());
+    assert_ne!(copy["container_id"], source["container_id"]);
+    assert!(workspaces_dir.join("test-clone-copy/README.md").exists());
+
+    // Everything else about the definition matches
+    for field in ["name", "container_id", "created_at", "last_activity"] {
+        source.as_object_mut().unwrap().remove(field);
+        copy.as_object_mut().unwrap().remove(field);
+    }
+    assert_eq!(copy, source);
+
+    // Cleanup
+    for name in ["test-clone-source", "test-clone-copy"] {
+        Command::cargo_bin("crowdcontrol")
+            .unwrap()
+            .arg("--workspaces-dir")
+            .arg(&workspaces_dir)
+            .arg("remove")
+            .arg(name)
+            .arg("--force")
+            .assert()
+            .success();
+    }
+}
@@ -1,24 +1,229 @@
 use anyhow::{anyhow, Context, Result};
 use chrono::{DateTime, Utc};
 use fs2::FileExt;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::env;
 use std::fs::{self, OpenOptions};
 use std::io::{Read, Seek, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 use tracing::{debug, info, trace};
 
-use crate::{Agent, AgentStatus, Config};
+use crate::{Agent, AgentStatus, Config, CrowdControlError, EnvironmentCheck, WorkspaceLayout};
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Version of the `metadata.json` format this build writes. Bump it, and add a step to
+/// [`migrate_metadata`], when a change needs more than new fields with defaults.
+pub const METADATA_VERSION: u32 = 1;
+
+const METADATA_COMMENT: &str = "This file is auto-generated by CrowdControl. Do not edit manually.";
+
+/// Contents of an agent's `.crowdcontrol/metadata.json`
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct AgentMetadata {
+    /// Always "This file is auto-generated by CrowdControl. Do not edit manually.":
+    /// crowdcontrol rewrites the file, so change agents through the CLI instead
     #[serde(rename = "_comment")]
     pub comment: String,
+    /// Format version of the file; files written before versioning are version 0
+    #[serde(default)]
+    pub metadata_version: u32,
     pub name: String,
     pub repository: String,
     pub branch: Option<String>,
     pub created_at: DateTime<Utc>,
     pub container_id: Option<String>,
+    #[serde(default)]
+    pub memory: Option<String>,
+    #[serde(default)]
+    pub memory_swap: Option<String>,
+    #[serde(default)]
+    pub memory_reservation: Option<String>,
+    #[serde(default)]
+    pub cpus: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub local_mount: Option<PathBuf>,
+    #[serde(default)]
+    pub network: Option<String>,
+    #[serde(default)]
+    pub privileged: bool,
+    #[serde(default)]
+    pub cap_add: Vec<String>,
+    #[serde(default)]
+    pub read_only: bool,
+    #[serde(default)]
+    pub readonly_workspace: bool,
+    #[serde(default)]
+    pub no_mount_claude: bool,
+    #[serde(default)]
+    pub platform: Option<String>,
+    #[serde(default)]
+    pub image: Option<String>,
+    #[serde(default)]
+    pub restart_policy: Option<String>,
+    #[serde(default)]
+    pub gpus: Option<String>,
+    #[serde(default)]
+    pub volumes: Vec<String>,
+    #[serde(default)]
+    pub idle_timeout: Option<String>,
+    #[serde(default)]
+    pub post_create_done: bool,
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    #[serde(default)]
+    pub last_activity: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub workspace_layout: WorkspaceLayout,
+}
+
+impl AgentMetadata {
+    fn from_agent(agent: &Agent) -> Self {
+        Self {
+            comment: METADATA_COMMENT.to_string(),
+            metadata_version: METADATA_VERSION,
+            name: agent.name.clone(),
+            repository: agent.repository.clone(),
+            branch: agent.branch.clone(),
+            created_at: agent.created_at,
+            container_id: agent.container_id.clone(),
+            memory: agent.memory.clone(),
+            memory_swap: agent.memory_swap.clone(),
+            memory_reservation: agent.memory_reservation.clone(),
+            cpus: agent.cpus.clone(),
+            tags: agent.tags.clone(),
+            local_mount: agent.local_mount.clone(),
+            network: agent.network.clone(),
+            privileged: agent.privileged,
+            cap_add: agent.cap_add.clone(),
+            read_only: agent.read_only,
+            readonly_workspace: agent.readonly_workspace,
+            no_mount_claude: agent.no_mount_claude,
+            platform: agent.platform.clone(),
+            image: agent.image.clone(),
+            restart_policy: agent.restart_policy.clone(),
+            gpus: agent.gpus.clone(),
+            volumes: agent.volumes.clone(),
+            idle_timeout: agent.idle_timeout.clone(),
+            post_create_done: agent.post_create_done,
+            depends_on: agent.depends_on.clone(),
+            last_activity: agent.last_activity,
+            workspace_layout: agent.workspace_layout,
+        }
+    }
+
+    fn into_agent(self, workspace_path: PathBuf) -> Agent {
+        Agent {
+            name: self.name,
+            // Status is never persisted; it is computed live from Docker
+            status: AgentStatus::Created,
+            container_id: self.container_id,
+            repository: self.repository,
+            branch: self.branch,
+            created_at: self.created_at,
+            workspace_path,
+            memory: self.memory,
+            memory_swap: self.memory_swap,
+            memory_reservation: self.memory_reservation,
+            cpus: self.cpus,
+            tags: self.tags,
+            local_mount: self.local_mount,
+            network: self.network,
+            privileged: self.privileged,
+            cap_add: self.cap_add,
+            read_only: self.read_only,
+            readonly_workspace: self.readonly_workspace,
+            no_mount_claude: self.no_mount_claude,
+            platform: self.platform,
+            image: self.image,
+            restart_policy: self.restart_policy,
+            gpus: self.gpus,
+            volumes: self.volumes,
+            idle_timeout: self.idle_timeout,
+            post_create_done: self.post_create_done,
+            depends_on: self.depends_on,
+            last_activity: self.last_activity,
+            workspace_layout: self.workspace_layout,
+        }
+    }
+}
+
+/// Portable definition of an agent, used by `export`/`import` to move agents between
+/// machines. Machine-specific state (container ID, workspace and mount paths) is left out.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AgentSpec {
+    pub name: String,
+    pub repository: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_swap: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_reservation: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpus: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub network: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub privileged: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cap_add: Vec<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub read_only: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub readonly_workspace: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub no_mount_claude: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub platform: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub restart_policy: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gpus: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idle_timeout: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<String>,
+}
+
+impl AgentSpec {
+    pub fn from_agent(agent: &Agent) -> Self {
+        Self {
+            name: agent.name.clone(),
+            repository: agent.repository.clone(),
+            branch: agent.branch.clone(),
+            memory: agent.memory.clone(),
+            memory_swap: agent.memory_swap.clone(),
+            memory_reservation: agent.memory_reservation.clone(),
+            cpus: agent.cpus.clone(),
+            tags: agent.tags.clone(),
+            network: agent.network.clone(),
+            privileged: agent.privileged,
+            cap_add: agent.cap_add.clone(),
+            read_only: agent.read_only,
+            readonly_workspace: agent.readonly_workspace,
+            no_mount_claude: agent.no_mount_claude,
+            platform: agent.platform.clone(),
+            image: agent.image.clone(),
+            restart_policy: agent.restart_policy.clone(),
+            gpus: agent.gpus.clone(),
+            idle_timeout: agent.idle_timeout.clone(),
+            depends_on: agent.depends_on.clone(),
+        }
+    }
+
+    /// Parse a spec from JSON or YAML (JSON is valid YAML, so one parser handles both)
+    pub fn parse(document: &str) -> Result<Self> {
+        serde_yaml::from_str(document).context("Failed to parse agent spec")
+    }
 }
 
 pub fn save_agent_metadata(config: &Config, agent: &Agent) -> Result<()> {
@@ -38,14 +243,7 @@ pub fn save_agent_metadata(config: &Config, agent: &Agent) -> Result<()> {
         fs::create_dir_all(parent)?;
     }
 
-    let metadata = AgentMetadata {
-        comment: "This file is auto-generated by CrowdControl. Do not edit manually.".to_string(),
-        name: agent.name.clone(),
-        repository: agent.repository.clone(),
-        branch: agent.branch.clone(),
-        created_at: agent.created_at,
-        container_id: agent.container_id.clone(),
-    };
+    let metadata = AgentMetadata::from_agent(agent);
 
     let json = serde_json::to_string_pretty(&metadata)?;
 
@@ -92,7 +290,7 @@ pub fn load_agent_metadata(config: &Config, name: &str) -> Result<Agent> {
 
     if !metadata_path.exists() {
         debug!("Metadata file not found for agent '{}'", name);
-        return Err(anyhow!("Agent '{}' not found", name));
+        return Err(CrowdControlError::AgentNotFound(name.to_string()).into());
     }
 
     // Open file for reading
@@ -121,18 +319,53 @@ pub fn load_agent_metadata(config: &Config, name: &str) -> Result<Agent> {
 
     read_result?;
 
-    let metadata: AgentMetadata =
-        serde_json::from_str(&json).with_context(|| "Failed to parse agent metadata")?;
-
-    Ok(Agent {
-        name: metadata.name,
-        status: AgentStatus::Created,
-        container_id: metadata.container_id,
-        repository: metadata.repository,
-        branch: metadata.branch,
-        created_at: metadata.created_at,
-        workspace_path,
-    })
+    let metadata = parse_agent_metadata(&json)?;
+
+    Ok(metadata.into_agent(workspace_path))
+}
+
+/// Parse a `metadata.json` document, upgrading files written by older versions
+fn parse_agent_metadata(json: &str) -> Result<AgentMetadata> {
+    let mut document: serde_json::Value =
+        serde_json::from_str(json).with_context(|| "Failed to parse agent metadata")?;
+
+    let version = match document.get("metadata_version") {
+        None => 0,
+        Some(version) => version
+            .as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or_else(|| anyhow!("Invalid metadata_version in agent metadata: {}", version))?,
+    };
+    if version > METADATA_VERSION {
+        return Err(anyhow!(
+            "Agent metadata was written by a newer crowdcontrol (format version {}, this one \
+             reads up to {}). Upgrade crowdcontrol to use this agent.",
+            version,
+            METADATA_VERSION
+        ));
+    }
+    migrate_metadata(&mut document, version);
+
+    serde_json::from_value(document).with_context(|| "Failed to parse agent metadata")
+}
+
+/// Upgrade a metadata document from `version` to [`METADATA_VERSION`], one version at a time
+fn migrate_metadata(document: &mut serde_json::Value, version: u32) {
+    let Some(fields) = document.as_object_mut() else {
+        // Not an object; deserializing reports it
+        return;
+    };
+
+    if version < 1 {
+        // Version 0 predates the version field; fields added since then have defaults,
+        // and early files may lack the comment
+        debug!("Upgrading agent metadata from format version 0");
+        fields
+            .entry("_comment")
+            .or_insert_with(|| METADATA_COMMENT.into());
+    }
+
+    fields.insert("metadata_version".to_string(), METADATA_VERSION.into());
 }
 
 pub fn list_all_agents(config: &Config) -> Result<Vec<String>> {
@@ -156,6 +389,9 @@ pub fn list_all_agents(config: &Config) -> Result<Vec<String>> {
         }
     }
 
+    // Keep ordering deterministic regardless of directory iteration order
+    agents.sort();
+
     Ok(agents)
 }
 
@@ -169,7 +405,7 @@ where
     let metadata_path = workspace_path.join(".crowdcontrol").join("metadata.json");
 
     if !metadata_path.exists() {
-        return Err(anyhow!("Agent '{}' not found", name));
+        return Err(CrowdControlError::AgentNotFound(name.to_string()).into());
     }
 
     // Open file for read/write
@@ -191,32 +427,16 @@ where
     let mut json = String::new();
     file.read_to_string(&mut json)?;
 
-    let metadata: AgentMetadata =
-        serde_json::from_str(&json).with_context(|| "Failed to parse agent metadata")?;
+    let metadata = parse_agent_metadata(&json)?;
 
     // Create agent from metadata
-    let mut agent = Agent {
-        name: metadata.name,
-        status: AgentStatus::Created,
-        container_id: metadata.container_id,
-        repository: metadata.repository,
-        branch: metadata.branch,
-        created_at: metadata.created_at,
-        workspace_path: workspace_path.clone(),
-    };
+    let mut agent = metadata.into_agent(workspace_path);
 
     // Apply the update
     update_fn(&mut agent)?;
 
     // Convert back to metadata
-    let updated_metadata = AgentMetadata {
-        comment: "This file is auto-generated by CrowdControl. Do not edit manually.".to_string(),
-        name: agent.name.clone(),
-        repository: agent.repository.clone(),
-        branch: agent.branch.clone(),
-        created_at: agent.created_at,
-        container_id: agent.container_id.clone(),
-    };
+    let updated_metadata = AgentMetadata::from_agent(&agent);
 
     let updated_json = serde_json::to_string_pretty(&updated_metadata)?;
 
@@ -252,14 +472,166 @@ pub fn validate_agent_name(name: &str) -> Result<()> {
     Ok(())
 }
 
+/// SSH settings used when cloning a repository over SSH
+#[derive(Debug, Clone, Default)]
+pub struct SshOptions {
+    /// Private key passed to ssh with `-i` instead of the agent/default keys
+    pub key: Option<PathBuf>,
+    /// Trust unknown host keys on first connection (`StrictHostKeyChecking=accept-new`)
+    pub accept_new_host_keys: bool,
+}
+
+/// Whether a repository URL is cloned over SSH (`ssh://` or scp-like `user@host:path`)
+pub fn is_ssh_url(repository: &str) -> bool {
+    if repository.starts_with("ssh://") {
+        return true;
+    }
+
+    // scp-like syntax has no scheme, e.g. git@github.com:org/repo.git
+    !repository.contains("://")
+        && repository
+            .split_once(':')
+            .is_some_and(|(host, _)| host.contains('@') && !host.contains('/'))
+}
+
+/// Check that `repository` is something git can clone before any work is done for it:
+/// an `https://`, `http://`, `ssh://`, `git://` or `file://` URL, scp-like `user@host:path`,
+/// or an existing local path. Returns the URL to clone; GitHub https URLs get their `.git`.
+pub fn validate_repository_url(repository: &str) -> Result<String> {
+    let invalid = |reason: &str| {
+        anyhow!(
+            "Invalid repository URL '{}': {}.\n\
+             Use https://host/org/repo.git, git@host:org/repo.git, ssh://, file:// or a local path.",
+            repository,
+            reason
+        )
+    };
+
+    if repository.is_empty() {
+        return Err(invalid("it is empty"));
+    }
+    if repository.chars().any(char::is_whitespace) {
+        return Err(invalid("it contains whitespace"));
+    }
+
+    if let Some((scheme, rest)) = repository.split_once("://") {
+        return match scheme {
+            "file" if rest.is_empty() => Err(invalid("no path given")),
+            "file" => Ok(repository.to_string()),
+            "https" | "http" | "ssh" | "git" => {
+                let (host, path) = rest.split_once('/').unwrap_or((rest, ""));
+                if host.is_empty() || host.ends_with('@') {
+                    return Err(invalid("no host given"));
+                }
+                let path = path.trim_end_matches('/');
+                if path.is_empty() {
+                    return Err(invalid("no repository path given"));
+                }
+                if scheme == "https" && host == "github.com" && !path.ends_with(".git") {
+                    return Ok(format!("https://github.com/{}.git", path));
+                }
+                Ok(repository.to_string())
+            }
+            _ => Err(invalid(&format!("unsupported scheme '{}'", scheme))),
+        };
+    }
+
+    if is_ssh_url(repository) {
+        let (user_host, path) = repository.split_once(':').unwrap_or_default();
+        let (user, host) = user_host.rsplit_once('@').unwrap_or_default();
+        if user.is_empty() || host.is_empty() {
+            return Err(invalid("expected user@host:path"));
+        }
+        if path.is_empty() {
+            return Err(invalid("no repository path given"));
+        }
+        return Ok(repository.to_string());
+    }
+
+    if Path::new(repository).exists() {
+        return Ok(repository.to_string());
+    }
+
+    Err(invalid("not a URL or an existing local path"))
+}
+
+/// Build the `GIT_SSH_COMMAND` for the given options, if any customization is needed
+pub fn git_ssh_command(options: &SshOptions) -> Option<String> {
+    if options.key.is_none() && !options.accept_new_host_keys {
+        return None;
+    }
+
+    let mut command = "ssh".to_string();
+    if let Some(key) = &options.key {
+        command.push_str(&format!(" -i '{}' -o IdentitiesOnly=yes", key.display()));
+    }
+    if options.accept_new_host_keys {
+        command.push_str(" -o StrictHostKeyChecking=accept-new");
+    }
+    Some(command)
+}
+
+/// Ensure some SSH credentials are available before attempting an SSH clone,
+/// so a missing key produces an actionable error instead of a git failure
+fn check_ssh_credentials(repository: &str, options: &SshOptions) -> Result<()> {
+    if let Some(key) = &options.key {
+        if !key.exists() {
+            return Err(anyhow!("SSH key not found: {:?}", key));
+        }
+        return Ok(());
+    }
+
+    // A running ssh-agent supplies keys without them touching disk
+    if env::var_os("SSH_AUTH_SOCK").is_some() {
+        return Ok(());
+    }
+
+    let has_default_key = dirs::home_dir()
+        .map(|home| home.join(".ssh"))
+        .is_some_and(|ssh_dir| {
+            ["config", "id_ed25519", "id_ecdsa", "id_rsa"]
+                .iter()
+                .any(|file| ssh_dir.join(file).exists())
+        });
+
+    if has_default_key {
+        return Ok(());
+    }
+
+    Err(anyhow!(
+        "No SSH credentials found to clone {}.\n\
+         Load your key into ssh-agent, pass --ssh-key <path>, or use an https:// URL.\n\
+         The key is only used by git on the host and is never copied into the container.",
+        repository
+    ))
+}
+
 pub fn clone_repository(
     repository: &str,
     target_path: &PathBuf,
     branch: Option<&str>,
+) -> Result<()> {
+    clone_repository_with_ssh(repository, target_path, branch, &SshOptions::default())
+}
+
+pub fn clone_repository_with_ssh(
+    repository: &str,
+    target_path: &PathBuf,
+    branch: Option<&str>,
+    ssh: &SshOptions,
 ) -> Result<()> {
     let mut cmd = Command::new("git");
     cmd.arg("clone");
 
+    if is_ssh_url(repository) {
+        check_ssh_credentials(repository, ssh)?;
+
+        if let Some(ssh_command) = git_ssh_command(ssh) {
+            debug!("Using GIT_SSH_COMMAND: {}", ssh_command);
+            cmd.env("GIT_SSH_COMMAND", ssh_command);
+        }
+    }
+
     if let Some(branch_name) = branch {
         cmd.arg("-b").arg(branch_name);
         info!(
@@ -277,6 +649,13 @@ pub fn clone_repository(
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
+        if is_ssh_url(repository) && stderr.contains("Host key verification failed") {
+            return Err(anyhow!(
+                "Failed to clone repository: host key verification failed.\n\
+                 Add the host to ~/.ssh/known_hosts, or pass --ssh-accept-new to trust it on \
+                 first use (this skips protection against a spoofed host on the first connection)."
+            ));
+        }
         return Err(anyhow!("Failed to clone repository: {}", stderr));
     }
 
@@ -284,10 +663,144 @@ pub fn clone_repository(
     Ok(())
 }
 
-pub fn verify_repository_setup(workspace_path: &PathBuf) -> Result<bool> {
+/// Copy an existing local checkout into the agent workspace, including `.git`
+/// and any uncommitted work. Symlinks are copied as links, not followed.
+pub fn copy_local_repository(source: &Path, target_path: &Path) -> Result<()> {
+    if !source.is_dir() {
+        return Err(anyhow!(
+            "Local repository path is not a directory: {:?}",
+            source
+        ));
+    }
+
+    info!("Copying local repository {:?} to {:?}", source, target_path);
+    copy_dir_recursive(source, target_path)
+        .with_context(|| format!("Failed to copy local repository {:?}", source))?;
+
+    debug!("Local repository copied successfully");
+    Ok(())
+}
+
+fn copy_dir_recursive(source: &Path, target: &Path) -> Result<()> {
+    fs::create_dir_all(target)?;
+
+    for entry in fs::read_dir(source)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        let destination = target.join(entry.file_name());
+
+        if file_type.is_dir() {
+            copy_dir_recursive(&entry.path(), &destination)?;
+        } else if file_type.is_symlink() {
+            #[cfg(unix)]
+            std::os::unix::fs::symlink(fs::read_link(entry.path())?, &destination)?;
+            #[cfg(not(unix))]
+            fs::copy(entry.path(), &destination)?;
+        } else {
+            fs::copy(entry.path(), &destination)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Current branch of a local git checkout, or `None` if it isn't a git
+/// repository or HEAD is detached
+pub fn detect_current_branch(repo_path: &Path) -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--abbrev-ref", "HEAD"])
+        .current_dir(repo_path)
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let branch = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if branch.is_empty() || branch == "HEAD" {
+        None
+    } else {
+        Some(branch)
+    }
+}
+
+/// Create `branch` from the current HEAD of a host checkout and check it out. Fails
+/// if the branch already exists locally or on `origin`, unless `force` is set, in
+/// which case a local branch of that name is reset to HEAD.
+pub fn create_branch(repo_path: &Path, branch: &str, force: bool) -> Result<()> {
+    let git = |args: &[&str]| {
+        Command::new("git")
+            .args(args)
+            .current_dir(repo_path)
+            .output()
+            .context("Failed to execute git")
+    };
+
+    if !force {
+        for reference in [
+            format!("refs/heads/{}", branch),
+            format!("refs/remotes/origin/{}", branch),
+        ] {
+            if git(&["rev-parse", "--verify", "--quiet", &reference])?
+                .status
+                .success()
+            {
+                return Err(anyhow!(
+                    "Branch '{}' already exists; check it out with --branch, or pass --force \
+                     to recreate it from the cloned HEAD",
+                    branch
+                ));
+            }
+        }
+    }
+
+    info!("Creating branch {} in {:?}", branch, repo_path);
+    let flag = if force { "-B" } else { "-b" };
+    let output = git(&["checkout", flag, branch])?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "Failed to create branch '{}': {}",
+            branch,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    Ok(())
+}
+
+/// Files a repository can provide in `.crowdcontrol/`, with what each one does
+pub const REPOSITORY_SETUP_FILES: [(&str, &str); 4] = [
+    ("setup.sh", "one-time setup, run by `new`"),
+    ("start.sh", "runs every time the container starts"),
+    ("stop.sh", "runs when the container stops"),
+    ("config.toml", "repository settings"),
+];
+
+/// What a repository's `.crowdcontrol/` directory provides
+#[derive(Debug, Clone, PartialEq)]
+pub struct RepositorySetupReport {
+    /// Whether `.crowdcontrol/` exists at all
+    pub has_crowdcontrol_dir: bool,
+    /// Each of [`REPOSITORY_SETUP_FILES`], in order, and whether the repository has it
+    pub files: Vec<(&'static str, bool)>,
+}
+
+/// Check which of the [`REPOSITORY_SETUP_FILES`] a repository checked out at
+/// `workspace_path` has in its `.crowdcontrol/` directory
+pub fn verify_repository_setup(workspace_path: &Path) -> Result<RepositorySetupReport> {
     // Repository is now cloned directly to workspace root, so check for .crowdcontrol there
     let crowdcontrol_dir = workspace_path.join(".crowdcontrol");
-    Ok(crowdcontrol_dir.exists())
+    let has_crowdcontrol_dir = crowdcontrol_dir.is_dir();
+    let files = REPOSITORY_SETUP_FILES
+        .iter()
+        .map(|(name, _)| (*name, crowdcontrol_dir.join(name).is_file()))
+        .collect();
+
+    Ok(RepositorySetupReport {
+        has_crowdcontrol_dir,
+        files,
+    })
 }
 
 pub fn format_duration(created_at: DateTime<Utc>) -> String {
@@ -305,27 +818,241 @@ pub fn format_duration(created_at: DateTime<Utc>) -> String {
     }
 }
 
+/// Total size of the files in a directory tree
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct DiskUsage {
+    pub bytes: u64,
+    /// Subdirectories that couldn't be read (e.g. permission denied) and were left out
+    pub skipped: Vec<PathBuf>,
+}
+
+/// Walk `path` recursively and add up the size of every file in it.
+/// Symlinks are counted as links and never followed. Unreadable subdirectories
+/// are skipped and reported in `DiskUsage::skipped`; only an unreadable `path` is an error.
+pub fn directory_size(path: &Path) -> Result<DiskUsage> {
+    let mut usage = DiskUsage::default();
+    let mut pending = vec![path.to_path_buf()];
+
+    while let Some(dir) = pending.pop() {
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) if dir == path => {
+                return Err(e)
+                    .with_context(|| format!("Failed to read directory {}", path.display()));
+            }
+            Err(e) => {
+                debug!("Skipping {} while sizing: {}", dir.display(), e);
+                usage.skipped.push(dir);
+                continue;
+            }
+        };
+
+        for entry in entries.flatten() {
+            let Ok(metadata) = entry.path().symlink_metadata() else {
+                continue;
+            };
+            if metadata.is_dir() {
+                pending.push(entry.path());
+            } else {
+                usage.bytes += metadata.len();
+            }
+        }
+    }
+
+    Ok(usage)
+}
+
+/// Human-readable byte count, e.g. "1.50 GiB"
+pub fn format_bytes(bytes: u64) -> String {
+    indicatif::HumanBytes(bytes).to_string()
+}
+
+/// Free space below which `doctor` warns about the workspaces directory
+pub const LOW_DISK_SPACE_BYTES: u64 = 1024 * 1024 * 1024;
+
+/// Preflight checks for `doctor` on the workspaces directory: it exists, crowdcontrol
+/// can read and write it (on Unix, also its mode and owner), and has some free space.
+/// The remaining checks are skipped when it doesn't exist or isn't a directory.
+pub fn check_workspaces_dir(dir: &Path) -> Vec<EnvironmentCheck> {
+    let mut checks = Vec::new();
+
+    match fs::metadata(dir) {
+        Ok(metadata) if metadata.is_dir() => checks.push(EnvironmentCheck::pass(
+            "Workspaces directory",
+            format!("{} exists", dir.display()),
+        )),
+        Ok(_) => {
+            checks.push(EnvironmentCheck::fail(
+                "Workspaces directory",
+                format!("{} is not a directory", dir.display()),
+            ));
+            return checks;
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            checks.push(EnvironmentCheck::warn(
+                "Workspaces directory",
+                format!(
+                    "{} doesn't exist yet; it's created with the first agent",
+                    dir.display()
+                ),
+            ));
+            return checks;
+        }
+        Err(e) => {
+            checks.push(EnvironmentCheck::fail(
+                "Workspaces directory",
+                format!("can't access {}: {}", dir.display(), e),
+            ));
+            return checks;
+        }
+    }
+
+    #[cfg(unix)]
+    checks.push(check_workspaces_dir_mode(dir));
+
+    // Try it for real: the mode alone doesn't account for ACLs or read-only mounts
+    let probe = dir.join(format!(".crowdcontrol-doctor-{}", std::process::id()));
+    let access = fs::read_dir(dir).map(|_| ()).and_then(|()| {
+        fs::write(&probe, "")?;
+        fs::remove_file(&probe)
+    });
+    checks.push(match access {
+        Ok(()) => EnvironmentCheck::pass("Workspaces access", "readable and writable"),
+        Err(e) => EnvironmentCheck::fail("Workspaces access", format!("permission problem: {}", e)),
+    });
+
+    checks.push(match fs2::available_space(dir) {
+        Ok(free) if free < LOW_DISK_SPACE_BYTES => EnvironmentCheck::warn(
+            "Disk space",
+            format!(
+                "only {} free; agents may fail to clone or build",
+                format_bytes(free)
+            ),
+        ),
+        Ok(free) => EnvironmentCheck::pass("Disk space", format!("{} free", format_bytes(free))),
+        Err(e) => EnvironmentCheck::warn("Disk space", format!("couldn't be determined: {}", e)),
+    });
+
+    checks
+}
+
+/// Mode and owner of the workspaces directory: the owner needs read, write and
+/// search access, and a directory owned by someone else is worth a warning
+#[cfg(unix)]
+fn check_workspaces_dir_mode(dir: &Path) -> EnvironmentCheck {
+    use std::os::unix::fs::MetadataExt;
+
+    let metadata = match fs::metadata(dir) {
+        Ok(metadata) => metadata,
+        Err(e) => return EnvironmentCheck::fail("Workspaces permissions", e.to_string()),
+    };
+    let mode = metadata.mode() & 0o777;
+    let uid = unsafe { libc::geteuid() };
+
+    if metadata.uid() != uid {
+        EnvironmentCheck::warn(
+            "Workspaces permissions",
+            format!(
+                "mode {:03o}, owned by uid {} rather than you (uid {})",
+                mode,
+                metadata.uid(),
+                uid
+            ),
+        )
+    } else if mode & 0o700 != 0o700 {
+        EnvironmentCheck::fail(
+            "Workspaces permissions",
+            format!(
+                "mode {:03o} doesn't let you read, write and enter it; run chmod u+rwx {}",
+                mode,
+                dir.display()
+            ),
+        )
+    } else {
+        EnvironmentCheck::pass("Workspaces permissions", format!("mode {:03o}", mode))
+    }
+}
+
+/// Parse a point in time given either relative to `now` (`30s`, `10m`, `2h`, `1d`)
+/// or as an absolute RFC3339 timestamp (`2024-01-01T00:00:00Z`)
+pub fn parse_time_spec(spec: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>> {
+    let spec = spec.trim();
+    let invalid = || {
+        anyhow!(
+            "Invalid time '{}': expected a duration like 10m or 2h, or an RFC3339 time like 2024-01-01T00:00:00Z",
+            spec
+        )
+    };
+
+    if let Ok(time) = DateTime::parse_from_rfc3339(spec) {
+        return Ok(time.with_timezone(&Utc));
+    }
+
+    let duration = parse_duration(spec).map_err(|_| invalid())?;
+    now.checked_sub_signed(duration).ok_or_else(invalid)
+}
+
+/// Parse a duration such as `30s`, `10m`, `2h` or `1d`
+pub fn parse_duration(spec: &str) -> Result<chrono::Duration> {
+    let spec = spec.trim();
+    let invalid = || {
+        anyhow!(
+            "Invalid duration '{}': expected a number followed by s, m, h or d (e.g. 30m)",
+            spec
+        )
+    };
+
+    let unit_start = spec
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(invalid)?;
+    let (amount, unit) = spec.split_at(unit_start);
+    let amount: i64 = amount.parse().map_err(|_| invalid())?;
+
+    match unit {
+        "s" => chrono::Duration::try_seconds(amount),
+        "m" => chrono::Duration::try_minutes(amount),
+        "h" => chrono::Duration::try_hours(amount),
+        "d" => chrono::Duration::try_days(amount),
+        _ => None,
+    }
+    .ok_or_else(invalid)
+}
+
+/// Record that the agent was just used through crowdcontrol (e.g. `connect`), for idle reaping
+pub fn record_agent_activity(config: &Config, name: &str) -> Result<()> {
+    update_agent_metadata(config, name, |agent| {
+        agent.last_activity = Some(Utc::now());
+        Ok(())
+    })
+}
+
 /// Auto-repair function to clear stale container IDs from agent metadata
 pub async fn auto_repair_stale_container_id(config: &Config, agent_name: &str) -> Result<()> {
     use crate::DockerClient;
-    
+
     // Load current agent metadata
     let agent = load_agent_metadata(config, agent_name)?;
-    
+
     if let Some(container_id) = &agent.container_id {
         // Create Docker client to validate container ID
         let docker = DockerClient::new(config.clone())?;
-        
+
         // Check if container ID is still valid
-        if !docker.validate_container_id(agent_name, container_id).await? {
+        if !docker
+            .validate_container_id(agent_name, container_id)
+            .await?
+        {
             // Container ID is stale, clear it
             update_agent_metadata(config, agent_name, |agent| {
-                debug!("Auto-repairing stale container ID for agent '{}'", agent_name);
+                debug!(
+                    "Auto-repairing stale container ID for agent '{}'",
+                    agent_name
+                );
                 agent.container_id = None;
                 Ok(())
             })?;
         }
     }
-    
+
     Ok(())
 }
@@ -0,0 +1,60 @@
+use std::future::Future;
+
+pub use tokio_util::sync::CancellationToken;
+
+/// Run `future` until it completes or `token` is cancelled, whichever comes first.
+/// Returns `None` when cancelled; the future is dropped, closing any stream or exec it held.
+pub async fn run_until_cancelled<F: Future>(
+    token: &CancellationToken,
+    future: F,
+) -> Option<F::Output> {
+    tokio::select! {
+        biased;
+        _ = token.cancelled() => None,
+        output = future => Some(output),
+    }
+}
+
+/// Cancel `token` when the process receives Ctrl-C
+pub fn cancel_on_ctrl_c(token: CancellationToken) {
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            token.cancel();
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_cancellation_stops_pending_future() {
+        let token = CancellationToken::new();
+
+        let canceller = token.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            canceller.cancel();
+        });
+
+        let result = tokio::time::timeout(
+            Duration::from_secs(5),
+            run_until_cancelled(&token, std::future::pending::<()>()),
+        )
+        .await
+        .expect("cancellation should end the wait");
+        assert_eq!(result, None);
+    }
+
+    #[tokio::test]
+    async fn test_completed_future_returns_output() {
+        let token = CancellationToken::new();
+        assert_eq!(run_until_cancelled(&token, async { 42 }).await, Some(42));
+
+        // Once cancelled, the future isn't polled at all
+        token.cancel();
+        assert_eq!(run_until_cancelled(&token, async { 42 }).await, None);
+    }
+}
@@ -1,7 +1,8 @@
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tracing::{debug, trace};
 
 use crate::Settings;
@@ -13,8 +14,76 @@ pub struct Config {
     pub verbose: u8,
     pub default_memory: Option<String>,
     pub default_cpus: Option<String>,
+    /// Platform for new agents' containers, e.g. `linux/amd64`
+    pub default_platform: Option<String>,
+    /// Mount the host's `~/.claude` and `~/.claude.json` into new agents
+    pub mount_claude_config: bool,
+    /// Command `connect` runs when not given one; `None` runs [`DEFAULT_CONNECT_COMMAND`]
+    pub connect_default_command: Option<String>,
+    /// Agent containers are named `<container_prefix>-<agent>`
+    pub container_prefix: String,
+    /// Fail `new` instead of warning when a repository has no `.crowdcontrol/` directory
+    pub require_crowdcontrol: bool,
+    /// macOS keychain service Claude Code's credentials are read from
+    pub keychain_service: String,
+    /// Where new agents' repositories go in their workspace directory
+    pub workspace_layout: WorkspaceLayout,
+    /// Start a created or stopped agent on `connect` rather than failing
+    pub connect_auto_start: bool,
 }
 
+/// Where an agent's repository sits in its workspace directory `workspaces_dir/<name>`,
+/// which is mounted at /workspace. Agents keep the layout they were created with.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum WorkspaceLayout {
+    /// The repository is cloned straight into the workspace directory, so it is /workspace
+    #[default]
+    Flat,
+    /// The repository is cloned into a subdirectory named after the agent, keeping it
+    /// apart from crowdcontrol's files; it is /workspace/<name>
+    Nested,
+}
+
+impl WorkspaceLayout {
+    /// Where the repository of agent `name` with workspace directory `workspace_path` is
+    pub fn repo_path(self, workspace_path: &Path, name: &str) -> PathBuf {
+        match self {
+            WorkspaceLayout::Flat => workspace_path.to_path_buf(),
+            WorkspaceLayout::Nested => workspace_path.join(name),
+        }
+    }
+
+    /// The repository of agent `name` as seen inside its container
+    pub fn container_repo_dir(self, name: &str) -> String {
+        match self {
+            WorkspaceLayout::Flat => CONTAINER_WORKSPACE_DIR.to_string(),
+            WorkspaceLayout::Nested => format!("{}/{}", CONTAINER_WORKSPACE_DIR, name),
+        }
+    }
+}
+
+impl std::str::FromStr for WorkspaceLayout {
+    type Err = anyhow::Error;
+
+    fn from_str(layout: &str) -> Result<Self> {
+        match layout.trim().to_lowercase().as_str() {
+            "flat" => Ok(WorkspaceLayout::Flat),
+            "nested" => Ok(WorkspaceLayout::Nested),
+            _ => Err(anyhow!(
+                "Invalid workspace_layout '{}': use flat or nested",
+                layout
+            )),
+        }
+    }
+}
+
+/// Where an agent's workspace directory is mounted in its container
+pub const CONTAINER_WORKSPACE_DIR: &str = "/workspace";
+
+/// Command `connect` runs unless `--command`, `--shell` or `connect_default_command` says otherwise
+pub const DEFAULT_CONNECT_COMMAND: &str = "claude --dangerously-skip-permissions";
+
 impl Config {
     /// Create config from settings
     pub fn from_settings(settings: Settings) -> Result<Self> {
@@ -33,18 +102,184 @@ impl Config {
             )
         })?;
 
+        validate_container_prefix(&settings.container_prefix)?;
+
         Ok(Config {
             workspaces_dir: settings.workspaces_dir,
             image: settings.image,
             verbose: settings.verbose,
             default_memory: settings.default_memory,
             default_cpus: settings.default_cpus,
+            default_platform: settings.default_platform,
+            mount_claude_config: settings.mount_claude_config,
+            connect_default_command: settings.connect_default_command,
+            container_prefix: settings.container_prefix,
+            require_crowdcontrol: settings.require_crowdcontrol,
+            keychain_service: settings.keychain_service,
+            workspace_layout: settings.workspace_layout,
+            connect_auto_start: settings.connect_auto_start,
         })
     }
 
+    /// The command line `connect` runs: `command` when given, otherwise the configured
+    /// default, otherwise [`DEFAULT_CONNECT_COMMAND`]. Split on whitespace, without
+    /// shell quoting.
+    pub fn connect_command(&self, command: Option<&str>) -> Vec<String> {
+        command
+            .or(self.connect_default_command.as_deref())
+            .unwrap_or(DEFAULT_CONNECT_COMMAND)
+            .split_whitespace()
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// Name of an agent's Docker container, e.g. `crowdcontrol-web`
+    pub fn container_name(&self, agent: &str) -> String {
+        format!("{}-{}", self.container_prefix, agent)
+    }
+
+    /// The agent a container belongs to, from its name with or without the leading `/`
+    /// Docker adds in listings. `None` for containers without this config's prefix.
+    pub fn agent_name_from_container(&self, container: &str) -> Option<String> {
+        let name = container.strip_prefix('/').unwrap_or(container);
+        name.strip_prefix(&self.container_prefix)?
+            .strip_prefix('-')
+            .filter(|agent| !agent.is_empty())
+            .map(str::to_string)
+    }
+
     pub fn agent_workspace_path(&self, name: &str) -> PathBuf {
         let path = self.workspaces_dir.join(name);
         trace!("Agent workspace path for '{}': {:?}", name, path);
         path
     }
+
+    /// Where a new agent's repository goes under the configured layout
+    pub fn agent_repo_path(&self, name: &str) -> PathBuf {
+        self.workspace_layout
+            .repo_path(&self.agent_workspace_path(name), name)
+    }
+}
+
+/// Check a container name prefix: Docker allows letters, digits, `_`, `.` and `-`,
+/// starting with a letter or digit
+pub fn validate_container_prefix(prefix: &str) -> Result<()> {
+    let valid = prefix
+        .chars()
+        .next()
+        .is_some_and(|first| first.is_ascii_alphanumeric())
+        && prefix
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'));
+    if !valid {
+        return Err(anyhow!(
+            "Invalid container_prefix '{}': use letters, digits, '_', '.' and '-', starting \
+             with a letter or digit",
+            prefix
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config_with_prefix(prefix: &str) -> Config {
+        Config {
+            workspaces_dir: PathBuf::from("/tmp/crowdcontrol-test"),
+            image: "crowdcontrol:latest".to_string(),
+            verbose: 0,
+            default_memory: None,
+            default_cpus: None,
+            default_platform: None,
+            mount_claude_config: false,
+            connect_default_command: None,
+            container_prefix: prefix.to_string(),
+            require_crowdcontrol: false,
+            keychain_service: "Claude Code-credentials".to_string(),
+            workspace_layout: WorkspaceLayout::Flat,
+            connect_auto_start: false,
+        }
+    }
+
+    #[test]
+    fn test_container_name() {
+        assert_eq!(
+            config_with_prefix("crowdcontrol").container_name("web"),
+            "crowdcontrol-web"
+        );
+        assert_eq!(
+            config_with_prefix("cc-dev").container_name("web"),
+            "cc-dev-web"
+        );
+    }
+
+    #[test]
+    fn test_agent_name_from_container() {
+        let config = config_with_prefix("crowdcontrol");
+        assert_eq!(
+            config.agent_name_from_container("crowdcontrol-web"),
+            Some("web".to_string())
+        );
+        // Docker lists container names with a leading slash
+        assert_eq!(
+            config.agent_name_from_container("/crowdcontrol-web"),
+            Some("web".to_string())
+        );
+        assert_eq!(
+            config.agent_name_from_container("/crowdcontrol-my-agent"),
+            Some("my-agent".to_string())
+        );
+        assert_eq!(config.agent_name_from_container("/crowdcontrol-"), None);
+        assert_eq!(config.agent_name_from_container("/crowdcontrol"), None);
+        assert_eq!(config.agent_name_from_container("/crowdcontrolweb"), None);
+        assert_eq!(config.agent_name_from_container("/postgres"), None);
+    }
+
+    #[test]
+    fn test_agent_name_from_container_round_trips_custom_prefix() {
+        let config = config_with_prefix("cc-dev");
+        let name = config.container_name("web");
+        assert_eq!(
+            config.agent_name_from_container(&format!("/{}", name)),
+            Some("web".to_string())
+        );
+        assert_eq!(config.agent_name_from_container("/crowdcontrol-web"), None);
+    }
+
+    #[test]
+    fn test_agent_repo_path_follows_layout() {
+        let mut config = config_with_prefix("crowdcontrol");
+        assert_eq!(
+            config.agent_repo_path("web"),
+            PathBuf::from("/tmp/crowdcontrol-test/web")
+        );
+        assert_eq!(
+            config.agent_repo_path("web"),
+            config.agent_workspace_path("web")
+        );
+
+        config.workspace_layout = WorkspaceLayout::Nested;
+        assert_eq!(
+            config.agent_repo_path("web"),
+            PathBuf::from("/tmp/crowdcontrol-test/web/web")
+        );
+        assert_eq!(
+            config.agent_workspace_path("web"),
+            PathBuf::from("/tmp/crowdcontrol-test/web")
+        );
+    }
+
+    #[test]
+    fn test_container_repo_dir() {
+        assert_eq!(
+            WorkspaceLayout::Flat.container_repo_dir("web"),
+            "/workspace"
+        );
+        assert_eq!(
+            WorkspaceLayout::Nested.container_repo_dir("web"),
+            "/workspace/web"
+        );
+    }
 }
@@ -1,23 +1,40 @@
 use anyhow::{anyhow, Context, Result};
 use bollard::container::{
-    Config as ContainerConfig, CreateContainerOptions, InspectContainerOptions, ListContainersOptions, LogsOptions,
-    RemoveContainerOptions, StartContainerOptions, StopContainerOptions,
+    Config as ContainerConfig, CreateContainerOptions, InspectContainerOptions,
+    ListContainersOptions, LogOutput, LogsOptions, MemoryStatsStats, RemoveContainerOptions,
+    StartContainerOptions, Stats, StatsOptions, StopContainerOptions, UpdateContainerOptions,
 };
-use bollard::exec::{CreateExecOptions, StartExecResults};
-use bollard::image::CreateImageOptions;
-use bollard::models::{HostConfig, Mount, MountTypeEnum};
+use bollard::exec::{CreateExecOptions, ResizeExecOptions, StartExecResults};
+use bollard::image::{BuildImageOptions, CreateImageOptions};
+use bollard::models::{
+    ContainerInspectResponse, ContainerState, ContainerStateStatusEnum, DeviceRequest,
+    HealthConfig, HealthStatusEnum, HostConfig, Mount, MountTypeEnum, RestartPolicy,
+    RestartPolicyNameEnum,
+};
+use bollard::network::{ConnectNetworkOptions, DisconnectNetworkOptions, InspectNetworkOptions};
 use bollard::{Docker, API_DEFAULT_VERSION};
 use chrono::{DateTime, Utc};
 use futures_util::StreamExt;
+use indicatif::{ProgressBar, ProgressStyle};
+use regex::Regex;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::env;
-use std::path::PathBuf;
+use std::fmt;
+use std::io::{self, IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+use tokio::sync::OnceCell;
 use tracing::{debug, info, trace, warn};
 
-use crate::Config;
+use crate::pull_progress::PullProgress;
+use crate::terminal::{terminal_size, DetachDetector, DetachKeys, RawModeGuard};
+use crate::{Config, CrowdControlError, WorkspaceLayout, CONTAINER_WORKSPACE_DIR};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// An agent as recorded in its metadata. `Default` gives an agent with no container
+/// and no options set, for building test fixtures with `..Default::default()`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Agent {
     pub name: String,
     pub status: AgentStatus,
@@ -26,14 +43,99 @@ pub struct Agent {
     pub branch: Option<String>,
     pub created_at: DateTime<Utc>,
     pub workspace_path: PathBuf,
+    /// Memory limit applied to the container (e.g. "2g")
+    #[serde(default)]
+    pub memory: Option<String>,
+    /// Total memory plus swap the container may use (e.g. "4g")
+    #[serde(default)]
+    pub memory_swap: Option<String>,
+    /// Soft memory limit Docker enforces when the host runs low on memory
+    #[serde(default)]
+    pub memory_reservation: Option<String>,
+    /// CPU limit applied to the container (e.g. "1.5")
+    #[serde(default)]
+    pub cpus: Option<String>,
+    /// Free-form tags used to group and filter agents
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Local directory bind-mounted as /workspace instead of the agent workspace
+    /// (`new --from-local <path> --mount`)
+    #[serde(default)]
+    pub local_mount: Option<PathBuf>,
+    /// Docker network the container is attached to (`none`, `bridge`, `host` or a
+    /// user-defined network); `None` uses Docker's default
+    #[serde(default)]
+    pub network: Option<String>,
+    /// Whether the container runs in privileged mode (`new --privileged`)
+    #[serde(default)]
+    pub privileged: bool,
+    /// Linux capabilities added to the container (`new --cap-add`)
+    #[serde(default)]
+    pub cap_add: Vec<String>,
+    /// Whether the root filesystem is mounted read-only (`new --read-only`)
+    #[serde(default)]
+    pub read_only: bool,
+    /// Whether /workspace is mounted read-only, for review-only agents
+    /// (`new --readonly-workspace`)
+    #[serde(default)]
+    pub readonly_workspace: bool,
+    /// Whether the host's Claude credentials are kept out of the container (`new --no-mount-claude`)
+    #[serde(default)]
+    pub no_mount_claude: bool,
+    /// Platform the container was created for, e.g. `linux/amd64` (`new --platform`)
+    #[serde(default)]
+    pub platform: Option<String>,
+    /// Image the container is created from when not the configured one (`new --image`)
+    #[serde(default)]
+    pub image: Option<String>,
+    /// Docker restart policy: `no`, `on-failure`, `unless-stopped` or `always` (`new --restart`)
+    #[serde(default)]
+    pub restart_policy: Option<String>,
+    /// NVIDIA GPUs passed through to the container: `all`, a count or `device=<ids>`
+    /// (`new --gpus`)
+    #[serde(default)]
+    pub gpus: Option<String>,
+    /// Extra bind mounts as `host:container[:ro]` (`new --volume`)
+    #[serde(default)]
+    pub volumes: Vec<String>,
+    /// Stop the agent after this long without activity when `reap` runs (e.g. "2h")
+    #[serde(default)]
+    pub idle_timeout: Option<String>,
+    /// Whether the repository's `post_create_command` has run in the current container
+    #[serde(default)]
+    pub post_create_done: bool,
+    /// Agents to start before this one and stop after it (`new --depends-on`)
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    /// Last time a crowdcontrol session (`connect`, `start`) used the agent
+    #[serde(default)]
+    pub last_activity: Option<DateTime<Utc>>,
+    /// Where the repository sits in `workspace_path`; agents created before layouts
+    /// existed are flat
+    #[serde(default)]
+    pub workspace_layout: WorkspaceLayout,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 pub enum AgentStatus {
+    #[default]
     Created,
     Running,
+    Paused,
     Stopped,
     Error,
+    /// Running, but its health check hasn't passed yet
+    Starting,
+    /// Exited and about to be restarted by its restart policy
+    Restarting,
+}
+
+impl AgentStatus {
+    /// Whether the container is up: `Running`, or `Starting` while its health check
+    /// hasn't passed yet
+    pub fn is_running(&self) -> bool {
+        matches!(self, AgentStatus::Running | AgentStatus::Starting)
+    }
 }
 
 impl Agent {
@@ -44,20 +146,921 @@ impl Agent {
             None => Ok(AgentStatus::Created),
             Some(container_id) => {
                 // First validate the container ID is still valid for this agent
-                if !docker.validate_container_id(&self.name, container_id).await? {
+                if !docker
+                    .validate_container_id(&self.name, container_id)
+                    .await?
+                {
                     // Container ID is stale, agent is effectively Created
                     return Ok(AgentStatus::Created);
                 }
-                
+
                 // Get live status from Docker
                 docker.get_container_status(&self.name).await
             }
         }
     }
+
+    /// Resolve the status from a snapshot of containers obtained with a single
+    /// `list_all_containers` call, applying the same rules as `compute_live_status`.
+    pub fn status_from_containers(
+        &self,
+        containers: &[bollard::models::ContainerSummary],
+        config: &Config,
+    ) -> AgentStatus {
+        match self.container_from(containers, config) {
+            Some(c) => status_of(c),
+            // No container, or its ID is stale: the agent is effectively Created
+            None => AgentStatus::Created,
+        }
+    }
+
+    /// Find the agent's container in a snapshot from `list_all_containers`.
+    /// Only the container recorded in metadata is returned.
+    pub fn container_from<'a>(
+        &self,
+        containers: &'a [bollard::models::ContainerSummary],
+        config: &Config,
+    ) -> Option<&'a bollard::models::ContainerSummary> {
+        let container_id = self.container_id.as_deref()?;
+        let expected_name = format!("/{}", config.container_name(&self.name));
+
+        containers.iter().find(|c| {
+            c.names
+                .as_ref()
+                .is_some_and(|names| names.iter().any(|n| n == &expected_name))
+                && c.id
+                    .as_deref()
+                    .is_some_and(|id| id.starts_with(container_id))
+        })
+    }
+}
+
+impl Agent {
+    /// Whether the agent carries the given tag
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+
+    /// Whether the agent's repository URL or path contains `pattern`, ignoring case
+    pub fn repository_matches(&self, pattern: &str) -> bool {
+        self.repository
+            .to_lowercase()
+            .contains(&pattern.to_lowercase())
+    }
+
+    /// When the agent was last used through crowdcontrol, falling back to its creation time
+    pub fn last_active_at(&self) -> DateTime<Utc> {
+        self.last_activity.unwrap_or(self.created_at)
+    }
+
+    /// Container settings recorded for the agent, used to recreate its container.
+    /// The health check and `--env-passthrough` variables aren't stored and are left empty.
+    pub fn container_options(&self) -> ContainerOptions {
+        ContainerOptions {
+            memory: self.memory.clone(),
+            memory_swap: self.memory_swap.clone(),
+            memory_reservation: self.memory_reservation.clone(),
+            cpus: self.cpus.clone(),
+            healthcheck: None,
+            tags: self.tags.clone(),
+            repository: Some(self.repository.clone()),
+            branch: self.branch.clone(),
+            network: self.network.clone(),
+            privileged: self.privileged,
+            cap_add: self.cap_add.clone(),
+            read_only: self.read_only,
+            readonly_workspace: self.readonly_workspace,
+            no_mount_claude: self.no_mount_claude,
+            platform: self.platform.clone(),
+            image: self.image.clone(),
+            restart_policy: self.restart_policy.clone(),
+            gpus: self.gpus.clone(),
+            volumes: self.volumes.clone(),
+            env: Vec::new(),
+        }
+    }
+
+    /// Directory the container sees as /workspace: the local mount if there is one,
+    /// otherwise the agent workspace
+    pub fn mount_path(&self) -> &Path {
+        self.local_mount.as_deref().unwrap_or(&self.workspace_path)
+    }
+
+    /// The repository on the host: the local mount if there is one, otherwise where
+    /// the agent's layout put it in the workspace
+    pub fn repo_path(&self) -> PathBuf {
+        match &self.local_mount {
+            Some(local_mount) => local_mount.clone(),
+            None => self
+                .workspace_layout
+                .repo_path(&self.workspace_path, &self.name),
+        }
+    }
+
+    /// The repository as seen inside the container
+    pub fn container_repo_dir(&self) -> String {
+        match &self.local_mount {
+            // The local checkout itself is mounted, whatever the layout
+            Some(_) => CONTAINER_WORKSPACE_DIR.to_string(),
+            None => self.workspace_layout.container_repo_dir(&self.name),
+        }
+    }
+}
+
+/// Choose the agents a batch start should act on: every agent that isn't already running,
+/// paused or restarting, optionally restricted to those carrying `tag`. Order is preserved.
+pub fn select_agents_to_start(
+    agents: Vec<(Agent, AgentStatus)>,
+    tag: Option<&str>,
+) -> Vec<(Agent, AgentStatus)> {
+    agents
+        .into_iter()
+        .filter(|(agent, status)| {
+            !matches!(
+                status,
+                AgentStatus::Running
+                    | AgentStatus::Starting
+                    | AgentStatus::Paused
+                    | AgentStatus::Restarting
+            ) && tag.is_none_or(|tag| agent.has_tag(tag))
+        })
+        .collect()
+}
+
+/// Choose the agents a batch stop should act on, optionally restricted to those carrying
+/// `tag` and to those whose live status is `status`. Without a status every agent is
+/// kept, since stopping one that isn't running does nothing. Order is preserved.
+pub fn select_agents_to_stop(
+    agents: Vec<(Agent, AgentStatus)>,
+    tag: Option<&str>,
+    status: Option<&AgentStatus>,
+) -> Vec<Agent> {
+    agents
+        .into_iter()
+        .filter(|(agent, agent_status)| {
+            status.is_none_or(|status| agent_status == status)
+                && tag.is_none_or(|tag| agent.has_tag(tag))
+        })
+        .map(|(agent, _)| agent)
+        .collect()
+}
+
+/// Split the agents for a batch refresh into the running ones to refresh and the rest,
+/// with their status, to skip. Order is preserved.
+pub fn select_agents_to_refresh(
+    agents: Vec<(Agent, AgentStatus)>,
+) -> (Vec<Agent>, Vec<(Agent, AgentStatus)>) {
+    let (running, skipped): (Vec<_>, Vec<_>) = agents
+        .into_iter()
+        .partition(|(_, status)| status.is_running());
+    (
+        running.into_iter().map(|(agent, _)| agent).collect(),
+        skipped,
+    )
+}
+
+/// Choose the running agents that have been idle for longer than their idle timeout.
+/// Agents without an idle timeout are never selected. Order is preserved.
+pub fn select_agents_to_reap(agents: Vec<(Agent, AgentStatus)>, now: DateTime<Utc>) -> Vec<Agent> {
+    agents
+        .into_iter()
+        .filter(|(_, status)| status.is_running())
+        .map(|(agent, _)| agent)
+        .filter(|agent| {
+            let Some(timeout) = agent.idle_timeout.as_deref() else {
+                return false;
+            };
+            match crate::parse_duration(timeout) {
+                Ok(timeout) => now.signed_duration_since(agent.last_active_at()) > timeout,
+                Err(e) => {
+                    warn!("Ignoring idle timeout for agent '{}': {}", agent.name, e);
+                    false
+                }
+            }
+        })
+        .collect()
+}
+
+/// Order agents so each comes after the agents it depends on, otherwise keeping the
+/// given order. Dependencies not among `items` are ignored. A dependency cycle is an
+/// error naming the agents in it.
+pub fn order_by_dependencies<T>(items: Vec<T>, agent: impl Fn(&T) -> &Agent) -> Result<Vec<T>> {
+    let agents: Vec<&Agent> = items.iter().map(&agent).collect();
+    let mut order = Vec::with_capacity(agents.len());
+    let mut visited = vec![false; agents.len()];
+    let mut path = Vec::new();
+    for index in 0..agents.len() {
+        visit_dependencies(index, &agents, &mut visited, &mut path, &mut order)?;
+    }
+
+    let mut items: Vec<Option<T>> = items.into_iter().map(Some).collect();
+    Ok(order
+        .into_iter()
+        .filter_map(|index| items[index].take())
+        .collect())
+}
+
+/// Depth-first step of `order_by_dependencies`: appends `index` to `order` after its
+/// dependencies. `path` holds the agents currently being visited, to report cycles.
+fn visit_dependencies(
+    index: usize,
+    agents: &[&Agent],
+    visited: &mut [bool],
+    path: &mut Vec<usize>,
+    order: &mut Vec<usize>,
+) -> Result<()> {
+    if let Some(start) = path.iter().position(|&i| i == index) {
+        let cycle: Vec<&str> = path[start..]
+            .iter()
+            .chain([&index])
+            .map(|&i| agents[i].name.as_str())
+            .collect();
+        return Err(anyhow!(
+            "Dependency cycle between agents: {}",
+            cycle.join(" -> ")
+        ));
+    }
+    if visited[index] {
+        return Ok(());
+    }
+
+    path.push(index);
+    for dependency in &agents[index].depends_on {
+        if let Some(dep) = agents.iter().position(|agent| &agent.name == dependency) {
+            visit_dependencies(dep, agents, visited, path, order)?;
+        }
+    }
+    path.pop();
+
+    visited[index] = true;
+    order.push(index);
+    Ok(())
+}
+
+/// Prefix of the container labels that record an agent's tags
+pub const TAG_LABEL_PREFIX: &str = "crowdcontrol.tag.";
+
+/// Build container labels (`crowdcontrol.tag.<tag>=true`) for a set of tags
+pub fn tag_labels(tags: &[String]) -> HashMap<String, String> {
+    tags.iter()
+        .map(|tag| (format!("{}{}", TAG_LABEL_PREFIX, tag), "true".to_string()))
+        .collect()
+}
+
+/// Container label holding the agent name
+pub const NAME_LABEL: &str = "crowdcontrol.name";
+/// Container label holding the repository the agent was created from
+pub const REPOSITORY_LABEL: &str = "crowdcontrol.repository";
+/// Container label holding the branch checked out when the container was created
+pub const BRANCH_LABEL: &str = "crowdcontrol.branch";
+
+/// The agent a listed container belongs to, going by its name under `config`'s prefix.
+/// Containers whose name label names another agent are skipped, so with a shorter
+/// prefix another user's `crowdcontrol-alice-web` isn't taken for agent `alice-web`.
+pub fn agent_name_of(
+    container: &bollard::models::ContainerSummary,
+    config: &Config,
+) -> Option<String> {
+    let name = config.agent_name_from_container(container.names.as_ref()?.first()?)?;
+    let labelled = container
+        .labels
+        .as_ref()
+        .and_then(|labels| labels.get(NAME_LABEL));
+    labelled.is_none_or(|label| *label == name).then_some(name)
+}
+
+/// All labels set on an agent's container: `app=crowdcontrol`, the agent name,
+/// repository and branch when known, and one label per tag
+pub fn container_labels(name: &str, options: &ContainerOptions) -> HashMap<String, String> {
+    let mut labels = HashMap::new();
+    labels.insert("app".to_string(), "crowdcontrol".to_string());
+    labels.insert(NAME_LABEL.to_string(), name.to_string());
+    if let Some(repository) = &options.repository {
+        labels.insert(REPOSITORY_LABEL.to_string(), repository.clone());
+    }
+    if let Some(branch) = &options.branch {
+        labels.insert(BRANCH_LABEL.to_string(), branch.clone());
+    }
+    labels.extend(tag_labels(&options.tags));
+    labels
+}
+
+/// Whether container labels mark it as crowdcontrol's container for `agent_name`
+pub fn is_agent_container(labels: Option<&HashMap<String, String>>, agent_name: &str) -> bool {
+    labels.is_some_and(|labels| {
+        labels.get("app").map(String::as_str) == Some("crowdcontrol")
+            && labels.get(NAME_LABEL).map(String::as_str) == Some(agent_name)
+    })
+}
+
+/// Recover tags from container labels, e.g. for containers without metadata
+pub fn tags_from_labels(labels: &HashMap<String, String>) -> Vec<String> {
+    let mut tags: Vec<String> = labels
+        .keys()
+        .filter_map(|key| key.strip_prefix(TAG_LABEL_PREFIX))
+        .map(|tag| tag.to_string())
+        .collect();
+    tags.sort();
+    tags
+}
+
+/// Map a Docker container state string to an `AgentStatus`. `health` is the state of the
+/// container's health check, if it has one: a running container whose check hasn't
+/// passed yet is `Starting`.
+pub fn status_from_docker_state(state: &str, health: Option<&HealthStatus>) -> AgentStatus {
+    match state {
+        "created" => AgentStatus::Created,
+        "running" if health == Some(&HealthStatus::Starting) => AgentStatus::Starting,
+        "running" => AgentStatus::Running,
+        "restarting" => AgentStatus::Restarting,
+        "paused" => AgentStatus::Paused,
+        "exited" => AgentStatus::Stopped,
+        "dead" | "removing" => AgentStatus::Error,
+        _ => AgentStatus::Stopped,
+    }
+}
+
+/// Health check state from the status text `docker ps` shows, e.g.
+/// `Up 5 seconds (health: starting)`. `None` for containers without a health check.
+pub fn health_from_status_text(status: &str) -> Option<HealthStatus> {
+    if status.contains("(health: starting)") {
+        Some(HealthStatus::Starting)
+    } else if status.contains("(unhealthy)") {
+        Some(HealthStatus::Unhealthy)
+    } else if status.contains("(healthy)") {
+        Some(HealthStatus::Healthy)
+    } else {
+        None
+    }
+}
+
+/// Live status of a container from a `list_containers` summary
+pub fn status_of(container: &bollard::models::ContainerSummary) -> AgentStatus {
+    let health = container
+        .status
+        .as_deref()
+        .and_then(health_from_status_text);
+    status_from_docker_state(
+        container.state.as_deref().unwrap_or("unknown"),
+        health.as_ref(),
+    )
+}
+
+/// Abbreviate a container ID to the 12 characters `docker ps` shows
+pub fn short_container_id(id: &str) -> &str {
+    id.get(..12).unwrap_or(id)
+}
+
+/// Render container ports like `docker ps`, e.g. `0.0.0.0:8080->80/tcp, 9000/tcp`.
+/// A port bound on both IPv4 and IPv6 is shown once.
+pub fn format_ports(ports: &[bollard::models::Port]) -> String {
+    let mut sorted: Vec<&bollard::models::Port> = ports.iter().collect();
+    // IPv4 bindings sort ahead of their IPv6 twins so they are the ones shown
+    sorted.sort_by_key(|port| {
+        let ipv6 = port.ip.as_deref().is_some_and(|ip| ip.contains(':'));
+        (port.private_port, port.public_port, ipv6)
+    });
+
+    let mut seen = HashSet::new();
+    let mut rendered = Vec::new();
+    for port in sorted {
+        let protocol = port
+            .typ
+            .as_ref()
+            .map(|typ| typ.to_string())
+            .filter(|typ| !typ.is_empty())
+            .unwrap_or_else(|| "tcp".to_string());
+        if !seen.insert((port.private_port, port.public_port, protocol.clone())) {
+            continue;
+        }
+
+        rendered.push(match port.public_port {
+            Some(public) => format!(
+                "{}:{}->{}/{}",
+                port.ip.as_deref().unwrap_or("0.0.0.0"),
+                public,
+                port.private_port,
+                protocol
+            ),
+            None => format!("{}/{}", port.private_port, protocol),
+        });
+    }
+    rendered.join(", ")
+}
+
+/// Settings applied when creating an agent's container
+#[derive(Debug, Clone, Default)]
+pub struct ContainerOptions {
+    pub memory: Option<String>,
+    /// Memory plus swap limit; must be at least `memory` when both are set
+    pub memory_swap: Option<String>,
+    /// Soft memory limit
+    pub memory_reservation: Option<String>,
+    pub cpus: Option<String>,
+    /// Shell command Docker runs periodically to determine container health
+    pub healthcheck: Option<String>,
+    /// Agent tags, stored on the container as labels
+    pub tags: Vec<String>,
+    /// Repository URL or path, stored on the container as a label
+    pub repository: Option<String>,
+    /// Branch, stored on the container as a label
+    pub branch: Option<String>,
+    /// Docker network mode: `none`, `bridge`, `host` or a user-defined network name
+    pub network: Option<String>,
+    /// Run the container privileged; off by default
+    pub privileged: bool,
+    /// Linux capabilities to add, e.g. `NET_ADMIN`
+    pub cap_add: Vec<String>,
+    /// Mount the root filesystem read-only, keeping /workspace and scratch paths writable
+    pub read_only: bool,
+    /// Mount /workspace read-only so the agent can't change the repository
+    pub readonly_workspace: bool,
+    /// Don't mount the host's `~/.claude` and `~/.claude.json` into the container
+    pub no_mount_claude: bool,
+    /// Image platform such as `linux/amd64`; `None` uses the daemon's native platform
+    pub platform: Option<String>,
+    /// Image to create the container from; `None` uses the configured image
+    pub image: Option<String>,
+    /// Docker restart policy name; `None` leaves Docker's default (`no`)
+    pub restart_policy: Option<String>,
+    /// GPUs to request as `docker run --gpus` takes them; `None` passes none through
+    pub gpus: Option<String>,
+    /// Extra bind mounts as `host:container[:ro]`
+    pub volumes: Vec<String>,
+    /// Extra `KEY=VALUE` environment variables; never written to agent metadata
+    pub env: Vec<String>,
+}
+
+/// Health check state of a container as reported by Docker
+#[derive(Debug, Clone, PartialEq)]
+pub enum HealthStatus {
+    Starting,
+    Healthy,
+    Unhealthy,
+}
+
+/// Result of inspecting a container's health check
+#[derive(Debug, Clone)]
+pub struct ContainerHealth {
+    pub status: HealthStatus,
+    /// Output of the most recent health check probe
+    pub last_output: Option<String>,
+}
+
+/// How a session started with `exec_in_container_as_user` ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionEnd {
+    /// The command finished, or was started in the background
+    Exited,
+    /// The detach keys were typed; the command is still running in the container
+    Detached,
+}
+
+/// Output of a command run with `exec_capture_as_user`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExecOutput {
+    pub exit_code: i64,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl ExecOutput {
+    pub fn success(&self) -> bool {
+        self.exit_code == 0
+    }
+}
+
+/// CPU counters from one stats snapshot, in nanoseconds
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CpuSample {
+    /// CPU time used by the container
+    pub total_usage: u64,
+    /// CPU time used by the whole host
+    pub system_usage: u64,
+    pub online_cpus: u64,
+}
+
+impl CpuSample {
+    fn from_docker(stats: &bollard::container::CPUStats) -> Self {
+        let online_cpus = stats.online_cpus.unwrap_or_else(|| {
+            stats
+                .cpu_usage
+                .percpu_usage
+                .as_ref()
+                .map_or(0, |cpus| cpus.len() as u64)
+        });
+        Self {
+            total_usage: stats.cpu_usage.total_usage,
+            system_usage: stats.system_cpu_usage.unwrap_or(0),
+            online_cpus,
+        }
+    }
+}
+
+/// CPU usage between two snapshots as a percentage of one CPU, computed like
+/// `docker stats`: a container saturating two cores reports 200%
+pub fn cpu_percent(previous: &CpuSample, current: &CpuSample) -> f64 {
+    let cpu_delta = current.total_usage.saturating_sub(previous.total_usage);
+    let system_delta = current.system_usage.saturating_sub(previous.system_usage);
+    if cpu_delta == 0 || system_delta == 0 {
+        return 0.0;
+    }
+    cpu_delta as f64 / system_delta as f64 * current.online_cpus.max(1) as f64 * 100.0
+}
+
+/// Resource usage of a running container
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContainerStats {
+    pub cpu_percent: f64,
+    /// Memory in use, excluding the page cache (as `docker stats` reports it)
+    pub memory_usage: u64,
+    pub memory_limit: u64,
+    pub net_rx_bytes: u64,
+    pub net_tx_bytes: u64,
+}
+
+impl ContainerStats {
+    fn from_docker(stats: &Stats) -> Self {
+        let cache = match &stats.memory_stats.stats {
+            Some(MemoryStatsStats::V1(v1)) => v1.total_inactive_file,
+            Some(MemoryStatsStats::V2(v2)) => v2.inactive_file,
+            None => 0,
+        };
+        let (net_rx_bytes, net_tx_bytes) = stats
+            .networks
+            .iter()
+            .flat_map(|networks| networks.values())
+            .fold((0, 0), |(rx, tx), network| {
+                (rx + network.rx_bytes, tx + network.tx_bytes)
+            });
+
+        Self {
+            cpu_percent: cpu_percent(
+                &CpuSample::from_docker(&stats.precpu_stats),
+                &CpuSample::from_docker(&stats.cpu_stats),
+            ),
+            memory_usage: stats.memory_stats.usage.unwrap_or(0).saturating_sub(cache),
+            memory_limit: stats.memory_stats.limit.unwrap_or(0),
+            net_rx_bytes,
+            net_tx_bytes,
+        }
+    }
+
+    /// Memory usage as a percentage of the limit
+    pub fn memory_percent(&self) -> f64 {
+        if self.memory_limit == 0 {
+            return 0.0;
+        }
+        self.memory_usage as f64 / self.memory_limit as f64 * 100.0
+    }
+}
+
+/// Which container logs to fetch
+#[derive(Debug, Clone, Default)]
+pub struct LogsQuery {
+    pub follow: bool,
+    /// Lines to show from the end, or "all" (defaults to 50)
+    pub tail: Option<String>,
+    pub timestamps: bool,
+    /// Only show logs since this Unix timestamp
+    pub since: Option<i64>,
+    /// Only show logs before this Unix timestamp
+    pub until: Option<i64>,
+    /// Only show lines this filter keeps
+    pub grep: Option<LineFilter>,
+}
+
+/// Line filter for `logs --grep`: keeps the lines whose message matches a regex, or
+/// with `invert` the lines that don't
+#[derive(Debug, Clone)]
+pub struct LineFilter {
+    pattern: Regex,
+    invert: bool,
+}
+
+impl LineFilter {
+    /// Compile `pattern`, failing on an invalid regex
+    pub fn new(pattern: &str, invert: bool) -> Result<Self> {
+        let pattern =
+            Regex::new(pattern).with_context(|| format!("Invalid --grep pattern '{}'", pattern))?;
+        Ok(LineFilter { pattern, invert })
+    }
+
+    /// Whether to show a log line. With `timestamps` the leading timestamp Docker adds
+    /// is skipped, so patterns anchored with `^` match the start of the message.
+    pub fn keeps(&self, line: &str, timestamps: bool) -> bool {
+        let line = line.trim_end_matches(['\n', '\r']);
+        let message = if timestamps {
+            line.split_once(' ').map_or(line, |(_, message)| message)
+        } else {
+            line
+        };
+        self.pattern.is_match(message) != self.invert
+    }
+}
+
+/// Lines of history to show with `logs --tail`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LogTail {
+    Lines(u32),
+    All,
+}
+
+impl LogTail {
+    /// Lines shown when neither `--tail` nor `--since` is given
+    pub const DEFAULT_LINES: u32 = 50;
+
+    /// Value for the Docker API's `tail` parameter. A tail of 0 shows no history,
+    /// which combined with `--follow` means only new output is streamed.
+    pub fn to_docker_value(self) -> String {
+        match self {
+            LogTail::Lines(lines) => lines.to_string(),
+            LogTail::All => "all".to_string(),
+        }
+    }
+
+    /// The tail to request: the explicit value if given, otherwise the whole log
+    /// when a time range is set (so the range isn't cut short) and the default
+    /// number of lines when it isn't
+    pub fn resolve(tail: Option<LogTail>, has_time_range: bool) -> LogTail {
+        match (tail, has_time_range) {
+            (Some(tail), _) => tail,
+            (None, true) => LogTail::All,
+            (None, false) => LogTail::Lines(Self::DEFAULT_LINES),
+        }
+    }
+}
+
+impl FromStr for LogTail {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        if s.eq_ignore_ascii_case("all") {
+            return Ok(LogTail::All);
+        }
+        match s.parse::<i64>() {
+            Ok(lines) if lines < 0 => Err(anyhow!(
+                "Invalid tail '{}': the number of lines can't be negative",
+                s
+            )),
+            Ok(lines) => u32::try_from(lines)
+                .map(LogTail::Lines)
+                .map_err(|_| anyhow!("Invalid tail '{}': too many lines, use 'all'", s)),
+            Err(_) => Err(anyhow!(
+                "Invalid tail '{}': expected a number of lines or 'all'",
+                s
+            )),
+        }
+    }
+}
+
+/// What `logs --follow` should do once the log stream has ended
+#[derive(Debug, Clone, PartialEq)]
+pub enum FollowAction {
+    /// The container is running again; reopen the stream
+    Reconnect,
+    /// Not running (yet), e.g. restarting; check again shortly
+    Wait,
+    /// Stop following
+    GiveUp,
+}
+
+/// Decide how to continue following logs, given the container's current status
+/// (`None` if it couldn't be determined) and the number of failed attempts so far
+pub fn follow_action(
+    status: Option<&AgentStatus>,
+    failed_attempts: u32,
+    max_attempts: u32,
+) -> FollowAction {
+    match status {
+        Some(status) if status.is_running() => FollowAction::Reconnect,
+        Some(AgentStatus::Error) => FollowAction::GiveUp,
+        _ if failed_attempts >= max_attempts => FollowAction::GiveUp,
+        _ => FollowAction::Wait,
+    }
+}
+
+/// How to reach the Docker daemon, derived from the environment
+#[derive(Debug, PartialEq)]
+enum ConnectionMethod {
+    /// DOCKER_HOST is set; let bollard handle unix, npipe and plain tcp hosts
+    DockerHostDefaults { host: String },
+    /// DOCKER_HOST is a tcp:// daemon and TLS was requested
+    Tls { host: String, cert_path: PathBuf },
+    /// DOCKER_HOST is not set; probe common local socket locations
+    LocalSocket,
+}
+
+/// Default grace period before a stopping container is killed.
+/// Kept short since dev containers rarely need long to shut down.
+pub const DEFAULT_STOP_TIMEOUT_SECS: u64 = 5;
+
+/// How long `start` watches a started container for an immediate exit.
+/// Long enough for the entrypoint's setup to fail, short enough not to slow every start.
+pub const STARTUP_CHECK_SECS: u64 = 3;
+
+/// Default limit on pulling the image, and then on creating the container, for `new`.
+/// Generous for slow registries, but stops a pull that hangs from blocking forever.
+pub const DEFAULT_CREATE_TIMEOUT_SECS: u64 = 300;
+
+/// Oldest Docker Engine API version crowdcontrol supports (Docker 20.10)
+pub const MIN_DOCKER_API_VERSION: &str = "1.41";
+
+/// Outcome of one environment preflight check, as reported by `doctor`
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnvironmentCheck {
+    pub name: &'static str,
+    pub passed: bool,
+    /// Passed, but with something worth looking at (e.g. low disk space)
+    pub warning: bool,
+    pub detail: String,
+}
+
+impl EnvironmentCheck {
+    pub fn pass(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            passed: true,
+            warning: false,
+            detail: detail.into(),
+        }
+    }
+
+    pub fn warn(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            passed: true,
+            warning: true,
+            detail: detail.into(),
+        }
+    }
+
+    pub fn fail(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            passed: false,
+            warning: false,
+            detail: detail.into(),
+        }
+    }
+}
+
+impl fmt::Display for EnvironmentCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mark = match (self.passed, self.warning) {
+            (false, _) => "✗",
+            (true, true) => "⚠",
+            (true, false) => "✓",
+        };
+        write!(f, "{} {}: {}", mark, self.name, self.detail)
+    }
+}
+
+/// Whether a Docker API version such as `1.43` is at least `minimum`
+pub fn api_version_supported(version: &str, minimum: &str) -> bool {
+    let parse = |version: &str| -> Option<(u32, u32)> {
+        let (major, minor) = version.trim().split_once('.')?;
+        Some((major.parse().ok()?, minor.parse().ok()?))
+    };
+    match (parse(version), parse(minimum)) {
+        (Some(version), Some(minimum)) => version >= minimum,
+        _ => false,
+    }
+}
+
+/// What to do about a daemon that can't be reached: install Docker if there's no
+/// `docker` executable on the PATH, otherwise start it
+pub fn docker_unavailable_hint() -> &'static str {
+    let installed = env::var_os("PATH").is_some_and(|path| {
+        env::split_paths(&path)
+            .any(|dir| dir.join("docker").is_file() || dir.join("docker.exe").is_file())
+    });
+
+    match (installed, cfg!(target_os = "linux")) {
+        (false, true) => {
+            "Docker doesn't appear to be installed. Install Docker Engine: \
+             https://docs.docker.com/engine/install/"
+        }
+        (false, false) => {
+            "Docker doesn't appear to be installed. Install Docker Desktop: \
+             https://www.docker.com/products/docker-desktop/"
+        }
+        (true, true) => "Is the Docker daemon running? Start it with: sudo systemctl start docker",
+        (true, false) => "Is Docker Desktop running? Start it and try again.",
+    }
+}
+
+/// Whether an error comes from failing to reach the Docker daemon at all (no socket,
+/// connection refused), as opposed to the daemon rejecting a request. Only I/O errors
+/// underneath a Docker client error count, so missing workspace files don't.
+pub fn is_docker_unreachable(error: &anyhow::Error) -> bool {
+    error
+        .chain()
+        .skip_while(|cause| cause.downcast_ref::<bollard::errors::Error>().is_none())
+        .any(|cause| {
+            let io_error = match cause.downcast_ref::<bollard::errors::Error>() {
+                Some(bollard::errors::Error::IOError { err }) => Some(err),
+                _ => cause.downcast_ref::<io::Error>(),
+            };
+            matches!(
+                io_error.map(io::Error::kind),
+                Some(io::ErrorKind::NotFound | io::ErrorKind::ConnectionRefused)
+            )
+        })
+}
+
+/// Mark an error from an unreachable daemon as `DockerUnavailable`, with a hint on how
+/// to fix it. Errors that already carry a `CrowdControlError` are left alone.
+pub fn explain_docker_unavailable(error: anyhow::Error) -> anyhow::Error {
+    if CrowdControlError::find(&error).is_some() || !is_docker_unreachable(&error) {
+        return error;
+    }
+    error.context(CrowdControlError::DockerUnavailable(format!(
+        "Cannot connect to the Docker daemon. {}",
+        docker_unavailable_hint()
+    )))
+}
+
+/// Messages Docker gives when an image has no variant for the requested platform,
+/// e.g. an amd64-only image on Apple Silicon
+const PLATFORM_MISMATCH_MESSAGES: [&str; 3] = [
+    "no matching manifest for",
+    "does not match the specified platform",
+    "does not match the detected host platform",
+];
+
+/// Whether an error comes from an image that isn't available for the platform asked for
+pub fn is_platform_mismatch(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        let message = cause.to_string().to_lowercase();
+        PLATFORM_MISMATCH_MESSAGES
+            .iter()
+            .any(|pattern| message.contains(pattern))
+    })
+}
+
+/// Explain a platform mismatch from pulling an image or creating a container, suggesting
+/// `--platform`. Other errors are returned unchanged.
+pub fn explain_platform_mismatch(error: anyhow::Error) -> anyhow::Error {
+    if !is_platform_mismatch(&error) {
+        return error;
+    }
+    error.context(
+        "The image isn't available for this machine's platform. Run it under emulation with \
+         --platform linux/amd64 (or set default_platform in the config), or use an image \
+         built for this platform with --image",
+    )
+}
+
+/// Message Docker gives when starting a container that requests GPUs it has no driver for
+const GPU_UNAVAILABLE_MESSAGE: &str = "could not select device driver";
+
+/// Whether an error comes from a GPU request the Docker daemon can't satisfy
+pub fn is_gpu_unavailable(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        cause
+            .to_string()
+            .to_lowercase()
+            .contains(GPU_UNAVAILABLE_MESSAGE)
+    })
+}
+
+/// Explain a GPU request failing because the daemon has no NVIDIA runtime. Other errors
+/// are returned unchanged.
+pub fn explain_gpu_unavailable(error: anyhow::Error) -> anyhow::Error {
+    if !is_gpu_unavailable(&error) {
+        return error;
+    }
+    error.context(
+        "The Docker daemon can't provide NVIDIA GPUs. Install the NVIDIA Container Toolkit, \
+         run `sudo nvidia-ctk runtime configure --runtime=docker` and restart Docker, or \
+         recreate the agent without --gpus",
+    )
+}
+
+/// Run a Docker operation, giving up with `CrowdControlError::Timeout` after `timeout`.
+/// The operation is dropped, which cancels any request still in flight.
+pub async fn with_timeout<T>(
+    waiting_for: &str,
+    timeout: Duration,
+    operation: impl std::future::Future<Output = Result<T>>,
+) -> Result<T> {
+    match tokio::time::timeout(timeout, operation).await {
+        Ok(result) => result,
+        Err(_) => Err(CrowdControlError::Timeout {
+            waiting_for: waiting_for.to_string(),
+            timeout,
+        }
+        .into()),
+    }
 }
 
 pub struct DockerClient {
+    /// Client speaking bollard's default API version, which may be newer than the daemon's
     docker: Docker,
+    /// `docker` once it has negotiated the API version with the daemon; see `api`
+    negotiated: OnceCell<Docker>,
     config: Config,
 }
 
@@ -67,11 +1070,33 @@ impl DockerClient {
 
         // Use connect_with_defaults() which respects DOCKER_HOST env var
         // If DOCKER_HOST is not set, it will try default locations
-        let docker = if let Ok(docker_host) = env::var("DOCKER_HOST") {
+        let method = connection_method(
+            env::var("DOCKER_HOST").ok(),
+            env::var("DOCKER_TLS_VERIFY").ok(),
+            env::var("DOCKER_CERT_PATH").ok(),
+        );
+
+        let docker = if let ConnectionMethod::Tls { host, cert_path } = method {
+            // Remote daemon secured with client certificates
+            info!("Connecting to Docker over TLS at: {}", host);
+            let (key, cert, ca) = tls_files(&cert_path)?;
+            Docker::connect_with_ssl(&host, &key, &cert, &ca, 120, API_DEFAULT_VERSION)
+                .with_context(|| {
+                    CrowdControlError::DockerUnavailable(format!(
+                        "Failed to connect to Docker over TLS at {}",
+                        host
+                    ))
+                })?
+        } else if let ConnectionMethod::DockerHostDefaults { host } = method {
             // If DOCKER_HOST is set, use it
-            info!("Connecting to Docker using DOCKER_HOST: {}", docker_host);
-            Docker::connect_with_defaults()
-                .context("Failed to connect to Docker using DOCKER_HOST")?
+            info!("Connecting to Docker using DOCKER_HOST: {}", host);
+            Docker::connect_with_defaults().with_context(|| {
+                CrowdControlError::DockerUnavailable(format!(
+                    "Failed to connect to Docker using DOCKER_HOST ({}). {}",
+                    host,
+                    docker_unavailable_hint()
+                ))
+            })?
         } else {
             // Try to detect the correct socket location
             #[cfg(unix)]
@@ -101,20 +1126,57 @@ impl DockerClient {
                 }
 
                 connected.ok_or_else(|| {
-                    anyhow!("Failed to connect to Docker. Docker socket not found at common locations.\n\
-                           Try setting DOCKER_HOST environment variable:\n\
-                           export DOCKER_HOST=unix://$HOME/.docker/run/docker.sock")
+                    CrowdControlError::DockerUnavailable(format!(
+                        "Failed to connect to Docker. Docker socket not found at common locations.\n\
+                         {}\n\
+                         If Docker listens elsewhere, set the DOCKER_HOST environment variable, e.g.:\n\
+                         export DOCKER_HOST=unix://$HOME/.docker/run/docker.sock",
+                        docker_unavailable_hint()
+                    ))
                 })?
             }
 
             #[cfg(windows)]
             {
-                Docker::connect_with_local_defaults()
-                    .context("Failed to connect to Docker. Is Docker Desktop running?")?
+                Docker::connect_with_local_defaults().with_context(|| {
+                    CrowdControlError::DockerUnavailable(format!(
+                        "Failed to connect to Docker. {}",
+                        docker_unavailable_hint()
+                    ))
+                })?
             }
         };
 
-        Ok(Self { docker, config })
+        Ok(Self {
+            docker,
+            negotiated: OnceCell::new(),
+            config,
+        })
+    }
+
+    /// The Docker API client, downgraded on first use to the API version the daemon
+    /// supports so an older daemon doesn't reject requests. If the daemon can't be
+    /// asked, requests go out at the default version and report the real error.
+    async fn api(&self) -> &Docker {
+        let negotiated = self
+            .negotiated
+            .get_or_try_init(|| async {
+                let docker = self.docker.clone().negotiate_version().await?;
+                debug!("Negotiated Docker API version {}", docker.client_version());
+                Ok::<_, bollard::errors::Error>(docker)
+            })
+            .await;
+        match negotiated {
+            Ok(docker) => docker,
+            Err(e) => {
+                debug!(
+                    "Couldn't negotiate the Docker API version, using {}: {}",
+                    self.docker.client_version(),
+                    e
+                );
+                &self.docker
+            }
+        }
     }
 
     pub async fn container_exists(&self, name: &str) -> Result<bool> {
@@ -127,7 +1189,11 @@ impl DockerClient {
             ..Default::default()
         };
 
-        let containers = self.docker.list_containers(Some(options)).await?;
+        let docker = self.api().await;
+        let containers = with_retry("list containers", || {
+            docker.list_containers(Some(options.clone()))
+        })
+        .await?;
         Ok(!containers.is_empty())
     }
 
@@ -138,11 +1204,26 @@ impl DockerClient {
         memory: Option<String>,
         cpus: Option<String>,
     ) -> Result<String> {
-        let container_name = format!("crowdcontrol-{}", name);
+        let options = ContainerOptions {
+            memory,
+            cpus,
+            ..Default::default()
+        };
+        self.create_container_with_options(name, workspace_path, &options)
+            .await
+    }
+
+    pub async fn create_container_with_options(
+        &self,
+        name: &str,
+        workspace_path: &PathBuf,
+        options: &ContainerOptions,
+    ) -> Result<String> {
+        let container_name = self.config.container_name(name);
 
         info!(
-            "Creating container '{}' with workspace: {:?}, memory: {:?}, cpus: {:?}",
-            container_name, workspace_path, memory, cpus
+            "Creating container '{}' with workspace: {:?}, options: {:?}",
+            container_name, workspace_path, options
         );
 
         // Set up mounts - canonicalize path to avoid Docker Desktop issues
@@ -153,114 +1234,229 @@ impl DockerClient {
             )
         })?;
 
-        let mut mounts = vec![
-            // Mount workspace
-            Mount {
-                target: Some("/workspace".to_string()),
-                source: Some(canonical_workspace.to_string_lossy().to_string()),
-                typ: Some(MountTypeEnum::BIND),
-                read_only: Some(false),
-                ..Default::default()
-            },
-        ];
-
-        // Mount Claude config - both new and legacy formats
-        let home_dir = dirs::home_dir().unwrap();
-        
-        // Mount .claude directory if it exists
-        let claude_dir = home_dir.join(".claude");
-        if claude_dir.exists() {
-            mounts.push(Mount {
-                target: Some("/mnt/claude-config/.claude".to_string()),
-                source: Some(claude_dir.to_string_lossy().to_string()),
-                typ: Some(MountTypeEnum::BIND),
-                read_only: Some(true),
-                ..Default::default()
-            });
-        }
-        
-        // Mount legacy .claude.json if it exists
-        let claude_legacy = home_dir.join(".claude.json");
-        if claude_legacy.exists() {
-            mounts.push(Mount {
-                target: Some("/mnt/claude-config/.claude.json".to_string()),
-                source: Some(claude_legacy.to_string_lossy().to_string()),
-                typ: Some(MountTypeEnum::BIND),
-                read_only: Some(true),
-                ..Default::default()
-            });
-        }
-        
-
-        let mut host_config = HostConfig {
-            privileged: Some(true),
-            mounts: Some(mounts),
-            ..Default::default()
+        let claude_home = if self.config.mount_claude_config && !options.no_mount_claude {
+            dirs::home_dir()
+        } else {
+            debug!("Not mounting host Claude config into {}", container_name);
+            None
         };
+        let mut mounts = container_mounts(
+            &canonical_workspace,
+            options.readonly_workspace,
+            claude_home.as_deref(),
+        );
+        mounts.extend(volume_mounts(&options.volumes)?);
 
-        // Set resource limits if provided
-        if let Some(memory_limit) = memory {
-            let memory_bytes = parse_memory_limit(&memory_limit)?;
-            host_config.memory = Some(memory_bytes);
-        }
+        let host_config = build_host_config(options, mounts)?;
 
-        if let Some(cpu_limit) = cpus {
-            let cpu_quota = (cpu_limit.parse::<f64>()? * 100000.0) as i64;
-            host_config.cpu_quota = Some(cpu_quota);
-            host_config.cpu_period = Some(100000);
+        // Fail early with a clear message rather than Docker's generic create error
+        if let Some(network) = &options.network {
+            self.ensure_network_exists(network).await?;
         }
 
         // Get host UID/GID for user mapping
         let user_id = unsafe { libc::getuid() };
         let group_id = unsafe { libc::getgid() };
 
-        let mut labels = HashMap::new();
-        labels.insert("app".to_string(), "crowdcontrol".to_string());
-        
+        let labels = container_labels(name, options);
+
+        let mut env = vec![
+            format!("HOST_UID={}", user_id),
+            format!("HOST_GID={}", group_id),
+        ];
+        // The entrypoint only starts the inner Docker daemon when it can run
+        if options.privileged {
+            env.push("CROWDCONTROL_PRIVILEGED=1".to_string());
+        }
+        env.extend(options.env.iter().cloned());
+
         let container_config = ContainerConfig {
-            image: Some(self.config.image.clone()),
+            image: Some(self.image_for(options).to_string()),
             host_config: Some(host_config),
-            env: Some(vec![
-                format!("HOST_UID={}", user_id),
-                format!("HOST_GID={}", group_id),
-            ]),
+            env: Some(env),
             labels: Some(labels),
+            healthcheck: options.healthcheck.as_deref().map(build_healthcheck),
             ..Default::default()
         };
 
-        let options = CreateContainerOptions {
-            name: container_name.clone(),
-            platform: None,
-        };
+        let create_options = create_container_options(&container_name, options);
 
         let container = self
-            .docker
-            .create_container(Some(options), container_config)
+            .api()
+            .await
+            .create_container(Some(create_options), container_config)
             .await
-            .context("Failed to create container")?;
+            .context("Failed to create container")
+            .map_err(explain_platform_mismatch)?;
 
         Ok(container.id)
     }
 
-    pub async fn start_container(&self, container_id: &str) -> Result<()> {
-        info!("Starting container: {}", container_id);
-        self.docker
-            .start_container(container_id, None::<StartContainerOptions<String>>)
-            .await
-            .context("Failed to start container")?;
+    /// Check that a network passed to `--network` exists. Built-in modes always do.
+    pub async fn ensure_network_exists(&self, network: &str) -> Result<()> {
+        if is_builtin_network(network) {
+            return Ok(());
+        }
+
+        match self
+            .api()
+            .await
+            .inspect_network(network, None::<InspectNetworkOptions<String>>)
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(bollard::errors::Error::DockerResponseServerError {
+                status_code: 404, ..
+            }) => Err(anyhow!(
+                "Docker network '{}' does not exist. Create it with: docker network create {}",
+                network,
+                network
+            )),
+            Err(e) => {
+                Err(e).with_context(|| format!("Failed to inspect Docker network '{}'", network))
+            }
+        }
+    }
+
+    /// Move a stopped container onto a different network, replacing its current ones.
+    /// Host networking can't be switched in place; such agents must be recreated.
+    pub async fn set_container_network(&self, container_id: &str, network: &str) -> Result<()> {
+        self.ensure_network_exists(network).await?;
+
+        let info = self
+            .api()
+            .await
+            .inspect_container(container_id, None::<InspectContainerOptions>)
+            .await
+            .context("Failed to inspect container")?;
+        let current: Vec<String> = info
+            .network_settings
+            .and_then(|settings| settings.networks)
+            .map(|networks| networks.into_keys().collect())
+            .unwrap_or_default();
+
+        if current.len() == 1 && current[0] == network {
+            return Ok(());
+        }
+
+        if network == "host" || current.iter().any(|name| name == "host") {
+            return Err(anyhow!(
+                "Cannot switch an existing container to or from host networking; \
+                 recreate the agent with --network instead"
+            ));
+        }
+
+        debug!(
+            "Moving container {} from networks {:?} to '{}'",
+            container_id, current, network
+        );
+
+        for name in &current {
+            self.api()
+                .await
+                .disconnect_network(
+                    name,
+                    DisconnectNetworkOptions {
+                        container: container_id.to_string(),
+                        force: true,
+                    },
+                )
+                .await
+                .with_context(|| format!("Failed to disconnect from network '{}'", name))?;
+        }
+
+        self.api()
+            .await
+            .connect_network(
+                network,
+                ConnectNetworkOptions {
+                    container: container_id.to_string(),
+                    ..Default::default()
+                },
+            )
+            .await
+            .with_context(|| format!("Failed to connect to network '{}'", network))?;
+
+        Ok(())
+    }
+
+    /// Apply the memory and CPU limits in `limits` to an existing container
+    pub async fn update_container_resources(
+        &self,
+        container_id: &str,
+        limits: &ContainerOptions,
+    ) -> Result<()> {
+        debug!(
+            "Updating resources for container {}: memory: {:?}, memory swap: {:?}, \
+             memory reservation: {:?}, cpus: {:?}",
+            container_id, limits.memory, limits.memory_swap, limits.memory_reservation, limits.cpus
+        );
+
+        self.api()
+            .await
+            .update_container(container_id, resource_update(limits)?)
+            .await
+            .context("Failed to update container resources")?;
+        Ok(())
+    }
+
+    pub async fn start_container(&self, container_id: &str) -> Result<()> {
+        info!("Starting container: {}", container_id);
+        self.api()
+            .await
+            .start_container(container_id, None::<StartContainerOptions<String>>)
+            .await
+            .context("Failed to start container")
+            .map_err(explain_gpu_unavailable)?;
         debug!("Container {} started successfully", container_id);
         Ok(())
     }
 
+    /// Freeze every process in a running container
+    pub async fn pause_container(&self, container_id: &str) -> Result<()> {
+        info!("Pausing container: {}", container_id);
+        self.api()
+            .await
+            .pause_container(container_id)
+            .await
+            .context("Failed to pause container")?;
+        debug!("Container {} paused successfully", container_id);
+        Ok(())
+    }
+
+    /// Resume a paused container
+    pub async fn unpause_container(&self, container_id: &str) -> Result<()> {
+        info!("Unpausing container: {}", container_id);
+        self.api()
+            .await
+            .unpause_container(container_id)
+            .await
+            .context("Failed to unpause container")?;
+        debug!("Container {} unpaused successfully", container_id);
+        Ok(())
+    }
+
     pub async fn stop_container(&self, container_id: &str, force: bool) -> Result<()> {
-        info!("Stopping container: {} (force: {})", container_id, force);
-        let options = if force {
-            StopContainerOptions { t: 0 }
-        } else {
-            StopContainerOptions { t: 5 } // Quick stop for dev containers
-        };
+        self.stop_container_with_timeout(container_id, force, DEFAULT_STOP_TIMEOUT_SECS)
+            .await
+    }
+
+    /// Stop a container, giving it `timeout_secs` to shut down gracefully.
+    /// `force` always wins and kills the container immediately.
+    pub async fn stop_container_with_timeout(
+        &self,
+        container_id: &str,
+        force: bool,
+        timeout_secs: u64,
+    ) -> Result<()> {
+        info!(
+            "Stopping container: {} (force: {}, timeout: {}s)",
+            container_id, force, timeout_secs
+        );
+        let options = stop_options(force, timeout_secs);
 
-        self.docker
+        self.api()
+            .await
             .stop_container(container_id, Some(options))
             .await
             .context("Failed to stop container")?;
@@ -274,7 +1470,8 @@ impl DockerClient {
             ..Default::default()
         };
 
-        self.docker
+        self.api()
+            .await
             .remove_container(container_id, Some(options))
             .await
             .context("Failed to remove container")?;
@@ -287,16 +1484,23 @@ impl DockerClient {
         cmd: Vec<&str>,
         attach: bool,
     ) -> Result<()> {
-        self.exec_in_container_as_user(container_id, cmd, attach, None).await
+        self.exec_in_container_as_user(container_id, cmd, attach, None, &[], None)
+            .await
+            .map(|_| ())
     }
 
+    /// Run `cmd` in the container as `user`, attached to this terminal when `attach`
+    /// is set. `env` adds `KEY=VALUE` variables for this exec only. Typing `detach_keys`
+    /// in an attached session leaves it running in the container and returns.
     pub async fn exec_in_container_as_user(
         &self,
         container_id: &str,
         cmd: Vec<&str>,
         attach: bool,
         user: Option<&str>,
-    ) -> Result<()> {
+        env: &[String],
+        detach_keys: Option<&DetachKeys>,
+    ) -> Result<SessionEnd> {
         let exec_config = CreateExecOptions {
             cmd: Some(cmd),
             attach_stdout: Some(attach),
@@ -304,29 +1508,199 @@ impl DockerClient {
             attach_stdin: Some(attach),
             tty: Some(attach),
             user,
+            env: (!env.is_empty()).then(|| env.iter().map(String::as_str).collect()),
             ..Default::default()
         };
 
         let exec = self
-            .docker
+            .api()
+            .await
             .create_exec(container_id, exec_config)
             .await
             .context("Failed to create exec")?;
 
         if attach {
-            match self.docker.start_exec(&exec.id, None).await? {
-                StartExecResults::Attached { mut output, .. } => {
-                    while let Some(msg) = output.next().await {
-                        print!("{}", msg?);
+            match self.api().await.start_exec(&exec.id, None).await? {
+                StartExecResults::Attached {
+                    mut output,
+                    mut input,
+                } => {
+                    // Match the exec TTY to the local terminal so editors render correctly,
+                    // and keep it in sync as the terminal window is resized
+                    resize_exec_to_terminal(self.api().await, &exec.id).await;
+                    let resize_task = spawn_resize_forwarding(self.api().await, &exec.id);
+
+                    let raw_mode = RawModeGuard::enable();
+
+                    // Read stdin on a plain thread: a blocking read would otherwise keep
+                    // the runtime alive after the session ends
+                    let (tx, mut rx) = tokio::sync::mpsc::channel::<Vec<u8>>(32);
+                    std::thread::spawn(move || {
+                        let mut stdin = io::stdin();
+                        let mut buf = [0u8; 1024];
+                        loop {
+                            match stdin.read(&mut buf) {
+                                Ok(0) | Err(_) => break,
+                                Ok(n) => {
+                                    if tx.blocking_send(buf[..n].to_vec()).is_err() {
+                                        break;
+                                    }
+                                }
+                            }
+                        }
+                    });
+                    // The detach keys never reach the session; typing them ends it here
+                    let mut detector = detach_keys.map(DetachDetector::new);
+                    let (detach_tx, mut detach_rx) = tokio::sync::mpsc::channel::<()>(1);
+                    let stdin_task = tokio::spawn(async move {
+                        use tokio::io::AsyncWriteExt;
+                        while let Some(bytes) = rx.recv().await {
+                            let (bytes, detach) = match detector.as_mut() {
+                                Some(detector) => detector.scan(&bytes),
+                                None => (bytes, false),
+                            };
+                            if input.write_all(&bytes).await.is_err() {
+                                break;
+                            }
+                            if detach {
+                                let _ = detach_tx.send(()).await;
+                                break;
+                            }
+                        }
+                    });
+
+                    let mut stdout = io::stdout();
+                    let mut end = SessionEnd::Exited;
+                    loop {
+                        tokio::select! {
+                            msg = output.next() => match msg {
+                                Some(msg) => {
+                                    stdout.write_all(&msg?.into_bytes())?;
+                                    stdout.flush()?;
+                                }
+                                None => break,
+                            },
+                            Some(()) = detach_rx.recv() => {
+                                end = SessionEnd::Detached;
+                                break;
+                            }
+                        }
+                    }
+
+                    stdin_task.abort();
+                    if let Some(resize_task) = resize_task {
+                        resize_task.abort();
+                    }
+                    drop(raw_mode);
+
+                    if end == SessionEnd::Detached {
+                        return Ok(end);
+                    }
+
+                    // Surface a failing command the same way `docker exec` would
+                    let exit_code = self.api().await.inspect_exec(&exec.id).await?.exit_code;
+                    if let Some(code) = exit_code.filter(|code| *code != 0) {
+                        return Err(anyhow!("Command exited with status {}", code));
                     }
                 }
                 StartExecResults::Detached => {}
             }
         } else {
-            self.docker.start_exec(&exec.id, None).await?;
+            self.api().await.start_exec(&exec.id, None).await?;
         }
 
-        Ok(())
+        Ok(SessionEnd::Exited)
+    }
+
+    /// Run a command without a TTY and collect its output instead of streaming it
+    /// to the terminal. A non-zero exit code is reported in the result, not as an error.
+    pub async fn exec_capture_as_user(
+        &self,
+        container_id: &str,
+        cmd: Vec<&str>,
+        user: Option<&str>,
+    ) -> Result<ExecOutput> {
+        let exec_config = CreateExecOptions {
+            cmd: Some(cmd),
+            attach_stdout: Some(true),
+            attach_stderr: Some(true),
+            user,
+            ..Default::default()
+        };
+
+        let exec = self
+            .api()
+            .await
+            .create_exec(container_id, exec_config)
+            .await
+            .context("Failed to create exec")?;
+
+        let mut result = ExecOutput::default();
+        if let StartExecResults::Attached { mut output, .. } =
+            self.api().await.start_exec(&exec.id, None).await?
+        {
+            while let Some(msg) = output.next().await {
+                match msg? {
+                    LogOutput::StdErr { message } => {
+                        result.stderr.push_str(&String::from_utf8_lossy(&message))
+                    }
+                    other => result
+                        .stdout
+                        .push_str(&String::from_utf8_lossy(&other.into_bytes())),
+                }
+            }
+        }
+
+        result.exit_code = self
+            .api()
+            .await
+            .inspect_exec(&exec.id)
+            .await?
+            .exit_code
+            .unwrap_or(0);
+        Ok(result)
+    }
+
+    /// Run a command without a TTY, writing its stdout and stderr to `out` as they
+    /// arrive. Returns the exit code; a non-zero code is not an error.
+    pub async fn exec_streaming_as_user(
+        &self,
+        container_id: &str,
+        cmd: Vec<&str>,
+        user: Option<&str>,
+        out: &mut impl Write,
+    ) -> Result<i64> {
+        let exec_config = CreateExecOptions {
+            cmd: Some(cmd),
+            attach_stdout: Some(true),
+            attach_stderr: Some(true),
+            user,
+            ..Default::default()
+        };
+
+        let exec = self
+            .api()
+            .await
+            .create_exec(container_id, exec_config)
+            .await
+            .context("Failed to create exec")?;
+
+        if let StartExecResults::Attached { mut output, .. } =
+            self.api().await.start_exec(&exec.id, None).await?
+        {
+            while let Some(msg) = output.next().await {
+                out.write_all(&msg?.into_bytes())?;
+                out.flush()?;
+            }
+        }
+
+        Ok(self
+            .api()
+            .await
+            .inspect_exec(&exec.id)
+            .await?
+            .exit_code
+            .unwrap_or(0))
     }
 
     pub async fn get_container_logs(
@@ -336,27 +1710,118 @@ impl DockerClient {
         tail: Option<String>,
         timestamps: bool,
     ) -> Result<()> {
-        let options = LogsOptions {
+        let query = LogsQuery {
             follow,
-            stdout: true,
-            stderr: true,
-            tail: tail.unwrap_or_else(|| "50".to_string()),
+            tail,
             timestamps,
             ..Default::default()
         };
+        self.write_container_logs(container_id, &query, &mut io::stdout())
+            .await
+    }
+
+    /// Stream container logs into any writer (stdout, a file, ...).
+    /// The writer is flushed after each chunk so followed logs appear promptly.
+    /// With `query.grep` only whole lines the filter keeps are written.
+    pub async fn write_container_logs(
+        &self,
+        container_id: &str,
+        query: &LogsQuery,
+        writer: &mut impl Write,
+    ) -> Result<()> {
+        let options = LogsOptions {
+            follow: query.follow,
+            stdout: true,
+            stderr: true,
+            tail: query.tail.clone().unwrap_or_else(|| "50".to_string()),
+            timestamps: query.timestamps,
+            // 0 means unbounded
+            since: query.since.unwrap_or(0),
+            until: query.until.unwrap_or(0),
+        };
 
-        let mut stream = self.docker.logs(container_id, Some(options));
+        let mut stream = self.api().await.logs(container_id, Some(options));
+        // Chunks needn't end at a line break; filtering holds back the partial last line
+        let mut pending = Vec::new();
 
         while let Some(msg) = stream.next().await {
             match msg {
-                Ok(output) => print!("{}", output),
+                Ok(output) => {
+                    match &query.grep {
+                        Some(filter) => {
+                            pending.extend_from_slice(&output.into_bytes());
+                            write_kept_lines(&mut pending, filter, query.timestamps, writer)?;
+                        }
+                        None => writer
+                            .write_all(&output.into_bytes())
+                            .context("Failed to write logs")?,
+                    }
+                    writer.flush().context("Failed to write logs")?;
+                }
                 Err(e) => eprintln!("Error reading logs: {}", e),
             }
         }
 
+        // Output that didn't end with a line break
+        if let Some(filter) = &query.grep {
+            if filter.keeps(&String::from_utf8_lossy(&pending), query.timestamps) {
+                writer.write_all(&pending).context("Failed to write logs")?;
+                writer.flush().context("Failed to write logs")?;
+            }
+        }
+
         Ok(())
     }
 
+    /// Take one resource usage sample of a container. Docker waits for a second
+    /// snapshot so the CPU percentage covers a real interval.
+    pub async fn stats(&self, container_id: &str) -> Result<ContainerStats> {
+        let options = StatsOptions {
+            stream: false,
+            one_shot: false,
+        };
+        let stats = self
+            .api()
+            .await
+            .stats(container_id, Some(options))
+            .next()
+            .await
+            .ok_or_else(|| anyhow!("Docker returned no stats for container {}", container_id))?
+            .with_context(|| format!("Failed to get stats for container {}", container_id))?;
+
+        Ok(ContainerStats::from_docker(&stats))
+    }
+
+    /// Sample every running CrowdControl container concurrently, keyed by agent name
+    /// and sorted by it. Containers that stop while being sampled are left out.
+    pub async fn running_agent_stats(&self) -> Result<Vec<(String, ContainerStats)>> {
+        let containers = self.list_all_containers().await?;
+        let running: Vec<(String, String)> = containers
+            .iter()
+            .filter(|c| c.state.as_deref() == Some("running"))
+            .filter_map(|c| Some((agent_name_of(c, &self.config)?, c.id.clone()?)))
+            .collect();
+
+        let samples = running
+            .iter()
+            .map(|(_, container_id)| self.stats(container_id));
+        let samples = futures_util::future::join_all(samples).await;
+
+        let mut stats: Vec<(String, ContainerStats)> = running
+            .into_iter()
+            .zip(samples)
+            .filter_map(|((name, _), sample)| match sample {
+                Ok(sample) => Some((name, sample)),
+                Err(e) => {
+                    debug!("Skipping stats for agent '{}': {:#}", name, e);
+                    None
+                }
+            })
+            .collect();
+        stats.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(stats)
+    }
+
     /// List all CrowdControl containers (running and stopped)
     pub async fn list_all_containers(&self) -> Result<Vec<bollard::models::ContainerSummary>> {
         let mut filters = HashMap::new();
@@ -368,15 +1833,17 @@ impl DockerClient {
             ..Default::default()
         };
 
-        self.docker
-            .list_containers(Some(options))
-            .await
-            .context("Failed to list containers")
+        let docker = self.api().await;
+        with_retry("list containers", || {
+            docker.list_containers(Some(options.clone()))
+        })
+        .await
+        .context("Failed to list containers")
     }
 
     pub async fn get_container_status(&self, name: &str) -> Result<AgentStatus> {
         let mut filters = HashMap::new();
-        filters.insert("name".to_string(), vec![format!("crowdcontrol-{}", name)]);
+        filters.insert("name".to_string(), vec![self.config.container_name(name)]);
 
         let options = ListContainersOptions {
             all: true,
@@ -384,106 +1851,2123 @@ impl DockerClient {
             ..Default::default()
         };
 
-        let containers = self.docker.list_containers(Some(options)).await?;
+        let docker = self.api().await;
+        let containers = with_retry("list containers", || {
+            docker.list_containers(Some(options.clone()))
+        })
+        .await?;
 
         if containers.is_empty() {
             return Ok(AgentStatus::Created);
         }
 
-        let container = &containers[0];
-        let state = container
-            .state
-            .as_ref()
-            .map(|s| s.as_str())
-            .unwrap_or("unknown");
-
-        match state {
-            "created" => Ok(AgentStatus::Created),
-            "running" => Ok(AgentStatus::Running),
-            "exited" => Ok(AgentStatus::Stopped),
-            "dead" | "removing" => Ok(AgentStatus::Error),
-            _ => Ok(AgentStatus::Stopped),
-        }
+        Ok(status_of(&containers[0]))
     }
 
     pub async fn pull_image(&self) -> Result<()> {
-        // First check if the image exists locally
-        let images = self.docker.list_images::<String>(None).await?;
-        let image_exists = images.iter().any(|img| {
-            img.repo_tags.iter().any(|tag| {
-                tag == &self.config.image || tag.starts_with(&format!("{}:", self.config.image))
-            })
+        self.pull_image_with_output(true).await
+    }
+
+    /// Image a container with these options is created from: the agent's own, if it
+    /// has one, otherwise the configured image
+    pub fn image_for<'a>(&'a self, options: &'a ContainerOptions) -> &'a str {
+        options.image.as_deref().unwrap_or(&self.config.image)
+    }
+
+    /// The daemon's Docker version and the API version it speaks, e.g. `("24.0.7", "1.43")`
+    pub async fn daemon_version(&self) -> Result<(String, String)> {
+        let version = self
+            .api()
+            .await
+            .version()
+            .await
+            .context("Failed to get the Docker version")?;
+        Ok((
+            version.version.unwrap_or_else(|| "unknown".to_string()),
+            version.api_version.unwrap_or_else(|| "unknown".to_string()),
+        ))
+    }
+
+    /// The configuration this client was created with
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Docker API version this client makes its requests with, after negotiating
+    /// it with the daemon
+    pub async fn client_api_version(&self) -> String {
+        self.api().await.client_version().to_string()
+    }
+
+    /// Check whether `image` is present locally
+    pub async fn image_exists(&self, image: &str) -> Result<bool> {
+        let images = self.api().await.list_images::<String>(None).await?;
+        Ok(images.iter().any(|img| {
+            img.repo_tags
+                .iter()
+                .any(|tag| tag == image || tag.starts_with(&format!("{}:", image)))
+        }))
+    }
+
+    /// Preflight checks for `doctor`: the daemon answers, speaks a supported API
+    /// version, and the configured image is present locally or can be pulled.
+    /// The remaining checks are skipped when the daemon can't be reached.
+    pub async fn check_environment(&self) -> Vec<EnvironmentCheck> {
+        let mut checks = Vec::new();
+
+        let version = match self.api().await.version().await {
+            Ok(version) => version,
+            Err(e) => {
+                checks.push(EnvironmentCheck::fail(
+                    "Docker daemon",
+                    format!("not reachable: {}", e),
+                ));
+                return checks;
+            }
+        };
+        checks.push(EnvironmentCheck::pass(
+            "Docker daemon",
+            format!(
+                "reachable (Docker {})",
+                version.version.as_deref().unwrap_or("unknown version")
+            ),
+        ));
+
+        checks.push(match version.api_version.as_deref() {
+            Some(api) if api_version_supported(api, MIN_DOCKER_API_VERSION) => {
+                EnvironmentCheck::pass(
+                    "Docker API version",
+                    format!("{} (minimum {})", api, MIN_DOCKER_API_VERSION),
+                )
+            }
+            Some(api) => EnvironmentCheck::fail(
+                "Docker API version",
+                format!(
+                    "{} is older than the minimum {}; upgrade Docker",
+                    api, MIN_DOCKER_API_VERSION
+                ),
+            ),
+            None => EnvironmentCheck::fail("Docker API version", "not reported by the daemon"),
+        });
+
+        let image = &self.config.image;
+        checks.push(match self.image_exists(image).await {
+            Ok(true) => EnvironmentCheck::pass("Image", format!("{} is available locally", image)),
+            // Ask the registry, through the daemon, without downloading anything
+            Ok(false) => match self.api().await.inspect_registry_image(image, None).await {
+                Ok(_) => EnvironmentCheck::pass(
+                    "Image",
+                    format!("{} is not present locally but can be pulled", image),
+                ),
+                Err(e) => EnvironmentCheck::fail(
+                    "Image",
+                    format!(
+                        "{} is not present locally and can't be pulled: {}",
+                        image, e
+                    ),
+                ),
+            },
+            Err(e) => EnvironmentCheck::fail("Image", format!("failed to list images: {}", e)),
         });
 
-        if image_exists {
-            println!("Docker image {} already exists locally", self.config.image);
+        checks
+    }
+
+    /// Pull the configured image if it is not present locally.
+    /// With `show_output` disabled nothing is written to stdout, for machine-readable modes.
+    /// Fails if the registry reports an error, e.g. for a locally built image that can't be pulled.
+    pub async fn pull_image_with_output(&self, show_output: bool) -> Result<()> {
+        self.pull_image_for_platform(&self.config.image, show_output, None)
+            .await
+    }
+
+    /// Like `pull_image_with_output` for `image`, pulling the variant for `platform`
+    /// (e.g. `linux/amd64`) instead of the daemon's native one. An image already present
+    /// locally is used as is.
+    pub async fn pull_image_for_platform(
+        &self,
+        image: &str,
+        show_output: bool,
+        platform: Option<&str>,
+    ) -> Result<()> {
+        // First check if the image exists locally
+        if self.image_exists(image).await? {
+            if show_output {
+                println!("Docker image {} already exists locally", image);
+            }
             return Ok(());
         }
 
-        println!("Pulling Docker image: {}", self.config.image);
+        if show_output {
+            println!("Pulling Docker image: {}", image);
+        }
 
         let options = CreateImageOptions {
-            from_image: self.config.image.clone(),
+            from_image: image.to_string(),
+            platform: platform.unwrap_or_default().to_string(),
             ..Default::default()
         };
 
-        let mut stream = self.docker.create_image(Some(options), None, None);
+        let mut stream = self.api().await.create_image(Some(options), None, None);
+
+        // Interactive terminals get an aggregate progress bar; otherwise (e.g. CI logs)
+        // print line-based updates so progress isn't collapsed onto one line
+        let bar = (show_output && std::io::stdout().is_terminal()).then(pull_progress_bar);
+        let mut progress = PullProgress::new();
+        let mut pull_error = None;
 
         while let Some(msg) = stream.next().await {
             match msg {
                 Ok(info) => {
-                    if let Some(status) = info.status {
-                        print!("\r{}", status);
+                    if let Some(error) = &info.error {
+                        pull_error = Some(error.clone());
+                    }
+                    let line = progress.update(&info);
+                    if let Some(bar) = &bar {
+                        let (current, total) = progress.bytes();
+                        bar.set_length(total);
+                        bar.set_position(current);
+                        bar.set_message(progress.summary());
+                    } else if let Some(line) = line.filter(|_| show_output) {
+                        println!("{}", line);
                     }
                 }
-                Err(e) => eprintln!("Error pulling image: {}", e),
+                Err(e) => pull_error = Some(e.to_string()),
             }
         }
-        println!();
+
+        if let Some(bar) = &bar {
+            bar.finish_and_clear();
+        }
+
+        if let Some(error) = pull_error {
+            return Err(explain_platform_mismatch(anyhow!(
+                "Failed to pull image {}: {}",
+                image,
+                error
+            )));
+        }
+
+        if bar.is_some() {
+            println!("Pulled Docker image: {}", image);
+        }
 
         Ok(())
     }
 
-    /// Validate that a container ID actually belongs to the specified agent
-    pub async fn validate_container_id(&self, agent_name: &str, container_id: &str) -> Result<bool> {
-        let expected_container_name = format!("crowdcontrol-{}", agent_name);
-        
-        // Get container details
-        match self.docker.inspect_container(container_id, None::<InspectContainerOptions>).await {
-            Ok(container) => {
-                // Check if container name matches expected agent name
-                if let Some(name) = container.name {
-                    // Docker container names start with "/"
-                    let clean_name = name.strip_prefix('/').unwrap_or(&name);
-                    Ok(clean_name == expected_container_name)
-                } else {
-                    Ok(false)
+    /// Build `image` from a directory containing a Dockerfile, tagging it with that name
+    pub async fn build_image(
+        &self,
+        image: &str,
+        context_dir: &Path,
+        show_output: bool,
+    ) -> Result<()> {
+        let context = build_context_tar(context_dir)?;
+
+        let options = BuildImageOptions {
+            dockerfile: "Dockerfile".to_string(),
+            t: image.to_string(),
+            rm: true,
+            ..Default::default()
+        };
+
+        let mut stream = self
+            .api()
+            .await
+            .build_image(options, None, Some(context.into()));
+
+        while let Some(msg) = stream.next().await {
+            let info = msg.context("Failed to build image")?;
+            if let Some(error) = info.error {
+                return Err(anyhow!("Failed to build image {}: {}", image, error));
+            }
+            if show_output {
+                if let Some(output) = info.stream {
+                    print!("{}", output);
                 }
             }
-            Err(_) => {
-                // Container doesn't exist or can't be inspected
-                Ok(false)
+        }
+
+        Ok(())
+    }
+
+    /// Docker's full inspect document for an agent's container, looked up by container
+    /// name so it works whether or not the agent is running
+    pub async fn inspect_agent_container(&self, name: &str) -> Result<ContainerInspectResponse> {
+        let container_name = self.config.container_name(name);
+        match self
+            .api()
+            .await
+            .inspect_container(&container_name, None::<InspectContainerOptions>)
+            .await
+        {
+            Ok(info) => Ok(info),
+            Err(bollard::errors::Error::DockerResponseServerError {
+                status_code: 404, ..
+            }) => Err(anyhow!(
+                "Agent '{}' has no container yet. Create one with: crowdcontrol start {}",
+                name,
+                name
+            )),
+            Err(e) => {
+                Err(e).with_context(|| format!("Failed to inspect container '{}'", container_name))
             }
         }
     }
-}
 
-fn parse_memory_limit(memory: &str) -> Result<i64> {
-    let memory_lower = memory.to_lowercase();
-    let multiplier = if memory_lower.ends_with("g") {
-        1_073_741_824
-    } else if memory_lower.ends_with("m") {
-        1_048_576
-    } else if memory_lower.ends_with("k") {
-        1_024
-    } else {
-        return Err(anyhow!(
-            "Invalid memory format. Use format like: 2g, 1024m, or 512k"
-        ));
-    };
+    /// Get the health check state of a container.
+    /// Returns `None` if the container has no health check configured.
+    pub async fn get_container_health(
+        &self,
+        container_id: &str,
+    ) -> Result<Option<ContainerHealth>> {
+        let docker = self.api().await;
+        let container = with_retry("inspect container", || {
+            docker.inspect_container(container_id, None::<InspectContainerOptions>)
+        })
+        .await
+        .context("Failed to inspect container")?;
 
-    let number_part = &memory_lower[..memory_lower.len() - 1];
-    let number: i64 = number_part.parse().context("Invalid memory value")?;
+        let health = match container.state.and_then(|state| state.health) {
+            Some(health) => health,
+            None => return Ok(None),
+        };
+
+        let status = match health.status {
+            Some(HealthStatusEnum::HEALTHY) => HealthStatus::Healthy,
+            Some(HealthStatusEnum::UNHEALTHY) => HealthStatus::Unhealthy,
+            Some(HealthStatusEnum::STARTING) => HealthStatus::Starting,
+            // "none" means no health check is configured
+            _ => return Ok(None),
+        };
+
+        let last_output = health
+            .log
+            .and_then(|log| log.into_iter().last())
+            .and_then(|result| result.output)
+            .map(|output| output.trim().to_string());
+
+        Ok(Some(ContainerHealth {
+            status,
+            last_output,
+        }))
+    }
 
-    Ok(number * multiplier)
+    /// Wait until the container is running, polling with backoff. Fails as soon as the
+    /// container has exited, and with `CrowdControlError::Timeout` once `timeout` passes.
+    pub async fn wait_for_container_running(
+        &self,
+        container_id: &str,
+        timeout: Duration,
+    ) -> Result<()> {
+        self.wait_for_state(container_id, timeout, "to be running", |state| {
+            Ok(state.running == Some(true))
+        })
+        .await
+    }
+
+    /// Wait until the container's health check passes. Like `wait_for_container_running`,
+    /// but a container without a health check is an error rather than something to wait on.
+    pub async fn wait_for_healthy(&self, container_id: &str, timeout: Duration) -> Result<()> {
+        self.wait_for_state(
+            container_id,
+            timeout,
+            "to become healthy",
+            |state| match state.health.as_ref().and_then(|health| health.status) {
+                Some(HealthStatusEnum::HEALTHY) => Ok(true),
+                Some(HealthStatusEnum::STARTING | HealthStatusEnum::UNHEALTHY) => Ok(false),
+                _ => Err(anyhow!(
+                    "Container {} has no health check",
+                    short_container_id(container_id)
+                )),
+            },
+        )
+        .await
+    }
+
+    /// Exit code of a container that has stopped, or `None` while it's created or running
+    pub async fn get_exit_code(&self, container_id: &str) -> Result<Option<i64>> {
+        let docker = self.api().await;
+        let container = with_retry("inspect container", || {
+            docker.inspect_container(container_id, None::<InspectContainerOptions>)
+        })
+        .await
+        .context("Failed to inspect container")?;
+
+        Ok(exit_code_of(&container.state.unwrap_or_default()))
+    }
+
+    /// Watch a just-started container for `window`, failing with
+    /// `CrowdControlError::ContainerExited` if it stops in that time. Agents whose
+    /// entrypoint dies at once would otherwise be reported as started.
+    pub async fn check_started(&self, container_id: &str, window: Duration) -> Result<()> {
+        let deadline = std::time::Instant::now() + window;
+        let mut interval = WAIT_INITIAL_INTERVAL;
+
+        loop {
+            if let Some(exited) = self.startup_failure(container_id).await? {
+                return Err(exited.into());
+            }
+
+            let now = std::time::Instant::now();
+            if now >= deadline {
+                return Ok(());
+            }
+            tokio::time::sleep(interval.min(deadline - now)).await;
+            interval = (interval * 2).min(WAIT_MAX_INTERVAL);
+        }
+    }
+
+    /// `CrowdControlError::ContainerExited` for a container that has stopped, with its
+    /// exit code and last log lines, or `None` while it's created or running
+    pub async fn startup_failure(&self, container_id: &str) -> Result<Option<CrowdControlError>> {
+        let docker = self.api().await;
+        let container = with_retry("inspect container", || {
+            docker.inspect_container(container_id, None::<InspectContainerOptions>)
+        })
+        .await
+        .context("Failed to inspect container")?;
+
+        let exit_code = match exit_code_of(&container.state.unwrap_or_default()) {
+            Some(exit_code) => exit_code,
+            None => return Ok(None),
+        };
+        // Docker container names start with "/"
+        let name = container
+            .name
+            .as_deref()
+            .map(|name| name.trim_start_matches('/').to_string())
+            .unwrap_or_else(|| short_container_id(container_id).to_string());
+
+        let query = LogsQuery {
+            tail: Some(STARTUP_LOG_LINES.to_string()),
+            ..Default::default()
+        };
+        let mut logs = Vec::new();
+        self.write_container_logs(container_id, &query, &mut logs)
+            .await?;
+
+        Ok(Some(CrowdControlError::ContainerExited {
+            container: name,
+            exit_code,
+            logs: String::from_utf8_lossy(&logs).trim_end().to_string(),
+        }))
+    }
+
+    /// Poll the container's state until `done` returns true, doubling the interval
+    /// between checks up to `WAIT_MAX_INTERVAL`
+    async fn wait_for_state(
+        &self,
+        container_id: &str,
+        timeout: Duration,
+        condition: &str,
+        done: impl Fn(&ContainerState) -> Result<bool>,
+    ) -> Result<()> {
+        let deadline = std::time::Instant::now() + timeout;
+        let mut interval = WAIT_INITIAL_INTERVAL;
+
+        loop {
+            let docker = self.api().await;
+            let container = with_retry("inspect container", || {
+                docker.inspect_container(container_id, None::<InspectContainerOptions>)
+            })
+            .await
+            .context("Failed to inspect container")?;
+            let state = container.state.unwrap_or_default();
+
+            if done(&state)? {
+                return Ok(());
+            }
+            // A created container may still be starting; an exited one never will
+            if matches!(
+                state.status,
+                Some(ContainerStateStatusEnum::EXITED | ContainerStateStatusEnum::DEAD)
+            ) {
+                return Err(anyhow!(
+                    "Container {} stopped while waiting for it {}",
+                    short_container_id(container_id),
+                    condition
+                ));
+            }
+
+            let now = std::time::Instant::now();
+            if now >= deadline {
+                return Err(CrowdControlError::Timeout {
+                    waiting_for: format!(
+                        "container {} {}",
+                        short_container_id(container_id),
+                        condition
+                    ),
+                    timeout,
+                }
+                .into());
+            }
+            tokio::time::sleep(interval.min(deadline - now)).await;
+            interval = (interval * 2).min(WAIT_MAX_INTERVAL);
+        }
+    }
+
+    /// Validate that a container ID actually belongs to the specified agent
+    pub async fn validate_container_id(
+        &self,
+        agent_name: &str,
+        container_id: &str,
+    ) -> Result<bool> {
+        let expected_container_name = self.config.container_name(agent_name);
+
+        // Get container details
+        let docker = self.api().await;
+        match with_retry("inspect container", || {
+            docker.inspect_container(container_id, None::<InspectContainerOptions>)
+        })
+        .await
+        {
+            Ok(container) => {
+                // Check if container name matches expected agent name
+                if let Some(name) = container.name {
+                    // Docker container names start with "/"
+                    let clean_name = name.strip_prefix('/').unwrap_or(&name);
+                    Ok(clean_name == expected_container_name)
+                } else {
+                    Ok(false)
+                }
+            }
+            Err(_) => {
+                // Container doesn't exist or can't be inspected
+                Ok(false)
+            }
+        }
+    }
+
+    /// ID of the container named after `agent_name`, if there is one and it is labelled
+    /// as that agent's container. Finds a container whose ID was never recorded.
+    pub async fn labelled_container_id(&self, agent_name: &str) -> Result<Option<String>> {
+        let container_name = self.config.container_name(agent_name);
+        let docker = self.api().await;
+        let container = match docker
+            .inspect_container(&container_name, None::<InspectContainerOptions>)
+            .await
+        {
+            Ok(container) => container,
+            Err(bollard::errors::Error::DockerResponseServerError {
+                status_code: 404, ..
+            }) => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        let labels = container.config.and_then(|config| config.labels);
+        Ok(container
+            .id
+            .filter(|_| is_agent_container(labels.as_ref(), agent_name)))
+    }
+}
+
+/// Attempts made for idempotent Docker calls before giving up
+const RETRY_ATTEMPTS: u32 = 3;
+
+/// Delay before the first retry; doubled for each further attempt
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(100);
+
+/// First delay between checks in the wait helpers; doubled after each check
+const WAIT_INITIAL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Longest delay between checks in the wait helpers
+const WAIT_MAX_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Log lines reported when a container exits right after starting
+const STARTUP_LOG_LINES: usize = 20;
+
+/// Exit code of a stopped container; `None` while it's created, running or restarting
+fn exit_code_of(state: &ContainerState) -> Option<i64> {
+    match state.status {
+        Some(ContainerStateStatusEnum::EXITED | ContainerStateStatusEnum::DEAD) => {
+            Some(state.exit_code.unwrap_or_default())
+        }
+        _ => None,
+    }
+}
+
+/// Whether a failed Docker call is worth retrying. Connection problems and server
+/// errors (5xx) are; client errors such as 404 (not found) or 409 (conflict) are not.
+fn is_transient(error: &bollard::errors::Error) -> bool {
+    use bollard::errors::Error;
+
+    match error {
+        Error::DockerResponseServerError { status_code, .. } => *status_code >= 500,
+        Error::IOError { .. } | Error::RequestTimeoutError | Error::HyperResponseError { .. } => {
+            true
+        }
+        // Client connection failures (e.g. resets) surface under transport-specific variants
+        other => other.to_string().to_lowercase().contains("connection"),
+    }
+}
+
+/// Write the complete lines at the start of `buffer` that `filter` keeps, leaving any
+/// unterminated last line in the buffer
+fn write_kept_lines(
+    buffer: &mut Vec<u8>,
+    filter: &LineFilter,
+    timestamps: bool,
+    writer: &mut impl Write,
+) -> Result<()> {
+    let Some(end) = buffer.iter().rposition(|&byte| byte == b'\n') else {
+        return Ok(());
+    };
+    for line in buffer[..=end].split_inclusive(|&byte| byte == b'\n') {
+        if filter.keeps(&String::from_utf8_lossy(line), timestamps) {
+            writer.write_all(line).context("Failed to write logs")?;
+        }
+    }
+    buffer.drain(..=end);
+    Ok(())
+}
+
+/// Run an idempotent Docker call, retrying transient failures with exponential backoff.
+/// Never use this for calls with side effects such as creating a container.
+async fn with_retry<T, F, Fut>(operation: &str, mut call: F) -> Result<T, bollard::errors::Error>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<T, bollard::errors::Error>>,
+{
+    let mut delay = RETRY_BASE_DELAY;
+    let mut attempt = 1;
+
+    loop {
+        match call().await {
+            Err(e) if attempt < RETRY_ATTEMPTS && is_transient(&e) => {
+                warn!(
+                    "Docker {} failed (attempt {}/{}): {}. Retrying in {:?}",
+                    operation, attempt, RETRY_ATTEMPTS, e, delay
+                );
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Choose the connection method from DOCKER_HOST, DOCKER_TLS_VERIFY and DOCKER_CERT_PATH
+fn connection_method(
+    docker_host: Option<String>,
+    tls_verify: Option<String>,
+    cert_path: Option<String>,
+) -> ConnectionMethod {
+    let host = match docker_host.filter(|h| !h.is_empty()) {
+        Some(host) => host,
+        None => return ConnectionMethod::LocalSocket,
+    };
+
+    // Like the docker CLI, any non-empty value other than "0" enables verification
+    let tls_verify = tls_verify.is_some_and(|v| !v.is_empty() && v != "0");
+    let cert_path = cert_path.filter(|p| !p.is_empty());
+
+    if host.starts_with("tcp://") && (tls_verify || cert_path.is_some()) {
+        let cert_path = cert_path
+            .map(PathBuf::from)
+            .unwrap_or_else(|| dirs::home_dir().unwrap_or_default().join(".docker"));
+        return ConnectionMethod::Tls { host, cert_path };
+    }
+
+    ConnectionMethod::DockerHostDefaults { host }
+}
+
+/// Locate the client key, client certificate and CA certificate in a cert directory
+fn tls_files(cert_path: &Path) -> Result<(PathBuf, PathBuf, PathBuf)> {
+    let key = cert_path.join("key.pem");
+    let cert = cert_path.join("cert.pem");
+    let ca = cert_path.join("ca.pem");
+
+    for file in [&key, &cert, &ca] {
+        if !file.exists() {
+            return Err(anyhow!(
+                "TLS was requested for DOCKER_HOST (DOCKER_TLS_VERIFY/DOCKER_CERT_PATH) \
+                 but {:?} is missing.\n\
+                 Set DOCKER_CERT_PATH to a directory containing key.pem, cert.pem and ca.pem.",
+                file
+            ));
+        }
+    }
+
+    Ok((key, cert, ca))
+}
+
+/// Package a directory as an uncompressed tarball to send as a Docker build context
+pub fn build_context_tar(context_dir: &Path) -> Result<Vec<u8>> {
+    if !context_dir.join("Dockerfile").exists() {
+        return Err(anyhow!("No Dockerfile found in {:?}", context_dir));
+    }
+
+    let mut builder = tar::Builder::new(Vec::new());
+    builder
+        .append_dir_all(".", context_dir)
+        .with_context(|| format!("Failed to package build context: {:?}", context_dir))?;
+    builder
+        .into_inner()
+        .context("Failed to package build context")
+}
+
+/// Resize an exec's TTY to the current size of the local terminal, if there is one
+async fn resize_exec_to_terminal(docker: &Docker, exec_id: &str) {
+    if let Some((width, height)) = terminal_size() {
+        if let Err(e) = docker
+            .resize_exec(exec_id, ResizeExecOptions { width, height })
+            .await
+        {
+            warn!("Failed to resize exec TTY: {}", e);
+        }
+    }
+}
+
+/// Re-send the terminal size to an exec on every SIGWINCH for the lifetime of the session.
+/// Returns `None` when not attached to a terminal, where there is nothing to forward.
+#[cfg(unix)]
+fn spawn_resize_forwarding(docker: &Docker, exec_id: &str) -> Option<tokio::task::JoinHandle<()>> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    if !io::stdin().is_terminal() || terminal_size().is_none() {
+        return None;
+    }
+
+    let mut resizes = match signal(SignalKind::window_change()) {
+        Ok(resizes) => resizes,
+        Err(e) => {
+            warn!("Failed to listen for terminal resize events: {}", e);
+            return None;
+        }
+    };
+
+    let docker = docker.clone();
+    let exec_id = exec_id.to_string();
+    Some(tokio::spawn(async move {
+        while resizes.recv().await.is_some() {
+            resize_exec_to_terminal(&docker, &exec_id).await;
+        }
+    }))
+}
+
+#[cfg(not(unix))]
+fn spawn_resize_forwarding(
+    _docker: &Docker,
+    _exec_id: &str,
+) -> Option<tokio::task::JoinHandle<()>> {
+    None
+}
+
+/// Aggregate progress bar for image pulls, sized in bytes across all layers
+fn pull_progress_bar() -> ProgressBar {
+    let bar = ProgressBar::new(0);
+    bar.set_style(
+        ProgressStyle::default_bar()
+            .template("{spinner:.green} {msg} [{bar:30}] {bytes}/{total_bytes}")
+            .unwrap(),
+    );
+    bar.enable_steady_tick(Duration::from_millis(100));
+    bar
+}
+
+/// Build the stop options for a graceful stop, or an immediate kill when forced
+fn stop_options(force: bool, timeout_secs: u64) -> StopContainerOptions {
+    if force {
+        StopContainerOptions { t: 0 }
+    } else {
+        StopContainerOptions {
+            t: i64::try_from(timeout_secs).unwrap_or(i64::MAX),
+        }
+    }
+}
+
+/// Network modes Docker provides without a user-defined network
+const BUILTIN_NETWORKS: [&str; 3] = ["none", "bridge", "host"];
+
+pub fn is_builtin_network(network: &str) -> bool {
+    BUILTIN_NETWORKS.contains(&network)
+}
+
+/// Where the refresh script writes Claude credentials inside the container
+#[cfg(test)]
+const CLAUDE_CREDENTIALS_PATH: &str = "/home/developer/.claude/.credentials.json";
+
+/// Paths that stay writable (as tmpfs) when the root filesystem is read-only.
+/// The Claude config directory is included so auth refresh keeps working.
+const READ_ONLY_TMPFS_PATHS: [(&str, &str); 2] = [
+    ("/tmp", "rw,exec,mode=1777"),
+    ("/home/developer/.claude", "rw,mode=0700"),
+];
+
+fn read_only_tmpfs() -> HashMap<String, String> {
+    READ_ONLY_TMPFS_PATHS
+        .iter()
+        .map(|(path, options)| (path.to_string(), options.to_string()))
+        .collect()
+}
+
+/// Mounts for a new container: the workspace (read-only if asked), plus the Claude
+/// config (both the `.claude` directory and legacy `.claude.json`) from `claude_home`
+/// when given
+fn container_mounts(
+    workspace: &Path,
+    readonly_workspace: bool,
+    claude_home: Option<&Path>,
+) -> Vec<Mount> {
+    let mut mounts = vec![
+        // Mount workspace
+        Mount {
+            target: Some(CONTAINER_WORKSPACE_DIR.to_string()),
+            source: Some(workspace.to_string_lossy().to_string()),
+            typ: Some(MountTypeEnum::BIND),
+            read_only: Some(readonly_workspace),
+            ..Default::default()
+        },
+    ];
+
+    let Some(home_dir) = claude_home else {
+        return mounts;
+    };
+
+    // Mount .claude directory if it exists
+    let claude_dir = home_dir.join(".claude");
+    if claude_dir.exists() {
+        mounts.push(Mount {
+            target: Some("/mnt/claude-config/.claude".to_string()),
+            source: Some(claude_dir.to_string_lossy().to_string()),
+            typ: Some(MountTypeEnum::BIND),
+            read_only: Some(true),
+            ..Default::default()
+        });
+    }
+
+    // Mount legacy .claude.json if it exists
+    let claude_legacy = home_dir.join(".claude.json");
+    if claude_legacy.exists() {
+        mounts.push(Mount {
+            target: Some("/mnt/claude-config/.claude.json".to_string()),
+            source: Some(claude_legacy.to_string_lossy().to_string()),
+            typ: Some(MountTypeEnum::BIND),
+            read_only: Some(true),
+            ..Default::default()
+        });
+    }
+
+    mounts
+}
+
+/// An extra bind mount given as `host:container[:ro]`
+#[derive(Debug, Clone, PartialEq)]
+pub struct VolumeSpec {
+    /// Canonical path on the host
+    pub host: PathBuf,
+    /// Absolute path inside the container
+    pub container: String,
+    pub read_only: bool,
+}
+
+impl VolumeSpec {
+    /// Parse `host:container[:ro|:rw]`. The host path must exist and is canonicalized
+    /// like the workspace mount, so relative paths and symlinks resolve once, up front.
+    pub fn parse(spec: &str) -> Result<Self> {
+        let invalid = || {
+            anyhow!(
+                "Invalid volume '{}': use host:container or host:container:ro",
+                spec
+            )
+        };
+
+        let parts: Vec<&str> = spec.split(':').collect();
+        let (host, container, read_only) = match parts.as_slice() {
+            [host, container] => (*host, *container, false),
+            [host, container, "ro"] => (*host, *container, true),
+            [host, container, "rw"] => (*host, *container, false),
+            _ => return Err(invalid()),
+        };
+        if host.is_empty() || !container.starts_with('/') {
+            return Err(invalid());
+        }
+
+        let host = Path::new(host)
+            .canonicalize()
+            .with_context(|| format!("Volume host path does not exist: {}", host))?;
+
+        Ok(Self {
+            host,
+            container: container.to_string(),
+            read_only,
+        })
+    }
+
+    fn mount(&self) -> Mount {
+        Mount {
+            target: Some(self.container.clone()),
+            source: Some(self.host.to_string_lossy().to_string()),
+            typ: Some(MountTypeEnum::BIND),
+            read_only: Some(self.read_only),
+            ..Default::default()
+        }
+    }
+}
+
+impl fmt::Display for VolumeSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.host.display(), self.container)?;
+        if self.read_only {
+            write!(f, ":ro")?;
+        }
+        Ok(())
+    }
+}
+
+/// Host environment variables resolved for `--env-passthrough`
+#[derive(Debug, Default, PartialEq)]
+pub struct EnvPassthrough {
+    /// `KEY=VALUE` entries for the container environment
+    pub vars: Vec<String>,
+    /// Requested names that aren't set on the host
+    pub missing: Vec<String>,
+}
+
+/// Look up each named variable with `lookup` (normally `std::env::var`), keeping
+/// the ones that are set and collecting the names of those that aren't
+pub fn resolve_env_passthrough<F>(names: &[String], lookup: F) -> EnvPassthrough
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut resolved = EnvPassthrough::default();
+    for name in names {
+        match lookup(name) {
+            Some(value) => resolved.vars.push(format!("{}={}", name, value)),
+            None => resolved.missing.push(name.clone()),
+        }
+    }
+    resolved
+}
+
+/// Check a `KEY=VALUE` environment entry such as `connect --env` takes. The key must be
+/// a valid variable name; the value may be empty and may contain `=`.
+pub fn validate_env_entry(entry: &str) -> Result<()> {
+    let (key, _) = entry.split_once('=').ok_or_else(|| {
+        anyhow!(
+            "Invalid environment variable '{}': expected KEY=VALUE",
+            entry
+        )
+    })?;
+
+    let valid_key = key
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid_key {
+        return Err(anyhow!(
+            "Invalid environment variable name '{}': use letters, digits and underscores, \
+             not starting with a digit",
+            key
+        ));
+    }
+
+    Ok(())
+}
+
+/// Bind mounts for the `--volume` specs recorded on an agent
+fn volume_mounts(volumes: &[String]) -> Result<Vec<Mount>> {
+    volumes
+        .iter()
+        .map(|spec| VolumeSpec::parse(spec).map(|volume| volume.mount()))
+        .collect()
+}
+
+/// Build the host configuration for an agent container
+fn build_host_config(options: &ContainerOptions, mounts: Vec<Mount>) -> Result<HostConfig> {
+    let mut host_config = HostConfig {
+        privileged: Some(options.privileged),
+        cap_add: (!options.cap_add.is_empty()).then(|| options.cap_add.clone()),
+        mounts: Some(mounts),
+        network_mode: options.network.clone(),
+        ..Default::default()
+    };
+
+    if let Some(restart_policy) = &options.restart_policy {
+        host_config.restart_policy = Some(RestartPolicy {
+            name: Some(parse_restart_policy(restart_policy)?),
+            maximum_retry_count: None,
+        });
+    }
+
+    if let Some(gpus) = &options.gpus {
+        host_config.device_requests = Some(vec![parse_gpus(gpus)?]);
+    }
+
+    if options.read_only {
+        host_config.readonly_rootfs = Some(true);
+        host_config.tmpfs = Some(read_only_tmpfs());
+    }
+
+    // Set resource limits if provided
+    if let Some(memory_limit) = &options.memory {
+        let memory_bytes = parse_memory_limit(memory_limit)?;
+        host_config.memory = Some(memory_bytes);
+    }
+
+    validate_memory_swap(options.memory.as_deref(), options.memory_swap.as_deref())?;
+    if let Some(memory_swap) = &options.memory_swap {
+        host_config.memory_swap = Some(parse_memory_limit(memory_swap)?);
+    }
+
+    if let Some(memory_reservation) = &options.memory_reservation {
+        host_config.memory_reservation = Some(parse_memory_limit(memory_reservation)?);
+    }
+
+    if let Some(cpu_limit) = &options.cpus {
+        host_config.cpu_quota = Some(parse_cpu_quota(cpu_limit)?);
+        host_config.cpu_period = Some(CPU_PERIOD);
+    }
+
+    Ok(host_config)
+}
+
+/// The resource limits in `limits` as a container update, matching `build_host_config`
+fn resource_update(limits: &ContainerOptions) -> Result<UpdateContainerOptions<String>> {
+    validate_memory_swap(limits.memory.as_deref(), limits.memory_swap.as_deref())?;
+    let parse = |limit: &Option<String>| limit.as_deref().map(parse_memory_limit).transpose();
+
+    let mut update = UpdateContainerOptions::<String> {
+        memory: parse(&limits.memory)?,
+        memory_swap: parse(&limits.memory_swap)?,
+        memory_reservation: parse(&limits.memory_reservation)?,
+        ..Default::default()
+    };
+
+    if let Some(cpu_limit) = &limits.cpus {
+        update.cpu_quota = Some(parse_cpu_quota(cpu_limit)?);
+        update.cpu_period = Some(CPU_PERIOD);
+    }
+
+    Ok(update)
+}
+
+/// Options for the create call; the platform selects the image variant (e.g. amd64 on Apple Silicon)
+fn create_container_options(
+    container_name: &str,
+    options: &ContainerOptions,
+) -> CreateContainerOptions<String> {
+    CreateContainerOptions {
+        name: container_name.to_string(),
+        platform: options.platform.clone(),
+    }
+}
+
+/// Build a Docker health check that runs the given command through the shell
+fn build_healthcheck(command: &str) -> HealthConfig {
+    const SECOND: i64 = 1_000_000_000;
+
+    HealthConfig {
+        test: Some(vec!["CMD-SHELL".to_string(), command.to_string()]),
+        interval: Some(5 * SECOND),
+        timeout: Some(10 * SECOND),
+        retries: Some(3),
+        ..Default::default()
+    }
+}
+
+/// CFS scheduler period used together with the CPU quota
+const CPU_PERIOD: i64 = 100000;
+
+fn parse_cpu_quota(cpus: &str) -> Result<i64> {
+    let cpus = parse_cpus(cpus)?;
+    Ok((cpus * CPU_PERIOD as f64) as i64)
+}
+
+/// Parse a CPU limit such as `1.5`; must be a positive number
+pub fn parse_cpus(cpus: &str) -> Result<f64> {
+    match cpus.trim().parse::<f64>() {
+        Ok(value) if value.is_finite() && value > 0.0 => Ok(value),
+        _ => Err(anyhow!(
+            "Invalid CPU value '{}': must be a positive number like 0.5, 1 or 2",
+            cpus
+        )),
+    }
+}
+
+/// Check a container platform such as `linux/amd64`, `linux/arm64` or `linux/arm/v7`
+pub fn validate_platform(platform: &str) -> Result<()> {
+    let parts: Vec<&str> = platform.split('/').collect();
+    let well_formed = matches!(parts.len(), 2 | 3)
+        && parts[0] == "linux"
+        && parts.iter().all(|part| {
+            !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        });
+
+    if well_formed {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "Invalid platform '{}': use os/arch, e.g. linux/amd64 or linux/arm64",
+            platform
+        ))
+    }
+}
+
+/// Parse a Docker restart policy name: `no`, `on-failure`, `unless-stopped` or `always`
+pub fn parse_restart_policy(name: &str) -> Result<RestartPolicyNameEnum> {
+    match name {
+        "no" => Ok(RestartPolicyNameEnum::NO),
+        "on-failure" => Ok(RestartPolicyNameEnum::ON_FAILURE),
+        "unless-stopped" => Ok(RestartPolicyNameEnum::UNLESS_STOPPED),
+        "always" => Ok(RestartPolicyNameEnum::ALWAYS),
+        _ => Err(anyhow!(
+            "Invalid restart policy '{}': use no, on-failure, unless-stopped or always",
+            name
+        )),
+    }
+}
+
+/// Parse a GPU request as `docker run --gpus` takes it: `all`, a number of GPUs, or
+/// `device=<id>[,<id>...]` for specific GPUs by index or UUID
+pub fn parse_gpus(gpus: &str) -> Result<DeviceRequest> {
+    let invalid = || {
+        anyhow!(
+            "Invalid GPU request '{}': use all, a number of GPUs like 2, or device=0,1",
+            gpus
+        )
+    };
+
+    let (count, device_ids) = if gpus == "all" {
+        // Docker's way of asking for every GPU
+        (Some(-1), None)
+    } else if let Some(ids) = gpus.strip_prefix("device=") {
+        let ids: Vec<String> = ids.split(',').map(|id| id.trim().to_string()).collect();
+        if ids.iter().any(String::is_empty) {
+            return Err(invalid());
+        }
+        (None, Some(ids))
+    } else {
+        match gpus.parse::<i64>() {
+            Ok(count) if count > 0 => (Some(count), None),
+            _ => return Err(invalid()),
+        }
+    };
+
+    Ok(DeviceRequest {
+        driver: Some("nvidia".to_string()),
+        count,
+        device_ids,
+        capabilities: Some(vec![vec!["gpu".to_string()]]),
+        options: None,
+    })
+}
+
+/// Parse a memory limit such as `2g`, `1024m` or `512k` into bytes
+pub fn parse_memory_limit(memory: &str) -> Result<i64> {
+    let invalid = || {
+        anyhow!(
+            "Invalid memory value '{}': use a positive size like 2g, 1024m or 512k",
+            memory
+        )
+    };
+
+    let memory_lower = memory.trim().to_lowercase();
+    let multiplier: i64 = if memory_lower.ends_with("g") {
+        1_073_741_824
+    } else if memory_lower.ends_with("m") {
+        1_048_576
+    } else if memory_lower.ends_with("k") {
+        1_024
+    } else {
+        return Err(invalid());
+    };
+
+    let number_part = &memory_lower[..memory_lower.len() - 1];
+    let number: i64 = number_part.parse().map_err(|_| invalid())?;
+    if number <= 0 {
+        return Err(invalid());
+    }
+
+    number.checked_mul(multiplier).ok_or_else(invalid)
+}
+
+/// Check a swap limit against the memory limit. Docker's swap limit is the total of
+/// memory and swap, so it can't be smaller than the memory limit.
+pub fn validate_memory_swap(memory: Option<&str>, memory_swap: Option<&str>) -> Result<()> {
+    let (Some(memory), Some(memory_swap)) = (memory, memory_swap) else {
+        return Ok(());
+    };
+
+    if parse_memory_limit(memory_swap)? < parse_memory_limit(memory)? {
+        return Err(anyhow!(
+            "Memory swap limit '{}' is smaller than the memory limit '{}': it counts memory \
+             plus swap, so it must be at least as large",
+            memory_swap,
+            memory
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn owned(value: &str) -> Option<String> {
+        Some(value.to_string())
+    }
+
+    #[test]
+    fn test_connection_method_without_docker_host() {
+        assert_eq!(
+            connection_method(None, owned("1"), owned("/certs")),
+            ConnectionMethod::LocalSocket
+        );
+        assert_eq!(
+            connection_method(owned(""), None, None),
+            ConnectionMethod::LocalSocket
+        );
+    }
+
+    #[test]
+    fn test_connection_method_plain_hosts_use_defaults() {
+        assert_eq!(
+            connection_method(owned("unix:///var/run/docker.sock"), owned("1"), None),
+            ConnectionMethod::DockerHostDefaults {
+                host: "unix:///var/run/docker.sock".to_string()
+            }
+        );
+        assert_eq!(
+            connection_method(owned("tcp://remote:2375"), owned("0"), None),
+            ConnectionMethod::DockerHostDefaults {
+                host: "tcp://remote:2375".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_connection_method_tls() {
+        assert_eq!(
+            connection_method(owned("tcp://remote:2376"), owned("1"), owned("/certs")),
+            ConnectionMethod::Tls {
+                host: "tcp://remote:2376".to_string(),
+                cert_path: PathBuf::from("/certs"),
+            }
+        );
+
+        // A cert path alone is enough to request TLS
+        assert_eq!(
+            connection_method(owned("tcp://remote:2376"), None, owned("/certs")),
+            ConnectionMethod::Tls {
+                host: "tcp://remote:2376".to_string(),
+                cert_path: PathBuf::from("/certs"),
+            }
+        );
+    }
+
+    #[test]
+    fn test_tls_files_missing_certs() {
+        let cert_dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(cert_dir.path().join("key.pem"), "key").unwrap();
+
+        let err = tls_files(cert_dir.path()).unwrap_err();
+        assert!(err.to_string().contains("cert.pem"));
+
+        std::fs::write(cert_dir.path().join("cert.pem"), "cert").unwrap();
+        std::fs::write(cert_dir.path().join("ca.pem"), "ca").unwrap();
+        assert!(tls_files(cert_dir.path()).is_ok());
+    }
+
+    #[test]
+    fn test_api_version_supported() {
+        assert!(api_version_supported("1.41", "1.41"));
+        assert!(api_version_supported("1.45", "1.41"));
+        assert!(api_version_supported("2.0", "1.41"));
+        assert!(!api_version_supported("1.40", "1.41"));
+        assert!(!api_version_supported("1.9", "1.41"));
+        assert!(!api_version_supported("garbage", "1.41"));
+    }
+
+    #[test]
+    fn test_environment_check_display() {
+        assert_eq!(
+            EnvironmentCheck::pass("Docker daemon", "reachable (Docker 24.0.7)").to_string(),
+            "✓ Docker daemon: reachable (Docker 24.0.7)"
+        );
+        assert_eq!(
+            EnvironmentCheck::fail("Image", "crowdcontrol:latest can't be pulled").to_string(),
+            "✗ Image: crowdcontrol:latest can't be pulled"
+        );
+    }
+
+    #[test]
+    fn test_docker_unreachable_errors() {
+        let docker_io = |kind| {
+            anyhow::Error::from(bollard::errors::Error::IOError {
+                err: io::Error::from(kind),
+            })
+        };
+
+        let refused = docker_io(io::ErrorKind::ConnectionRefused).context("Failed to list");
+        assert!(is_docker_unreachable(&refused));
+        let explained = explain_docker_unavailable(refused);
+        assert!(matches!(
+            CrowdControlError::find(&explained),
+            Some(CrowdControlError::DockerUnavailable(message))
+                if message.starts_with("Cannot connect to the Docker daemon.")
+        ));
+
+        // The daemon answering with an error, or a missing file outside Docker, isn't it
+        assert!(!is_docker_unreachable(&docker_io(
+            io::ErrorKind::InvalidData
+        )));
+        assert!(!is_docker_unreachable(&anyhow::Error::from(
+            io::Error::from(io::ErrorKind::NotFound)
+        )));
+
+        // Errors that already say what went wrong keep their meaning
+        let not_found = anyhow::Error::from(CrowdControlError::AgentNotFound("web".to_string()));
+        let not_found = explain_docker_unavailable(not_found);
+        assert!(matches!(
+            CrowdControlError::find(&not_found),
+            Some(CrowdControlError::AgentNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_explain_platform_mismatch() {
+        let mismatch = anyhow!(
+            "Failed to pull image crowdcontrol:latest: no matching manifest for linux/arm64/v8 \
+             in the manifest list entries"
+        );
+        assert!(is_platform_mismatch(&mismatch));
+        let explained = explain_platform_mismatch(mismatch);
+        assert!(explained.to_string().contains("--platform linux/amd64"));
+        // The original error stays in the chain
+        assert!(format!("{:#}", explained).contains("no matching manifest"));
+
+        let create = anyhow::Error::from(bollard::errors::Error::DockerResponseServerError {
+            status_code: 500,
+            message: "image with reference crowdcontrol:latest was found but does not match \
+                      the specified platform: wanted linux/amd64, actual: linux/arm64"
+                .to_string(),
+        })
+        .context("Failed to create container");
+        assert!(is_platform_mismatch(&create));
+
+        // Anything else is left as it was
+        let other = explain_platform_mismatch(anyhow!("Failed to pull image x: unauthorized"));
+        assert_eq!(other.to_string(), "Failed to pull image x: unauthorized");
+        assert!(!is_platform_mismatch(&other));
+    }
+
+    #[tokio::test]
+    async fn test_with_timeout_gives_up() {
+        let timeout = Duration::from_millis(10);
+        let result: Result<()> = with_timeout("the image", timeout, async {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            Ok(())
+        })
+        .await;
+        assert_eq!(
+            CrowdControlError::find(&result.unwrap_err()),
+            Some(&CrowdControlError::Timeout {
+                waiting_for: "the image".to_string(),
+                timeout,
+            })
+        );
+
+        let finished = with_timeout("the image", timeout, async { Ok(42) }).await;
+        assert_eq!(finished.unwrap(), 42);
+    }
+
+    #[test]
+    fn test_validate_env_entry() {
+        assert!(validate_env_entry("ANTHROPIC_MODEL=claude-sonnet").is_ok());
+        assert!(validate_env_entry("_PRIVATE=1").is_ok());
+        assert!(validate_env_entry("EMPTY=").is_ok());
+        assert!(validate_env_entry("URL=https://example.com/?a=b").is_ok());
+
+        assert!(validate_env_entry("NO_VALUE").is_err());
+        assert!(validate_env_entry("=value").is_err());
+        assert!(validate_env_entry("1ST=value").is_err());
+        assert!(validate_env_entry("MY-VAR=value").is_err());
+    }
+
+    #[test]
+    fn test_container_labels() {
+        let options = ContainerOptions {
+            tags: vec!["frontend".to_string()],
+            repository: Some("https://github.com/example/app.git".to_string()),
+            branch: Some("main".to_string()),
+            ..Default::default()
+        };
+        let labels = container_labels("myapp", &options);
+        assert_eq!(labels.get("app").map(String::as_str), Some("crowdcontrol"));
+        assert_eq!(labels.get(NAME_LABEL).map(String::as_str), Some("myapp"));
+        assert_eq!(
+            labels.get(REPOSITORY_LABEL).map(String::as_str),
+            Some("https://github.com/example/app.git")
+        );
+        assert_eq!(labels.get(BRANCH_LABEL).map(String::as_str), Some("main"));
+        assert_eq!(tags_from_labels(&labels), vec!["frontend"]);
+        assert!(is_agent_container(Some(&labels), "myapp"));
+        assert!(!is_agent_container(Some(&labels), "other"));
+        assert!(!is_agent_container(None, "myapp"));
+
+        // Unknown values are left out rather than set empty
+        let labels = container_labels("myapp", &ContainerOptions::default());
+        assert!(!labels.contains_key(REPOSITORY_LABEL));
+        assert!(!labels.contains_key(BRANCH_LABEL));
+    }
+
+    #[test]
+    fn test_tag_labels_round_trip() {
+        let tags = vec!["frontend".to_string(), "client-a".to_string()];
+        let mut labels = tag_labels(&tags);
+        assert_eq!(
+            labels.get("crowdcontrol.tag.frontend").map(String::as_str),
+            Some("true")
+        );
+
+        // Unrelated labels are ignored when reading tags back
+        labels.insert("app".to_string(), "crowdcontrol".to_string());
+        assert_eq!(tags_from_labels(&labels), vec!["client-a", "frontend"]);
+    }
+
+    fn server_error(status_code: u16) -> bollard::errors::Error {
+        bollard::errors::Error::DockerResponseServerError {
+            status_code,
+            message: "test error".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_with_retry_recovers_from_transient_errors() {
+        let attempts = std::cell::Cell::new(0);
+
+        let result = with_retry("test call", || {
+            attempts.set(attempts.get() + 1);
+            let attempt = attempts.get();
+            async move {
+                if attempt < 3 {
+                    Err(server_error(500))
+                } else {
+                    Ok("ok")
+                }
+            }
+        })
+        .await;
+
+        assert_eq!(result.unwrap(), "ok");
+        assert_eq!(attempts.get(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_with_retry_gives_up_after_max_attempts() {
+        let attempts = std::cell::Cell::new(0);
+
+        let result: Result<(), _> = with_retry("test call", || {
+            attempts.set(attempts.get() + 1);
+            async { Err(server_error(503)) }
+        })
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(attempts.get(), RETRY_ATTEMPTS);
+    }
+
+    #[tokio::test]
+    async fn test_with_retry_does_not_retry_client_errors() {
+        for status_code in [404, 409] {
+            let attempts = std::cell::Cell::new(0);
+
+            let result: Result<(), _> = with_retry("test call", || {
+                attempts.set(attempts.get() + 1);
+                async move { Err(server_error(status_code)) }
+            })
+            .await;
+
+            assert!(result.is_err());
+            assert_eq!(attempts.get(), 1);
+        }
+    }
+
+    #[test]
+    fn test_stop_options() {
+        assert_eq!(stop_options(false, DEFAULT_STOP_TIMEOUT_SECS).t, 5);
+        assert_eq!(stop_options(false, 30).t, 30);
+        assert_eq!(stop_options(false, 0).t, 0);
+
+        // Timeouts beyond what Docker accepts are clamped rather than wrapping negative
+        assert_eq!(stop_options(false, u64::MAX).t, i64::MAX);
+
+        // Force always kills immediately, regardless of the timeout
+        assert_eq!(stop_options(true, 30).t, 0);
+    }
+
+    #[test]
+    fn test_build_healthcheck_uses_shell_form() {
+        let healthcheck = build_healthcheck("true");
+        assert_eq!(
+            healthcheck.test,
+            Some(vec!["CMD-SHELL".to_string(), "true".to_string()])
+        );
+        assert_eq!(healthcheck.interval, Some(5_000_000_000));
+        assert_eq!(healthcheck.retries, Some(3));
+    }
+
+    #[test]
+    fn test_host_config_network_mode() {
+        let host_config = build_host_config(&ContainerOptions::default(), Vec::new()).unwrap();
+        assert_eq!(host_config.network_mode, None);
+
+        for network in ["none", "bridge", "host", "my-agents"] {
+            let options = ContainerOptions {
+                network: owned(network),
+                ..Default::default()
+            };
+            let host_config = build_host_config(&options, Vec::new()).unwrap();
+            assert_eq!(host_config.network_mode.as_deref(), Some(network));
+        }
+    }
+
+    #[test]
+    fn test_host_config_privilege() {
+        // Containers are unprivileged unless explicitly requested
+        let host_config = build_host_config(&ContainerOptions::default(), Vec::new()).unwrap();
+        assert_eq!(host_config.privileged, Some(false));
+        assert_eq!(host_config.cap_add, None);
+
+        let options = ContainerOptions {
+            privileged: true,
+            ..Default::default()
+        };
+        let host_config = build_host_config(&options, Vec::new()).unwrap();
+        assert_eq!(host_config.privileged, Some(true));
+
+        let options = ContainerOptions {
+            cap_add: vec!["NET_ADMIN".to_string(), "SYS_PTRACE".to_string()],
+            ..Default::default()
+        };
+        let host_config = build_host_config(&options, Vec::new()).unwrap();
+        assert_eq!(host_config.privileged, Some(false));
+        assert_eq!(
+            host_config.cap_add,
+            Some(vec!["NET_ADMIN".to_string(), "SYS_PTRACE".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_cpu_percent_from_snapshots() {
+        let previous = CpuSample {
+            total_usage: 1_000_000_000,
+            system_usage: 100_000_000_000,
+            online_cpus: 4,
+        };
+        // The container used 0.5s of CPU while the host's 4 CPUs ran 4s in total
+        let current = CpuSample {
+            total_usage: 1_500_000_000,
+            system_usage: 104_000_000_000,
+            online_cpus: 4,
+        };
+        assert!((cpu_percent(&previous, &current) - 50.0).abs() < 1e-9);
+
+        // No elapsed time, or counters that went backwards after a restart, read as idle
+        assert_eq!(cpu_percent(&current, &current), 0.0);
+        assert_eq!(cpu_percent(&current, &previous), 0.0);
+    }
+
+    #[test]
+    fn test_short_container_id() {
+        assert_eq!(
+            short_container_id("0123456789abcdef0123456789abcdef"),
+            "0123456789ab"
+        );
+        assert_eq!(short_container_id("abc123"), "abc123");
+    }
+
+    #[test]
+    fn test_status_from_docker_state() {
+        let cases = [
+            ("created", AgentStatus::Created),
+            ("running", AgentStatus::Running),
+            ("paused", AgentStatus::Paused),
+            ("restarting", AgentStatus::Restarting),
+            ("exited", AgentStatus::Stopped),
+            ("removing", AgentStatus::Error),
+            ("dead", AgentStatus::Error),
+            ("unknown", AgentStatus::Stopped),
+        ];
+        for (state, expected) in cases {
+            assert_eq!(status_from_docker_state(state, None), expected, "{}", state);
+        }
+
+        // Only a running container waiting on its health check is starting
+        let starting = Some(&HealthStatus::Starting);
+        assert_eq!(
+            status_from_docker_state("running", starting),
+            AgentStatus::Starting
+        );
+        assert_eq!(
+            status_from_docker_state("running", Some(&HealthStatus::Healthy)),
+            AgentStatus::Running
+        );
+        assert_eq!(
+            status_from_docker_state("running", Some(&HealthStatus::Unhealthy)),
+            AgentStatus::Running
+        );
+        assert_eq!(
+            status_from_docker_state("exited", starting),
+            AgentStatus::Stopped
+        );
+    }
+
+    #[test]
+    fn test_status_of_reads_health_from_status_text() {
+        let container = |state: &str, status: &str| bollard::models::ContainerSummary {
+            state: Some(state.to_string()),
+            status: Some(status.to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            status_of(&container("running", "Up 3 seconds (health: starting)")),
+            AgentStatus::Starting
+        );
+        assert_eq!(
+            status_of(&container("running", "Up 2 minutes (healthy)")),
+            AgentStatus::Running
+        );
+        assert_eq!(
+            status_of(&container("running", "Up 2 minutes")),
+            AgentStatus::Running
+        );
+        assert_eq!(
+            status_of(&container("restarting", "Restarting (1) 5 seconds ago")),
+            AgentStatus::Restarting
+        );
+        assert_eq!(
+            health_from_status_text("Up 10 minutes (unhealthy)"),
+            Some(HealthStatus::Unhealthy)
+        );
+        assert_eq!(status_of(&Default::default()), AgentStatus::Stopped);
+    }
+
+    #[test]
+    fn test_starting_agents_count_as_running() {
+        assert!(AgentStatus::Running.is_running());
+        assert!(AgentStatus::Starting.is_running());
+        assert!(!AgentStatus::Restarting.is_running());
+        assert!(!AgentStatus::Paused.is_running());
+
+        assert_eq!(
+            follow_action(Some(&AgentStatus::Starting), 5, 5),
+            FollowAction::Reconnect
+        );
+        assert_eq!(
+            follow_action(Some(&AgentStatus::Restarting), 0, 5),
+            FollowAction::Wait
+        );
+    }
+
+    #[test]
+    fn test_format_ports() {
+        use bollard::models::{Port, PortTypeEnum};
+
+        let port = |ip: Option<&str>, private_port, public_port, typ| Port {
+            ip: ip.map(str::to_string),
+            private_port,
+            public_port,
+            typ: Some(typ),
+        };
+        let ports = vec![
+            port(Some("::"), 80, Some(8080), PortTypeEnum::TCP),
+            port(Some("0.0.0.0"), 80, Some(8080), PortTypeEnum::TCP),
+            port(None, 9000, None, PortTypeEnum::TCP),
+            port(None, 53, None, PortTypeEnum::UDP),
+        ];
+
+        assert_eq!(
+            format_ports(&ports),
+            "53/udp, 0.0.0.0:8080->80/tcp, 9000/tcp"
+        );
+        assert_eq!(format_ports(&[]), "");
+    }
+
+    #[test]
+    fn test_resolve_env_passthrough_skips_unset() {
+        let host_env: HashMap<&str, &str> =
+            HashMap::from([("GITHUB_TOKEN", "ghp_secret"), ("EMPTY", "")]);
+        let names: Vec<String> = ["GITHUB_TOKEN", "NPM_TOKEN", "EMPTY"]
+            .iter()
+            .map(|name| name.to_string())
+            .collect();
+
+        let resolved = resolve_env_passthrough(&names, |name| {
+            host_env.get(name).map(|value| value.to_string())
+        });
+
+        // Set-but-empty variables are still passed through
+        assert_eq!(resolved.vars, vec!["GITHUB_TOKEN=ghp_secret", "EMPTY="]);
+        assert_eq!(resolved.missing, vec!["NPM_TOKEN"]);
+    }
+
+    #[test]
+    fn test_volume_mounts() {
+        let host = tempfile::TempDir::new().unwrap();
+        let cargo = host.path().join("cargo");
+        let models = host.path().join("models");
+        std::fs::create_dir(&cargo).unwrap();
+        std::fs::create_dir(&models).unwrap();
+
+        let volumes = vec![
+            format!("{}:/home/developer/.cargo", cargo.display()),
+            format!("{}:/models:ro", models.display()),
+        ];
+        let mounts = volume_mounts(&volumes).unwrap();
+
+        assert_eq!(mounts.len(), 2);
+        assert_eq!(mounts[0].target.as_deref(), Some("/home/developer/.cargo"));
+        assert_eq!(
+            mounts[0].source,
+            Some(cargo.canonicalize().unwrap().to_string_lossy().to_string())
+        );
+        assert_eq!(mounts[0].read_only, Some(false));
+        assert_eq!(mounts[1].target.as_deref(), Some("/models"));
+        assert_eq!(mounts[1].read_only, Some(true));
+        assert!(mounts
+            .iter()
+            .all(|mount| mount.typ == Some(MountTypeEnum::BIND)));
+    }
+
+    #[test]
+    fn test_volume_spec_validation() {
+        let host = tempfile::TempDir::new().unwrap();
+        let dir = host.path().display();
+
+        assert!(VolumeSpec::parse(&format!("{}:/data:rw", dir)).is_ok());
+        for spec in [
+            format!("{}/missing:/data", dir),
+            format!("{}:relative", dir),
+            format!("{}:/data:rx", dir),
+            dir.to_string(),
+            ":/data".to_string(),
+        ] {
+            assert!(VolumeSpec::parse(&spec).is_err(), "{}", spec);
+        }
+
+        // The stored form is canonical and round-trips
+        let spec = VolumeSpec::parse(&format!("{}:/data:ro", dir)).unwrap();
+        assert_eq!(VolumeSpec::parse(&spec.to_string()).unwrap(), spec);
+    }
+
+    #[test]
+    fn test_container_mounts_without_claude_config() {
+        let home = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir(home.path().join(".claude")).unwrap();
+        std::fs::write(home.path().join(".claude.json"), "{}").unwrap();
+        let workspace = Path::new("/tmp/workspaces/agent");
+
+        let targets = |mounts: Vec<Mount>| -> Vec<String> {
+            mounts.into_iter().filter_map(|m| m.target).collect()
+        };
+
+        assert_eq!(
+            targets(container_mounts(workspace, false, Some(home.path()))),
+            vec![
+                "/workspace",
+                "/mnt/claude-config/.claude",
+                "/mnt/claude-config/.claude.json"
+            ]
+        );
+
+        // With --no-mount-claude only the workspace is mounted
+        assert_eq!(
+            targets(container_mounts(workspace, false, None)),
+            vec!["/workspace"]
+        );
+    }
+
+    #[test]
+    fn test_container_mounts_readonly_workspace() {
+        let workspace = Path::new("/tmp/workspaces/agent");
+
+        assert_eq!(
+            container_mounts(workspace, false, None)[0].read_only,
+            Some(false)
+        );
+        assert_eq!(
+            container_mounts(workspace, true, None)[0].read_only,
+            Some(true)
+        );
+    }
+
+    #[test]
+    fn test_host_config_read_only() {
+        let host_config = build_host_config(&ContainerOptions::default(), Vec::new()).unwrap();
+        assert_eq!(host_config.readonly_rootfs, None);
+        assert_eq!(host_config.tmpfs, None);
+
+        let options = ContainerOptions {
+            read_only: true,
+            ..Default::default()
+        };
+        let host_config = build_host_config(&options, Vec::new()).unwrap();
+        assert_eq!(host_config.readonly_rootfs, Some(true));
+
+        let tmpfs = host_config.tmpfs.unwrap();
+        assert!(tmpfs.contains_key("/tmp"));
+        assert!(tmpfs.contains_key("/home/developer/.claude"));
+    }
+
+    #[test]
+    fn test_host_config_restart_policy() {
+        let host_config = build_host_config(&ContainerOptions::default(), Vec::new()).unwrap();
+        assert_eq!(host_config.restart_policy, None);
+
+        for (name, expected) in [
+            ("no", RestartPolicyNameEnum::NO),
+            ("on-failure", RestartPolicyNameEnum::ON_FAILURE),
+            ("unless-stopped", RestartPolicyNameEnum::UNLESS_STOPPED),
+            ("always", RestartPolicyNameEnum::ALWAYS),
+        ] {
+            let options = ContainerOptions {
+                restart_policy: Some(name.to_string()),
+                ..Default::default()
+            };
+            let host_config = build_host_config(&options, Vec::new()).unwrap();
+            assert_eq!(host_config.restart_policy.unwrap().name, Some(expected));
+        }
+
+        let options = ContainerOptions {
+            restart_policy: Some("sometimes".to_string()),
+            ..Default::default()
+        };
+        assert!(build_host_config(&options, Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_host_config_gpus() {
+        let host_config = build_host_config(&ContainerOptions::default(), Vec::new()).unwrap();
+        assert_eq!(host_config.device_requests, None);
+
+        let options = ContainerOptions {
+            gpus: Some("all".to_string()),
+            ..Default::default()
+        };
+        let host_config = build_host_config(&options, Vec::new()).unwrap();
+        assert_eq!(
+            host_config.device_requests,
+            Some(vec![DeviceRequest {
+                driver: Some("nvidia".to_string()),
+                count: Some(-1),
+                device_ids: None,
+                capabilities: Some(vec![vec!["gpu".to_string()]]),
+                options: None,
+            }])
+        );
+
+        let two = parse_gpus("2").unwrap();
+        assert_eq!(two.count, Some(2));
+        assert_eq!(two.device_ids, None);
+
+        let devices = parse_gpus("device=0,GPU-3a4b").unwrap();
+        assert_eq!(devices.count, None);
+        assert_eq!(
+            devices.device_ids,
+            Some(vec!["0".to_string(), "GPU-3a4b".to_string()])
+        );
+
+        for invalid in ["", "0", "-1", "some", "device=", "device=0,"] {
+            assert!(
+                parse_gpus(invalid).is_err(),
+                "{:?} should be rejected",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn test_explain_gpu_unavailable() {
+        let start = anyhow::Error::from(bollard::errors::Error::DockerResponseServerError {
+            status_code: 500,
+            message: "could not select device driver \"nvidia\" with capabilities: [[gpu]]"
+                .to_string(),
+        })
+        .context("Failed to start container");
+        let explained = explain_gpu_unavailable(start);
+        assert!(explained.to_string().contains("NVIDIA Container Toolkit"));
+        assert!(format!("{:#}", explained).contains("could not select device driver"));
+
+        let other = explain_gpu_unavailable(anyhow!("Failed to start container: port in use"));
+        assert_eq!(other.to_string(), "Failed to start container: port in use");
+    }
+
+    #[test]
+    fn test_claude_credentials_writable_when_read_only() {
+        // The refresh script must be able to write credentials under a read-only root
+        let credentials = Path::new(CLAUDE_CREDENTIALS_PATH);
+        assert!(read_only_tmpfs()
+            .keys()
+            .any(|path| credentials.starts_with(path)));
+    }
+
+    #[test]
+    fn test_parse_memory_limit() {
+        assert_eq!(parse_memory_limit("2g").unwrap(), 2 * 1_073_741_824);
+        assert_eq!(parse_memory_limit("1024M").unwrap(), 1024 * 1_048_576);
+        assert_eq!(parse_memory_limit("512k").unwrap(), 512 * 1_024);
+
+        for value in ["notaunit", "2", "g", "-1g", "0m", "1.5g"] {
+            assert!(
+                parse_memory_limit(value).is_err(),
+                "{} should be rejected",
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn test_host_config_memory_swap_and_reservation() {
+        let host_config = build_host_config(&ContainerOptions::default(), Vec::new()).unwrap();
+        assert_eq!(host_config.memory_swap, None);
+        assert_eq!(host_config.memory_reservation, None);
+
+        let options = ContainerOptions {
+            memory: owned("2g"),
+            memory_swap: owned("4g"),
+            memory_reservation: owned("1g"),
+            ..Default::default()
+        };
+        let host_config = build_host_config(&options, Vec::new()).unwrap();
+        assert_eq!(host_config.memory, Some(2 * 1_073_741_824));
+        assert_eq!(host_config.memory_swap, Some(4 * 1_073_741_824));
+        assert_eq!(host_config.memory_reservation, Some(1_073_741_824));
+
+        // Each is optional without --memory
+        let options = ContainerOptions {
+            memory_reservation: owned("512m"),
+            ..Default::default()
+        };
+        let host_config = build_host_config(&options, Vec::new()).unwrap();
+        assert_eq!(host_config.memory, None);
+        assert_eq!(host_config.memory_reservation, Some(512 * 1_048_576));
+
+        let options = ContainerOptions {
+            memory: owned("2g"),
+            memory_swap: owned("1g"),
+            ..Default::default()
+        };
+        assert!(build_host_config(&options, Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_resource_update() {
+        let update = resource_update(&ContainerOptions::default()).unwrap();
+        assert_eq!(update.memory, None);
+        assert_eq!(update.memory_swap, None);
+        assert_eq!(update.memory_reservation, None);
+        assert_eq!(update.cpu_quota, None);
+
+        let limits = ContainerOptions {
+            memory: owned("2g"),
+            memory_swap: owned("4g"),
+            memory_reservation: owned("1g"),
+            cpus: owned("1.5"),
+            ..Default::default()
+        };
+        let update = resource_update(&limits).unwrap();
+        assert_eq!(update.memory, Some(2 * 1_073_741_824));
+        assert_eq!(update.memory_swap, Some(4 * 1_073_741_824));
+        assert_eq!(update.memory_reservation, Some(1_073_741_824));
+        assert_eq!(update.cpu_quota, Some(150_000));
+        assert_eq!(update.cpu_period, Some(CPU_PERIOD));
+
+        let limits = ContainerOptions {
+            memory: owned("2g"),
+            memory_swap: owned("1g"),
+            ..Default::default()
+        };
+        assert!(resource_update(&limits).is_err());
+    }
+
+    #[test]
+    fn test_validate_memory_swap() {
+        assert!(validate_memory_swap(Some("2g"), Some("4g")).is_ok());
+        assert!(validate_memory_swap(Some("2g"), Some("2048m")).is_ok());
+        assert!(validate_memory_swap(None, Some("1g")).is_ok());
+        assert!(validate_memory_swap(Some("2g"), None).is_ok());
+
+        let err = validate_memory_swap(Some("2g"), Some("1g")).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Memory swap limit '1g' is smaller than the memory limit '2g'"));
+        assert!(validate_memory_swap(Some("2g"), Some("lots")).is_err());
+    }
+
+    #[test]
+    fn test_create_container_options_platform() {
+        let options = ContainerOptions {
+            platform: owned("linux/amd64"),
+            ..Default::default()
+        };
+        let create = create_container_options("crowdcontrol-test", &options);
+        assert_eq!(create.name, "crowdcontrol-test");
+        assert_eq!(create.platform.as_deref(), Some("linux/amd64"));
+
+        // Without --platform Docker picks the native platform
+        let create = create_container_options("crowdcontrol-test", &ContainerOptions::default());
+        assert_eq!(create.platform, None);
+    }
+
+    #[test]
+    fn test_validate_platform() {
+        for platform in [
+            "linux/amd64",
+            "linux/arm64",
+            "linux/arm/v7",
+            "linux/arm64/v8",
+        ] {
+            assert!(validate_platform(platform).is_ok(), "{}", platform);
+        }
+        for platform in [
+            "amd64",
+            "linux",
+            "linux/",
+            "windows/amd64",
+            "linux/amd64/v1/x",
+        ] {
+            assert!(validate_platform(platform).is_err(), "{}", platform);
+        }
+    }
+
+    #[test]
+    fn test_parse_cpus() {
+        assert_eq!(parse_cpus("1.5").unwrap(), 1.5);
+        assert_eq!(parse_cpus("2").unwrap(), 2.0);
+
+        for value in ["-1", "0", "abc", "NaN", "inf", ""] {
+            assert!(parse_cpus(value).is_err(), "{} should be rejected", value);
+        }
+    }
+
+    #[test]
+    fn test_line_filter() {
+        let filter = LineFilter::new("^ERROR|panicked", false).unwrap();
+        assert!(filter.keeps("ERROR: connection refused\n", false));
+        assert!(filter.keeps("thread 'main' panicked at src/main.rs\n", false));
+        assert!(!filter.keeps("INFO: listening on :3000\n", false));
+
+        // Docker's timestamp doesn't get in the way of anchored patterns
+        assert!(filter.keeps(
+            "2024-05-01T12:00:00.000000000Z ERROR: connection refused\n",
+            true
+        ));
+        assert!(!filter.keeps(
+            "2024-05-01T12:00:00.000000000Z INFO: listening on :3000\n",
+            true
+        ));
+
+        let inverted = LineFilter::new("^ERROR", true).unwrap();
+        assert!(!inverted.keeps("ERROR: connection refused", false));
+        assert!(inverted.keeps("INFO: listening on :3000", false));
+
+        assert!(LineFilter::new("(unclosed", false).is_err());
+    }
+
+    #[test]
+    fn test_write_kept_lines_holds_back_partial_lines() {
+        let filter = LineFilter::new("keep", false).unwrap();
+        let mut buffer = b"keep 1\ndrop 2\nkeep 3\nkee".to_vec();
+        let mut output = Vec::new();
+
+        write_kept_lines(&mut buffer, &filter, false, &mut output).unwrap();
+        assert_eq!(output, b"keep 1\nkeep 3\n");
+        assert_eq!(buffer, b"kee");
+
+        buffer.extend_from_slice(b"p 4\n");
+        write_kept_lines(&mut buffer, &filter, false, &mut output).unwrap();
+        assert_eq!(output, b"keep 1\nkeep 3\nkeep 4\n");
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn test_log_tail_normalization() {
+        let tail = |value: &str| value.parse::<LogTail>().unwrap().to_docker_value();
+        assert_eq!(tail("50"), "50");
+        assert_eq!(tail("0"), "0");
+        assert_eq!(tail("all"), "all");
+        assert_eq!(tail("ALL"), "all");
+
+        for value in ["-1", "ten", "", "1.5", "99999999999"] {
+            assert!(
+                value.parse::<LogTail>().is_err(),
+                "{} should be rejected",
+                value
+            );
+        }
+
+        // Without --tail: the default, or everything when a time range is given
+        assert_eq!(LogTail::resolve(None, false), LogTail::Lines(50));
+        assert_eq!(LogTail::resolve(None, true), LogTail::All);
+        assert_eq!(
+            LogTail::resolve(Some(LogTail::Lines(0)), true),
+            LogTail::Lines(0)
+        );
+    }
+
+    #[test]
+    fn test_follow_action_reconnects_when_running_again() {
+        // Stream ended during a restart: stopped for a couple of checks, then running
+        let statuses = [Some(AgentStatus::Stopped), None, Some(AgentStatus::Running)];
+        let actions: Vec<FollowAction> = statuses
+            .iter()
+            .enumerate()
+            .map(|(failed, status)| follow_action(status.as_ref(), failed as u32, 5))
+            .collect();
+        assert_eq!(
+            actions,
+            vec![
+                FollowAction::Wait,
+                FollowAction::Wait,
+                FollowAction::Reconnect
+            ]
+        );
+    }
+
+    #[test]
+    fn test_follow_action_gives_up() {
+        // Never comes back
+        assert_eq!(
+            follow_action(Some(&AgentStatus::Stopped), 5, 5),
+            FollowAction::GiveUp
+        );
+        assert_eq!(follow_action(None, 5, 5), FollowAction::GiveUp);
+        assert_eq!(
+            follow_action(Some(&AgentStatus::Created), 4, 5),
+            FollowAction::Wait
+        );
+
+        // Dead or being removed
+        assert_eq!(
+            follow_action(Some(&AgentStatus::Error), 0, 5),
+            FollowAction::GiveUp
+        );
+
+        // A running container is always worth reconnecting to
+        assert_eq!(
+            follow_action(Some(&AgentStatus::Running), 5, 5),
+            FollowAction::Reconnect
+        );
+
+        // With no reconnects allowed, stop as soon as the stream ends
+        assert_eq!(
+            follow_action(Some(&AgentStatus::Stopped), 0, 0),
+            FollowAction::GiveUp
+        );
+    }
+
+    #[test]
+    fn test_builtin_networks() {
+        assert!(is_builtin_network("none"));
+        assert!(is_builtin_network("bridge"));
+        assert!(is_builtin_network("host"));
+        assert!(!is_builtin_network("my-agents"));
+    }
 }
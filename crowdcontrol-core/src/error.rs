@@ -0,0 +1,195 @@
+use std::fmt;
+use std::time::Duration;
+
+/// Failures callers may want to tell apart, e.g. to pick an exit code.
+///
+/// Functions still return `anyhow::Result`; these errors travel inside it and can
+/// be recovered with [`CrowdControlError::find`] (or `anyhow::Error::downcast_ref`).
+/// Anything not covered here is a generic failure.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CrowdControlError {
+    /// No agent metadata exists under this name
+    AgentNotFound(String),
+    /// An agent with this name already exists
+    AgentAlreadyExists(String),
+    /// The agent needs to be running. `action` names what was attempted, for
+    /// commands that say so in their message.
+    AgentNotRunning {
+        name: String,
+        action: Option<String>,
+    },
+    /// The agent is paused and must be unpaused first
+    AgentPaused(String),
+    /// The Docker daemon can't be reached; the message says why
+    DockerUnavailable(String),
+    /// A wait gave up. `waiting_for` completes "waiting for ...", e.g.
+    /// "container 1a2b3c4d5e6f to become healthy"
+    Timeout {
+        waiting_for: String,
+        timeout: Duration,
+    },
+    /// A container stopped right after starting. `logs` holds its last log lines,
+    /// empty if it wrote none.
+    ContainerExited {
+        container: String,
+        exit_code: i64,
+        logs: String,
+    },
+    /// Ctrl-C stopped the command before it finished; whatever it had started
+    /// has been rolled back or completed
+    Interrupted,
+}
+
+impl CrowdControlError {
+    pub fn not_running(name: &str) -> Self {
+        CrowdControlError::AgentNotRunning {
+            name: name.to_string(),
+            action: None,
+        }
+    }
+
+    /// Not running, for a command that says what it needs the agent running for,
+    /// e.g. "refresh configs"
+    pub fn not_running_to(name: &str, action: &str) -> Self {
+        CrowdControlError::AgentNotRunning {
+            name: name.to_string(),
+            action: Some(action.to_string()),
+        }
+    }
+
+    /// The first `CrowdControlError` in an error's chain, including context layers
+    pub fn find(error: &anyhow::Error) -> Option<&CrowdControlError> {
+        // downcast_ref sees context layers; the chain covers errors kept as a `source`
+        error.downcast_ref::<CrowdControlError>().or_else(|| {
+            error
+                .chain()
+                .find_map(|cause| cause.downcast_ref::<CrowdControlError>())
+        })
+    }
+}
+
+impl fmt::Display for CrowdControlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CrowdControlError::AgentNotFound(name) => write!(f, "Agent '{}' not found", name),
+            CrowdControlError::AgentAlreadyExists(name) => {
+                write!(f, "Agent '{}' already exists", name)
+            }
+            CrowdControlError::AgentNotRunning { name, action: None } => write!(
+                f,
+                "Agent '{}' is not running. Start it with: crowdcontrol start {}",
+                name, name
+            ),
+            CrowdControlError::AgentNotRunning {
+                name,
+                action: Some(action),
+            } => write!(
+                f,
+                "Agent '{}' must be running to {}. Start it first with: crowdcontrol start {}",
+                name, action, name
+            ),
+            CrowdControlError::AgentPaused(name) => write!(
+                f,
+                "Agent '{}' is paused. Resume it with: crowdcontrol unpause {}",
+                name, name
+            ),
+            CrowdControlError::DockerUnavailable(message) => write!(f, "{}", message),
+            CrowdControlError::Timeout {
+                waiting_for,
+                timeout,
+            } => write!(
+                f,
+                "Timed out after {}s waiting for {}",
+                timeout.as_secs(),
+                waiting_for
+            ),
+            CrowdControlError::ContainerExited {
+                container,
+                exit_code,
+                logs,
+            } if logs.is_empty() => write!(
+                f,
+                "Container {} exited with code {} right after starting, without any output",
+                container, exit_code
+            ),
+            CrowdControlError::ContainerExited {
+                container,
+                exit_code,
+                logs,
+            } => write!(
+                f,
+                "Container {} exited with code {} right after starting. Last log lines:\n{}",
+                container, exit_code, logs
+            ),
+            CrowdControlError::Interrupted => write!(f, "Interrupted"),
+        }
+    }
+}
+
+impl std::error::Error for CrowdControlError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn test_find_through_context() {
+        let error = Err::<(), _>(CrowdControlError::AgentNotFound("web".to_string()))
+            .context("Failed to load agent")
+            .unwrap_err();
+        assert_eq!(
+            CrowdControlError::find(&error),
+            Some(&CrowdControlError::AgentNotFound("web".to_string()))
+        );
+
+        // Used as the context itself, as for Docker connection failures
+        let error = Err::<(), _>(std::io::Error::from(std::io::ErrorKind::NotFound))
+            .context(CrowdControlError::DockerUnavailable(
+                "Failed to connect to Docker".to_string(),
+            ))
+            .unwrap_err();
+        assert!(matches!(
+            CrowdControlError::find(&error),
+            Some(CrowdControlError::DockerUnavailable(_))
+        ));
+
+        assert_eq!(CrowdControlError::find(&anyhow::anyhow!("other")), None);
+    }
+
+    #[test]
+    fn test_messages_match_previous_wording() {
+        assert_eq!(
+            CrowdControlError::not_running("web").to_string(),
+            "Agent 'web' is not running. Start it with: crowdcontrol start web"
+        );
+        assert_eq!(
+            CrowdControlError::not_running_to("web", "refresh configs").to_string(),
+            "Agent 'web' must be running to refresh configs. Start it first with: crowdcontrol start web"
+        );
+    }
+
+    #[test]
+    fn test_container_exited_message_includes_logs() {
+        let exited = CrowdControlError::ContainerExited {
+            container: "crowdcontrol-web".to_string(),
+            exit_code: 3,
+            logs: "Running repository setup for web...\nsetup failed".to_string(),
+        };
+        assert_eq!(
+            exited.to_string(),
+            "Container crowdcontrol-web exited with code 3 right after starting. Last log lines:\n\
+             Running repository setup for web...\nsetup failed"
+        );
+
+        let silent = CrowdControlError::ContainerExited {
+            container: "crowdcontrol-web".to_string(),
+            exit_code: 137,
+            logs: String::new(),
+        };
+        assert_eq!(
+            silent.to_string(),
+            "Container crowdcontrol-web exited with code 137 right after starting, without any output"
+        );
+    }
+}
@@ -0,0 +1,102 @@
+use anyhow::{anyhow, Result};
+
+/// Keychain service Claude Code stores its credentials under on macOS
+pub const DEFAULT_KEYCHAIN_SERVICE: &str = "Claude Code-credentials";
+
+/// Service names Claude Code has stored credentials under, tried in order when the
+/// configured one has no entry
+pub const KEYCHAIN_SERVICE_FALLBACKS: [&str; 2] = [DEFAULT_KEYCHAIN_SERVICE, "Claude Code"];
+
+/// Credentials read from the keychain, with the service they were found under
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeychainCredentials {
+    pub service: String,
+    pub credentials: String,
+}
+
+/// Look up credentials under `service`, then under each of [`KEYCHAIN_SERVICE_FALLBACKS`]
+/// not tried yet. `lookup` returns `Ok(None)` when the keychain has nothing for a service;
+/// an error from it ends the search.
+pub fn find_keychain_credentials(
+    service: &str,
+    mut lookup: impl FnMut(&str) -> Result<Option<String>>,
+) -> Result<KeychainCredentials> {
+    let mut tried: Vec<&str> = Vec::new();
+    for candidate in std::iter::once(service).chain(KEYCHAIN_SERVICE_FALLBACKS) {
+        if tried.contains(&candidate) {
+            continue;
+        }
+        tried.push(candidate);
+
+        if let Some(credentials) = lookup(candidate)? {
+            return Ok(KeychainCredentials {
+                service: candidate.to_string(),
+                credentials,
+            });
+        }
+    }
+
+    Err(anyhow!(
+        "No Claude Code credentials found in keychain (tried {}). Make sure you're logged in \
+         to Claude Code, or name the keychain service with --keychain-service.",
+        tried
+            .iter()
+            .map(|service| format!("'{}'", service))
+            .collect::<Vec<_>>()
+            .join(", ")
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_configured_service_is_tried_first() {
+        let mut tried = Vec::new();
+        let found = find_keychain_credentials("Acme Claude", |service| {
+            tried.push(service.to_string());
+            Ok(Some(format!("token-for-{}", service)))
+        })
+        .unwrap();
+
+        assert_eq!(found.service, "Acme Claude");
+        assert_eq!(found.credentials, "token-for-Acme Claude");
+        assert_eq!(tried, ["Acme Claude"]);
+    }
+
+    #[test]
+    fn test_falls_back_to_known_services() {
+        let mut tried = Vec::new();
+        let found = find_keychain_credentials(DEFAULT_KEYCHAIN_SERVICE, |service| {
+            tried.push(service.to_string());
+            Ok((service == "Claude Code").then(|| "token".to_string()))
+        })
+        .unwrap();
+
+        assert_eq!(found.service, "Claude Code");
+        // The default is also the first fallback; it isn't looked up twice
+        assert_eq!(tried, [DEFAULT_KEYCHAIN_SERVICE, "Claude Code"]);
+    }
+
+    #[test]
+    fn test_reports_every_service_tried() {
+        let error = find_keychain_credentials("Acme Claude", |_| Ok(None)).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("tried 'Acme Claude', 'Claude Code-credentials', 'Claude Code'"));
+    }
+
+    #[test]
+    fn test_lookup_errors_stop_the_search() {
+        let mut calls = 0;
+        let error = find_keychain_credentials(DEFAULT_KEYCHAIN_SERVICE, |_| {
+            calls += 1;
+            Err(anyhow!("Failed to execute security command"))
+        })
+        .unwrap_err();
+
+        assert_eq!(calls, 1);
+        assert!(error.to_string().contains("security command"));
+    }
+}
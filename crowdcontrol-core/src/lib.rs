@@ -1,13 +1,43 @@
 pub mod agent;
+pub mod cancel;
 pub mod config;
 pub mod docker;
+pub mod error;
+pub mod keychain;
 pub mod logger;
+pub mod manager;
+pub mod pull_progress;
+pub mod ready_file;
+pub mod repo_config;
+pub mod schema;
 pub mod settings;
 pub mod state_validator;
+pub mod template;
+pub mod terminal;
 
 pub use agent::*;
-pub use config::Config;
-pub use docker::{Agent, AgentStatus, DockerClient};
-pub use logger::init_logger;
+pub use cancel::{cancel_on_ctrl_c, run_until_cancelled, CancellationToken};
+pub use config::{Config, WorkspaceLayout, CONTAINER_WORKSPACE_DIR};
+pub use docker::{
+    agent_name_of, api_version_supported, build_context_tar, container_labels, cpu_percent,
+    docker_unavailable_hint, explain_docker_unavailable, explain_gpu_unavailable,
+    explain_platform_mismatch, follow_action, format_ports, health_from_status_text,
+    is_docker_unreachable, is_gpu_unavailable, is_platform_mismatch, order_by_dependencies,
+    parse_cpus, parse_gpus, parse_memory_limit, parse_restart_policy, resolve_env_passthrough,
+    select_agents_to_reap, select_agents_to_refresh, select_agents_to_start, select_agents_to_stop,
+    short_container_id, status_from_docker_state, status_of, tag_labels, tags_from_labels,
+    validate_env_entry, validate_memory_swap, validate_platform, with_timeout, Agent, AgentStatus,
+    ContainerHealth, ContainerOptions, ContainerStats, CpuSample, DockerClient, EnvPassthrough,
+    EnvironmentCheck, ExecOutput, FollowAction, HealthStatus, LineFilter, LogTail, LogsQuery,
+    SessionEnd, VolumeSpec, MIN_DOCKER_API_VERSION, STARTUP_CHECK_SECS,
+};
+pub use error::CrowdControlError;
+pub use keychain::{find_keychain_credentials, KeychainCredentials, DEFAULT_KEYCHAIN_SERVICE};
+pub use logger::{init_logger, LogFormat};
+pub use manager::{AgentEntry, AgentManager, CreateAgent};
+pub use ready_file::{write_ready_file, ReadyStatus};
+pub use repo_config::RepoConfig;
+pub use schema::{metadata_schema, settings_schema};
 pub use settings::Settings;
-pub use state_validator::{StateInconsistency, StateValidator};
+pub use state_validator::{RepairOptions, StateInconsistency, StateValidator};
+pub use template::{find_template, load_templates, templates_path, Template};
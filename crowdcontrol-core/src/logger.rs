@@ -1,8 +1,62 @@
 use anyhow::{Context, Result};
-use std::path::PathBuf;
-use tracing::Level;
+use chrono::{Days, NaiveDate, Utc};
+use std::path::{Path, PathBuf};
+use tracing::{Level, Subscriber};
 use tracing_appender::rolling::{RollingFileAppender, Rotation};
-use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+use tracing_subscriber::{
+    fmt, fmt::MakeWriter, layer::SubscriberExt, registry::LookupSpan, util::SubscriberInitExt,
+    EnvFilter, Layer,
+};
+
+/// Base name of the log file; the daily appender adds a `.YYYY-MM-DD` suffix
+const LOG_FILE_NAME: &str = "crowdcontrol.log";
+
+/// Days of logs kept when `CROWDCONTROL_LOG_RETENTION_DAYS` is not set
+const DEFAULT_LOG_RETENTION_DAYS: u64 = 7;
+
+/// Format of the log file output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Human-readable lines (default)
+    Text,
+    /// One JSON object per line, for log aggregation pipelines
+    Json,
+}
+
+impl LogFormat {
+    /// Read the format from `CROWDCONTROL_LOG_FORMAT`, defaulting to text
+    pub fn from_env() -> Self {
+        Self::parse(std::env::var("CROWDCONTROL_LOG_FORMAT").ok().as_deref())
+    }
+
+    fn parse(value: Option<&str>) -> Self {
+        match value {
+            Some(v) if v.eq_ignore_ascii_case("json") => LogFormat::Json,
+            _ => LogFormat::Text,
+        }
+    }
+}
+
+/// Build the file layer in the requested format
+fn file_layer<S, W>(format: LogFormat, writer: W) -> Box<dyn Layer<S> + Send + Sync>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    W: for<'writer> MakeWriter<'writer> + Send + Sync + 'static,
+{
+    match format {
+        LogFormat::Text => fmt::layer()
+            .with_writer(writer)
+            .with_ansi(false)
+            .with_target(false)
+            .boxed(),
+        LogFormat::Json => fmt::layer()
+            .json()
+            .with_writer(writer)
+            .with_ansi(false)
+            .with_target(false)
+            .boxed(),
+    }
+}
 
 /// Get the standard log directory for the application
 fn get_log_dir() -> Result<PathBuf> {
@@ -20,6 +74,45 @@ fn get_log_dir() -> Result<PathBuf> {
     Ok(log_dir)
 }
 
+/// Number of days of logs to keep, from `CROWDCONTROL_LOG_RETENTION_DAYS`
+fn log_retention_days() -> u64 {
+    std::env::var("CROWDCONTROL_LOG_RETENTION_DAYS")
+        .ok()
+        .and_then(|days| days.parse().ok())
+        .unwrap_or(DEFAULT_LOG_RETENTION_DAYS)
+}
+
+/// Delete rotated log files (`crowdcontrol.log.YYYY-MM-DD`) older than `retention_days`,
+/// counting `today` as the first day kept. Files that don't match the naming scheme are left alone.
+/// Returns the number of files deleted.
+fn prune_old_logs(log_dir: &Path, retention_days: u64, today: NaiveDate) -> Result<usize> {
+    let Some(cutoff) = today.checked_sub_days(Days::new(retention_days)) else {
+        return Ok(0);
+    };
+
+    let prefix = format!("{}.", LOG_FILE_NAME);
+    let mut deleted = 0;
+
+    for entry in std::fs::read_dir(log_dir)
+        .with_context(|| format!("Failed to read log directory: {:?}", log_dir))?
+    {
+        let path = entry?.path();
+        let date = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_prefix(&prefix))
+            .and_then(|suffix| NaiveDate::parse_from_str(suffix, "%Y-%m-%d").ok());
+
+        if date.is_some_and(|date| date <= cutoff) && path.is_file() {
+            std::fs::remove_file(&path)
+                .with_context(|| format!("Failed to delete old log file: {:?}", path))?;
+            deleted += 1;
+        }
+    }
+
+    Ok(deleted)
+}
+
 /// Initialize the tracing subscriber with both console and file outputs
 pub fn init_logger(verbosity: u8) -> Result<()> {
     let log_level = match verbosity {
@@ -32,17 +125,13 @@ pub fn init_logger(verbosity: u8) -> Result<()> {
     // Get log directory
     let log_dir = get_log_dir()?;
 
-    // Create a rolling file appender that rotates daily and keeps 7 days of logs
-    let file_appender =
-        RollingFileAppender::new(Rotation::DAILY, log_dir.clone(), "crowdcontrol.log");
+    // Create a rolling file appender that rotates daily; old files are pruned below
+    let file_appender = RollingFileAppender::new(Rotation::DAILY, log_dir.clone(), LOG_FILE_NAME);
 
-    // Create the file layer
-    let file_layer = fmt::layer()
-        .with_writer(file_appender)
-        .with_ansi(false)
-        .with_target(false);
+    // Create the file layer (JSON lines if CROWDCONTROL_LOG_FORMAT=json)
+    let file_layer = file_layer(LogFormat::from_env(), file_appender);
 
-    // Create the console layer (stderr)
+    // Create the console layer (stderr), always human-readable
     let console_layer = fmt::layer().with_writer(std::io::stderr).with_target(false);
 
     // Create env filter that respects RUST_LOG or falls back to our verbosity
@@ -62,9 +151,21 @@ pub fn init_logger(verbosity: u8) -> Result<()> {
 
     tracing::info!(
         "Logger initialized. Log file: {:?}",
-        log_dir.join("crowdcontrol.log")
+        log_dir.join(LOG_FILE_NAME)
     );
 
+    // Keep the last few days of logs (7 by default). The appender names files by UTC date.
+    let retention_days = log_retention_days();
+    match prune_old_logs(&log_dir, retention_days, Utc::now().date_naive()) {
+        Ok(0) => {}
+        Ok(deleted) => tracing::debug!(
+            "Deleted {} log file(s) older than {} days",
+            deleted,
+            retention_days
+        ),
+        Err(e) => tracing::warn!("Failed to prune old log files: {}", e),
+    }
+
     Ok(())
 }
 
@@ -104,6 +205,100 @@ mod tests {
         });
     }
 
+    /// Writer that captures log output in memory
+    #[derive(Clone, Default)]
+    struct BufferWriter(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl std::io::Write for BufferWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn capture_file_layer_output(format: LogFormat) -> String {
+        let buffer = BufferWriter::default();
+        let writer = buffer.clone();
+        let subscriber =
+            tracing_subscriber::registry().with(file_layer(format, move || writer.clone()));
+
+        tracing::subscriber::with_default(subscriber, || {
+            info!(agent = "test-agent", "Agent started");
+        });
+
+        let output = buffer.0.lock().unwrap().clone();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_log_format_from_env_value() {
+        assert_eq!(LogFormat::parse(Some("json")), LogFormat::Json);
+        assert_eq!(LogFormat::parse(Some("JSON")), LogFormat::Json);
+        assert_eq!(LogFormat::parse(Some("text")), LogFormat::Text);
+        assert_eq!(LogFormat::parse(Some("")), LogFormat::Text);
+        assert_eq!(LogFormat::parse(None), LogFormat::Text);
+    }
+
+    #[test]
+    fn test_json_file_layer_writes_json_lines() {
+        let output = capture_file_layer_output(LogFormat::Json);
+        let line: serde_json::Value = serde_json::from_str(output.trim()).unwrap();
+        assert_eq!(line["fields"]["message"], "Agent started");
+        assert_eq!(line["fields"]["agent"], "test-agent");
+    }
+
+    #[test]
+    fn test_text_file_layer_is_human_readable() {
+        let output = capture_file_layer_output(LogFormat::Text);
+        assert!(output.contains("Agent started"));
+        assert!(serde_json::from_str::<serde_json::Value>(output.trim()).is_err());
+    }
+
+    #[test]
+    fn test_prune_old_logs_keeps_recent_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let today = NaiveDate::from_ymd_opt(2024, 3, 10).unwrap();
+
+        let files = [
+            "crowdcontrol.log.2024-03-10",
+            "crowdcontrol.log.2024-03-04",
+            "crowdcontrol.log.2024-03-03",
+            "crowdcontrol.log.2024-02-01",
+            // Unrelated files are never touched
+            "crowdcontrol.log",
+            "crowdcontrol.log.backup",
+            "other.log.2024-01-01",
+            "notes.txt",
+        ];
+        for file in files {
+            std::fs::write(temp_dir.path().join(file), "log").unwrap();
+        }
+
+        let deleted = prune_old_logs(temp_dir.path(), 7, today).unwrap();
+        assert_eq!(deleted, 2);
+
+        let mut remaining: Vec<String> = std::fs::read_dir(temp_dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        remaining.sort();
+        assert_eq!(
+            remaining,
+            vec![
+                "crowdcontrol.log",
+                "crowdcontrol.log.2024-03-04",
+                "crowdcontrol.log.2024-03-10",
+                "crowdcontrol.log.backup",
+                "notes.txt",
+                "other.log.2024-01-01",
+            ]
+        );
+    }
+
     #[test]
     fn test_get_log_dir() {
         let result = get_log_dir();
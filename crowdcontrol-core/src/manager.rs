@@ -0,0 +1,583 @@
+use anyhow::{anyhow, Context, Result};
+use bollard::models::ContainerSummary;
+use chrono::Utc;
+use std::fs;
+use std::sync::OnceLock;
+use tracing::{debug, warn};
+
+use crate::{
+    clone_repository_with_ssh, list_all_agents, load_agent_metadata, record_agent_activity,
+    save_agent_metadata, update_agent_metadata, validate_agent_name, Agent, AgentStatus, Config,
+    ContainerOptions, CrowdControlError, DockerClient, LogsQuery, RepoConfig, SshOptions,
+};
+
+/// An agent as reported by `AgentManager::list`
+#[derive(Debug, Clone)]
+pub struct AgentEntry {
+    pub agent: Agent,
+    /// Live status; `Error` when Docker couldn't be asked
+    pub status: AgentStatus,
+    /// The agent's container, if Docker reported one
+    pub container: Option<ContainerSummary>,
+}
+
+/// Everything `AgentManager::create` needs to set up an agent from a repository
+#[derive(Debug, Clone, Default)]
+pub struct CreateAgent {
+    pub name: String,
+    pub repository: String,
+    pub branch: Option<String>,
+    pub ssh: SshOptions,
+    /// Container settings; the repository and branch labels are filled in from above
+    pub options: ContainerOptions,
+    pub idle_timeout: Option<String>,
+    /// Agents that must be running before this one starts
+    pub depends_on: Vec<String>,
+}
+
+/// High-level operations on agents for programs embedding crowdcontrol.
+///
+/// Methods return typed results and never print; failures the caller may want to
+/// handle carry a `CrowdControlError`. The Docker connection is made on first use,
+/// so metadata-only calls such as `list_metadata` work without Docker.
+pub struct AgentManager {
+    config: Config,
+    docker: OnceLock<DockerClient>,
+}
+
+impl AgentManager {
+    pub fn new(config: Config) -> Self {
+        Self {
+            config,
+            docker: OnceLock::new(),
+        }
+    }
+
+    /// Use an existing Docker client instead of connecting on first use
+    pub fn with_docker(config: Config, docker: DockerClient) -> Self {
+        let manager = Self::new(config);
+        let _ = manager.docker.set(docker);
+        manager
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// The Docker client, connecting if this is the first call that needs one
+    pub fn docker(&self) -> Result<&DockerClient> {
+        if let Some(docker) = self.docker.get() {
+            return Ok(docker);
+        }
+        let docker = DockerClient::new(self.config.clone())?;
+        Ok(self.docker.get_or_init(|| docker))
+    }
+
+    /// Metadata of every agent, sorted by name, without asking Docker anything.
+    /// Agents whose metadata can't be read are skipped.
+    pub fn list_metadata(&self) -> Result<Vec<Agent>> {
+        let mut agents = Vec::new();
+        for name in list_all_agents(&self.config)? {
+            match load_agent_metadata(&self.config, &name) {
+                Ok(agent) => agents.push(agent),
+                Err(e) => debug!("Skipping agent '{}': {:#}", name, e),
+            }
+        }
+        Ok(agents)
+    }
+
+    /// Every agent with its live status, resolved from a single container listing
+    pub async fn list(&self) -> Result<Vec<AgentEntry>> {
+        let agents = self.list_metadata()?;
+        if agents.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let containers = match self.docker() {
+            Ok(docker) => docker.list_all_containers().await.ok(),
+            Err(_) => None,
+        };
+
+        Ok(agents
+            .into_iter()
+            .map(|agent| {
+                let (status, container) = match &containers {
+                    Some(containers) => (
+                        agent.status_from_containers(containers, &self.config),
+                        agent.container_from(containers, &self.config).cloned(),
+                    ),
+                    None => (AgentStatus::Error, None),
+                };
+                AgentEntry {
+                    agent,
+                    status,
+                    container,
+                }
+            })
+            .collect())
+    }
+
+    /// Clone the repository into a new workspace, create the container and save the
+    /// agent's metadata. The image must already be available locally or pullable.
+    pub async fn create(&self, request: CreateAgent) -> Result<Agent> {
+        let workspace_path = self.prepare_workspace(&request.name)?;
+        let repo_path = self.config.agent_repo_path(&request.name);
+
+        if let Err(e) = clone_repository_with_ssh(
+            &request.repository,
+            &repo_path,
+            request.branch.as_deref(),
+            &request.ssh,
+        ) {
+            if let Err(cleanup_err) = fs::remove_dir_all(&workspace_path) {
+                warn!(
+                    "Failed to clean up workspace {:?} after clone failure: {}",
+                    workspace_path, cleanup_err
+                );
+            }
+            return Err(e);
+        }
+
+        let docker = self.docker()?;
+        docker
+            .pull_image_for_platform(
+                docker.image_for(&request.options),
+                false,
+                request.options.platform.as_deref(),
+            )
+            .await?;
+
+        let mut options = request.options;
+        if options.healthcheck.is_none() {
+            options.healthcheck = RepoConfig::load(&repo_path)?.healthcheck;
+        }
+
+        let agent = Agent {
+            name: request.name,
+            status: AgentStatus::Created,
+            container_id: None,
+            repository: request.repository,
+            branch: request.branch,
+            created_at: Utc::now(),
+            workspace_path,
+            memory: options.memory.clone(),
+            memory_swap: options.memory_swap.clone(),
+            memory_reservation: options.memory_reservation.clone(),
+            cpus: options.cpus.clone(),
+            tags: options.tags.clone(),
+            local_mount: None,
+            network: options.network.clone(),
+            privileged: options.privileged,
+            cap_add: options.cap_add.clone(),
+            read_only: options.read_only,
+            readonly_workspace: options.readonly_workspace,
+            no_mount_claude: options.no_mount_claude,
+            platform: options.platform.clone(),
+            image: options.image.clone(),
+            restart_policy: options.restart_policy.clone(),
+            gpus: options.gpus.clone(),
+            volumes: options.volumes.clone(),
+            idle_timeout: request.idle_timeout,
+            post_create_done: false,
+            depends_on: request.depends_on,
+            last_activity: None,
+            workspace_layout: self.config.workspace_layout,
+        };
+
+        self.register(agent, &options).await
+    }
+
+    /// Check the name is valid and unused, then create the agent's workspace directory
+    pub fn prepare_workspace(&self, name: &str) -> Result<std::path::PathBuf> {
+        validate_agent_name(name)?;
+
+        let workspace_path = self.config.agent_workspace_path(name);
+        if workspace_path.exists() {
+            return Err(CrowdControlError::AgentAlreadyExists(name.to_string()).into());
+        }
+
+        fs::create_dir_all(&workspace_path).with_context(|| {
+            format!("Failed to create workspace directory: {:?}", workspace_path)
+        })?;
+        Ok(workspace_path)
+    }
+
+    /// Create the container for an agent whose workspace is ready and save its
+    /// metadata, returning the agent with its container ID set
+    pub async fn register(&self, mut agent: Agent, options: &ContainerOptions) -> Result<Agent> {
+        let mut options = options.clone();
+        options.repository = Some(agent.repository.clone());
+        options.branch = agent.branch.clone();
+
+        let mount_path = agent.mount_path().to_path_buf();
+        let container_id = self
+            .docker()?
+            .create_container_with_options(&agent.name, &mount_path, &options)
+            .await?;
+
+        agent.container_id = Some(container_id);
+        save_agent_metadata(&self.config, &agent)?;
+        Ok(agent)
+    }
+
+    /// Whether the agent has no usable container: none is recorded (`new --workspace-only`),
+    /// or the recorded one no longer exists or belongs to another agent
+    pub async fn needs_container(&self, agent: &Agent) -> Result<bool> {
+        match &agent.container_id {
+            None => Ok(true),
+            Some(container_id) => Ok(!self
+                .docker()?
+                .validate_container_id(&agent.name, container_id)
+                .await?),
+        }
+    }
+
+    /// Create a fresh container from the agent's stored settings if it has no usable
+    /// one (see `needs_container`), recording the new ID in its metadata. Agents with
+    /// a valid container are returned as is.
+    pub async fn ensure_container(&self, mut agent: Agent) -> Result<Agent> {
+        if !self.needs_container(&agent).await? {
+            return Ok(agent);
+        }
+
+        let mount_path = agent.mount_path().to_path_buf();
+        if !mount_path.exists() {
+            return Err(anyhow!(
+                "Workspace {} no longer exists",
+                mount_path.display()
+            ));
+        }
+
+        // The agent's own container whose ID was never recorded, e.g. one stopped by an
+        // older version that cleared the ID on stop: take it over
+        let docker = self.docker()?;
+        if let Some(container_id) = docker.labelled_container_id(&agent.name).await? {
+            debug!(
+                "Recording existing container {} for agent '{}'",
+                container_id, agent.name
+            );
+            update_agent_metadata(&self.config, &agent.name, |stored| {
+                stored.container_id = Some(container_id.clone());
+                Ok(())
+            })?;
+            agent.container_id = Some(container_id);
+            return Ok(agent);
+        }
+
+        // Something else holds the name
+        let container_name = self.config.container_name(&agent.name);
+        if docker.container_exists(&container_name).await? {
+            return Err(anyhow!(
+                "Container {} exists but isn't the one recorded for agent '{}'. \
+                 Remove it with `docker rm {}` and try again",
+                container_name,
+                agent.name,
+                container_name
+            ));
+        }
+
+        // The health check comes from the repository config, as in `new`
+        let options = ContainerOptions {
+            healthcheck: RepoConfig::load(&agent.repo_path())?.healthcheck,
+            ..agent.container_options()
+        };
+        docker
+            .pull_image_for_platform(docker.image_for(&options), false, agent.platform.as_deref())
+            .await?;
+
+        debug!("Creating container for agent '{}'", agent.name);
+        let container_id = docker
+            .create_container_with_options(&agent.name, &mount_path, &options)
+            .await?;
+        // A new container hasn't run the repository's post-create command yet
+        update_agent_metadata(&self.config, &agent.name, |stored| {
+            stored.container_id = Some(container_id.clone());
+            stored.post_create_done = false;
+            Ok(())
+        })?;
+
+        agent.container_id = Some(container_id);
+        agent.post_create_done = false;
+        Ok(agent)
+    }
+
+    /// Replace an agent's container with a fresh one created from its stored settings,
+    /// keeping the workspace and metadata (`reset`). Returns the agent with the new
+    /// container ID, left stopped.
+    pub async fn reset_container(&self, mut agent: Agent) -> Result<Agent> {
+        let repo_path = agent.repo_path();
+        if !repo_path.exists() {
+            return Err(anyhow!(
+                "Repository {} of agent '{}' no longer exists. Remove the agent and create \
+                 it again with `crowdcontrol new`",
+                repo_path.display(),
+                agent.name
+            ));
+        }
+
+        // The recorded container may already be gone, or belong to another agent
+        if let Some(container_id) = agent.container_id.take() {
+            let docker = self.docker()?;
+            if docker
+                .validate_container_id(&agent.name, &container_id)
+                .await?
+            {
+                debug!(
+                    "Removing container {} of agent '{}'",
+                    container_id, agent.name
+                );
+                docker.remove_container(&container_id).await?;
+            }
+            update_agent_metadata(&self.config, &agent.name, |stored| {
+                stored.container_id = None;
+                Ok(())
+            })?;
+        }
+
+        self.ensure_container(agent).await
+    }
+
+    /// Start an agent by name, creating its container first if it has none. Returns
+    /// the live status without doing anything if it's already running.
+    pub async fn start(&self, name: &str) -> Result<AgentStatus> {
+        let agent = self
+            .ensure_container(load_agent_metadata(&self.config, name)?)
+            .await?;
+        let status = agent.compute_live_status(self.docker()?).await?;
+        if status.is_running() {
+            return Ok(status);
+        }
+
+        for warning in self.start_agent(&agent, &status).await? {
+            warn!("{}", warning);
+        }
+        Ok(AgentStatus::Running)
+    }
+
+    /// Start an agent whose live status is already known. Steps that fail without
+    /// preventing the start (reapplying resource limits, recording activity) are
+    /// returned as warnings.
+    pub async fn start_agent(&self, agent: &Agent, status: &AgentStatus) -> Result<Vec<String>> {
+        match status {
+            AgentStatus::Error => {
+                return Err(anyhow!(
+                    "Agent '{}' is in error state. Please remove and recreate it.",
+                    agent.name
+                ));
+            }
+            AgentStatus::Paused => {
+                return Err(CrowdControlError::AgentPaused(agent.name.clone()).into());
+            }
+            _ => {}
+        }
+
+        let container_id = container_id_of(agent)?;
+        let docker = self.docker()?;
+        let mut warnings = Vec::new();
+
+        // Reapply the resource limits recorded when the agent was created
+        let limits = agent.container_options();
+        if limits.memory.is_some()
+            || limits.memory_swap.is_some()
+            || limits.memory_reservation.is_some()
+            || limits.cpus.is_some()
+        {
+            if let Err(e) = docker
+                .update_container_resources(container_id, &limits)
+                .await
+            {
+                warnings.push(format!("Failed to apply resource limits: {}", e));
+            }
+        }
+
+        docker.start_container(container_id).await?;
+
+        // A freshly started agent counts as active, so `reap` doesn't stop it right away
+        if let Err(e) = record_agent_activity(&self.config, &agent.name) {
+            warnings.push(format!("Failed to record agent activity: {}", e));
+        }
+
+        Ok(warnings)
+    }
+
+    /// Stop a running or paused agent, keeping its container. Returns false if the
+    /// agent wasn't running.
+    pub async fn stop(&self, name: &str, force: bool, timeout: u64) -> Result<bool> {
+        let agent = load_agent_metadata(&self.config, name)?;
+        let docker = self.docker()?;
+
+        // Check current status (validates container_id and gets live status)
+        let status = agent.compute_live_status(docker).await?;
+
+        // Docker can stop paused and restarting containers directly
+        if !matches!(
+            status,
+            AgentStatus::Running
+                | AgentStatus::Starting
+                | AgentStatus::Paused
+                | AgentStatus::Restarting
+        ) {
+            return Ok(false);
+        }
+
+        // The stopped container keeps its ID so `start` resumes it
+        docker
+            .stop_container_with_timeout(container_id_of(&agent)?, force, timeout)
+            .await?;
+
+        Ok(true)
+    }
+
+    /// Remove an agent by name, deleting its workspace unless `keep_workspace` is set
+    pub async fn remove(&self, name: &str, keep_workspace: bool) -> Result<()> {
+        let agent = load_agent_metadata(&self.config, name)?;
+        for warning in self.remove_agent(&agent, keep_workspace).await? {
+            warn!("{}", warning);
+        }
+        Ok(())
+    }
+
+    /// Remove an agent's container and workspace. A container that can't be removed
+    /// is reported as a warning rather than stopping the removal.
+    pub async fn remove_agent(&self, agent: &Agent, keep_workspace: bool) -> Result<Vec<String>> {
+        let mut warnings = Vec::new();
+
+        if let Some(container_id) = &agent.container_id {
+            if let Err(e) = self.docker()?.remove_container(container_id).await {
+                warnings.push(format!("Failed to remove container: {}", e));
+            }
+        }
+
+        if !keep_workspace {
+            fs::remove_dir_all(&agent.workspace_path)?;
+        } else {
+            // Remove metadata file only
+            let metadata_path = agent.workspace_path.join(".crowdcontrol-metadata.json");
+            if metadata_path.exists() {
+                fs::remove_file(metadata_path)?;
+            }
+        }
+
+        Ok(warnings)
+    }
+
+    /// Container ID recorded for an agent
+    pub fn container_id(&self, name: &str) -> Result<String> {
+        let agent = load_agent_metadata(&self.config, name)?;
+        container_id_of(&agent).map(str::to_string)
+    }
+
+    /// Logs matching `query` as a string. `query.follow` is ignored, since a followed
+    /// log never ends; stream with `DockerClient::write_container_logs` instead.
+    pub async fn logs(&self, name: &str, query: &LogsQuery) -> Result<String> {
+        let container_id = self.container_id(name)?;
+        let query = LogsQuery {
+            follow: false,
+            ..query.clone()
+        };
+
+        let mut output = Vec::new();
+        self.docker()?
+            .write_container_logs(&container_id, &query, &mut output)
+            .await?;
+        Ok(String::from_utf8_lossy(&output).into_owned())
+    }
+}
+
+fn container_id_of(agent: &Agent) -> Result<&str> {
+    agent
+        .container_id
+        .as_deref()
+        .ok_or_else(|| anyhow!("No container ID found for agent '{}'", agent.name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::WorkspaceLayout;
+    use std::path::Path;
+    use tempfile::TempDir;
+
+    fn test_config(workspaces_dir: &Path) -> Config {
+        Config {
+            workspaces_dir: workspaces_dir.to_path_buf(),
+            image: "crowdcontrol:latest".to_string(),
+            verbose: 0,
+            default_memory: None,
+            default_cpus: None,
+            default_platform: None,
+            mount_claude_config: true,
+            connect_default_command: None,
+            container_prefix: "crowdcontrol".to_string(),
+            require_crowdcontrol: false,
+            keychain_service: "Claude Code-credentials".to_string(),
+            workspace_layout: WorkspaceLayout::Flat,
+            connect_auto_start: false,
+        }
+    }
+
+    fn test_agent(config: &Config, name: &str, tags: &[&str]) -> Agent {
+        Agent {
+            name: name.to_string(),
+            repository: "https://github.com/example/app.git".to_string(),
+            branch: Some("main".to_string()),
+            created_at: Utc::now(),
+            workspace_path: config.agent_workspace_path(name),
+            memory: Some("2g".to_string()),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_list_reads_metadata_without_containers() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = test_config(temp_dir.path());
+        save_agent_metadata(&config, &test_agent(&config, "web", &["frontend"])).unwrap();
+        save_agent_metadata(&config, &test_agent(&config, "api", &[])).unwrap();
+
+        // A directory without metadata isn't an agent; unreadable metadata is skipped
+        fs::create_dir_all(temp_dir.path().join("scratch")).unwrap();
+        let broken = temp_dir.path().join("broken").join(".crowdcontrol");
+        fs::create_dir_all(&broken).unwrap();
+        fs::write(broken.join("metadata.json"), "not json").unwrap();
+
+        let manager = AgentManager::new(config);
+        let agents = manager.list_metadata().unwrap();
+        let names: Vec<&str> = agents.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(names, vec!["api", "web"]);
+        assert_eq!(agents[1].tags, vec!["frontend"]);
+        assert_eq!(agents[1].memory.as_deref(), Some("2g"));
+
+        // Without containers (or without Docker at all) every agent is still listed
+        let entries = manager.list().await.unwrap();
+        let names: Vec<&str> = entries.iter().map(|e| e.agent.name.as_str()).collect();
+        assert_eq!(names, vec!["api", "web"]);
+        assert!(entries.iter().all(|entry| entry.container.is_none()));
+    }
+
+    #[tokio::test]
+    async fn test_list_empty_workspaces_dir() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = AgentManager::new(test_config(&temp_dir.path().join("missing")));
+        assert!(manager.list_metadata().unwrap().is_empty());
+        assert!(manager.list().await.unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_prepare_workspace_rejects_existing_agent() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = test_config(temp_dir.path());
+        save_agent_metadata(&config, &test_agent(&config, "web", &[])).unwrap();
+
+        let manager = AgentManager::new(config);
+        let error = manager.prepare_workspace("web").unwrap_err();
+        assert_eq!(
+            CrowdControlError::find(&error),
+            Some(&CrowdControlError::AgentAlreadyExists("web".to_string()))
+        );
+
+        let path = manager.prepare_workspace("api").unwrap();
+        assert!(path.is_dir());
+    }
+}
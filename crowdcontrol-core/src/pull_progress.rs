@@ -0,0 +1,218 @@
+use bollard::models::CreateImageInfo;
+use indicatif::HumanBytes;
+use std::collections::HashMap;
+
+/// Download progress is reported in line mode each time a layer crosses one of these steps
+const PROGRESS_STEP_PERCENT: u64 = 25;
+
+#[derive(Debug, Default)]
+struct LayerState {
+    status: String,
+    current: u64,
+    total: u64,
+    /// Last progress step reported for the layer, in percent
+    reported_percent: u64,
+}
+
+/// Tracks the per-layer state of an image pull stream.
+///
+/// Events are keyed by the layer `id` in each `CreateImageInfo`. The tracker can
+/// render an aggregate byte count for a progress bar, or line-based updates for
+/// non-interactive output such as CI logs.
+#[derive(Debug, Default)]
+pub struct PullProgress {
+    layers: HashMap<String, LayerState>,
+}
+
+impl PullProgress {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a pull event and return a line worth printing in non-interactive output.
+    /// Returns `None` for repeated progress updates that don't cross a reporting step.
+    pub fn update(&mut self, info: &CreateImageInfo) -> Option<String> {
+        if let Some(error) = &info.error {
+            return Some(format!("Error: {}", error));
+        }
+
+        let status = info.status.as_deref()?;
+
+        // Events without a layer id describe the whole image (e.g. digest, final status)
+        let id = match info.id.as_deref() {
+            Some(id) if !is_image_level_status(status) => id,
+            _ => return Some(status.to_string()),
+        };
+
+        let layer = self.layers.entry(id.to_string()).or_default();
+
+        if status == "Downloading" {
+            if let Some(detail) = &info.progress_detail {
+                layer.current = detail.current.unwrap_or(0).max(0) as u64;
+                layer.total = detail.total.unwrap_or(0).max(0) as u64;
+            }
+        } else if is_download_finished(status) {
+            // Layers that exist locally or finished downloading count as fully transferred
+            layer.current = layer.total;
+        }
+
+        let status_changed = layer.status != status;
+        layer.status = status.to_string();
+
+        if status == "Downloading" && layer.total > 0 {
+            let percent = layer.current * 100 / layer.total;
+            let step = percent / PROGRESS_STEP_PERCENT * PROGRESS_STEP_PERCENT;
+            if status_changed || step > layer.reported_percent {
+                layer.reported_percent = step;
+                return Some(format!(
+                    "{}: Downloading {}% ({} / {})",
+                    id,
+                    percent,
+                    HumanBytes(layer.current),
+                    HumanBytes(layer.total)
+                ));
+            }
+            return None;
+        }
+
+        if status_changed {
+            Some(format!("{}: {}", id, status))
+        } else {
+            None
+        }
+    }
+
+    /// Bytes downloaded and total bytes known so far across all layers
+    pub fn bytes(&self) -> (u64, u64) {
+        self.layers
+            .values()
+            .fold((0, 0), |(current, total), layer| {
+                (current + layer.current, total + layer.total)
+            })
+    }
+
+    /// Short summary of layer completion, e.g. "3/5 layers"
+    pub fn summary(&self) -> String {
+        let done = self
+            .layers
+            .values()
+            .filter(|layer| is_layer_complete(&layer.status))
+            .count();
+        format!("{}/{} layers", done, self.layers.len())
+    }
+}
+
+/// Statuses that carry the image reference in `id` rather than a layer id
+fn is_image_level_status(status: &str) -> bool {
+    status.starts_with("Pulling from")
+        || status.starts_with("Digest:")
+        || status.starts_with("Status:")
+}
+
+fn is_download_finished(status: &str) -> bool {
+    matches!(
+        status,
+        "Download complete"
+            | "Verifying Checksum"
+            | "Extracting"
+            | "Pull complete"
+            | "Already exists"
+    )
+}
+
+fn is_layer_complete(status: &str) -> bool {
+    matches!(status, "Pull complete" | "Already exists")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bollard::models::ProgressDetail;
+
+    fn event(id: Option<&str>, status: &str, progress: Option<(i64, i64)>) -> CreateImageInfo {
+        CreateImageInfo {
+            id: id.map(str::to_string),
+            status: Some(status.to_string()),
+            progress_detail: progress.map(|(current, total)| ProgressDetail {
+                current: Some(current),
+                total: Some(total),
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_line_output_for_pull_stream() {
+        let stream = vec![
+            event(Some("latest"), "Pulling from library/crowdcontrol", None),
+            event(Some("aaa"), "Pulling fs layer", None),
+            event(Some("bbb"), "Already exists", None),
+            event(Some("aaa"), "Downloading", Some((10, 1000))),
+            event(Some("aaa"), "Downloading", Some((20, 1000))),
+            event(Some("aaa"), "Downloading", Some((300, 1000))),
+            event(Some("aaa"), "Downloading", Some((310, 1000))),
+            event(Some("aaa"), "Download complete", None),
+            event(Some("aaa"), "Extracting", Some((500, 1000))),
+            event(Some("aaa"), "Extracting", Some((1000, 1000))),
+            event(Some("aaa"), "Pull complete", None),
+            event(None, "Digest: sha256:abc", None),
+            event(
+                None,
+                "Status: Downloaded newer image for crowdcontrol:latest",
+                None,
+            ),
+        ];
+
+        let mut progress = PullProgress::new();
+        let lines: Vec<String> = stream
+            .iter()
+            .filter_map(|info| progress.update(info))
+            .collect();
+
+        assert_eq!(
+            lines,
+            vec![
+                "Pulling from library/crowdcontrol",
+                "aaa: Pulling fs layer",
+                "bbb: Already exists",
+                "aaa: Downloading 1% (10 B / 1000 B)",
+                "aaa: Downloading 30% (300 B / 1000 B)",
+                "aaa: Download complete",
+                "aaa: Extracting",
+                "aaa: Pull complete",
+                "Digest: sha256:abc",
+                "Status: Downloaded newer image for crowdcontrol:latest",
+            ]
+        );
+
+        // No carriage returns in line mode
+        assert!(lines.iter().all(|line| !line.contains('\r')));
+    }
+
+    #[test]
+    fn test_aggregate_bytes_and_summary() {
+        let mut progress = PullProgress::new();
+        progress.update(&event(Some("aaa"), "Downloading", Some((250, 1000))));
+        progress.update(&event(Some("bbb"), "Downloading", Some((100, 500))));
+        assert_eq!(progress.bytes(), (350, 1500));
+        assert_eq!(progress.summary(), "0/2 layers");
+
+        progress.update(&event(Some("bbb"), "Download complete", None));
+        progress.update(&event(Some("bbb"), "Pull complete", None));
+        assert_eq!(progress.bytes(), (750, 1500));
+        assert_eq!(progress.summary(), "1/2 layers");
+    }
+
+    #[test]
+    fn test_error_events_are_reported() {
+        let mut progress = PullProgress::new();
+        let info = CreateImageInfo {
+            error: Some("manifest unknown".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            progress.update(&info),
+            Some("Error: manifest unknown".to_string())
+        );
+    }
+}
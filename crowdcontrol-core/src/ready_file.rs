@@ -0,0 +1,45 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// Outcome of a background readiness wait (`start --wait --ready-file`), written as
+/// `{"ready": true}` or `{"ready": false, "error": "..."}`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReadyStatus {
+    pub ready: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl ReadyStatus {
+    pub fn from_result(result: &Result<()>) -> Self {
+        match result {
+            Ok(()) => Self {
+                ready: true,
+                error: None,
+            },
+            Err(e) => Self {
+                ready: false,
+                error: Some(format!("{:#}", e)),
+            },
+        }
+    }
+}
+
+/// Write `status` to `path`. The file is written next to its destination and renamed
+/// into place, so anyone watching `path` only ever sees a complete status.
+pub fn write_ready_file(path: &Path, status: &ReadyStatus) -> Result<()> {
+    let file_name = path
+        .file_name()
+        .with_context(|| format!("Invalid ready file path: {:?}", path))?;
+    let mut temp_name = file_name.to_os_string();
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+
+    fs::write(&temp_path, serde_json::to_string(status)?)
+        .with_context(|| format!("Failed to write ready file: {:?}", temp_path))?;
+    fs::rename(&temp_path, path)
+        .with_context(|| format!("Failed to write ready file: {:?}", path))?;
+    Ok(())
+}
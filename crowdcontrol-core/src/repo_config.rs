@@ -0,0 +1,125 @@
+use anyhow::{Context, Result};
+use config::{Config as ConfigBuilder, File};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use tracing::{debug, trace, warn};
+
+/// Repository-specific configuration read from `.crowdcontrol/config.toml`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RepoConfig {
+    /// Shell command used as the container's Docker health check
+    #[serde(default)]
+    pub healthcheck: Option<String>,
+
+    /// Shell command run once in `/workspace` after the agent's container is created
+    /// and first started
+    #[serde(default)]
+    pub post_create_command: Option<String>,
+
+    /// Shell command run in `/workspace` every time the agent starts
+    #[serde(default)]
+    pub post_start_command: Option<String>,
+}
+
+impl RepoConfig {
+    /// Load the repository config from a workspace, falling back to defaults
+    /// when the repository doesn't provide one. Lifecycle commands not set in
+    /// `config.toml` are taken from a dev container definition, if there is one.
+    pub fn load(workspace_path: &Path) -> Result<Self> {
+        let config_path = workspace_path.join(".crowdcontrol").join("config.toml");
+
+        let mut repo_config = if config_path.exists() {
+            debug!("Loading repository config from {:?}", config_path);
+
+            ConfigBuilder::builder()
+                .add_source(File::from(config_path.clone()))
+                .build()
+                .with_context(|| format!("Failed to load repository config: {:?}", config_path))?
+                .try_deserialize()
+                .with_context(|| format!("Failed to parse repository config: {:?}", config_path))?
+        } else {
+            debug!("No repository config found at {:?}", config_path);
+            Self::default()
+        };
+
+        if let Some(devcontainer) = DevcontainerCommands::load(workspace_path) {
+            repo_config.post_create_command = repo_config
+                .post_create_command
+                .or(devcontainer.post_create_command);
+            repo_config.post_start_command = repo_config
+                .post_start_command
+                .or(devcontainer.post_start_command);
+        }
+
+        trace!("Loaded repository config: {:?}", repo_config);
+        Ok(repo_config)
+    }
+
+    /// Whether the repository declares commands to run when the agent starts
+    pub fn has_lifecycle_commands(&self) -> bool {
+        self.post_create_command.is_some() || self.post_start_command.is_some()
+    }
+}
+
+/// Lifecycle commands from a VS Code dev container definition
+#[derive(Debug, Default)]
+struct DevcontainerCommands {
+    post_create_command: Option<String>,
+    post_start_command: Option<String>,
+}
+
+impl DevcontainerCommands {
+    /// Read `.devcontainer/devcontainer.json` or `.devcontainer.json`. The file belongs
+    /// to another tool, so anything crowdcontrol can't use is logged and skipped rather
+    /// than failing the agent.
+    fn load(workspace_path: &Path) -> Option<Self> {
+        let path = [
+            workspace_path
+                .join(".devcontainer")
+                .join("devcontainer.json"),
+            workspace_path.join(".devcontainer.json"),
+        ]
+        .into_iter()
+        .find(|path| path.exists())?;
+
+        debug!("Loading dev container lifecycle commands from {:?}", path);
+        let document: serde_json::Value = match fs::read_to_string(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|contents| Ok(serde_json::from_str(&contents)?))
+        {
+            Ok(document) => document,
+            Err(e) => {
+                warn!("Ignoring {:?}: {} (comments aren't supported)", path, e);
+                return None;
+            }
+        };
+
+        let command = |key: &str| match document.get(key)? {
+            serde_json::Value::String(command) => Some(command.clone()),
+            // The array form runs without a shell, so quote each argument
+            serde_json::Value::Array(args) => args
+                .iter()
+                .map(|arg| arg.as_str().map(shell_quote))
+                .collect::<Option<Vec<_>>>()
+                .map(|args| args.join(" ")),
+            _ => {
+                warn!(
+                    "Ignoring {} in {:?}: only strings and arrays are supported",
+                    key, path
+                );
+                None
+            }
+        };
+
+        Some(Self {
+            post_create_command: command("postCreateCommand"),
+            post_start_command: command("postStartCommand"),
+        })
+    }
+}
+
+/// Quote `arg` for bash so it is passed through as a single word
+fn shell_quote(arg: &str) -> String {
+    format!("'{}'", arg.replace('\'', r"'\''"))
+}
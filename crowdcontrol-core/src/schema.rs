@@ -0,0 +1,15 @@
+use anyhow::Result;
+use schemars::schema_for;
+use serde_json::Value;
+
+use crate::{AgentMetadata, Settings};
+
+/// JSON Schema for an agent's `.crowdcontrol/metadata.json`
+pub fn metadata_schema() -> Result<Value> {
+    Ok(serde_json::to_value(schema_for!(AgentMetadata))?)
+}
+
+/// JSON Schema for the settings file (`config.toml`)
+pub fn settings_schema() -> Result<Value> {
+    Ok(serde_json::to_value(schema_for!(Settings))?)
+}
@@ -1,10 +1,16 @@
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
 use config::{Config as ConfigBuilder, Environment, File};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::env;
+use std::fmt;
+use std::path::{Path, PathBuf};
 use tracing::{debug, info, trace};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use crate::WorkspaceLayout;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Settings {
     /// Directory for storing agent workspaces
     #[serde(default = "default_workspaces_dir")]
@@ -22,6 +28,41 @@ pub struct Settings {
     #[serde(default)]
     pub default_cpus: Option<String>,
 
+    /// Default container platform for agents (e.g. linux/amd64)
+    #[serde(default)]
+    pub default_platform: Option<String>,
+
+    /// Mount the host's Claude credentials into new agents
+    #[serde(default = "default_mount_claude_config")]
+    pub mount_claude_config: bool,
+
+    /// Command `connect` runs when neither --command nor --shell is given
+    /// (defaults to `claude --dangerously-skip-permissions`)
+    #[serde(default)]
+    pub connect_default_command: Option<String>,
+
+    /// Prefix of agent container names, `<prefix>-<agent>`; set a different one per user
+    /// when several share a Docker host
+    #[serde(default = "default_container_prefix")]
+    pub container_prefix: String,
+
+    /// Make `new` fail, rather than warn, when a repository has no `.crowdcontrol/` directory
+    #[serde(default)]
+    pub require_crowdcontrol: bool,
+
+    /// macOS keychain service `refresh --extract-keychain` reads Claude Code's credentials from
+    #[serde(default = "default_keychain_service")]
+    pub keychain_service: String,
+
+    /// Where new agents' repositories go: `flat` clones into `workspaces_dir/<name>`,
+    /// `nested` into `workspaces_dir/<name>/<name>`
+    #[serde(default)]
+    pub workspace_layout: WorkspaceLayout,
+
+    /// Start a created or stopped agent on `connect` instead of failing, as `--auto-start` does
+    #[serde(default)]
+    pub connect_auto_start: bool,
+
     /// Verbosity level
     #[serde(default)]
     pub verbose: u8,
@@ -34,6 +75,14 @@ impl Default for Settings {
             image: default_image(),
             default_memory: None,
             default_cpus: None,
+            default_platform: None,
+            mount_claude_config: default_mount_claude_config(),
+            connect_default_command: None,
+            container_prefix: default_container_prefix(),
+            require_crowdcontrol: false,
+            keychain_service: default_keychain_service(),
+            workspace_layout: WorkspaceLayout::default(),
+            connect_auto_start: false,
             verbose: 0,
         }
     }
@@ -45,19 +94,31 @@ impl Settings {
     /// Priority order (highest to lowest):
     /// 1. CLI arguments (handled by caller)
     /// 2. Environment variables (CROWDCONTROL_*)
-    /// 3. Config file (~/.config/crowdcontrol/config.toml)
+    /// 3. Config file (~/.config/crowdcontrol/config.toml, config.yaml or config.json)
     /// 4. Default values
     pub fn load() -> Result<Self> {
         debug!("Loading settings from configuration sources");
 
-        let config_path = dirs::config_dir()
-            .map(|p| p.join("crowdcontrol/config.toml"))
-            .filter(|p| p.exists());
+        Self::load_from_dir(config_dir().as_deref())
+    }
+
+    /// Load settings using the config files found in `config_dir` (if any)
+    pub fn load_from_dir(config_dir: Option<&Path>) -> Result<Self> {
+        Self::load_from_files(&config_dir.map(find_config_files).unwrap_or_default())
+    }
 
-        if let Some(ref path) = config_path {
-            info!("Found config file at: {:?}", path);
-        } else {
+    /// Load settings from exactly this config file instead of searching for one
+    pub fn load_from_file(path: &Path) -> Result<Self> {
+        Self::load_from_files(&resolve_config_files(Some(path))?)
+    }
+
+    /// Load settings from `config_files`, highest precedence first, and the environment
+    fn load_from_files(config_files: &[PathBuf]) -> Result<Self> {
+        if config_files.is_empty() {
             debug!("No config file found, using defaults and environment variables");
+        } else {
+            info!("Found config files: {:?}", config_files);
+            check_for_conflicts(config_files)?;
         }
 
         let mut builder = ConfigBuilder::builder()
@@ -65,11 +126,12 @@ impl Settings {
                 "workspaces_dir",
                 default_workspaces_dir().to_string_lossy().to_string(),
             )?
-            .set_default("image", default_image())?;
+            .set_default("image", default_image())?
+            .set_default("container_prefix", default_container_prefix())?;
 
-        // Add config file if it exists
-        if let Some(path) = config_path {
-            builder = builder.add_source(File::from(path));
+        // Add config files, lowest precedence first so earlier names win
+        for path in config_files.iter().rev() {
+            builder = builder.add_source(File::from(path.as_path()));
         }
 
         // Add environment variables with CROWDCONTROL_ prefix
@@ -79,23 +141,76 @@ impl Settings {
                 .try_parsing(true),
         );
 
-        let settings = builder
+        let mut settings: Settings = builder
             .build()
             .context("Failed to load configuration")?
             .try_deserialize()
             .context("Failed to parse configuration")?;
 
+        // CI machines opt out of sharing credentials with a single variable
+        if no_claude_mount_from_env() {
+            debug!("{} is set, not mounting Claude config", NO_CLAUDE_MOUNT_ENV);
+            settings.mount_claude_config = false;
+        }
+
+        // The generic mapping would read CROWDCONTROL_CONNECT_DEFAULT_COMMAND; use the shorter name
+        if let Some(command) = connect_command_from_env() {
+            debug!("Using connect command from {}", CONNECT_COMMAND_ENV);
+            settings.connect_default_command = Some(command);
+        }
+
+        // Multi-word keys don't survive the generic mapping, which splits on "_"
+        if let Some(prefix) = env::var(CONTAINER_PREFIX_ENV)
+            .ok()
+            .filter(|prefix| !prefix.trim().is_empty())
+        {
+            debug!("Using container prefix from {}", CONTAINER_PREFIX_ENV);
+            settings.container_prefix = prefix;
+        }
+        if let Some(require) = require_crowdcontrol_from_env() {
+            debug!(
+                "Using require_crowdcontrol from {}",
+                REQUIRE_CROWDCONTROL_ENV
+            );
+            settings.require_crowdcontrol = require;
+        }
+        if let Some(service) = env::var(KEYCHAIN_SERVICE_ENV)
+            .ok()
+            .filter(|service| !service.trim().is_empty())
+        {
+            debug!("Using keychain service from {}", KEYCHAIN_SERVICE_ENV);
+            settings.keychain_service = service;
+        }
+        if let Some(layout) = env::var(WORKSPACE_LAYOUT_ENV)
+            .ok()
+            .filter(|layout| !layout.trim().is_empty())
+        {
+            debug!("Using workspace layout from {}", WORKSPACE_LAYOUT_ENV);
+            settings.workspace_layout = layout
+                .parse()
+                .with_context(|| format!("Invalid {}", WORKSPACE_LAYOUT_ENV))?;
+        }
+        if let Some(auto_start) = connect_auto_start_from_env() {
+            debug!("Using connect_auto_start from {}", CONNECT_AUTO_START_ENV);
+            settings.connect_auto_start = auto_start;
+        }
+
         trace!("Loaded settings: {:?}", settings);
         Ok(settings)
     }
 
-    /// Create settings with CLI overrides
+    /// Create settings with CLI overrides, reading `config_file` instead of the
+    /// standard locations when one is given
     pub fn with_overrides(
+        config_file: Option<&Path>,
         workspaces_dir: Option<PathBuf>,
         image: Option<String>,
         verbose: u8,
     ) -> Result<Self> {
-        let mut settings = Self::load()?;
+        let mut settings = match config_file {
+            Some(path) => Self::load_from_file(path)?,
+            None => Self::load()?,
+        };
 
         // Apply CLI overrides
         if let Some(dir) = workspaces_dir {
@@ -115,6 +230,196 @@ impl Settings {
     }
 }
 
+/// Where a setting's effective value came from
+#[derive(Debug, Clone, PartialEq)]
+pub enum SettingSource {
+    Default,
+    File(PathBuf),
+    Env,
+    Cli,
+}
+
+impl fmt::Display for SettingSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SettingSource::Default => write!(f, "default"),
+            SettingSource::File(path) => write!(f, "file ({})", path.display()),
+            SettingSource::Env => write!(f, "env"),
+            SettingSource::Cli => write!(f, "cli"),
+        }
+    }
+}
+
+/// Every setting key, in display order
+pub const SETTING_KEYS: [&str; 13] = [
+    "workspaces_dir",
+    "image",
+    "default_memory",
+    "default_cpus",
+    "default_platform",
+    "mount_claude_config",
+    "connect_default_command",
+    "container_prefix",
+    "require_crowdcontrol",
+    "keychain_service",
+    "workspace_layout",
+    "connect_auto_start",
+    "verbose",
+];
+
+/// Environment variable that turns off mounting Claude credentials, e.g. on CI
+pub const NO_CLAUDE_MOUNT_ENV: &str = "CROWDCONTROL_NO_CLAUDE_MOUNT";
+
+/// Whether `CROWDCONTROL_NO_CLAUDE_MOUNT` is set to anything but an empty, `0` or `false` value
+fn no_claude_mount_from_env() -> bool {
+    env::var(NO_CLAUDE_MOUNT_ENV)
+        .is_ok_and(|value| !matches!(value.to_lowercase().as_str(), "" | "0" | "false"))
+}
+
+/// Environment variable overriding the command `connect` runs by default
+pub const CONNECT_COMMAND_ENV: &str = "CROWDCONTROL_CONNECT_COMMAND";
+
+/// Environment variable setting the container name prefix
+pub const CONTAINER_PREFIX_ENV: &str = "CROWDCONTROL_CONTAINER_PREFIX";
+
+/// Environment variable making a missing `.crowdcontrol/` directory an error
+pub const REQUIRE_CROWDCONTROL_ENV: &str = "CROWDCONTROL_REQUIRE_CROWDCONTROL";
+
+/// Environment variable naming the keychain service Claude Code's credentials are under
+pub const KEYCHAIN_SERVICE_ENV: &str = "CROWDCONTROL_KEYCHAIN_SERVICE";
+
+/// Environment variable choosing the workspace layout, `flat` or `nested`
+pub const WORKSPACE_LAYOUT_ENV: &str = "CROWDCONTROL_WORKSPACE_LAYOUT";
+
+/// Environment variable making `connect` start agents that aren't running
+pub const CONNECT_AUTO_START_ENV: &str = "CROWDCONTROL_CONNECT_AUTO_START";
+
+/// `CROWDCONTROL_REQUIRE_CROWDCONTROL`, unless unset or blank; `0` and `false` turn it off
+fn require_crowdcontrol_from_env() -> Option<bool> {
+    env::var(REQUIRE_CROWDCONTROL_ENV)
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+        .map(|value| !matches!(value.to_lowercase().as_str(), "0" | "false"))
+}
+
+/// `CROWDCONTROL_CONNECT_AUTO_START`, unless unset or blank; `0` and `false` turn it off
+fn connect_auto_start_from_env() -> Option<bool> {
+    env::var(CONNECT_AUTO_START_ENV)
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+        .map(|value| !matches!(value.to_lowercase().as_str(), "0" | "false"))
+}
+
+/// `CROWDCONTROL_CONNECT_COMMAND`, unless unset or blank
+fn connect_command_from_env() -> Option<String> {
+    env::var(CONNECT_COMMAND_ENV)
+        .ok()
+        .filter(|command| !command.trim().is_empty())
+}
+
+/// Work out where each setting came from, following the same precedence as `Settings::load`.
+/// This is a reconstruction: `cli_keys` names the settings passed as command-line flags,
+/// environment variables are detected by name and `config_files` (as given by
+/// [`resolve_config_files`]) are re-read.
+pub fn setting_sources(
+    config_files: &[PathBuf],
+    cli_keys: &[&str],
+) -> Result<Vec<(&'static str, SettingSource)>> {
+    let mut files = Vec::new();
+    for path in config_files {
+        let values = read_config_file(path)?;
+        files.push((path.clone(), values));
+    }
+
+    Ok(SETTING_KEYS
+        .iter()
+        .map(|&key| {
+            let source = if cli_keys.contains(&key) {
+                SettingSource::Cli
+            } else if env::var_os(format!("CROWDCONTROL_{}", key.to_uppercase())).is_some()
+                || (key == "mount_claude_config" && no_claude_mount_from_env())
+                || (key == "connect_default_command" && connect_command_from_env().is_some())
+            {
+                SettingSource::Env
+            } else if let Some((path, _)) =
+                files.iter().find(|(_, values)| values.contains_key(key))
+            {
+                SettingSource::File(path.clone())
+            } else {
+                SettingSource::Default
+            };
+            (key, source)
+        })
+        .collect())
+}
+
+/// Directory holding CrowdControl's config files (`~/.config/crowdcontrol` on Linux)
+pub fn config_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|p| p.join("crowdcontrol"))
+}
+
+/// Config file names looked for in the config directory, highest precedence first
+pub const CONFIG_FILE_NAMES: [&str; 3] = ["config.toml", "config.yaml", "config.json"];
+
+/// Existing config files in `dir`, highest precedence first
+pub fn find_config_files(dir: &Path) -> Vec<PathBuf> {
+    CONFIG_FILE_NAMES
+        .iter()
+        .map(|name| dir.join(name))
+        .filter(|path| path.is_file())
+        .collect()
+}
+
+/// The config files settings are read from: `explicit` alone when given (it must exist),
+/// otherwise those found in the config directory, highest precedence first
+pub fn resolve_config_files(explicit: Option<&Path>) -> Result<Vec<PathBuf>> {
+    match explicit {
+        Some(path) if path.is_file() => Ok(vec![path.to_path_buf()]),
+        Some(path) => Err(anyhow!("Config file {} does not exist", path.display())),
+        None => Ok(config_dir()
+            .map(|dir| find_config_files(&dir))
+            .unwrap_or_default()),
+    }
+}
+
+/// Several config files may be used together, but they must not set the same
+/// option to different values; that is almost certainly a stale file.
+fn check_for_conflicts(config_files: &[PathBuf]) -> Result<()> {
+    let mut seen: HashMap<String, (serde_json::Value, &Path)> = HashMap::new();
+
+    for path in config_files {
+        for (key, value) in read_config_file(path)? {
+            match seen.get(&key) {
+                Some((existing, existing_path)) if *existing != value => {
+                    return Err(anyhow!(
+                        "Conflicting values for '{}' in {:?} ({}) and {:?} ({}). \
+                         Remove the setting from one of the files.",
+                        key,
+                        existing_path,
+                        existing,
+                        path,
+                        value
+                    ));
+                }
+                Some(_) => {}
+                None => {
+                    seen.insert(key, (value, path.as_path()));
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn read_config_file(path: &Path) -> Result<HashMap<String, serde_json::Value>> {
+    ConfigBuilder::builder()
+        .add_source(File::from(path))
+        .build()
+        .and_then(|config| config.try_deserialize())
+        .with_context(|| format!("Failed to load configuration from {:?}", path))
+}
+
 fn default_workspaces_dir() -> PathBuf {
     dirs::home_dir()
         .expect("Unable to determine home directory")
@@ -124,3 +429,15 @@ fn default_workspaces_dir() -> PathBuf {
 fn default_image() -> String {
     "crowdcontrol:latest".to_string()
 }
+
+fn default_mount_claude_config() -> bool {
+    true
+}
+
+fn default_container_prefix() -> String {
+    "crowdcontrol".to_string()
+}
+
+fn default_keychain_service() -> String {
+    crate::DEFAULT_KEYCHAIN_SERVICE.to_string()
+}
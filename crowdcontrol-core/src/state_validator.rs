@@ -1,7 +1,10 @@
-use crate::agent::{list_all_agents, load_agent_metadata};
-use crate::{AgentStatus, Config, DockerClient};
-use anyhow::Result;
-use std::collections::HashSet;
+use crate::agent::{list_all_agents, load_agent_metadata, update_agent_metadata};
+use crate::{
+    agent_name_of, status_of, tags_from_labels, AgentStatus, Config, ContainerOptions,
+    DockerClient, RepoConfig,
+};
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
 use tracing::{debug, error, info, warn};
 
 /// Represents various inconsistencies that can occur in the system
@@ -11,12 +14,17 @@ pub enum StateInconsistency {
     MissingWorkspace { agent_name: String },
 
     /// Container exists but metadata is missing
-    OrphanedContainer { container_name: String },
+    OrphanedContainer {
+        container_name: String,
+        /// Tags recovered from the container's labels
+        tags: Vec<String>,
+    },
 
-    /// Metadata says running but container doesn't exist
+    /// Metadata says running or paused, or records a container ID, but the
+    /// container doesn't exist
     MissingContainer { agent_name: String },
 
-    /// Metadata says stopped but container is running
+    /// Metadata status doesn't match the container's state
     IncorrectStatus {
         agent_name: String,
         expected: AgentStatus,
@@ -40,6 +48,18 @@ pub enum StateInconsistency {
     CorruptedMetadata { agent_name: String, error: String },
 }
 
+/// Controls how far `repair_inconsistencies` goes beyond metadata fixes
+#[derive(Debug, Clone, Default)]
+pub struct RepairOptions {
+    /// Recreate missing containers from the agent's stored settings instead of
+    /// marking the agent stopped (`doctor --repair --recreate`)
+    pub recreate_containers: bool,
+    /// Remove stopped containers that have no agent metadata (`doctor --repair --prune-orphans`)
+    pub prune_orphans: bool,
+    /// Also remove orphaned containers that are still running or paused
+    pub force: bool,
+}
+
 /// Validates the consistency of the entire system state
 pub struct StateValidator {
     config: Config,
@@ -66,12 +86,12 @@ impl StateValidator {
 
         // Get all containers from Docker
         let containers = self.docker_client.list_all_containers().await?;
-        let container_names: HashSet<String> = containers
+        let container_names: HashMap<String, Vec<String>> = containers
             .iter()
-            .filter_map(|c| c.names.as_ref()?.first())
-            .filter_map(|name| {
-                // Container names start with "/crowdcontrol-"
-                name.strip_prefix("/crowdcontrol-").map(|s| s.to_string())
+            .filter_map(|c| {
+                let name = agent_name_of(c, &self.config)?;
+                let tags = c.labels.as_ref().map(tags_from_labels).unwrap_or_default();
+                Some((name, tags))
             })
             .collect();
 
@@ -89,9 +109,12 @@ impl StateValidator {
         }
 
         // Check for orphaned containers (containers without metadata)
-        for container_name in container_names {
+        for (container_name, tags) in container_names {
             if !agent_names.contains(&container_name) {
-                inconsistencies.push(StateInconsistency::OrphanedContainer { container_name });
+                inconsistencies.push(StateInconsistency::OrphanedContainer {
+                    container_name,
+                    tags,
+                });
             }
         }
 
@@ -115,30 +138,53 @@ impl StateValidator {
         // Get container details from Docker for validation
         let container_info = self
             .docker_client
-            .find_container_details(&format!("crowdcontrol-{}", agent_name))
+            .find_container_details(&self.config.container_name(agent_name))
             .await?;
 
-        match (agent.status, &container_info) {
-            (AgentStatus::Running, &None) => {
+        match (&agent.status, &container_info) {
+            (
+                AgentStatus::Running
+                | AgentStatus::Starting
+                | AgentStatus::Restarting
+                | AgentStatus::Paused,
+                None,
+            ) => {
                 // Metadata says running but no container exists
                 inconsistencies.push(StateInconsistency::MissingContainer {
                     agent_name: agent_name.to_string(),
                 });
             }
-            (AgentStatus::Stopped, &Some(ref status)) if status.is_running => {
+            (_, None) if agent.container_id.is_some() => {
+                // Status isn't persisted, so a recorded container ID is what
+                // marks an agent whose container has disappeared
+                inconsistencies.push(StateInconsistency::MissingContainer {
+                    agent_name: agent_name.to_string(),
+                });
+            }
+            (AgentStatus::Stopped, Some(info)) if info.status.is_running() => {
                 // Metadata says stopped but container is running
                 inconsistencies.push(StateInconsistency::IncorrectStatus {
                     agent_name: agent_name.to_string(),
                     expected: AgentStatus::Stopped,
-                    actual: AgentStatus::Running,
+                    actual: info.status.clone(),
                 });
             }
-            (AgentStatus::Running, &Some(ref status)) if !status.is_running => {
-                // Metadata says running but container is stopped
+            (expected @ (AgentStatus::Running | AgentStatus::Starting), Some(info))
+                if !info.status.is_running() =>
+            {
+                // Metadata says running but container is paused, restarting or stopped
                 inconsistencies.push(StateInconsistency::IncorrectStatus {
                     agent_name: agent_name.to_string(),
-                    expected: AgentStatus::Running,
-                    actual: AgentStatus::Stopped,
+                    expected: expected.clone(),
+                    actual: info.status.clone(),
+                });
+            }
+            (AgentStatus::Paused, Some(info)) if info.status != AgentStatus::Paused => {
+                // Metadata says paused but container has been resumed or stopped
+                inconsistencies.push(StateInconsistency::IncorrectStatus {
+                    agent_name: agent_name.to_string(),
+                    expected: AgentStatus::Paused,
+                    actual: info.status.clone(),
                 });
             }
             _ => {}
@@ -160,7 +206,7 @@ impl StateValidator {
         // Check for duplicate containers
         let matching_containers = self
             .docker_client
-            .find_containers_by_name(&format!("crowdcontrol-{}", agent_name))
+            .find_containers_by_name(&self.config.container_name(agent_name))
             .await?;
 
         if matching_containers.len() > 1 {
@@ -180,9 +226,8 @@ impl StateValidator {
     pub async fn repair_inconsistencies(
         &self,
         inconsistencies: Vec<StateInconsistency>,
+        options: &RepairOptions,
     ) -> Result<()> {
-        use crate::agent::update_agent_metadata;
-
         info!(
             "Attempting to repair {} inconsistencies",
             inconsistencies.len()
@@ -197,15 +242,47 @@ impl StateValidator {
                     );
                 }
 
-                StateInconsistency::OrphanedContainer { container_name } => {
-                    warn!(
-                        "Found orphaned container '{}'. Consider removing it manually.",
-                        container_name
-                    );
-                    // Could auto-remove: self.docker_client.remove_container(&format!("crowdcontrol-{}", container_name)).await?;
+                StateInconsistency::OrphanedContainer { container_name, .. } => {
+                    if !options.prune_orphans {
+                        warn!(
+                            "Found orphaned container '{}'. Consider removing it manually.",
+                            container_name
+                        );
+                        continue;
+                    }
+
+                    match self.prune_orphan(&container_name, options.force).await {
+                        Ok(()) => info!(
+                            "Fixed: Removed orphaned container '{}'",
+                            self.config.container_name(&container_name)
+                        ),
+                        Err(e) => warn!(
+                            "Could not remove orphaned container '{}': {:#}",
+                            self.config.container_name(&container_name),
+                            e
+                        ),
+                    }
                 }
 
                 StateInconsistency::MissingContainer { agent_name } => {
+                    if options.recreate_containers {
+                        match self.recreate_container(&agent_name).await {
+                            Ok(container_id) => {
+                                info!(
+                                    "Fixed: Recreated container {} for agent '{}'",
+                                    container_id, agent_name
+                                );
+                                continue;
+                            }
+                            Err(e) => {
+                                error!(
+                                    "Failed to recreate container for agent '{}': {:#}",
+                                    agent_name, e
+                                );
+                            }
+                        }
+                    }
+
                     // Update metadata to reflect container is stopped
                     debug!(
                         "Updating agent '{}' status to Stopped (container missing)",
@@ -273,13 +350,89 @@ impl StateValidator {
 
         Ok(())
     }
+
+    /// Remove the orphaned container `<prefix>-<name>`. Running or paused
+    /// containers are only removed with `force`.
+    async fn prune_orphan(&self, container_name: &str, force: bool) -> Result<()> {
+        let full_name = self.config.container_name(container_name);
+
+        // Only containers labelled app=crowdcontrol are listed here, so anything
+        // else that happens to share the name is never touched
+        let Some(info) = self
+            .docker_client
+            .find_container_details(&full_name)
+            .await?
+        else {
+            return Err(anyhow!(
+                "no container named {} with the app=crowdcontrol label",
+                full_name
+            ));
+        };
+
+        let running = matches!(
+            info.status,
+            AgentStatus::Running
+                | AgentStatus::Starting
+                | AgentStatus::Paused
+                | AgentStatus::Restarting
+        );
+        if running && !force {
+            return Err(anyhow!("it is still running; stop it first or add --force"));
+        }
+
+        debug!("Removing orphaned container {} ({})", full_name, info.id);
+        self.docker_client.remove_container(&info.id).await
+    }
+
+    /// Create a new container for an agent from its stored settings and record
+    /// its ID in the metadata. Returns the new container ID.
+    async fn recreate_container(&self, agent_name: &str) -> Result<String> {
+        let agent = load_agent_metadata(&self.config, agent_name)?;
+        let mount_path = agent.mount_path().to_path_buf();
+        if !mount_path.exists() {
+            return Err(anyhow!(
+                "Workspace {} no longer exists",
+                mount_path.display()
+            ));
+        }
+
+        // The health check comes from the repository config, as in `new`
+        let repo_config = RepoConfig::load(&agent.repo_path())?;
+        let options = ContainerOptions {
+            healthcheck: repo_config.healthcheck,
+            ..agent.container_options()
+        };
+
+        self.docker_client
+            .pull_image_for_platform(
+                self.docker_client.image_for(&options),
+                false,
+                agent.platform.as_deref(),
+            )
+            .await?;
+        debug!("Recreating container for agent '{}'", agent_name);
+        let container_id = self
+            .docker_client
+            .create_container_with_options(agent_name, &mount_path, &options)
+            .await?;
+
+        update_agent_metadata(&self.config, agent_name, |agent| {
+            agent.status = AgentStatus::Created;
+            agent.container_id = Some(container_id.clone());
+            agent.post_create_done = false;
+            Ok(())
+        })?;
+
+        Ok(container_id)
+    }
 }
 
 /// Container validation details
 #[derive(Debug)]
 pub struct ContainerValidationInfo {
     pub id: String,
-    pub is_running: bool,
+    /// Live status of the container
+    pub status: AgentStatus,
 }
 
 /// Extension methods for DockerClient to support state validation
@@ -295,8 +448,8 @@ impl DockerClient {
             if let Some(names) = &container.names {
                 if names.iter().any(|n| n == &format!("/{}", container_name)) {
                     return Ok(Some(ContainerValidationInfo {
+                        status: status_of(&container),
                         id: container.id.unwrap_or_default(),
-                        is_running: container.state == Some("running".to_string()),
                     }));
                 }
             }
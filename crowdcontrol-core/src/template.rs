@@ -0,0 +1,159 @@
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::{debug, trace};
+
+use crate::{parse_cpus, parse_duration, parse_memory_limit, validate_platform};
+
+/// File in the config directory that holds named presets for `new --template`
+pub const TEMPLATES_FILE_NAME: &str = "templates.toml";
+
+/// A named preset of `crowdcontrol new` options. Each top-level table in
+/// templates.toml is one template:
+///
+/// ```toml
+/// [backend]
+/// memory = "4g"
+/// cpus = "2"
+/// tags = ["backend"]
+/// env = { RUST_LOG = "debug" }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Template {
+    #[serde(default)]
+    pub memory: Option<String>,
+    #[serde(default)]
+    pub cpus: Option<String>,
+    #[serde(default)]
+    pub network: Option<String>,
+    #[serde(default)]
+    pub platform: Option<String>,
+    #[serde(default)]
+    pub idle_timeout: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Environment variables set in the container; like `--env-passthrough`
+    /// values they are not written to agent metadata
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// Host environment variables to forward, as with `--env-passthrough`
+    #[serde(default)]
+    pub env_passthrough: Vec<String>,
+}
+
+impl Template {
+    /// Layer options given on the command line over the template. Single values
+    /// from `overrides` win; tags, environment variables and passthrough names
+    /// are combined.
+    pub fn with_overrides(&self, overrides: &Template) -> Template {
+        let mut env = self.env.clone();
+        env.extend(overrides.env.clone());
+
+        Template {
+            memory: overrides.memory.clone().or_else(|| self.memory.clone()),
+            cpus: overrides.cpus.clone().or_else(|| self.cpus.clone()),
+            network: overrides.network.clone().or_else(|| self.network.clone()),
+            platform: overrides.platform.clone().or_else(|| self.platform.clone()),
+            idle_timeout: overrides
+                .idle_timeout
+                .clone()
+                .or_else(|| self.idle_timeout.clone()),
+            tags: combine(&self.tags, &overrides.tags),
+            env,
+            env_passthrough: combine(&self.env_passthrough, &overrides.env_passthrough),
+        }
+    }
+
+    /// The template's environment as `KEY=VALUE` entries
+    pub fn env_vars(&self) -> Vec<String> {
+        self.env
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect()
+    }
+
+    /// Check values with the same rules as the matching `new` flags
+    pub fn validate(&self) -> Result<()> {
+        if let Some(memory) = &self.memory {
+            parse_memory_limit(memory)?;
+        }
+        if let Some(cpus) = &self.cpus {
+            parse_cpus(cpus)?;
+        }
+        if let Some(platform) = &self.platform {
+            validate_platform(platform)?;
+        }
+        if let Some(idle_timeout) = &self.idle_timeout {
+            parse_duration(idle_timeout)?;
+        }
+        Ok(())
+    }
+}
+
+/// Items of `first` followed by those of `second` not already present
+fn combine(first: &[String], second: &[String]) -> Vec<String> {
+    let mut combined = first.to_vec();
+    for item in second {
+        if !combined.contains(item) {
+            combined.push(item.clone());
+        }
+    }
+    combined
+}
+
+/// Location of templates.toml: `$XDG_CONFIG_HOME/crowdcontrol`, falling back to
+/// `~/.config/crowdcontrol`
+pub fn templates_path() -> Option<PathBuf> {
+    env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".config")))
+        .map(|dir| dir.join("crowdcontrol").join(TEMPLATES_FILE_NAME))
+}
+
+/// Load every template defined in `path`; a missing file defines none
+pub fn load_templates(path: &Path) -> Result<BTreeMap<String, Template>> {
+    if !path.exists() {
+        debug!("No templates file found at {:?}", path);
+        return Ok(BTreeMap::new());
+    }
+
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read templates file: {:?}", path))?;
+    let templates: BTreeMap<String, Template> = toml::from_str(&contents)
+        .with_context(|| format!("Failed to parse templates file: {:?}", path))?;
+
+    trace!("Loaded templates: {:?}", templates);
+    Ok(templates)
+}
+
+/// Load and validate the template called `name`, listing the available ones if
+/// there is no such template
+pub fn find_template(path: &Path, name: &str) -> Result<Template> {
+    let mut templates = load_templates(path)?;
+
+    let Some(template) = templates.remove(name) else {
+        if templates.is_empty() {
+            return Err(anyhow!(
+                "Unknown template '{}': no templates are defined in {}",
+                name,
+                path.display()
+            ));
+        }
+        let available: Vec<&str> = templates.keys().map(String::as_str).collect();
+        return Err(anyhow!(
+            "Unknown template '{}'. Available templates: {}",
+            name,
+            available.join(", ")
+        ));
+    };
+
+    template
+        .validate()
+        .with_context(|| format!("Invalid template '{}' in {}", name, path.display()))?;
+    Ok(template)
+}
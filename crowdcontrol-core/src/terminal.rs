@@ -0,0 +1,243 @@
+/// Size of the local terminal as (width, height) in character cells.
+/// Returns `None` when stdout is not a terminal or the size can't be determined.
+#[cfg(unix)]
+pub fn terminal_size() -> Option<(u16, u16)> {
+    let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+    let result = unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) };
+    if result != 0 {
+        return None;
+    }
+    size_from_cells(size.ws_col, size.ws_row)
+}
+
+#[cfg(not(unix))]
+pub fn terminal_size() -> Option<(u16, u16)> {
+    None
+}
+
+/// Validate a reported terminal size; some terminals (and pipes) report 0x0
+#[cfg_attr(not(unix), allow(dead_code))]
+fn size_from_cells(cols: u16, rows: u16) -> Option<(u16, u16)> {
+    if cols == 0 || rows == 0 {
+        None
+    } else {
+        Some((cols, rows))
+    }
+}
+
+/// Puts stdin into raw mode so keystrokes (Ctrl-C, arrow keys, ...) are passed
+/// straight through to the container. The previous mode is restored on drop.
+#[cfg(unix)]
+pub struct RawModeGuard {
+    original: libc::termios,
+}
+
+#[cfg(unix)]
+impl RawModeGuard {
+    /// Enable raw mode. Returns `None` if stdin is not a terminal.
+    pub fn enable() -> Option<Self> {
+        unsafe {
+            if libc::isatty(libc::STDIN_FILENO) != 1 {
+                return None;
+            }
+
+            let mut original: libc::termios = std::mem::zeroed();
+            if libc::tcgetattr(libc::STDIN_FILENO, &mut original) != 0 {
+                return None;
+            }
+
+            let mut raw = original;
+            libc::cfmakeraw(&mut raw);
+            if libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &raw) != 0 {
+                return None;
+            }
+
+            Some(Self { original })
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for RawModeGuard {
+    fn drop(&mut self) {
+        unsafe {
+            libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &self.original);
+        }
+    }
+}
+
+#[cfg(not(unix))]
+pub struct RawModeGuard;
+
+#[cfg(not(unix))]
+impl RawModeGuard {
+    pub fn enable() -> Option<Self> {
+        None
+    }
+}
+
+/// Key sequence that detaches from an interactive session, leaving the command running.
+/// Written as Docker writes it: comma-separated keys, each a single character or
+/// `ctrl-<key>` with `<key>` a letter or one of `@ [ \ ] ^ _`. An empty sequence turns
+/// detaching off.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DetachKeys(Vec<u8>);
+
+/// Docker's default detach sequence
+pub const DEFAULT_DETACH_KEYS: &str = "ctrl-p,ctrl-q";
+
+impl DetachKeys {
+    /// The bytes the terminal sends for the sequence
+    pub fn bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl Default for DetachKeys {
+    fn default() -> Self {
+        DEFAULT_DETACH_KEYS
+            .parse()
+            .expect("the default detach keys are valid")
+    }
+}
+
+impl std::str::FromStr for DetachKeys {
+    type Err = anyhow::Error;
+
+    fn from_str(keys: &str) -> anyhow::Result<Self> {
+        if keys.is_empty() {
+            return Ok(Self(Vec::new()));
+        }
+
+        let invalid = |key: &str| {
+            anyhow::anyhow!(
+                "Invalid detach key '{}': use a single character, or ctrl- followed by a \
+                 letter or one of @ [ \\ ] ^ _",
+                key
+            )
+        };
+        keys.split(',')
+            .map(|key| {
+                let lower = key.to_lowercase();
+                match (key.chars().count(), lower.strip_prefix("ctrl-")) {
+                    (1, _) if key.is_ascii() => Ok(key.as_bytes()[0]),
+                    (_, Some(ctrl)) => match ctrl.as_bytes() {
+                        [c @ b'a'..=b'z'] => Ok(c - b'a' + 1),
+                        [b'@'] => Ok(0),
+                        [c @ (b'[' | b'\\' | b']' | b'^' | b'_')] => Ok(c - b'[' + 27),
+                        _ => Err(invalid(key)),
+                    },
+                    _ => Err(invalid(key)),
+                }
+            })
+            .collect::<anyhow::Result<_>>()
+            .map(Self)
+    }
+}
+
+/// Watches stdin for the detach sequence. Bytes that could start the sequence are held
+/// back until it's clear whether they do; if not, they are passed on after all.
+#[derive(Debug)]
+pub struct DetachDetector {
+    keys: Vec<u8>,
+    /// How many bytes of the sequence have been seen in a row
+    matched: usize,
+}
+
+impl DetachDetector {
+    pub fn new(keys: &DetachKeys) -> Self {
+        Self {
+            keys: keys.bytes().to_vec(),
+            matched: 0,
+        }
+    }
+
+    /// Scan a chunk of input. Returns the bytes to forward to the session and whether
+    /// the sequence was completed, in which case input after it is dropped.
+    pub fn scan(&mut self, input: &[u8]) -> (Vec<u8>, bool) {
+        if self.keys.is_empty() {
+            return (input.to_vec(), false);
+        }
+
+        let mut forward = Vec::with_capacity(input.len());
+        for &byte in input {
+            if byte != self.keys[self.matched] {
+                // Not the sequence after all: release what was held back and see whether
+                // this byte starts it afresh
+                forward.extend_from_slice(&self.keys[..self.matched]);
+                self.matched = 0;
+                if byte != self.keys[0] {
+                    forward.push(byte);
+                    continue;
+                }
+            }
+
+            self.matched += 1;
+            if self.matched == self.keys.len() {
+                self.matched = 0;
+                return (forward, true);
+            }
+        }
+        (forward, false)
+    }
+}
+
+// Resize forwarding can be checked by hand: `crowdcontrol connect <agent> --shell`,
+// run `stty size`, resize the window and run it again; the new size should be reported.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_size_from_cells() {
+        assert_eq!(size_from_cells(120, 40), Some((120, 40)));
+        assert_eq!(size_from_cells(0, 40), None);
+        assert_eq!(size_from_cells(120, 0), None);
+    }
+
+    #[test]
+    fn test_parse_detach_keys() {
+        assert_eq!(DetachKeys::default().bytes(), &[0x10, 0x11]);
+        assert_eq!(
+            "ctrl-a,X,ctrl-@,ctrl-_"
+                .parse::<DetachKeys>()
+                .unwrap()
+                .bytes(),
+            &[0x01, b'X', 0x00, 0x1f]
+        );
+        assert_eq!("Ctrl-Q".parse::<DetachKeys>().unwrap().bytes(), &[0x11]);
+        assert!("".parse::<DetachKeys>().unwrap().bytes().is_empty());
+
+        for invalid in ["ctrl-1", "ctrl-", "ab", "ctrl-p,", "é"] {
+            assert!(
+                invalid.parse::<DetachKeys>().is_err(),
+                "{:?} should be rejected",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn test_detach_detector() {
+        let keys = DetachKeys::default();
+
+        // The whole sequence detaches; input before it still goes through
+        let mut detector = DetachDetector::new(&keys);
+        assert_eq!(detector.scan(b"ls\x10\x11rest"), (b"ls".to_vec(), true));
+
+        // Split across reads, with the first key held back in between
+        let mut detector = DetachDetector::new(&keys);
+        assert_eq!(detector.scan(b"a\x10"), (b"a".to_vec(), false));
+        assert_eq!(detector.scan(b"\x11"), (Vec::new(), true));
+
+        // A broken sequence releases the held key, and a repeated first key restarts it
+        let mut detector = DetachDetector::new(&keys);
+        assert_eq!(detector.scan(b"\x10x"), (b"\x10x".to_vec(), false));
+        assert_eq!(detector.scan(b"\x10\x10"), (b"\x10".to_vec(), false));
+        assert_eq!(detector.scan(b"\x11"), (Vec::new(), true));
+
+        // With no keys everything is forwarded
+        let mut detector = DetachDetector::new(&"".parse().unwrap());
+        assert_eq!(detector.scan(b"\x10\x11"), (b"\x10\x11".to_vec(), false));
+    }
+}
@@ -1,5 +1,5 @@
 use chrono::Utc;
-use crowdcontrol_core::{agent::*, Agent, AgentStatus, Config};
+use crowdcontrol_core::{agent::*, Agent, AgentStatus, Config, CrowdControlError, WorkspaceLayout};
 use std::path::PathBuf;
 use std::sync::{Arc, Barrier};
 use std::thread;
@@ -12,7 +12,15 @@ fn create_test_config() -> (Config, tempfile::TempDir) {
         image: "test:latest".to_string(),
         default_memory: None,
         default_cpus: None,
+        default_platform: None,
         verbose: 0,
+        mount_claude_config: true,
+        connect_default_command: None,
+        container_prefix: "crowdcontrol".to_string(),
+        require_crowdcontrol: false,
+        keychain_service: "Claude Code-credentials".to_string(),
+        workspace_layout: WorkspaceLayout::Flat,
+        connect_auto_start: false,
     };
     (config, temp_dir)
 }
@@ -20,12 +28,12 @@ fn create_test_config() -> (Config, tempfile::TempDir) {
 fn create_test_agent(name: &str) -> Agent {
     Agent {
         name: name.to_string(),
-        status: AgentStatus::Created,
         container_id: Some("test-container-id".to_string()),
         repository: "https://github.com/test/repo.git".to_string(),
         branch: Some("main".to_string()),
         created_at: Utc::now(),
         workspace_path: PathBuf::from("/test/workspace"),
+        ..Default::default()
     }
 }
 
@@ -46,6 +54,179 @@ fn test_save_and_load_metadata() {
     assert_eq!(loaded_agent.container_id, agent.container_id);
 }
 
+#[test]
+fn test_resource_limits_round_trip() {
+    let (config, _temp_dir) = create_test_config();
+    let mut agent = create_test_agent("limits-agent");
+    agent.memory = Some("2g".to_string());
+    agent.cpus = Some("1.5".to_string());
+
+    save_agent_metadata(&config, &agent).unwrap();
+
+    let loaded_agent = load_agent_metadata(&config, "limits-agent").unwrap();
+    assert_eq!(loaded_agent.memory.as_deref(), Some("2g"));
+    assert_eq!(loaded_agent.cpus.as_deref(), Some("1.5"));
+}
+
+#[test]
+fn test_tags_round_trip() {
+    let (config, _temp_dir) = create_test_config();
+    let mut agent = create_test_agent("tagged-agent");
+    agent.tags = vec!["frontend".to_string(), "client-a".to_string()];
+
+    save_agent_metadata(&config, &agent).unwrap();
+
+    let loaded_agent = load_agent_metadata(&config, "tagged-agent").unwrap();
+    assert_eq!(loaded_agent.tags, vec!["frontend", "client-a"]);
+    assert!(loaded_agent.has_tag("frontend"));
+    assert!(!loaded_agent.has_tag("backend"));
+}
+
+#[test]
+fn test_agent_spec_round_trip() {
+    let (config, _temp_dir) = create_test_config();
+    let mut agent = create_test_agent("portable-agent");
+    agent.memory = Some("4g".to_string());
+    agent.cpus = Some("2".to_string());
+    agent.tags = vec!["backend".to_string()];
+    agent.local_mount = Some(PathBuf::from("/home/me/src/repo"));
+
+    save_agent_metadata(&config, &agent).unwrap();
+    let loaded_agent = load_agent_metadata(&config, "portable-agent").unwrap();
+    let spec = AgentSpec::from_agent(&loaded_agent);
+
+    // Both export formats parse back to the same spec
+    let json = serde_json::to_string_pretty(&spec).unwrap();
+    let yaml = serde_yaml::to_string(&spec).unwrap();
+    assert_eq!(AgentSpec::parse(&json).unwrap(), spec);
+    assert_eq!(AgentSpec::parse(&yaml).unwrap(), spec);
+
+    assert_eq!(spec.name, "portable-agent");
+    assert_eq!(spec.repository, "https://github.com/test/repo.git");
+    assert_eq!(spec.branch.as_deref(), Some("main"));
+    assert_eq!(spec.memory.as_deref(), Some("4g"));
+    assert_eq!(spec.cpus.as_deref(), Some("2"));
+    assert_eq!(spec.tags, vec!["backend"]);
+
+    // Machine-specific fields are not exported
+    assert!(!json.contains("container_id"));
+    assert!(!json.contains("workspace"));
+    assert!(!json.contains("local_mount"));
+}
+
+#[test]
+fn test_agent_spec_optional_fields_default() {
+    let spec = AgentSpec::parse(
+        r#"{"name": "minimal", "repository": "https://github.com/test/repo.git"}"#,
+    )
+    .unwrap();
+    assert_eq!(spec.branch, None);
+    assert_eq!(spec.memory, None);
+    assert!(spec.tags.is_empty());
+
+    assert!(AgentSpec::parse("name: missing-repository").is_err());
+}
+
+#[test]
+fn test_load_metadata_without_resource_limits() {
+    let (config, _temp_dir) = create_test_config();
+    let metadata_dir = config
+        .agent_workspace_path("old-agent")
+        .join(".crowdcontrol");
+    std::fs::create_dir_all(&metadata_dir).unwrap();
+
+    // Metadata written before resource limits were persisted
+    std::fs::write(
+        metadata_dir.join("metadata.json"),
+        r#"{
+  "_comment": "This file is auto-generated by CrowdControl. Do not edit manually.",
+  "name": "old-agent",
+  "repository": "https://github.com/test/repo.git",
+  "branch": null,
+  "created_at": "2024-01-01T00:00:00Z",
+  "container_id": null
+}"#,
+    )
+    .unwrap();
+
+    let loaded_agent = load_agent_metadata(&config, "old-agent").unwrap();
+    assert_eq!(loaded_agent.name, "old-agent");
+    assert_eq!(loaded_agent.memory, None);
+    assert_eq!(loaded_agent.cpus, None);
+    assert!(loaded_agent.tags.is_empty());
+}
+
+#[test]
+fn test_load_unversioned_metadata_upgrades() {
+    let (config, _temp_dir) = create_test_config();
+    let metadata_dir = config
+        .agent_workspace_path("v0-agent")
+        .join(".crowdcontrol");
+    std::fs::create_dir_all(&metadata_dir).unwrap();
+    let metadata_path = metadata_dir.join("metadata.json");
+
+    // Version 0: no metadata_version, and none of the later fields
+    std::fs::write(
+        &metadata_path,
+        r#"{
+  "name": "v0-agent",
+  "repository": "https://github.com/test/repo.git",
+  "branch": "main",
+  "created_at": "2024-01-01T00:00:00Z",
+  "container_id": "abc123"
+}"#,
+    )
+    .unwrap();
+
+    let agent = load_agent_metadata(&config, "v0-agent").unwrap();
+    assert_eq!(agent.branch.as_deref(), Some("main"));
+    assert_eq!(agent.container_id.as_deref(), Some("abc123"));
+    assert!(agent.tags.is_empty());
+    assert_eq!(agent.workspace_layout, WorkspaceLayout::Flat);
+
+    // The next write stores the current version and the comment
+    update_agent_metadata(&config, "v0-agent", |agent| {
+        agent.tags.push("upgraded".to_string());
+        Ok(())
+    })
+    .unwrap();
+    let json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&metadata_path).unwrap()).unwrap();
+    assert_eq!(json["metadata_version"], METADATA_VERSION);
+    assert!(json["_comment"]
+        .as_str()
+        .unwrap()
+        .contains("auto-generated"));
+    assert_eq!(json["tags"][0], "upgraded");
+}
+
+#[test]
+fn test_load_metadata_from_newer_version_fails() {
+    let (config, _temp_dir) = create_test_config();
+    let agent = create_test_agent("future-agent");
+    save_agent_metadata(&config, &agent).unwrap();
+
+    let metadata_path = config
+        .agent_workspace_path("future-agent")
+        .join(".crowdcontrol")
+        .join("metadata.json");
+    let mut json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&metadata_path).unwrap()).unwrap();
+    json["metadata_version"] = (METADATA_VERSION + 1).into();
+    // A field this version doesn't know about must not be the error reported
+    json["container_id"] = serde_json::json!({"renamed": true});
+    std::fs::write(&metadata_path, json.to_string()).unwrap();
+
+    let error = load_agent_metadata(&config, "future-agent").unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .contains("written by a newer crowdcontrol"),
+        "unexpected error: {:#}",
+        error
+    );
+}
+
 #[test]
 fn test_metadata_has_comment() {
     let (config, _temp_dir) = create_test_config();
@@ -63,6 +244,7 @@ fn test_metadata_has_comment() {
     let content = std::fs::read_to_string(metadata_path).unwrap();
     assert!(content.contains("\"_comment\":"));
     assert!(content.contains("auto-generated"));
+    assert!(content.contains(&format!("\"metadata_version\": {}", METADATA_VERSION)));
 }
 
 #[test]
@@ -89,7 +271,7 @@ fn test_concurrent_reads() {
             let result = load_agent_metadata(&config, "concurrent-read-test");
 
             // Verify the read was successful
-            let agent = result.expect(&format!("Thread {} failed to read", i));
+            let agent = result.unwrap_or_else(|_| panic!("Thread {} failed to read", i));
             assert_eq!(agent.name, "concurrent-read-test");
             assert_eq!(agent.repository, "https://github.com/test/repo.git");
         });
@@ -118,12 +300,12 @@ fn test_concurrent_writes_are_serialized() {
         let handle = thread::spawn(move || {
             let agent = Agent {
                 name: format!("write-test-{}", i),
-                status: AgentStatus::Created,
                 container_id: Some(format!("container-{}", i)),
                 repository: format!("https://github.com/test/repo{}.git", i),
                 branch: Some("main".to_string()),
                 created_at: Utc::now(),
                 workspace_path: PathBuf::from(format!("/test/workspace{}", i)),
+                ..Default::default()
             };
 
             barrier.wait();
@@ -234,6 +416,21 @@ fn test_update_nonexistent_agent() {
     assert!(result.unwrap_err().to_string().contains("not found"));
 }
 
+#[test]
+fn test_missing_agent_is_a_typed_error() {
+    let (config, _temp_dir) = create_test_config();
+
+    let load_error = load_agent_metadata(&config, "nonexistent").unwrap_err();
+    let update_error = update_agent_metadata(&config, "nonexistent", |_| Ok(())).unwrap_err();
+
+    for error in [&load_error, &update_error] {
+        assert_eq!(
+            CrowdControlError::find(error),
+            Some(&CrowdControlError::AgentNotFound("nonexistent".to_string()))
+        );
+    }
+}
+
 #[test]
 fn test_list_all_agents() {
     let (config, _temp_dir) = create_test_config();
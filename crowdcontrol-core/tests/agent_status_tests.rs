@@ -2,10 +2,12 @@
 // and that container ID validation works correctly.
 
 use anyhow::Result;
-use crowdcontrol_core::{Agent, AgentStatus, Config, DockerClient};
+use crowdcontrol_core::{
+    order_by_dependencies, select_agents_to_reap, select_agents_to_refresh, select_agents_to_start,
+    select_agents_to_stop, Agent, AgentStatus, Config, DockerClient, WorkspaceLayout,
+};
 use std::fs;
 use tempfile::TempDir;
-use tokio;
 
 fn create_test_config() -> (Config, TempDir) {
     let temp_dir = tempfile::tempdir().unwrap();
@@ -15,6 +17,14 @@ fn create_test_config() -> (Config, TempDir) {
         verbose: 0,
         default_memory: None,
         default_cpus: None,
+        default_platform: None,
+        mount_claude_config: true,
+        connect_default_command: None,
+        container_prefix: "crowdcontrol".to_string(),
+        require_crowdcontrol: false,
+        keychain_service: "Claude Code-credentials".to_string(),
+        workspace_layout: WorkspaceLayout::Flat,
+        connect_auto_start: false,
     };
     (config, temp_dir)
 }
@@ -33,11 +43,11 @@ async fn test_agent_status_computed_live_from_docker() -> Result<()> {
     let agent = Agent {
         name: agent_name.to_string(),
         status: AgentStatus::Created, // This should be ignored
-        container_id: None,
         repository: "https://github.com/test/repo.git".to_string(),
         branch: Some("main".to_string()),
         created_at: chrono::Utc::now(),
         workspace_path: workspace_path.clone(),
+        ..Default::default()
     };
     
     crowdcontrol_core::agent::save_agent_metadata(&config, &agent)?;
@@ -93,6 +103,7 @@ async fn test_stale_container_id_detection() -> Result<()> {
         branch: Some("main".to_string()),
         created_at: chrono::Utc::now(),
         workspace_path: workspace_path.clone(),
+        ..Default::default()
     };
     
     // Test: Agent with stale container ID should detect and return Created status
@@ -151,6 +162,7 @@ async fn test_agent_auto_repair_stale_container_id() -> Result<()> {
         branch: Some("main".to_string()),
         created_at: chrono::Utc::now(),
         workspace_path: workspace_path.clone(),
+        ..Default::default()
     };
     
     crowdcontrol_core::agent::save_agent_metadata(&config, &agent)?;
@@ -182,6 +194,7 @@ async fn test_agent_always_loads_with_created_status() -> Result<()> {
         branch: Some("main".to_string()),
         created_at: chrono::Utc::now(),
         workspace_path: workspace_path.clone(),
+        ..Default::default()
     };
     
     crowdcontrol_core::agent::save_agent_metadata(&config, &agent)?;
@@ -208,11 +221,11 @@ async fn test_status_field_completely_ignored() -> Result<()> {
     let mut agent = Agent {
         name: agent_name.to_string(),
         status: AgentStatus::Running, // This should be completely ignored
-        container_id: None,
         repository: "https://github.com/test/repo.git".to_string(),
         branch: Some("main".to_string()),
         created_at: chrono::Utc::now(),
         workspace_path: workspace_path.clone(),
+        ..Default::default()
     };
     
     crowdcontrol_core::agent::save_agent_metadata(&config, &agent)?;
@@ -230,4 +243,391 @@ async fn test_status_field_completely_ignored() -> Result<()> {
     assert_eq!(loaded_agent.status, AgentStatus::Created);
     
     Ok(())
-}
\ No newline at end of file
+}
+fn container_summary(
+    config: &Config,
+    name: &str,
+    id: &str,
+    state: &str,
+) -> bollard::models::ContainerSummary {
+    bollard::models::ContainerSummary {
+        id: Some(id.to_string()),
+        names: Some(vec![format!("/{}", config.container_name(name))]),
+        state: Some(state.to_string()),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_status_resolved_from_single_container_snapshot() {
+    let (config, _temp_dir) = create_test_config();
+    let states = ["running", "exited", "created", "dead", "paused"];
+
+    // One snapshot (a single list_containers call) covers every agent
+    let snapshot: Vec<_> = (0..20)
+        .map(|i| {
+            container_summary(
+                &config,
+                &format!("agent-{}", i),
+                &format!("container-{}", i),
+                states[i % states.len()],
+            )
+        })
+        .collect();
+
+    for i in 0..20 {
+        let name = format!("agent-{}", i);
+        let agent = Agent {
+            name: name.clone(),
+            container_id: Some(format!("container-{}", i)),
+            repository: "https://github.com/test/repo.git".to_string(),
+            created_at: chrono::Utc::now(),
+            workspace_path: config.agent_workspace_path(&name),
+            ..Default::default()
+        };
+
+        let expected = match states[i % states.len()] {
+            "running" => AgentStatus::Running,
+            "paused" => AgentStatus::Paused,
+            "exited" => AgentStatus::Stopped,
+            "created" => AgentStatus::Created,
+            _ => AgentStatus::Error,
+        };
+        assert_eq!(agent.status_from_containers(&snapshot, &config), expected);
+    }
+}
+
+#[test]
+fn test_status_from_snapshot_uses_container_prefix() {
+    let (default_config, _temp_dir) = create_test_config();
+    let config = Config {
+        container_prefix: "team-a".to_string(),
+        ..default_config.clone()
+    };
+    let agent = Agent {
+        container_id: Some("container-web".to_string()),
+        ..tagged_agent(&config, "web", &[])
+    };
+
+    let prefixed = container_summary(&config, "web", "container-web", "running");
+    assert_eq!(
+        agent.status_from_containers(&[prefixed], &config),
+        AgentStatus::Running
+    );
+
+    // The same container under the default prefix belongs to someone else
+    let snapshot = vec![container_summary(
+        &default_config,
+        "web",
+        "container-web",
+        "running",
+    )];
+    assert_eq!(
+        agent.status_from_containers(&snapshot, &config),
+        AgentStatus::Created
+    );
+}
+
+#[test]
+fn test_status_from_snapshot_treats_stale_id_as_created() {
+    let (config, _temp_dir) = create_test_config();
+    let snapshot = vec![container_summary(
+        &config,
+        "stale",
+        "new-container-id",
+        "running",
+    )];
+
+    // Metadata points at a container that no longer backs this agent
+    let agent = Agent {
+        name: "stale".to_string(),
+        container_id: Some("old-container-id".to_string()),
+        repository: "https://github.com/test/repo.git".to_string(),
+        created_at: chrono::Utc::now(),
+        workspace_path: config.agent_workspace_path("stale"),
+        ..Default::default()
+    };
+    assert_eq!(agent.status_from_containers(&snapshot, &config), AgentStatus::Created);
+
+    // No container ID at all is always Created
+    let agent = Agent {
+        container_id: None,
+        ..agent
+    };
+    assert_eq!(agent.status_from_containers(&snapshot, &config), AgentStatus::Created);
+}
+
+fn tagged_agent(config: &Config, name: &str, tags: &[&str]) -> Agent {
+    Agent {
+        name: name.to_string(),
+        container_id: Some(format!("container-{}", name)),
+        repository: "https://github.com/test/repo.git".to_string(),
+        created_at: chrono::Utc::now(),
+        workspace_path: config.agent_workspace_path(name),
+        tags: tags.iter().map(|t| t.to_string()).collect(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_select_agents_to_start_skips_running() {
+    let (config, _temp_dir) = create_test_config();
+    let agents = vec![
+        (tagged_agent(&config, "running", &[]), AgentStatus::Running),
+        (tagged_agent(&config, "paused", &[]), AgentStatus::Paused),
+        (tagged_agent(&config, "stopped", &[]), AgentStatus::Stopped),
+        (tagged_agent(&config, "created", &[]), AgentStatus::Created),
+        (tagged_agent(&config, "broken", &[]), AgentStatus::Error),
+    ];
+
+    // Paused agents are left for `unpause`; error agents are kept so the batch start
+    // reports them as failures
+    let selected: Vec<String> = select_agents_to_start(agents, None)
+        .into_iter()
+        .map(|(agent, _)| agent.name)
+        .collect();
+    assert_eq!(selected, vec!["stopped", "created", "broken"]);
+}
+
+#[test]
+fn test_select_agents_to_start_by_tag() {
+    let (config, _temp_dir) = create_test_config();
+    let agents = vec![
+        (tagged_agent(&config, "web", &["frontend"]), AgentStatus::Stopped),
+        (tagged_agent(&config, "web-live", &["frontend"]), AgentStatus::Running),
+        (tagged_agent(&config, "api", &["backend"]), AgentStatus::Stopped),
+    ];
+
+    let selected: Vec<String> = select_agents_to_start(agents, Some("frontend"))
+        .into_iter()
+        .map(|(agent, _)| agent.name)
+        .collect();
+    assert_eq!(selected, vec!["web"]);
+}
+
+#[test]
+fn test_select_agents_to_stop_by_status() {
+    let (config, _temp_dir) = create_test_config();
+    let agents = || {
+        vec![
+            (
+                tagged_agent(&config, "web", &["frontend"]),
+                AgentStatus::Running,
+            ),
+            (
+                tagged_agent(&config, "web-broken", &["frontend"]),
+                AgentStatus::Error,
+            ),
+            (
+                tagged_agent(&config, "api-broken", &["backend"]),
+                AgentStatus::Error,
+            ),
+            (tagged_agent(&config, "worker", &[]), AgentStatus::Stopped),
+        ]
+    };
+    let names = |selected: Vec<Agent>| -> Vec<String> {
+        selected.into_iter().map(|agent| agent.name).collect()
+    };
+
+    // `stop --all --status error`
+    assert_eq!(
+        names(select_agents_to_stop(
+            agents(),
+            None,
+            Some(&AgentStatus::Error)
+        )),
+        vec!["web-broken", "api-broken"]
+    );
+    // `stop --all --tag frontend --status error`
+    assert_eq!(
+        names(select_agents_to_stop(
+            agents(),
+            Some("frontend"),
+            Some(&AgentStatus::Error)
+        )),
+        vec!["web-broken"]
+    );
+    // `stop --all` tries every agent; those not running are skipped when stopping
+    assert_eq!(names(select_agents_to_stop(agents(), None, None)).len(), 4);
+}
+
+#[test]
+fn test_select_agents_to_refresh_only_running() {
+    let (config, _temp_dir) = create_test_config();
+    let agent = |name: &str| tagged_agent(&config, name, &[]);
+    let agents = vec![
+        (agent("running"), AgentStatus::Running),
+        (agent("paused"), AgentStatus::Paused),
+        (agent("starting"), AgentStatus::Starting),
+        (agent("stopped"), AgentStatus::Stopped),
+        (agent("created"), AgentStatus::Created),
+        (agent("restarting"), AgentStatus::Restarting),
+    ];
+
+    let (selected, skipped) = select_agents_to_refresh(agents);
+    let selected: Vec<String> = selected.into_iter().map(|agent| agent.name).collect();
+    assert_eq!(selected, vec!["running", "starting"]);
+
+    let skipped: Vec<(String, AgentStatus)> = skipped
+        .into_iter()
+        .map(|(agent, status)| (agent.name, status))
+        .collect();
+    assert_eq!(
+        skipped,
+        vec![
+            ("paused".to_string(), AgentStatus::Paused),
+            ("stopped".to_string(), AgentStatus::Stopped),
+            ("created".to_string(), AgentStatus::Created),
+            ("restarting".to_string(), AgentStatus::Restarting),
+        ]
+    );
+}
+
+#[test]
+fn test_repository_matches_substring_ignoring_case() {
+    let (config, _temp_dir) = create_test_config();
+    let agents: Vec<Agent> = [
+        ("web", "git@github.com:Org/Web-App.git"),
+        ("web-fix", "https://github.com/org/web-app"),
+        ("api", "git@github.com:org/api.git"),
+        ("local", "/home/dev/src/web-app"),
+    ]
+    .into_iter()
+    .map(|(name, repository)| Agent {
+        repository: repository.to_string(),
+        ..tagged_agent(&config, name, &[])
+    })
+    .collect();
+
+    let matching = |pattern: &str| -> Vec<&str> {
+        agents
+            .iter()
+            .filter(|agent| agent.repository_matches(pattern))
+            .map(|agent| agent.name.as_str())
+            .collect()
+    };
+    assert_eq!(matching("web-app"), ["web", "web-fix", "local"]);
+    assert_eq!(matching("ORG/"), ["web", "web-fix", "api"]);
+    assert_eq!(matching("github.com:org/api"), ["api"]);
+    assert!(matching("mobile").is_empty());
+}
+
+#[test]
+fn test_select_agents_to_reap_idle_agents() {
+    let (config, _temp_dir) = create_test_config();
+    let now = chrono::Utc::now();
+
+    let idle_agent = |name: &str, timeout: Option<&str>, idle_for: chrono::Duration| {
+        let mut agent = tagged_agent(&config, name, &[]);
+        agent.idle_timeout = timeout.map(str::to_string);
+        agent.last_activity = Some(now - idle_for);
+        agent
+    };
+
+    let agents = vec![
+        (
+            idle_agent("stale", Some("2h"), chrono::Duration::days(3)),
+            AgentStatus::Running,
+        ),
+        (
+            idle_agent("recent", Some("2h"), chrono::Duration::minutes(30)),
+            AgentStatus::Running,
+        ),
+        (
+            idle_agent("no-timeout", None, chrono::Duration::days(3)),
+            AgentStatus::Running,
+        ),
+        (
+            idle_agent("already-stopped", Some("2h"), chrono::Duration::days(3)),
+            AgentStatus::Stopped,
+        ),
+        (
+            idle_agent("bad-timeout", Some("soon"), chrono::Duration::days(3)),
+            AgentStatus::Running,
+        ),
+    ];
+
+    let selected: Vec<String> = select_agents_to_reap(agents, now)
+        .into_iter()
+        .map(|agent| agent.name)
+        .collect();
+    assert_eq!(selected, vec!["stale"]);
+}
+
+#[test]
+fn test_select_agents_to_reap_falls_back_to_created_at() {
+    let (config, _temp_dir) = create_test_config();
+    let now = chrono::Utc::now();
+
+    // Never connected: idle time counts from creation
+    let mut agent = tagged_agent(&config, "never-connected", &[]);
+    agent.idle_timeout = Some("1h".to_string());
+    agent.created_at = now - chrono::Duration::hours(2);
+
+    let selected = select_agents_to_reap(vec![(agent, AgentStatus::Running)], now);
+    assert_eq!(selected.len(), 1);
+}
+
+fn dependent_agent(config: &Config, name: &str, depends_on: &[&str]) -> Agent {
+    let mut agent = tagged_agent(config, name, &[]);
+    agent.depends_on = depends_on.iter().map(|d| d.to_string()).collect();
+    agent
+}
+
+#[test]
+fn test_order_by_dependencies() {
+    let (config, _temp_dir) = create_test_config();
+    let agents = vec![
+        dependent_agent(&config, "frontend", &["backend"]),
+        dependent_agent(&config, "backend", &["db", "cache"]),
+        dependent_agent(&config, "docs", &[]),
+        dependent_agent(&config, "cache", &[]),
+        dependent_agent(&config, "db", &[]),
+    ];
+
+    let ordered: Vec<String> = order_by_dependencies(agents, |agent| agent)
+        .unwrap()
+        .into_iter()
+        .map(|agent| agent.name)
+        .collect();
+    assert_eq!(ordered, vec!["db", "cache", "backend", "frontend", "docs"]);
+}
+
+#[test]
+fn test_order_by_dependencies_ignores_unknown_agents() {
+    let (config, _temp_dir) = create_test_config();
+    let agents = vec![
+        (
+            dependent_agent(&config, "api", &["missing", "db"]),
+            AgentStatus::Stopped,
+        ),
+        (dependent_agent(&config, "db", &[]), AgentStatus::Stopped),
+    ];
+
+    let ordered: Vec<String> = order_by_dependencies(agents, |(agent, _)| agent)
+        .unwrap()
+        .into_iter()
+        .map(|(agent, _)| agent.name)
+        .collect();
+    assert_eq!(ordered, vec!["db", "api"]);
+}
+
+#[test]
+fn test_order_by_dependencies_rejects_cycles() {
+    let (config, _temp_dir) = create_test_config();
+    let agents = vec![
+        dependent_agent(&config, "docs", &[]),
+        dependent_agent(&config, "a", &["b"]),
+        dependent_agent(&config, "b", &["c"]),
+        dependent_agent(&config, "c", &["a"]),
+    ];
+
+    let error = order_by_dependencies(agents, |agent| agent).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Dependency cycle between agents: a -> b -> c -> a"
+    );
+
+    let self_dependent = vec![dependent_agent(&config, "loop", &["loop"])];
+    assert!(order_by_dependencies(self_dependent, |agent| agent).is_err());
+}
@@ -0,0 +1,49 @@
+use crowdcontrol_core::build_context_tar;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn tar_entries(tarball: &[u8]) -> Vec<String> {
+    let mut archive = tar::Archive::new(tarball);
+    archive
+        .entries()
+        .unwrap()
+        .map(|entry| entry.unwrap().path().unwrap().display().to_string())
+        .collect()
+}
+
+#[test]
+fn test_build_context_includes_dockerfile() {
+    let context = TempDir::new().unwrap();
+    fs::write(context.path().join("Dockerfile"), "FROM ubuntu:22.04\n").unwrap();
+    fs::write(context.path().join("entrypoint.sh"), "#!/bin/bash\n").unwrap();
+
+    let tarball = build_context_tar(context.path()).unwrap();
+    let entries = tar_entries(&tarball);
+
+    assert!(entries
+        .iter()
+        .any(|path| Path::new(path).file_name() == Some("Dockerfile".as_ref())));
+    assert!(entries
+        .iter()
+        .any(|path| Path::new(path).file_name() == Some("entrypoint.sh".as_ref())));
+}
+
+#[test]
+fn test_build_context_requires_dockerfile() {
+    let context = TempDir::new().unwrap();
+
+    let err = build_context_tar(context.path()).unwrap_err();
+    assert!(err.to_string().contains("No Dockerfile found"));
+}
+
+#[test]
+fn test_repository_dockerfile_packages() {
+    // The repository's own image definition must be a valid build context
+    let container_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../container");
+
+    let tarball = build_context_tar(&container_dir).unwrap();
+    assert!(tar_entries(&tarball)
+        .iter()
+        .any(|path| path.ends_with("Dockerfile")));
+}
@@ -2,11 +2,10 @@
 // Run with: cargo test --package crowdcontrol-core --ignored
 
 use anyhow::Result;
-use crowdcontrol_core::{Config, DockerClient};
+use crowdcontrol_core::{Config, DockerClient, WorkspaceLayout};
 use std::fs;
 use std::process::Command;
 use tempfile::TempDir;
-use tokio;
 
 /// Integration tests specifically focused on Claude Code behavior inside containers
 #[tokio::test]
@@ -19,6 +18,14 @@ async fn test_claude_code_availability_and_version() -> Result<()> {
         verbose: 0,
         default_memory: None,
         default_cpus: None,
+        default_platform: None,
+        mount_claude_config: true,
+        connect_default_command: None,
+        container_prefix: "crowdcontrol".to_string(),
+        require_crowdcontrol: false,
+        keychain_service: "Claude Code-credentials".to_string(),
+        workspace_layout: WorkspaceLayout::Flat,
+        connect_auto_start: false,
     };
 
     let docker = DockerClient::new(config.clone())?;
@@ -37,9 +44,9 @@ async fn test_claude_code_availability_and_version() -> Result<()> {
 
     // Test: Verify Claude Code is installed and accessible
     let output = Command::new("docker")
-        .args(&[
+        .args([
             "exec",
-            &format!("crowdcontrol-{}", agent_name),
+            &config.container_name(agent_name),
             "claude",
             "--version",
         ])
@@ -66,9 +73,9 @@ async fn test_claude_code_availability_and_version() -> Result<()> {
 
     // Test: Verify Claude Code can show help
     let output = Command::new("docker")
-        .args(&[
+        .args([
             "exec",
-            &format!("crowdcontrol-{}", agent_name),
+            &config.container_name(agent_name),
             "claude",
             "--help",
         ])
@@ -101,6 +108,14 @@ async fn test_claude_authentication_status() -> Result<()> {
         verbose: 0,
         default_memory: None,
         default_cpus: None,
+        default_platform: None,
+        mount_claude_config: true,
+        connect_default_command: None,
+        container_prefix: "crowdcontrol".to_string(),
+        require_crowdcontrol: false,
+        keychain_service: "Claude Code-credentials".to_string(),
+        workspace_layout: WorkspaceLayout::Flat,
+        connect_auto_start: false,
     };
 
     let docker = DockerClient::new(config.clone())?;
@@ -118,9 +133,9 @@ async fn test_claude_authentication_status() -> Result<()> {
 
     // Test: Check Claude authentication status (should fail without credentials)
     let output = Command::new("docker")
-        .args(&[
+        .args([
             "exec",
-            &format!("crowdcontrol-{}", agent_name),
+            &config.container_name(agent_name),
             "claude",
             "auth",
             "status",
@@ -162,6 +177,14 @@ async fn test_claude_with_mock_credentials() -> Result<()> {
         verbose: 0,
         default_memory: None,
         default_cpus: None,
+        default_platform: None,
+        mount_claude_config: true,
+        connect_default_command: None,
+        container_prefix: "crowdcontrol".to_string(),
+        require_crowdcontrol: false,
+        keychain_service: "Claude Code-credentials".to_string(),
+        workspace_layout: WorkspaceLayout::Flat,
+        connect_auto_start: false,
     };
 
     // Create mock Claude credentials that match expected format
@@ -197,9 +220,9 @@ async fn test_claude_with_mock_credentials() -> Result<()> {
 
     // Test: Run refresh script to configure Claude auth
     let output = Command::new("docker")
-        .args(&[
+        .args([
             "exec",
-            &format!("crowdcontrol-{}", agent_name),
+            &config.container_name(agent_name),
             "refresh-claude-auth.sh",
         ])
         .output()?;
@@ -215,9 +238,9 @@ async fn test_claude_with_mock_credentials() -> Result<()> {
 
     // Test: Verify credentials file was created with correct permissions
     let output = Command::new("docker")
-        .args(&[
+        .args([
             "exec",
-            &format!("crowdcontrol-{}", agent_name),
+            &config.container_name(agent_name),
             "ls",
             "-la",
             "/home/developer/.claude/",
@@ -232,9 +255,9 @@ async fn test_claude_with_mock_credentials() -> Result<()> {
         if ls_output.contains(".credentials.json") {
             // Test: Verify credentials file has correct permissions (600)
             let output = Command::new("docker")
-                .args(&[
+                .args([
                     "exec",
-                    &format!("crowdcontrol-{}", agent_name),
+                    &config.container_name(agent_name),
                     "stat",
                     "-c",
                     "%a",
@@ -250,9 +273,9 @@ async fn test_claude_with_mock_credentials() -> Result<()> {
 
             // Test: Verify credentials content
             let output = Command::new("docker")
-                .args(&[
+                .args([
                     "exec",
-                    &format!("crowdcontrol-{}", agent_name),
+                    &config.container_name(agent_name),
                     "cat",
                     "/home/developer/.claude/.credentials.json",
                 ])
@@ -270,9 +293,9 @@ async fn test_claude_with_mock_credentials() -> Result<()> {
 
     // Test: Verify .claude.json transformations were applied
     let output = Command::new("docker")
-        .args(&[
+        .args([
             "exec",
-            &format!("crowdcontrol-{}", agent_name),
+            &config.container_name(agent_name),
             "cat",
             "/home/developer/.claude.json",
         ])
@@ -308,9 +331,9 @@ async fn test_claude_with_mock_credentials() -> Result<()> {
 
     // Test: Attempt to check Claude auth status with mock credentials
     let output = Command::new("docker")
-        .args(&[
+        .args([
             "exec",
-            &format!("crowdcontrol-{}", agent_name),
+            &config.container_name(agent_name),
             "claude",
             "auth",
             "status",
@@ -349,6 +372,14 @@ async fn test_claude_project_detection() -> Result<()> {
         verbose: 0,
         default_memory: None,
         default_cpus: None,
+        default_platform: None,
+        mount_claude_config: true,
+        connect_default_command: None,
+        container_prefix: "crowdcontrol".to_string(),
+        require_crowdcontrol: false,
+        keychain_service: "Claude Code-credentials".to_string(),
+        workspace_layout: WorkspaceLayout::Flat,
+        connect_auto_start: false,
     };
 
     let docker = DockerClient::new(config.clone())?;
@@ -384,9 +415,9 @@ async fn test_claude_project_detection() -> Result<()> {
 
     // Test: Verify Claude can see and analyze project files
     let output = Command::new("docker")
-        .args(&[
+        .args([
             "exec",
-            &format!("crowdcontrol-{}", agent_name),
+            &config.container_name(agent_name),
             "find",
             "/workspace",
             "-name",
@@ -406,9 +437,9 @@ async fn test_claude_project_detection() -> Result<()> {
 
     // Test: Verify Claude can read project files
     let output = Command::new("docker")
-        .args(&[
+        .args([
             "exec",
-            &format!("crowdcontrol-{}", agent_name),
+            &config.container_name(agent_name),
             "cat",
             "/workspace/package.json",
         ])
@@ -426,9 +457,9 @@ async fn test_claude_project_detection() -> Result<()> {
 
     // Test: Verify directory structure is as expected
     let output = Command::new("docker")
-        .args(&[
+        .args([
             "exec",
-            &format!("crowdcontrol-{}", agent_name),
+            &config.container_name(agent_name),
             "tree",
             "/workspace",
         ])
@@ -437,9 +468,9 @@ async fn test_claude_project_detection() -> Result<()> {
     if !output.status.success() {
         // If tree is not available, use ls -la
         let output = Command::new("docker")
-            .args(&[
+            .args([
                 "exec",
-                &format!("crowdcontrol-{}", agent_name),
+                &config.container_name(agent_name),
                 "ls",
                 "-la",
                 "/workspace",
@@ -485,6 +516,14 @@ async fn test_claude_node_npm_integration() -> Result<()> {
         verbose: 0,
         default_memory: None,
         default_cpus: None,
+        default_platform: None,
+        mount_claude_config: true,
+        connect_default_command: None,
+        container_prefix: "crowdcontrol".to_string(),
+        require_crowdcontrol: false,
+        keychain_service: "Claude Code-credentials".to_string(),
+        workspace_layout: WorkspaceLayout::Flat,
+        connect_auto_start: false,
     };
 
     let docker = DockerClient::new(config.clone())?;
@@ -526,9 +565,9 @@ console.log("Working directory:", process.cwd());
 
     // Test: Verify Node.js is available and working
     let output = Command::new("docker")
-        .args(&[
+        .args([
             "exec",
-            &format!("crowdcontrol-{}", agent_name),
+            &config.container_name(agent_name),
             "node",
             "--version",
         ])
@@ -545,9 +584,9 @@ console.log("Working directory:", process.cwd());
 
     // Test: Verify npm is available
     let output = Command::new("docker")
-        .args(&[
+        .args([
             "exec",
-            &format!("crowdcontrol-{}", agent_name),
+            &config.container_name(agent_name),
             "npm",
             "--version",
         ])
@@ -560,9 +599,9 @@ console.log("Working directory:", process.cwd());
 
     // Test: Run the Node.js application
     let output = Command::new("docker")
-        .args(&[
+        .args([
             "exec",
-            &format!("crowdcontrol-{}", agent_name),
+            &config.container_name(agent_name),
             "node",
             "/workspace/index.js",
         ])
@@ -585,9 +624,9 @@ console.log("Working directory:", process.cwd());
 
     // Test: npm scripts
     let output = Command::new("docker")
-        .args(&[
+        .args([
             "exec",
-            &format!("crowdcontrol-{}", agent_name),
+            &config.container_name(agent_name),
             "npm",
             "run",
             "test",
@@ -603,12 +642,7 @@ console.log("Working directory:", process.cwd());
 
     // Test: npm start
     let output = Command::new("docker")
-        .args(&[
-            "exec",
-            &format!("crowdcontrol-{}", agent_name),
-            "npm",
-            "start",
-        ])
+        .args(["exec", &config.container_name(agent_name), "npm", "start"])
         .output()?;
 
     assert!(output.status.success(), "npm start should work");
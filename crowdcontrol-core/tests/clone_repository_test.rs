@@ -0,0 +1,231 @@
+use crowdcontrol_core::agent::{
+    copy_local_repository, create_branch, detect_current_branch, git_ssh_command, is_ssh_url,
+    validate_repository_url, verify_repository_setup, SshOptions, REPOSITORY_SETUP_FILES,
+};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tempfile::TempDir;
+
+fn git(repo_dir: &Path, args: &[&str]) {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(repo_dir)
+        .output()
+        .expect("Failed to run git");
+    assert!(output.status.success(), "git {:?} failed", args);
+}
+
+fn create_local_git_repo(repo_dir: &Path) {
+    fs::create_dir_all(repo_dir).unwrap();
+    fs::write(repo_dir.join("README.md"), "# Test Repository").unwrap();
+
+    git(repo_dir, &["init"]);
+    git(repo_dir, &["config", "user.email", "test@example.com"]);
+    git(repo_dir, &["config", "user.name", "Test User"]);
+    git(repo_dir, &["checkout", "-b", "feature/local"]);
+    git(repo_dir, &["add", "."]);
+    git(repo_dir, &["commit", "-m", "Initial commit"]);
+}
+
+#[test]
+fn test_is_ssh_url() {
+    assert!(is_ssh_url("git@github.com:org/repo.git"));
+    assert!(is_ssh_url("ssh://git@github.com/org/repo.git"));
+    assert!(is_ssh_url("deploy@git.example.com:team/repo"));
+
+    assert!(!is_ssh_url("https://github.com/org/repo.git"));
+    assert!(!is_ssh_url("file:///tmp/repo"));
+    assert!(!is_ssh_url("/tmp/repo"));
+    assert!(!is_ssh_url("/tmp/user@host:repo"));
+}
+
+#[test]
+fn test_validate_repository_url_accepts_clonable_forms() {
+    let temp_dir = TempDir::new().unwrap();
+    let local = temp_dir.path().to_str().unwrap();
+
+    for url in [
+        "https://github.com/org/repo.git",
+        "https://gitlab.com/org/repo",
+        "http://git.internal/team/repo.git",
+        "git@github.com:org/repo.git",
+        "ssh://git@github.com/org/repo.git",
+        "ssh://git@git.example.com:2222/org/repo.git",
+        "git://git.example.com/repo.git",
+        "file:///tmp/repo",
+        local,
+    ] {
+        assert_eq!(validate_repository_url(url).unwrap(), url);
+    }
+}
+
+#[test]
+fn test_validate_repository_url_adds_github_suffix() {
+    assert_eq!(
+        validate_repository_url("https://github.com/org/repo").unwrap(),
+        "https://github.com/org/repo.git"
+    );
+    assert_eq!(
+        validate_repository_url("https://github.com/org/repo/").unwrap(),
+        "https://github.com/org/repo.git"
+    );
+}
+
+#[test]
+fn test_validate_repository_url_rejects_garbage() {
+    for url in [
+        "",
+        "not-a-valid-url",
+        "https://",
+        "https://github.com",
+        "https://github.com/",
+        "ftp://example.com/repo.git",
+        "git@github.com:",
+        "@github.com:org/repo.git",
+        "https://github.com/org/my repo",
+        "file://",
+    ] {
+        let err = validate_repository_url(url).unwrap_err();
+        assert!(
+            err.to_string().contains("Invalid repository URL"),
+            "{:?}: {}",
+            url,
+            err
+        );
+    }
+}
+
+#[test]
+fn test_git_ssh_command() {
+    // Default options leave ssh configuration to the host
+    assert_eq!(git_ssh_command(&SshOptions::default()), None);
+
+    let options = SshOptions {
+        key: Some(PathBuf::from("/home/me/.ssh/deploy_key")),
+        accept_new_host_keys: false,
+    };
+    assert_eq!(
+        git_ssh_command(&options).as_deref(),
+        Some("ssh -i '/home/me/.ssh/deploy_key' -o IdentitiesOnly=yes")
+    );
+
+    let options = SshOptions {
+        key: None,
+        accept_new_host_keys: true,
+    };
+    assert_eq!(
+        git_ssh_command(&options).as_deref(),
+        Some("ssh -o StrictHostKeyChecking=accept-new")
+    );
+}
+
+#[test]
+fn test_copy_local_repository_includes_uncommitted_work() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo_dir = temp_dir.path().join("local-repo");
+    create_local_git_repo(&repo_dir);
+
+    // Uncommitted changes must come along
+    fs::create_dir_all(repo_dir.join("src")).unwrap();
+    fs::write(repo_dir.join("src").join("wip.rs"), "// work in progress").unwrap();
+
+    let workspace = temp_dir.path().join("workspace");
+    copy_local_repository(&repo_dir, &workspace).unwrap();
+
+    assert!(workspace.join("README.md").exists());
+    assert!(workspace.join(".git").is_dir());
+    assert_eq!(
+        fs::read_to_string(workspace.join("src").join("wip.rs")).unwrap(),
+        "// work in progress"
+    );
+    assert_eq!(
+        detect_current_branch(&workspace).as_deref(),
+        Some("feature/local")
+    );
+}
+
+#[test]
+fn test_copy_local_repository_requires_directory() {
+    let temp_dir = TempDir::new().unwrap();
+    let err = copy_local_repository(
+        &temp_dir.path().join("missing"),
+        &temp_dir.path().join("workspace"),
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("not a directory"));
+}
+
+#[test]
+fn test_detect_current_branch() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo_dir = temp_dir.path().join("local-repo");
+    create_local_git_repo(&repo_dir);
+
+    assert_eq!(
+        detect_current_branch(&repo_dir).as_deref(),
+        Some("feature/local")
+    );
+
+    // Not a git repository
+    let plain_dir = temp_dir.path().join("plain");
+    fs::create_dir_all(&plain_dir).unwrap();
+    assert_eq!(detect_current_branch(&plain_dir), None);
+}
+
+#[test]
+fn test_create_branch() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo_dir = temp_dir.path().join("local-repo");
+    create_local_git_repo(&repo_dir);
+
+    create_branch(&repo_dir, "feature-x", false).unwrap();
+    assert_eq!(
+        detect_current_branch(&repo_dir).as_deref(),
+        Some("feature-x")
+    );
+
+    // Existing branches are only recreated with force
+    let err = create_branch(&repo_dir, "feature/local", false).unwrap_err();
+    assert!(err.to_string().contains("already exists"));
+
+    create_branch(&repo_dir, "feature/local", true).unwrap();
+    assert_eq!(
+        detect_current_branch(&repo_dir).as_deref(),
+        Some("feature/local")
+    );
+}
+
+#[test]
+fn test_verify_repository_setup_without_crowdcontrol_dir() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("README.md"), "# Test").unwrap();
+
+    let report = verify_repository_setup(temp_dir.path()).unwrap();
+    assert!(!report.has_crowdcontrol_dir);
+    assert_eq!(report.files.len(), REPOSITORY_SETUP_FILES.len());
+    assert!(report.files.iter().all(|(_, present)| !present));
+}
+
+#[test]
+fn test_verify_repository_setup_reports_partial_setup() {
+    let temp_dir = TempDir::new().unwrap();
+    let crowdcontrol_dir = temp_dir.path().join(".crowdcontrol");
+    fs::create_dir(&crowdcontrol_dir).unwrap();
+    fs::write(crowdcontrol_dir.join("setup.sh"), "#!/bin/bash\n").unwrap();
+    fs::write(crowdcontrol_dir.join("config.toml"), "").unwrap();
+    // A directory where a script is expected doesn't count
+    fs::create_dir(crowdcontrol_dir.join("stop.sh")).unwrap();
+
+    let report = verify_repository_setup(temp_dir.path()).unwrap();
+    assert!(report.has_crowdcontrol_dir);
+    assert_eq!(
+        report.files,
+        vec![
+            ("setup.sh", true),
+            ("start.sh", false),
+            ("stop.sh", false),
+            ("config.toml", true),
+        ]
+    );
+}
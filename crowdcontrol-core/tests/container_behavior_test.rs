@@ -2,11 +2,13 @@
 // Run with: cargo test --package crowdcontrol-core --ignored
 
 use anyhow::Result;
-use crowdcontrol_core::{Config, DockerClient};
+use crowdcontrol_core::{
+    api_version_supported, AgentStatus, Config, ContainerOptions, CrowdControlError, DockerClient,
+    HealthStatus, LogsQuery, WorkspaceLayout,
+};
 use std::fs;
 use std::process::Command;
 use tempfile::TempDir;
-use tokio;
 /// Test that verifies the actual behavior inside containers matches expectations
 #[tokio::test]
 #[ignore = "requires Docker"]
@@ -18,6 +20,14 @@ async fn test_container_claude_authentication() -> Result<()> {
         verbose: 0,
         default_memory: None,
         default_cpus: None,
+        default_platform: None,
+        mount_claude_config: true,
+        connect_default_command: None,
+        container_prefix: "crowdcontrol".to_string(),
+        require_crowdcontrol: false,
+        keychain_service: "Claude Code-credentials".to_string(),
+        workspace_layout: WorkspaceLayout::Flat,
+        connect_auto_start: false,
     };
 
     let docker = DockerClient::new(config.clone())?;
@@ -43,9 +53,9 @@ async fn test_container_claude_authentication() -> Result<()> {
 
     // Test 1: Verify Claude CLI is available
     let output = Command::new("docker")
-        .args(&[
+        .args([
             "exec",
-            &format!("crowdcontrol-{}", agent_name),
+            &config.container_name(agent_name),
             "which",
             "claude",
         ])
@@ -58,7 +68,7 @@ async fn test_container_claude_authentication() -> Result<()> {
 
     // Test 2: Verify working directory is /workspace
     let output = Command::new("docker")
-        .args(&["exec", &format!("crowdcontrol-{}", agent_name), "pwd"])
+        .args(["exec", &config.container_name(agent_name), "pwd"])
         .output()?;
 
     let pwd_binding = String::from_utf8_lossy(&output.stdout);
@@ -70,9 +80,9 @@ async fn test_container_claude_authentication() -> Result<()> {
 
     // Test 3: Verify workspace files are accessible
     let output = Command::new("docker")
-        .args(&[
+        .args([
             "exec",
-            &format!("crowdcontrol-{}", agent_name),
+            &config.container_name(agent_name),
             "ls",
             "-la",
             "/workspace",
@@ -91,9 +101,9 @@ async fn test_container_claude_authentication() -> Result<()> {
 
     // Test 4: Verify Claude configuration directory exists
     let output = Command::new("docker")
-        .args(&[
+        .args([
             "exec",
-            &format!("crowdcontrol-{}", agent_name),
+            &config.container_name(agent_name),
             "ls",
             "-la",
             "/home/developer/.claude",
@@ -109,7 +119,7 @@ async fn test_container_claude_authentication() -> Result<()> {
 
     // Test 5: Verify user context
     let output = Command::new("docker")
-        .args(&["exec", &format!("crowdcontrol-{}", agent_name), "whoami"])
+        .args(["exec", &config.container_name(agent_name), "whoami"])
         .output()?;
 
     let user_binding = String::from_utf8_lossy(&output.stdout);
@@ -123,12 +133,7 @@ async fn test_container_claude_authentication() -> Result<()> {
     let tools = ["git", "jq", "curl", "node", "npm"];
     for tool in &tools {
         let output = Command::new("docker")
-            .args(&[
-                "exec",
-                &format!("crowdcontrol-{}", agent_name),
-                "which",
-                tool,
-            ])
+            .args(["exec", &config.container_name(agent_name), "which", tool])
             .output()?;
 
         assert!(
@@ -155,6 +160,14 @@ async fn test_container_claude_authentication_with_credentials() -> Result<()> {
         verbose: 0,
         default_memory: None,
         default_cpus: None,
+        default_platform: None,
+        mount_claude_config: true,
+        connect_default_command: None,
+        container_prefix: "crowdcontrol".to_string(),
+        require_crowdcontrol: false,
+        keychain_service: "Claude Code-credentials".to_string(),
+        workspace_layout: WorkspaceLayout::Flat,
+        connect_auto_start: false,
     };
 
     // Create mock Claude credentials
@@ -188,9 +201,9 @@ async fn test_container_claude_authentication_with_credentials() -> Result<()> {
 
     // Test: Run refresh script to set up Claude auth
     let output = Command::new("docker")
-        .args(&[
+        .args([
             "exec",
-            &format!("crowdcontrol-{}", agent_name),
+            &config.container_name(agent_name),
             "refresh-claude-auth.sh",
         ])
         .output()?;
@@ -200,9 +213,9 @@ async fn test_container_claude_authentication_with_credentials() -> Result<()> {
 
         // Verify credentials were copied
         let output = Command::new("docker")
-            .args(&[
+            .args([
                 "exec",
-                &format!("crowdcontrol-{}", agent_name),
+                &config.container_name(agent_name),
                 "cat",
                 "/home/developer/.claude/.credentials.json",
             ])
@@ -218,9 +231,9 @@ async fn test_container_claude_authentication_with_credentials() -> Result<()> {
 
         // Verify .claude.json transformations were applied
         let output = Command::new("docker")
-            .args(&[
+            .args([
                 "exec",
-                &format!("crowdcontrol-{}", agent_name),
+                &config.container_name(agent_name),
                 "cat",
                 "/home/developer/.claude.json",
             ])
@@ -265,6 +278,14 @@ async fn test_container_file_permissions() -> Result<()> {
         verbose: 0,
         default_memory: None,
         default_cpus: None,
+        default_platform: None,
+        mount_claude_config: true,
+        connect_default_command: None,
+        container_prefix: "crowdcontrol".to_string(),
+        require_crowdcontrol: false,
+        keychain_service: "Claude Code-credentials".to_string(),
+        workspace_layout: WorkspaceLayout::Flat,
+        connect_auto_start: false,
     };
 
     let docker = DockerClient::new(config.clone())?;
@@ -284,9 +305,9 @@ async fn test_container_file_permissions() -> Result<()> {
 
     // Test: Verify file is readable
     let output = Command::new("docker")
-        .args(&[
+        .args([
             "exec",
-            &format!("crowdcontrol-{}", agent_name),
+            &config.container_name(agent_name),
             "cat",
             "/workspace/test.txt",
         ])
@@ -301,9 +322,9 @@ async fn test_container_file_permissions() -> Result<()> {
 
     // Test: Verify file is writable
     let output = Command::new("docker")
-        .args(&[
+        .args([
             "exec",
-            &format!("crowdcontrol-{}", agent_name),
+            &config.container_name(agent_name),
             "sh",
             "-c",
             "echo 'new content' > /workspace/new_file.txt",
@@ -346,6 +367,14 @@ async fn test_container_environment_variables() -> Result<()> {
         verbose: 0,
         default_memory: None,
         default_cpus: None,
+        default_platform: None,
+        mount_claude_config: true,
+        connect_default_command: None,
+        container_prefix: "crowdcontrol".to_string(),
+        require_crowdcontrol: false,
+        keychain_service: "Claude Code-credentials".to_string(),
+        workspace_layout: WorkspaceLayout::Flat,
+        connect_auto_start: false,
     };
 
     let docker = DockerClient::new(config.clone())?;
@@ -370,12 +399,7 @@ async fn test_container_environment_variables() -> Result<()> {
 
     for (var, expected) in &env_vars {
         let output = Command::new("docker")
-            .args(&[
-                "exec",
-                &format!("crowdcontrol-{}", agent_name),
-                "printenv",
-                var,
-            ])
+            .args(["exec", &config.container_name(agent_name), "printenv", var])
             .output()?;
 
         if output.status.success() {
@@ -391,9 +415,9 @@ async fn test_container_environment_variables() -> Result<()> {
 
     // Test: Verify PATH includes common directories
     let output = Command::new("docker")
-        .args(&[
+        .args([
             "exec",
-            &format!("crowdcontrol-{}", agent_name),
+            &config.container_name(agent_name),
             "printenv",
             "PATH",
         ])
@@ -427,6 +451,14 @@ async fn test_container_git_functionality() -> Result<()> {
         verbose: 0,
         default_memory: None,
         default_cpus: None,
+        default_platform: None,
+        mount_claude_config: true,
+        connect_default_command: None,
+        container_prefix: "crowdcontrol".to_string(),
+        require_crowdcontrol: false,
+        keychain_service: "Claude Code-credentials".to_string(),
+        workspace_layout: WorkspaceLayout::Flat,
+        connect_auto_start: false,
     };
 
     let docker = DockerClient::new(config.clone())?;
@@ -438,29 +470,29 @@ async fn test_container_git_functionality() -> Result<()> {
 
     // Initialize git repo on host
     Command::new("git")
-        .args(&["init"])
+        .args(["init"])
         .current_dir(&workspace_path)
         .output()?;
 
     fs::write(workspace_path.join("README.md"), "# Test Repository")?;
 
     Command::new("git")
-        .args(&["config", "user.email", "test@example.com"])
+        .args(["config", "user.email", "test@example.com"])
         .current_dir(&workspace_path)
         .output()?;
 
     Command::new("git")
-        .args(&["config", "user.name", "Test User"])
+        .args(["config", "user.name", "Test User"])
         .current_dir(&workspace_path)
         .output()?;
 
     Command::new("git")
-        .args(&["add", "."])
+        .args(["add", "."])
         .current_dir(&workspace_path)
         .output()?;
 
     Command::new("git")
-        .args(&["commit", "-m", "Initial commit"])
+        .args(["commit", "-m", "Initial commit"])
         .current_dir(&workspace_path)
         .output()?;
 
@@ -473,12 +505,7 @@ async fn test_container_git_functionality() -> Result<()> {
 
     // Test: Verify git is functional in container
     let output = Command::new("docker")
-        .args(&[
-            "exec",
-            &format!("crowdcontrol-{}", agent_name),
-            "git",
-            "status",
-        ])
+        .args(["exec", &config.container_name(agent_name), "git", "status"])
         .output()?;
 
     assert!(
@@ -493,9 +520,9 @@ async fn test_container_git_functionality() -> Result<()> {
 
     // Test: Verify git log shows our commit
     let output = Command::new("docker")
-        .args(&[
+        .args([
             "exec",
-            &format!("crowdcontrol-{}", agent_name),
+            &config.container_name(agent_name),
             "git",
             "log",
             "--oneline",
@@ -511,9 +538,9 @@ async fn test_container_git_functionality() -> Result<()> {
 
     // Test: Verify we can make changes and commit them
     let output = Command::new("docker")
-        .args(&[
+        .args([
             "exec",
-            &format!("crowdcontrol-{}", agent_name),
+            &config.container_name(agent_name),
             "sh",
             "-c",
             "echo '## Test' >> README.md && git add README.md && git commit -m 'Update README'",
@@ -540,3 +567,697 @@ async fn test_container_git_functionality() -> Result<()> {
 
     Ok(())
 }
+
+/// Test that a configured health check reports the container as healthy
+#[tokio::test]
+#[ignore = "requires Docker"]
+async fn test_container_healthcheck_becomes_healthy() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let config = Config {
+        workspaces_dir: temp_dir.path().to_path_buf(),
+        image: "crowdcontrol:latest".to_string(),
+        verbose: 0,
+        default_memory: None,
+        default_cpus: None,
+        default_platform: None,
+        mount_claude_config: true,
+        connect_default_command: None,
+        container_prefix: "crowdcontrol".to_string(),
+        require_crowdcontrol: false,
+        keychain_service: "Claude Code-credentials".to_string(),
+        workspace_layout: WorkspaceLayout::Flat,
+        connect_auto_start: false,
+    };
+
+    let docker = DockerClient::new(config.clone())?;
+    let agent_name = "test-healthcheck";
+    let workspace_path = config.agent_workspace_path(agent_name);
+    fs::create_dir_all(&workspace_path)?;
+
+    let options = ContainerOptions {
+        healthcheck: Some("true".to_string()),
+        ..Default::default()
+    };
+    let container_id = docker
+        .create_container_with_options(agent_name, &workspace_path, &options)
+        .await?;
+    docker.start_container(&container_id).await?;
+
+    // The first probe runs after one interval (5s)
+    let mut healthy = false;
+    for _ in 0..30 {
+        if let Some(health) = docker.get_container_health(&container_id).await? {
+            if health.status == HealthStatus::Healthy {
+                healthy = true;
+                break;
+            }
+        }
+        tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+    }
+
+    // Cleanup
+    docker.stop_container(&container_id, true).await?;
+    docker.remove_container(&container_id).await?;
+
+    assert!(
+        healthy,
+        "Container with `true` health check should become healthy"
+    );
+
+    Ok(())
+}
+
+/// Test that container logs can be captured into a file
+#[tokio::test]
+#[ignore = "requires Docker"]
+async fn test_container_logs_written_to_file() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let config = Config {
+        workspaces_dir: temp_dir.path().to_path_buf(),
+        image: "crowdcontrol:latest".to_string(),
+        verbose: 0,
+        default_memory: None,
+        default_cpus: None,
+        default_platform: None,
+        mount_claude_config: true,
+        connect_default_command: None,
+        container_prefix: "crowdcontrol".to_string(),
+        require_crowdcontrol: false,
+        keychain_service: "Claude Code-credentials".to_string(),
+        workspace_layout: WorkspaceLayout::Flat,
+        connect_auto_start: false,
+    };
+
+    let docker = DockerClient::new(config.clone())?;
+    let agent_name = "test-logs-file";
+    let workspace_path = config.agent_workspace_path(agent_name);
+    fs::create_dir_all(&workspace_path)?;
+
+    let container_id = docker
+        .create_container(agent_name, &workspace_path, None, None)
+        .await?;
+    docker.start_container(&container_id).await?;
+
+    // Give the entrypoint time to produce output
+    tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
+
+    let log_path = temp_dir.path().join("agent.log");
+    let mut file = fs::File::create(&log_path)?;
+    let query = LogsQuery {
+        tail: Some("all".to_string()),
+        timestamps: true,
+        ..Default::default()
+    };
+    let result = docker
+        .write_container_logs(&container_id, &query, &mut file)
+        .await;
+
+    // Cleanup
+    docker.stop_container(&container_id, true).await?;
+    docker.remove_container(&container_id).await?;
+
+    result?;
+    let contents = fs::read_to_string(&log_path)?;
+    assert!(
+        !contents.is_empty(),
+        "Captured log file should not be empty"
+    );
+
+    Ok(())
+}
+
+/// Test that a read-only root filesystem still leaves the workspace writable
+#[tokio::test]
+#[ignore = "requires Docker"]
+async fn test_container_read_only_rootfs() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let config = Config {
+        workspaces_dir: temp_dir.path().to_path_buf(),
+        image: "crowdcontrol:latest".to_string(),
+        verbose: 0,
+        default_memory: None,
+        default_cpus: None,
+        default_platform: None,
+        mount_claude_config: true,
+        connect_default_command: None,
+        container_prefix: "crowdcontrol".to_string(),
+        require_crowdcontrol: false,
+        keychain_service: "Claude Code-credentials".to_string(),
+        workspace_layout: WorkspaceLayout::Flat,
+        connect_auto_start: false,
+    };
+
+    let docker = DockerClient::new(config.clone())?;
+    let agent_name = "test-read-only";
+    let workspace_path = config.agent_workspace_path(agent_name);
+    fs::create_dir_all(&workspace_path)?;
+
+    let options = ContainerOptions {
+        read_only: true,
+        ..Default::default()
+    };
+    let container_id = docker
+        .create_container_with_options(agent_name, &workspace_path, &options)
+        .await?;
+    docker.start_container(&container_id).await?;
+
+    // Wait a moment for container to fully start
+    tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+
+    let container_name = config.container_name(agent_name);
+    let touch = |path: &str| {
+        Command::new("docker")
+            .args(["exec", "-u", "root", &container_name, "touch", path])
+            .output()
+    };
+    let etc_write = touch("/etc/crowdcontrol-test")?;
+    let workspace_write = touch("/workspace/crowdcontrol-test")?;
+    let claude_write = touch("/home/developer/.claude/crowdcontrol-test")?;
+
+    // Cleanup
+    docker.stop_container(&container_id, true).await?;
+    docker.remove_container(&container_id).await?;
+
+    assert!(
+        !etc_write.status.success(),
+        "Writing to /etc should fail with a read-only root filesystem"
+    );
+    assert!(
+        workspace_write.status.success(),
+        "Writing to /workspace should succeed: {}",
+        String::from_utf8_lossy(&workspace_write.stderr)
+    );
+    assert!(
+        claude_write.status.success(),
+        "Claude config directory should stay writable for auth refresh: {}",
+        String::from_utf8_lossy(&claude_write.stderr)
+    );
+    assert!(workspace_path.join("crowdcontrol-test").exists());
+
+    Ok(())
+}
+
+/// Test that --readonly-workspace keeps the agent from writing to /workspace
+#[tokio::test]
+#[ignore = "requires Docker"]
+async fn test_container_readonly_workspace() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let config = Config {
+        workspaces_dir: temp_dir.path().to_path_buf(),
+        image: "crowdcontrol:latest".to_string(),
+        verbose: 0,
+        default_memory: None,
+        default_cpus: None,
+        default_platform: None,
+        mount_claude_config: false,
+        connect_default_command: None,
+        container_prefix: "crowdcontrol".to_string(),
+        require_crowdcontrol: false,
+        keychain_service: "Claude Code-credentials".to_string(),
+        workspace_layout: WorkspaceLayout::Flat,
+        connect_auto_start: false,
+    };
+    let docker = DockerClient::new(config.clone())?;
+
+    for readonly_workspace in [true, false] {
+        let agent_name = if readonly_workspace {
+            "test-readonly-workspace"
+        } else {
+            "test-writable-workspace"
+        };
+        let workspace_path = config.agent_workspace_path(agent_name);
+        fs::create_dir_all(&workspace_path)?;
+
+        let options = ContainerOptions {
+            readonly_workspace,
+            ..Default::default()
+        };
+        let container_id = docker
+            .create_container_with_options(agent_name, &workspace_path, &options)
+            .await?;
+        docker.start_container(&container_id).await?;
+        tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+
+        let write = Command::new("docker")
+            .args([
+                "exec",
+                "-u",
+                "root",
+                &config.container_name(agent_name),
+                "touch",
+                "/workspace/crowdcontrol-test",
+            ])
+            .output()?;
+
+        // Cleanup
+        docker.stop_container(&container_id, true).await?;
+        docker.remove_container(&container_id).await?;
+
+        if readonly_workspace {
+            assert!(
+                !write.status.success(),
+                "Writing to a read-only /workspace should fail"
+            );
+            assert!(!workspace_path.join("crowdcontrol-test").exists());
+        } else {
+            assert!(
+                write.status.success(),
+                "Writing to /workspace should succeed: {}",
+                String::from_utf8_lossy(&write.stderr)
+            );
+            assert!(workspace_path.join("crowdcontrol-test").exists());
+        }
+    }
+
+    Ok(())
+}
+
+/// Test that the agent name, repository and branch are readable from container labels
+#[tokio::test]
+#[ignore = "requires Docker"]
+async fn test_container_labels_for_external_tooling() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let config = Config {
+        workspaces_dir: temp_dir.path().to_path_buf(),
+        image: "crowdcontrol:latest".to_string(),
+        verbose: 0,
+        default_memory: None,
+        default_cpus: None,
+        default_platform: None,
+        mount_claude_config: true,
+        connect_default_command: None,
+        container_prefix: "crowdcontrol".to_string(),
+        require_crowdcontrol: false,
+        keychain_service: "Claude Code-credentials".to_string(),
+        workspace_layout: WorkspaceLayout::Flat,
+        connect_auto_start: false,
+    };
+
+    let docker = DockerClient::new(config.clone())?;
+    let agent_name = "test-labels";
+    let workspace_path = config.agent_workspace_path(agent_name);
+    fs::create_dir_all(&workspace_path)?;
+
+    let options = ContainerOptions {
+        repository: Some("https://github.com/example/app.git".to_string()),
+        branch: Some("feature/login".to_string()),
+        ..Default::default()
+    };
+    let container_id = docker
+        .create_container_with_options(agent_name, &workspace_path, &options)
+        .await?;
+
+    let inspect = Command::new("docker")
+        .args([
+            "inspect",
+            "--format",
+            "{{json .Config.Labels}}",
+            &container_id,
+        ])
+        .output()?;
+
+    // Cleanup
+    docker.remove_container(&container_id).await?;
+
+    assert!(
+        inspect.status.success(),
+        "docker inspect failed: {}",
+        String::from_utf8_lossy(&inspect.stderr)
+    );
+    let labels: serde_json::Value = serde_json::from_slice(&inspect.stdout)?;
+    assert_eq!(labels["app"], "crowdcontrol");
+    assert_eq!(labels["crowdcontrol.name"], agent_name);
+    assert_eq!(
+        labels["crowdcontrol.repository"],
+        "https://github.com/example/app.git"
+    );
+    assert_eq!(labels["crowdcontrol.branch"], "feature/login");
+
+    Ok(())
+}
+
+/// Test that pausing a running agent reports Paused and unpausing returns it to Running
+#[tokio::test]
+#[ignore = "requires Docker"]
+async fn test_container_pause_and_unpause() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let config = Config {
+        workspaces_dir: temp_dir.path().to_path_buf(),
+        image: "crowdcontrol:latest".to_string(),
+        verbose: 0,
+        default_memory: None,
+        default_cpus: None,
+        default_platform: None,
+        mount_claude_config: true,
+        connect_default_command: None,
+        container_prefix: "crowdcontrol".to_string(),
+        require_crowdcontrol: false,
+        keychain_service: "Claude Code-credentials".to_string(),
+        workspace_layout: WorkspaceLayout::Flat,
+        connect_auto_start: false,
+    };
+
+    let docker = DockerClient::new(config.clone())?;
+    let agent_name = "test-pause";
+    let workspace_path = config.agent_workspace_path(agent_name);
+    fs::create_dir_all(&workspace_path)?;
+
+    let container_id = docker
+        .create_container(agent_name, &workspace_path, None, None)
+        .await?;
+    docker.start_container(&container_id).await?;
+    assert_eq!(
+        docker.get_container_status(agent_name).await?,
+        AgentStatus::Running
+    );
+
+    docker.pause_container(&container_id).await?;
+    let paused = docker.get_container_status(agent_name).await?;
+
+    docker.unpause_container(&container_id).await?;
+    let resumed = docker.get_container_status(agent_name).await?;
+
+    // Cleanup
+    docker.stop_container(&container_id, true).await?;
+    docker.remove_container(&container_id).await?;
+
+    assert_eq!(paused, AgentStatus::Paused);
+    assert_eq!(resumed, AgentStatus::Running);
+
+    Ok(())
+}
+
+/// Test that waiting for a container returns as soon as it runs, and times out with a
+/// typed error for one that never starts
+#[tokio::test]
+#[ignore = "requires Docker"]
+async fn test_wait_for_container_running() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let config = Config {
+        workspaces_dir: temp_dir.path().to_path_buf(),
+        image: "crowdcontrol:latest".to_string(),
+        verbose: 0,
+        default_memory: None,
+        default_cpus: None,
+        default_platform: None,
+        mount_claude_config: true,
+        connect_default_command: None,
+        container_prefix: "crowdcontrol".to_string(),
+        require_crowdcontrol: false,
+        keychain_service: "Claude Code-credentials".to_string(),
+        workspace_layout: WorkspaceLayout::Flat,
+        connect_auto_start: false,
+    };
+
+    let docker = DockerClient::new(config.clone())?;
+    let agent_name = "test-wait-running";
+    let workspace_path = config.agent_workspace_path(agent_name);
+    fs::create_dir_all(&workspace_path)?;
+
+    let container_id = docker
+        .create_container(agent_name, &workspace_path, None, None)
+        .await?;
+
+    // Never started, so the wait gives up
+    let not_started = docker
+        .wait_for_container_running(&container_id, std::time::Duration::from_millis(500))
+        .await;
+
+    docker.start_container(&container_id).await?;
+    let started = std::time::Instant::now();
+    let running = docker
+        .wait_for_container_running(&container_id, std::time::Duration::from_secs(30))
+        .await;
+    let elapsed = started.elapsed();
+
+    // Cleanup
+    docker.stop_container(&container_id, true).await?;
+    docker.remove_container(&container_id).await?;
+
+    let error = not_started.unwrap_err();
+    assert!(matches!(
+        CrowdControlError::find(&error),
+        Some(CrowdControlError::Timeout { .. })
+    ));
+    running?;
+    assert!(
+        elapsed < std::time::Duration::from_secs(5),
+        "wait took {:?} for a started container",
+        elapsed
+    );
+
+    Ok(())
+}
+
+/// Test that the preflight checks pass against a working daemon with the image built
+#[tokio::test]
+#[ignore = "requires Docker"]
+async fn test_check_environment() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let config = Config {
+        workspaces_dir: temp_dir.path().to_path_buf(),
+        image: "crowdcontrol:latest".to_string(),
+        verbose: 0,
+        default_memory: None,
+        default_cpus: None,
+        default_platform: None,
+        mount_claude_config: true,
+        connect_default_command: None,
+        container_prefix: "crowdcontrol".to_string(),
+        require_crowdcontrol: false,
+        keychain_service: "Claude Code-credentials".to_string(),
+        workspace_layout: WorkspaceLayout::Flat,
+        connect_auto_start: false,
+    };
+
+    let docker = DockerClient::new(config)?;
+    let checks = docker.check_environment().await;
+
+    let names: Vec<_> = checks.iter().map(|check| check.name).collect();
+    assert_eq!(names, ["Docker daemon", "Docker API version", "Image"]);
+    for check in &checks {
+        assert!(check.passed, "{}", check);
+    }
+
+    Ok(())
+}
+
+/// Test that the client negotiates down to an API version the daemon supports
+#[tokio::test]
+#[ignore = "requires Docker"]
+async fn test_client_negotiates_api_version() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let config = Config {
+        workspaces_dir: temp_dir.path().to_path_buf(),
+        image: "crowdcontrol:latest".to_string(),
+        verbose: 0,
+        default_memory: None,
+        default_cpus: None,
+        default_platform: None,
+        mount_claude_config: true,
+        connect_default_command: None,
+        container_prefix: "crowdcontrol".to_string(),
+        require_crowdcontrol: false,
+        keychain_service: "Claude Code-credentials".to_string(),
+        workspace_layout: WorkspaceLayout::Flat,
+        connect_auto_start: false,
+    };
+
+    let docker = DockerClient::new(config)?;
+    let (_, daemon_api) = docker.daemon_version().await?;
+    let client_api = docker.client_api_version().await;
+
+    // Never newer than either side supports
+    assert!(
+        api_version_supported(&daemon_api, &client_api),
+        "client API {} is newer than the daemon's {}",
+        client_api,
+        daemon_api
+    );
+    assert!(api_version_supported(
+        &bollard::API_DEFAULT_VERSION.to_string(),
+        &client_api
+    ));
+
+    Ok(())
+}
+
+/// Test that a container is created from the agent's own image rather than the configured one
+#[tokio::test]
+#[ignore = "requires Docker"]
+async fn test_container_uses_agent_image() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let config = Config {
+        workspaces_dir: temp_dir.path().to_path_buf(),
+        image: "crowdcontrol:does-not-exist".to_string(),
+        verbose: 0,
+        default_memory: None,
+        default_cpus: None,
+        default_platform: None,
+        mount_claude_config: true,
+        connect_default_command: None,
+        container_prefix: "crowdcontrol".to_string(),
+        require_crowdcontrol: false,
+        keychain_service: "Claude Code-credentials".to_string(),
+        workspace_layout: WorkspaceLayout::Flat,
+        connect_auto_start: false,
+    };
+
+    let docker = DockerClient::new(config.clone())?;
+    let agent_name = "test-agent-image";
+    let workspace_path = config.agent_workspace_path(agent_name);
+    fs::create_dir_all(&workspace_path)?;
+
+    let options = ContainerOptions {
+        image: Some("crowdcontrol:latest".to_string()),
+        ..Default::default()
+    };
+    let container_id = docker
+        .create_container_with_options(agent_name, &workspace_path, &options)
+        .await?;
+
+    let output = Command::new("docker")
+        .args(["inspect", "--format", "{{.Config.Image}}", &container_id])
+        .output()?;
+
+    // Cleanup
+    docker.remove_container(&container_id).await?;
+
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout).trim(),
+        "crowdcontrol:latest"
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "requires Docker"]
+async fn test_container_prefix_used_for_lookups() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let config = Config {
+        workspaces_dir: temp_dir.path().to_path_buf(),
+        image: "crowdcontrol:latest".to_string(),
+        verbose: 0,
+        default_memory: None,
+        default_cpus: None,
+        default_platform: None,
+        mount_claude_config: false,
+        connect_default_command: None,
+        container_prefix: "cc-prefix-test".to_string(),
+        require_crowdcontrol: false,
+        keychain_service: "Claude Code-credentials".to_string(),
+        workspace_layout: WorkspaceLayout::Flat,
+        connect_auto_start: false,
+    };
+    let default_prefix = Config {
+        container_prefix: "crowdcontrol".to_string(),
+        ..config.clone()
+    };
+
+    let docker = DockerClient::new(config.clone())?;
+    let agent_name = "test-prefix";
+    let workspace_path = config.agent_workspace_path(agent_name);
+    fs::create_dir_all(&workspace_path)?;
+
+    let container_id = docker
+        .create_container(agent_name, &workspace_path, None, None)
+        .await?;
+
+    let result = async {
+        // Every lookup agrees on the prefixed name
+        assert!(
+            docker
+                .container_exists("cc-prefix-test-test-prefix")
+                .await?
+        );
+        assert!(
+            docker
+                .validate_container_id(agent_name, &container_id)
+                .await?
+        );
+        assert_eq!(
+            docker.get_container_status(agent_name).await?,
+            AgentStatus::Created
+        );
+
+        // A client with the default prefix doesn't see it as this agent's container
+        let other = DockerClient::new(default_prefix)?;
+        assert!(!other.container_exists("crowdcontrol-test-prefix").await?);
+        assert!(
+            !other
+                .validate_container_id(agent_name, &container_id)
+                .await?
+        );
+        Ok::<_, anyhow::Error>(())
+    }
+    .await;
+
+    docker.remove_container(&container_id).await?;
+    result
+}
+
+/// Test that a container which dies right after starting is reported with its exit code
+/// and last log lines instead of as started
+#[tokio::test]
+#[ignore = "requires Docker"]
+async fn test_check_started_reports_immediate_exit() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let config = Config {
+        workspaces_dir: temp_dir.path().to_path_buf(),
+        image: "crowdcontrol:latest".to_string(),
+        verbose: 0,
+        default_memory: None,
+        default_cpus: None,
+        default_platform: None,
+        mount_claude_config: false,
+        connect_default_command: None,
+        container_prefix: "crowdcontrol".to_string(),
+        require_crowdcontrol: false,
+        keychain_service: "Claude Code-credentials".to_string(),
+        workspace_layout: WorkspaceLayout::Flat,
+        connect_auto_start: false,
+    };
+
+    let docker = DockerClient::new(config.clone())?;
+    let agent_name = "test-immediate-exit";
+    let workspace_path = config.agent_workspace_path(agent_name);
+
+    // The entrypoint runs the repository's setup script and exits when it fails
+    let setup_dir = workspace_path.join("repo").join(".crowdcontrol");
+    fs::create_dir_all(&setup_dir)?;
+    fs::write(
+        setup_dir.join("setup.sh"),
+        "#!/bin/bash\necho 'setup exploded'\nexit 3\n",
+    )?;
+
+    let container_id = docker
+        .create_container(agent_name, &workspace_path, None, None)
+        .await?;
+
+    let result = async {
+        assert_eq!(docker.get_exit_code(&container_id).await?, None);
+
+        docker.start_container(&container_id).await?;
+        let error = docker
+            .check_started(&container_id, std::time::Duration::from_secs(30))
+            .await
+            .unwrap_err();
+
+        match CrowdControlError::find(&error) {
+            Some(CrowdControlError::ContainerExited {
+                exit_code, logs, ..
+            }) => {
+                assert_eq!(*exit_code, 3);
+                assert!(logs.contains("setup exploded"), "logs were: {}", logs);
+            }
+            other => panic!("expected ContainerExited, got {:?}", other),
+        }
+        assert_eq!(docker.get_exit_code(&container_id).await?, Some(3));
+        Ok::<_, anyhow::Error>(())
+    }
+    .await;
+
+    docker.remove_container(&container_id).await?;
+    result
+}
@@ -0,0 +1,108 @@
+use anyhow::Result;
+use crowdcontrol_core::{check_workspaces_dir, directory_size, format_bytes};
+use std::fs;
+use tempfile::TempDir;
+
+#[test]
+fn test_format_bytes() {
+    assert_eq!(format_bytes(0), "0 B");
+    assert_eq!(format_bytes(512), "512 B");
+    assert_eq!(format_bytes(1024), "1.00 KiB");
+    assert_eq!(format_bytes(1536 * 1024 * 1024), "1.50 GiB");
+}
+
+#[test]
+fn test_directory_size_counts_nested_files() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let root = temp_dir.path();
+    fs::write(root.join("README.md"), vec![b'a'; 100])?;
+    fs::create_dir_all(root.join("node_modules/left-pad/lib"))?;
+    fs::write(root.join("node_modules/left-pad/index.js"), vec![b'b'; 250])?;
+    fs::write(
+        root.join("node_modules/left-pad/lib/pad.js"),
+        vec![b'c'; 1000],
+    )?;
+    fs::create_dir(root.join("empty"))?;
+
+    let usage = directory_size(root)?;
+    assert_eq!(usage.bytes, 1350);
+    assert!(usage.skipped.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_directory_size_missing_directory_is_an_error() {
+    let temp_dir = TempDir::new().unwrap();
+    assert!(directory_size(&temp_dir.path().join("missing")).is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_directory_size_skips_unreadable_directories() -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = TempDir::new()?;
+    let root = temp_dir.path();
+    fs::write(root.join("visible.txt"), vec![b'a'; 10])?;
+    let locked = root.join("locked");
+    fs::create_dir(&locked)?;
+    fs::write(locked.join("hidden.txt"), vec![b'b'; 20])?;
+    fs::set_permissions(&locked, fs::Permissions::from_mode(0o000))?;
+
+    // Permissions aren't enforced for root, so there is nothing to skip
+    if fs::read_dir(&locked).is_ok() {
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o755))?;
+        return Ok(());
+    }
+
+    let usage = directory_size(root);
+    fs::set_permissions(&locked, fs::Permissions::from_mode(0o755))?;
+
+    let usage = usage?;
+    assert_eq!(usage.bytes, 10);
+    assert_eq!(usage.skipped, vec![locked]);
+
+    Ok(())
+}
+
+#[test]
+fn test_check_workspaces_dir() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+
+    let checks = check_workspaces_dir(temp_dir.path());
+    assert!(checks.iter().all(|check| check.passed), "{:?}", checks);
+    assert!(checks.iter().any(|check| check.name == "Disk space"));
+
+    // A missing directory is only a warning: `new` creates it
+    let checks = check_workspaces_dir(&temp_dir.path().join("missing"));
+    assert_eq!(checks.len(), 1);
+    assert!(checks[0].passed && checks[0].warning);
+
+    let file = temp_dir.path().join("file");
+    fs::write(&file, "")?;
+    assert!(!check_workspaces_dir(&file)[0].passed);
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn test_check_workspaces_dir_without_permissions() -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = TempDir::new()?;
+    let protected = temp_dir.path().join("protected");
+    fs::create_dir(&protected)?;
+    fs::set_permissions(&protected, fs::Permissions::from_mode(0o000))?;
+
+    let checks = check_workspaces_dir(&protected);
+    fs::set_permissions(&protected, fs::Permissions::from_mode(0o755))?;
+
+    let permissions = checks
+        .iter()
+        .find(|check| check.name == "Workspaces permissions")
+        .expect("permissions are checked");
+    assert!(!permissions.passed);
+    assert!(permissions.detail.contains("mode 000"));
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use crowdcontrol_core::{Config, DockerClient};
+use crowdcontrol_core::{Config, DockerClient, WorkspaceLayout};
 use std::fs;
 use tempfile::TempDir;
 
@@ -27,6 +27,14 @@ async fn test_claude_config_mount() -> anyhow::Result<()> {
         verbose: 0,
         default_memory: None,
         default_cpus: None,
+        default_platform: None,
+        mount_claude_config: true,
+        connect_default_command: None,
+        container_prefix: "crowdcontrol".to_string(),
+        require_crowdcontrol: false,
+        keychain_service: "Claude Code-credentials".to_string(),
+        workspace_layout: WorkspaceLayout::Flat,
+        connect_auto_start: false,
     };
     
     // Create workspace directory
@@ -70,6 +78,14 @@ async fn test_no_claude_config_no_mount() -> anyhow::Result<()> {
         verbose: 0,
         default_memory: None,
         default_cpus: None,
+        default_platform: None,
+        mount_claude_config: true,
+        connect_default_command: None,
+        container_prefix: "crowdcontrol".to_string(),
+        require_crowdcontrol: false,
+        keychain_service: "Claude Code-credentials".to_string(),
+        workspace_layout: WorkspaceLayout::Flat,
+        connect_auto_start: false,
     };
     
     // Create workspace directory
@@ -108,6 +124,14 @@ async fn test_container_has_crowdcontrol_label() -> anyhow::Result<()> {
         verbose: 0,
         default_memory: None,
         default_cpus: None,
+        default_platform: None,
+        mount_claude_config: true,
+        connect_default_command: None,
+        container_prefix: "crowdcontrol".to_string(),
+        require_crowdcontrol: false,
+        keychain_service: "Claude Code-credentials".to_string(),
+        workspace_layout: WorkspaceLayout::Flat,
+        connect_auto_start: false,
     };
     
     let workspace_path = config.workspaces_dir.join("test-agent");
@@ -153,6 +177,14 @@ async fn test_container_with_resource_limits() -> anyhow::Result<()> {
         verbose: 0,
         default_memory: Some("512m".to_string()),
         default_cpus: Some("0.5".to_string()),
+        default_platform: None,
+        mount_claude_config: true,
+        connect_default_command: None,
+        container_prefix: "crowdcontrol".to_string(),
+        require_crowdcontrol: false,
+        keychain_service: "Claude Code-credentials".to_string(),
+        workspace_layout: WorkspaceLayout::Flat,
+        connect_auto_start: false,
     };
     
     let workspace_path = config.workspaces_dir.join("test-agent");
@@ -0,0 +1,41 @@
+use anyhow::anyhow;
+use crowdcontrol_core::{write_ready_file, ReadyStatus};
+use std::fs;
+use tempfile::TempDir;
+
+#[test]
+fn test_ready_status_json() {
+    let ready = ReadyStatus::from_result(&Ok(()));
+    assert_eq!(serde_json::to_string(&ready).unwrap(), r#"{"ready":true}"#);
+
+    let failed = ReadyStatus::from_result(&Err(
+        anyhow!("Timed out").context("Agent 'web' did not finish initializing")
+    ));
+    assert!(!failed.ready);
+    assert_eq!(
+        failed.error.as_deref(),
+        Some("Agent 'web' did not finish initializing: Timed out")
+    );
+}
+
+#[test]
+fn test_write_ready_file_replaces_previous_status() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("web.ready");
+
+    write_ready_file(&path, &ReadyStatus::from_result(&Err(anyhow!("not yet")))).unwrap();
+    write_ready_file(&path, &ReadyStatus::from_result(&Ok(()))).unwrap();
+
+    let status: ReadyStatus = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(
+        status,
+        ReadyStatus {
+            ready: true,
+            error: None
+        }
+    );
+
+    // Only the status file is left behind
+    let entries: Vec<_> = fs::read_dir(temp_dir.path()).unwrap().collect();
+    assert_eq!(entries.len(), 1);
+}
@@ -2,11 +2,10 @@
 // Run with: cargo test --package crowdcontrol-core --ignored
 
 use anyhow::Result;
-use crowdcontrol_core::{Config, DockerClient};
+use crowdcontrol_core::{Config, DockerClient, WorkspaceLayout};
 use std::fs;
 use std::process::Command;
 use tempfile::TempDir;
-use tokio;
 
 /// Tests specifically for the refresh-claude-auth.sh script behavior
 #[tokio::test]
@@ -19,6 +18,14 @@ async fn test_refresh_script_with_no_credentials() -> Result<()> {
         verbose: 0,
         default_memory: None,
         default_cpus: None,
+        default_platform: None,
+        mount_claude_config: true,
+        connect_default_command: None,
+        container_prefix: "crowdcontrol".to_string(),
+        require_crowdcontrol: false,
+        keychain_service: "Claude Code-credentials".to_string(),
+        workspace_layout: WorkspaceLayout::Flat,
+        connect_auto_start: false,
     };
 
     let docker = DockerClient::new(config.clone())?;
@@ -36,9 +43,9 @@ async fn test_refresh_script_with_no_credentials() -> Result<()> {
 
     // Test: Run refresh script without any credentials
     let output = Command::new("docker")
-        .args(&[
+        .args([
             "exec",
-            &format!("crowdcontrol-{}", agent_name),
+            &config.container_name(agent_name),
             "refresh-claude-auth.sh",
         ])
         .output()?;
@@ -82,6 +89,14 @@ async fn test_refresh_script_with_keychain_credentials() -> Result<()> {
         verbose: 0,
         default_memory: None,
         default_cpus: None,
+        default_platform: None,
+        mount_claude_config: true,
+        connect_default_command: None,
+        container_prefix: "crowdcontrol".to_string(),
+        require_crowdcontrol: false,
+        keychain_service: "Claude Code-credentials".to_string(),
+        workspace_layout: WorkspaceLayout::Flat,
+        connect_auto_start: false,
     };
 
     let docker = DockerClient::new(config.clone())?;
@@ -102,9 +117,9 @@ async fn test_refresh_script_with_keychain_credentials() -> Result<()> {
         r#"{"api_key": "sk-ant-api03-keychain-mock", "organization_id": "org-123"}"#;
 
     let output = Command::new("docker")
-        .args(&[
+        .args([
             "exec",
-            &format!("crowdcontrol-{}", agent_name),
+            &config.container_name(agent_name),
             "refresh-claude-auth.sh",
             mock_keychain_creds,
         ])
@@ -129,9 +144,9 @@ async fn test_refresh_script_with_keychain_credentials() -> Result<()> {
 
     // Test: Verify credentials file was created
     let output = Command::new("docker")
-        .args(&[
+        .args([
             "exec",
-            &format!("crowdcontrol-{}", agent_name),
+            &config.container_name(agent_name),
             "cat",
             "/home/developer/.claude/.credentials.json",
         ])
@@ -153,9 +168,9 @@ async fn test_refresh_script_with_keychain_credentials() -> Result<()> {
 
     // Test: Verify file permissions
     let output = Command::new("docker")
-        .args(&[
+        .args([
             "exec",
-            &format!("crowdcontrol-{}", agent_name),
+            &config.container_name(agent_name),
             "stat",
             "-c",
             "%a",
@@ -173,9 +188,9 @@ async fn test_refresh_script_with_keychain_credentials() -> Result<()> {
 
     // Test: Verify file ownership
     let output = Command::new("docker")
-        .args(&[
+        .args([
             "exec",
-            &format!("crowdcontrol-{}", agent_name),
+            &config.container_name(agent_name),
             "stat",
             "-c",
             "%U:%G",
@@ -207,6 +222,14 @@ async fn test_refresh_script_with_file_based_credentials() -> Result<()> {
         verbose: 0,
         default_memory: None,
         default_cpus: None,
+        default_platform: None,
+        mount_claude_config: true,
+        connect_default_command: None,
+        container_prefix: "crowdcontrol".to_string(),
+        require_crowdcontrol: false,
+        keychain_service: "Claude Code-credentials".to_string(),
+        workspace_layout: WorkspaceLayout::Flat,
+        connect_auto_start: false,
     };
 
     // Create mock Claude credentials in the expected mount location
@@ -230,9 +253,9 @@ async fn test_refresh_script_with_file_based_credentials() -> Result<()> {
 
     // Mount the claude config directory
     Command::new("docker")
-        .args(&[
+        .args([
             "exec",
-            &format!("crowdcontrol-{}", agent_name),
+            &config.container_name(agent_name),
             "mkdir",
             "-p",
             "/mnt/claude-config",
@@ -241,10 +264,10 @@ async fn test_refresh_script_with_file_based_credentials() -> Result<()> {
 
     // Copy the mock credentials into the container (simulating the mount)
     Command::new("docker")
-        .args(&[
+        .args([
             "cp",
             &format!("{}/.claude", claude_mount.display()),
-            &format!("crowdcontrol-{}:/mnt/claude-config/", agent_name),
+            &format!("{}:/mnt/claude-config/", config.container_name(agent_name)),
         ])
         .output()?;
 
@@ -253,9 +276,9 @@ async fn test_refresh_script_with_file_based_credentials() -> Result<()> {
 
     // Test: Run refresh script with file-based credentials
     let output = Command::new("docker")
-        .args(&[
+        .args([
             "exec",
-            &format!("crowdcontrol-{}", agent_name),
+            &config.container_name(agent_name),
             "refresh-claude-auth.sh",
         ])
         .output()?;
@@ -278,9 +301,9 @@ async fn test_refresh_script_with_file_based_credentials() -> Result<()> {
 
         // Test: Verify credentials were copied
         let output = Command::new("docker")
-            .args(&[
+            .args([
                 "exec",
-                &format!("crowdcontrol-{}", agent_name),
+                &config.container_name(agent_name),
                 "cat",
                 "/home/developer/.claude/.credentials.json",
             ])
@@ -315,6 +338,14 @@ async fn test_refresh_script_claude_json_transformations() -> Result<()> {
         verbose: 0,
         default_memory: None,
         default_cpus: None,
+        default_platform: None,
+        mount_claude_config: true,
+        connect_default_command: None,
+        container_prefix: "crowdcontrol".to_string(),
+        require_crowdcontrol: false,
+        keychain_service: "Claude Code-credentials".to_string(),
+        workspace_layout: WorkspaceLayout::Flat,
+        connect_auto_start: false,
     };
 
     let docker = DockerClient::new(config.clone())?;
@@ -343,9 +374,9 @@ async fn test_refresh_script_claude_json_transformations() -> Result<()> {
     }"#;
 
     let output = Command::new("docker")
-        .args(&[
+        .args([
             "exec",
-            &format!("crowdcontrol-{}", agent_name),
+            &config.container_name(agent_name),
             "sh",
             "-c",
             &format!(
@@ -362,9 +393,9 @@ async fn test_refresh_script_claude_json_transformations() -> Result<()> {
 
     // Test: Run refresh script to apply transformations
     let output = Command::new("docker")
-        .args(&[
+        .args([
             "exec",
-            &format!("crowdcontrol-{}", agent_name),
+            &config.container_name(agent_name),
             "refresh-claude-auth.sh",
         ])
         .output()?;
@@ -386,9 +417,9 @@ async fn test_refresh_script_claude_json_transformations() -> Result<()> {
 
         // Test: Verify transformations were applied
         let output = Command::new("docker")
-            .args(&[
+            .args([
                 "exec",
-                &format!("crowdcontrol-{}", agent_name),
+                &config.container_name(agent_name),
                 "cat",
                 "/home/developer/.claude.json",
             ])
@@ -437,6 +468,14 @@ async fn test_refresh_script_error_handling() -> Result<()> {
         verbose: 0,
         default_memory: None,
         default_cpus: None,
+        default_platform: None,
+        mount_claude_config: true,
+        connect_default_command: None,
+        container_prefix: "crowdcontrol".to_string(),
+        require_crowdcontrol: false,
+        keychain_service: "Claude Code-credentials".to_string(),
+        workspace_layout: WorkspaceLayout::Flat,
+        connect_auto_start: false,
     };
 
     let docker = DockerClient::new(config.clone())?;
@@ -454,9 +493,9 @@ async fn test_refresh_script_error_handling() -> Result<()> {
 
     // Test: Empty credentials parameter
     let output = Command::new("docker")
-        .args(&[
+        .args([
             "exec",
-            &format!("crowdcontrol-{}", agent_name),
+            &config.container_name(agent_name),
             "refresh-claude-auth.sh",
             "",
         ])
@@ -470,9 +509,9 @@ async fn test_refresh_script_error_handling() -> Result<()> {
 
     // Test: Invalid JSON as credentials parameter
     let output = Command::new("docker")
-        .args(&[
+        .args([
             "exec",
-            &format!("crowdcontrol-{}", agent_name),
+            &config.container_name(agent_name),
             "refresh-claude-auth.sh",
             "invalid-json",
         ])
@@ -489,9 +528,9 @@ async fn test_refresh_script_error_handling() -> Result<()> {
 
         // Verify the invalid content was written
         let output = Command::new("docker")
-            .args(&[
+            .args([
                 "exec",
-                &format!("crowdcontrol-{}", agent_name),
+                &config.container_name(agent_name),
                 "cat",
                 "/home/developer/.claude/.credentials.json",
             ])
@@ -510,9 +549,9 @@ async fn test_refresh_script_error_handling() -> Result<()> {
     // Test: Very long credentials string
     let long_creds = "x".repeat(10000);
     let output = Command::new("docker")
-        .args(&[
+        .args([
             "exec",
-            &format!("crowdcontrol-{}", agent_name),
+            &config.container_name(agent_name),
             "refresh-claude-auth.sh",
             &long_creds,
         ])
@@ -527,9 +566,9 @@ async fn test_refresh_script_error_handling() -> Result<()> {
     // Test: Credentials with special characters
     let special_creds = r#"{"key": "test with spaces & symbols!@#$%^&*()"}"#;
     let output = Command::new("docker")
-        .args(&[
+        .args([
             "exec",
-            &format!("crowdcontrol-{}", agent_name),
+            &config.container_name(agent_name),
             "refresh-claude-auth.sh",
             special_creds,
         ])
@@ -557,6 +596,14 @@ async fn test_refresh_script_directory_creation() -> Result<()> {
         verbose: 0,
         default_memory: None,
         default_cpus: None,
+        default_platform: None,
+        mount_claude_config: true,
+        connect_default_command: None,
+        container_prefix: "crowdcontrol".to_string(),
+        require_crowdcontrol: false,
+        keychain_service: "Claude Code-credentials".to_string(),
+        workspace_layout: WorkspaceLayout::Flat,
+        connect_auto_start: false,
     };
 
     let docker = DockerClient::new(config.clone())?;
@@ -574,9 +621,9 @@ async fn test_refresh_script_directory_creation() -> Result<()> {
 
     // Test: Ensure .claude directory doesn't exist initially
     let output = Command::new("docker")
-        .args(&[
+        .args([
             "exec",
-            &format!("crowdcontrol-{}", agent_name),
+            &config.container_name(agent_name),
             "ls",
             "/home/developer/.claude",
         ])
@@ -591,9 +638,9 @@ async fn test_refresh_script_directory_creation() -> Result<()> {
     // Test: Run refresh script with credentials to create directory
     let mock_creds = r#"{"api_key": "test-key"}"#;
     let output = Command::new("docker")
-        .args(&[
+        .args([
             "exec",
-            &format!("crowdcontrol-{}", agent_name),
+            &config.container_name(agent_name),
             "refresh-claude-auth.sh",
             mock_creds,
         ])
@@ -606,9 +653,9 @@ async fn test_refresh_script_directory_creation() -> Result<()> {
 
     // Test: Verify directory was created
     let output = Command::new("docker")
-        .args(&[
+        .args([
             "exec",
-            &format!("crowdcontrol-{}", agent_name),
+            &config.container_name(agent_name),
             "ls",
             "-la",
             "/home/developer/.claude",
@@ -627,9 +674,9 @@ async fn test_refresh_script_directory_creation() -> Result<()> {
 
     // Test: Verify directory permissions
     let output = Command::new("docker")
-        .args(&[
+        .args([
             "exec",
-            &format!("crowdcontrol-{}", agent_name),
+            &config.container_name(agent_name),
             "stat",
             "-c",
             "%a",
@@ -0,0 +1,112 @@
+use crowdcontrol_core::RepoConfig;
+use std::fs;
+use tempfile::TempDir;
+
+#[test]
+fn test_repo_config_defaults_when_missing() {
+    let workspace = TempDir::new().unwrap();
+
+    let repo_config = RepoConfig::load(workspace.path()).unwrap();
+    assert!(repo_config.healthcheck.is_none());
+}
+
+#[test]
+fn test_repo_config_loads_healthcheck() {
+    let workspace = TempDir::new().unwrap();
+    let crowdcontrol_dir = workspace.path().join(".crowdcontrol");
+    fs::create_dir_all(&crowdcontrol_dir).unwrap();
+    fs::write(
+        crowdcontrol_dir.join("config.toml"),
+        r#"healthcheck = "curl -f http://localhost:3000/health""#,
+    )
+    .unwrap();
+
+    let repo_config = RepoConfig::load(workspace.path()).unwrap();
+    assert_eq!(
+        repo_config.healthcheck.as_deref(),
+        Some("curl -f http://localhost:3000/health")
+    );
+}
+
+#[test]
+fn test_repo_config_loads_lifecycle_commands() {
+    let workspace = TempDir::new().unwrap();
+    let crowdcontrol_dir = workspace.path().join(".crowdcontrol");
+    fs::create_dir_all(&crowdcontrol_dir).unwrap();
+    fs::write(
+        crowdcontrol_dir.join("config.toml"),
+        "post_create_command = \"npm install\"\npost_start_command = \"npm run dev &\"\n",
+    )
+    .unwrap();
+
+    let repo_config = RepoConfig::load(workspace.path()).unwrap();
+    assert_eq!(
+        repo_config.post_create_command.as_deref(),
+        Some("npm install")
+    );
+    assert_eq!(
+        repo_config.post_start_command.as_deref(),
+        Some("npm run dev &")
+    );
+    assert!(repo_config.has_lifecycle_commands());
+}
+
+#[test]
+fn test_repo_config_reads_devcontainer_commands() {
+    let workspace = TempDir::new().unwrap();
+    let devcontainer_dir = workspace.path().join(".devcontainer");
+    fs::create_dir_all(&devcontainer_dir).unwrap();
+    fs::write(
+        devcontainer_dir.join("devcontainer.json"),
+        r#"{
+            "image": "mcr.microsoft.com/devcontainers/base",
+            "postCreateCommand": "make deps",
+            "postStartCommand": ["echo", "it's up"]
+        }"#,
+    )
+    .unwrap();
+
+    let repo_config = RepoConfig::load(workspace.path()).unwrap();
+    assert_eq!(
+        repo_config.post_create_command.as_deref(),
+        Some("make deps")
+    );
+    assert_eq!(
+        repo_config.post_start_command.as_deref(),
+        Some(r"'echo' 'it'\''s up'")
+    );
+}
+
+#[test]
+fn test_repo_config_prefers_config_toml_over_devcontainer() {
+    let workspace = TempDir::new().unwrap();
+    let crowdcontrol_dir = workspace.path().join(".crowdcontrol");
+    fs::create_dir_all(&crowdcontrol_dir).unwrap();
+    fs::write(
+        crowdcontrol_dir.join("config.toml"),
+        r#"post_create_command = "npm ci""#,
+    )
+    .unwrap();
+    fs::write(
+        workspace.path().join(".devcontainer.json"),
+        r#"{"postCreateCommand": "npm install", "postStartCommand": "npm start"}"#,
+    )
+    .unwrap();
+
+    let repo_config = RepoConfig::load(workspace.path()).unwrap();
+    assert_eq!(repo_config.post_create_command.as_deref(), Some("npm ci"));
+    assert_eq!(repo_config.post_start_command.as_deref(), Some("npm start"));
+}
+
+#[test]
+fn test_repo_config_ignores_unparseable_devcontainer() {
+    let workspace = TempDir::new().unwrap();
+    fs::write(
+        workspace.path().join(".devcontainer.json"),
+        "{\n  // comments aren't JSON\n  \"postCreateCommand\": \"make\"\n}",
+    )
+    .unwrap();
+
+    let repo_config = RepoConfig::load(workspace.path()).unwrap();
+    assert!(!repo_config.has_lifecycle_commands());
+}
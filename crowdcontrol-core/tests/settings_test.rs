@@ -0,0 +1,293 @@
+use crowdcontrol_core::config::DEFAULT_CONNECT_COMMAND;
+use crowdcontrol_core::settings::find_config_files;
+use crowdcontrol_core::{Config, Settings, WorkspaceLayout};
+use std::fs;
+use std::path::PathBuf;
+use tempfile::tempdir;
+
+#[test]
+fn test_load_settings_from_yaml() {
+    let config_dir = tempdir().unwrap();
+    fs::write(
+        config_dir.path().join("config.yaml"),
+        "workspaces_dir: /tmp/yaml-workspaces\nimage: from-yaml:latest\ndefault_memory: 2g\n",
+    )
+    .unwrap();
+
+    let settings = Settings::load_from_dir(Some(config_dir.path())).unwrap();
+    assert_eq!(
+        settings.workspaces_dir,
+        PathBuf::from("/tmp/yaml-workspaces")
+    );
+    assert_eq!(settings.image, "from-yaml:latest");
+    assert_eq!(settings.default_memory.as_deref(), Some("2g"));
+}
+
+#[test]
+fn test_load_settings_from_json() {
+    let config_dir = tempdir().unwrap();
+    fs::write(
+        config_dir.path().join("config.json"),
+        r#"{"image": "from-json:latest", "default_cpus": "1.5"}"#,
+    )
+    .unwrap();
+
+    let settings = Settings::load_from_dir(Some(config_dir.path())).unwrap();
+    assert_eq!(settings.image, "from-json:latest");
+    assert_eq!(settings.default_cpus.as_deref(), Some("1.5"));
+}
+
+#[test]
+fn test_load_settings_from_explicit_file() {
+    let config_dir = tempdir().unwrap();
+    // Files in the same directory are not searched
+    fs::write(
+        config_dir.path().join("config.yaml"),
+        "default_memory: 8g\n",
+    )
+    .unwrap();
+    let path = config_dir.path().join("my.toml");
+    fs::write(&path, "image = \"explicit:latest\"\ndefault_cpus = \"3\"\n").unwrap();
+
+    let settings = Settings::load_from_file(&path).unwrap();
+    assert_eq!(settings.image, "explicit:latest");
+    assert_eq!(settings.default_cpus.as_deref(), Some("3"));
+    assert_eq!(settings.default_memory, None);
+
+    let missing = config_dir.path().join("missing.toml");
+    let err = Settings::load_from_file(&missing).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!("Config file {} does not exist", missing.display())
+    );
+}
+
+#[test]
+fn test_config_file_precedence() {
+    let config_dir = tempdir().unwrap();
+    fs::write(config_dir.path().join("config.json"), "{}").unwrap();
+    fs::write(config_dir.path().join("config.toml"), "").unwrap();
+    fs::write(config_dir.path().join("config.yaml"), "").unwrap();
+
+    let names: Vec<String> = find_config_files(config_dir.path())
+        .iter()
+        .map(|path| path.file_name().unwrap().to_string_lossy().to_string())
+        .collect();
+    assert_eq!(names, vec!["config.toml", "config.yaml", "config.json"]);
+}
+
+#[test]
+fn test_multiple_config_files_are_merged() {
+    let config_dir = tempdir().unwrap();
+    fs::write(
+        config_dir.path().join("config.toml"),
+        "image = \"shared:latest\"\n",
+    )
+    .unwrap();
+    fs::write(
+        config_dir.path().join("config.yaml"),
+        "image: shared:latest\ndefault_memory: 4g\n",
+    )
+    .unwrap();
+
+    let settings = Settings::load_from_dir(Some(config_dir.path())).unwrap();
+    assert_eq!(settings.image, "shared:latest");
+    assert_eq!(settings.default_memory.as_deref(), Some("4g"));
+}
+
+#[test]
+fn test_conflicting_config_files_are_rejected() {
+    let config_dir = tempdir().unwrap();
+    fs::write(
+        config_dir.path().join("config.toml"),
+        "image = \"from-toml:latest\"\n",
+    )
+    .unwrap();
+    fs::write(
+        config_dir.path().join("config.yaml"),
+        "image: from-yaml:latest\n",
+    )
+    .unwrap();
+
+    let err = Settings::load_from_dir(Some(config_dir.path())).unwrap_err();
+    let message = err.to_string();
+    assert!(message.contains("Conflicting values for 'image'"));
+    assert!(message.contains("config.toml"));
+    assert!(message.contains("config.yaml"));
+}
+
+#[test]
+fn test_mount_claude_config_setting() {
+    let config_dir = tempdir().unwrap();
+    let settings = Settings::load_from_dir(Some(config_dir.path())).unwrap();
+    assert!(settings.mount_claude_config);
+
+    fs::write(
+        config_dir.path().join("config.toml"),
+        "mount_claude_config = false\n",
+    )
+    .unwrap();
+    let settings = Settings::load_from_dir(Some(config_dir.path())).unwrap();
+    assert!(!settings.mount_claude_config);
+}
+
+#[test]
+fn test_connect_command_resolution() {
+    let config_dir = tempdir().unwrap();
+    let settings = Settings {
+        workspaces_dir: config_dir.path().join("workspaces"),
+        ..Settings::default()
+    };
+    let mut config = Config::from_settings(settings).unwrap();
+
+    // The built-in default, unless --command is given
+    assert_eq!(
+        config.connect_command(None),
+        DEFAULT_CONNECT_COMMAND.split(' ').collect::<Vec<_>>()
+    );
+    assert_eq!(config.connect_command(Some("npm test")), ["npm", "test"]);
+
+    // A configured default replaces the built-in one, but not --command
+    config.connect_default_command = Some("claude --model opus".to_string());
+    assert_eq!(config.connect_command(None), ["claude", "--model", "opus"]);
+    assert_eq!(config.connect_command(Some("bash")), ["bash"]);
+}
+
+#[test]
+fn test_load_connect_default_command_from_file() {
+    let config_dir = tempdir().unwrap();
+    fs::write(
+        config_dir.path().join("config.toml"),
+        "connect_default_command = \"./scripts/agent.sh\"\n",
+    )
+    .unwrap();
+
+    let settings = Settings::load_from_dir(Some(config_dir.path())).unwrap();
+    assert_eq!(
+        settings.connect_default_command.as_deref(),
+        Some("./scripts/agent.sh")
+    );
+}
+
+#[test]
+fn test_container_prefix_setting() {
+    let config_dir = tempdir().unwrap();
+    assert_eq!(Settings::default().container_prefix, "crowdcontrol");
+
+    fs::write(
+        config_dir.path().join("config.toml"),
+        format!(
+            "container_prefix = \"alice\"\nworkspaces_dir = {:?}\n",
+            config_dir.path().join("workspaces")
+        ),
+    )
+    .unwrap();
+    let settings = Settings::load_from_dir(Some(config_dir.path())).unwrap();
+    assert_eq!(settings.container_prefix, "alice");
+
+    let config = Config::from_settings(settings).unwrap();
+    assert_eq!(config.container_name("web"), "alice-web");
+    assert_eq!(
+        config.agent_name_from_container("/alice-web").as_deref(),
+        Some("web")
+    );
+    assert_eq!(config.agent_name_from_container("/crowdcontrol-web"), None);
+}
+
+#[test]
+fn test_invalid_container_prefix_is_rejected() {
+    let config_dir = tempdir().unwrap();
+    for prefix in ["", "-team", "my team", "team/a"] {
+        let settings = Settings {
+            workspaces_dir: config_dir.path().join("workspaces"),
+            container_prefix: prefix.to_string(),
+            ..Settings::default()
+        };
+        let error = Config::from_settings(settings).unwrap_err();
+        assert!(
+            error.to_string().contains("Invalid container_prefix"),
+            "{}",
+            error
+        );
+    }
+}
+
+#[test]
+fn test_require_crowdcontrol_setting() {
+    let config_dir = tempdir().unwrap();
+    assert!(!Settings::default().require_crowdcontrol);
+
+    fs::write(
+        config_dir.path().join("config.toml"),
+        format!(
+            "require_crowdcontrol = true\nworkspaces_dir = {:?}\n",
+            config_dir.path().join("workspaces")
+        ),
+    )
+    .unwrap();
+    let settings = Settings::load_from_dir(Some(config_dir.path())).unwrap();
+    assert!(settings.require_crowdcontrol);
+    assert!(
+        Config::from_settings(settings)
+            .unwrap()
+            .require_crowdcontrol
+    );
+}
+
+#[test]
+fn test_keychain_service_setting() {
+    let config_dir = tempdir().unwrap();
+    assert_eq!(
+        Settings::default().keychain_service,
+        crowdcontrol_core::DEFAULT_KEYCHAIN_SERVICE
+    );
+
+    fs::write(
+        config_dir.path().join("config.toml"),
+        format!(
+            "keychain_service = \"Acme Claude\"\nworkspaces_dir = {:?}\n",
+            config_dir.path().join("workspaces")
+        ),
+    )
+    .unwrap();
+    let settings = Settings::load_from_dir(Some(config_dir.path())).unwrap();
+    assert_eq!(settings.keychain_service, "Acme Claude");
+}
+
+#[test]
+fn test_workspace_layout_setting() {
+    let config_dir = tempdir().unwrap();
+    let workspaces_dir = config_dir.path().join("workspaces");
+    assert_eq!(Settings::default().workspace_layout, WorkspaceLayout::Flat);
+
+    fs::write(
+        config_dir.path().join("config.toml"),
+        format!(
+            "workspace_layout = \"nested\"\nworkspaces_dir = {:?}\n",
+            workspaces_dir
+        ),
+    )
+    .unwrap();
+    let settings = Settings::load_from_dir(Some(config_dir.path())).unwrap();
+    assert_eq!(settings.workspace_layout, WorkspaceLayout::Nested);
+
+    let config = Config::from_settings(settings).unwrap();
+    assert_eq!(
+        config.agent_repo_path("api"),
+        workspaces_dir.join("api").join("api")
+    );
+}
+
+#[test]
+fn test_connect_auto_start_setting() {
+    let config_dir = tempdir().unwrap();
+    assert!(!Settings::default().connect_auto_start);
+
+    fs::write(
+        config_dir.path().join("config.toml"),
+        "connect_auto_start = true\n",
+    )
+    .unwrap();
+    let settings = Settings::load_from_dir(Some(config_dir.path())).unwrap();
+    assert!(settings.connect_auto_start);
+}
@@ -1,6 +1,9 @@
 use chrono::Utc;
 use crowdcontrol_core::agent::{save_agent_metadata, update_agent_metadata};
-use crowdcontrol_core::{Agent, AgentStatus, Config, StateInconsistency, StateValidator};
+use crowdcontrol_core::{
+    Agent, AgentStatus, Config, DockerClient, RepairOptions, StateInconsistency, StateValidator,
+    WorkspaceLayout,
+};
 use std::fs;
 use std::path::PathBuf;
 use tempfile::tempdir;
@@ -12,7 +15,15 @@ fn create_test_config() -> (Config, tempfile::TempDir) {
         image: "test:latest".to_string(),
         default_memory: None,
         default_cpus: None,
+        default_platform: None,
         verbose: 0,
+        mount_claude_config: true,
+        connect_default_command: None,
+        container_prefix: "crowdcontrol".to_string(),
+        require_crowdcontrol: false,
+        keychain_service: "Claude Code-credentials".to_string(),
+        workspace_layout: WorkspaceLayout::Flat,
+        connect_auto_start: false,
     };
     (config, temp_dir)
 }
@@ -32,6 +43,7 @@ fn create_test_agent(name: &str, status: AgentStatus) -> Agent {
         branch: Some("main".to_string()),
         created_at: Utc::now(),
         workspace_path: PathBuf::from("/test/workspace"),
+        ..Default::default()
     }
 }
 
@@ -160,3 +172,92 @@ async fn test_metadata_container_id_persistence() {
     assert_eq!(updated_agent.status, AgentStatus::Created);
     assert_eq!(updated_agent.container_id, None);
 }
+
+#[tokio::test]
+#[ignore = "requires Docker"]
+async fn test_repair_recreates_missing_container() {
+    let (mut config, _temp_dir) = create_test_config();
+    config.image = "crowdcontrol:latest".to_string();
+    let agent_name = "recreate-test";
+
+    let mut agent = create_test_agent(agent_name, AgentStatus::Stopped);
+    agent.workspace_path = config.agent_workspace_path(agent_name);
+    agent.container_id = Some("removed-container-id".to_string());
+    agent.memory = Some("512m".to_string());
+    fs::create_dir_all(&agent.workspace_path).unwrap();
+    save_agent_metadata(&config, &agent).unwrap();
+
+    let validator = StateValidator::new(config.clone()).unwrap();
+    let issues = validator.validate_all().await.unwrap();
+    assert!(issues.iter().any(|issue| matches!(
+        issue,
+        StateInconsistency::MissingContainer { agent_name: name } if name == agent_name
+    )));
+
+    let options = RepairOptions {
+        recreate_containers: true,
+        ..Default::default()
+    };
+    validator
+        .repair_inconsistencies(issues, &options)
+        .await
+        .unwrap();
+
+    let repaired = crowdcontrol_core::agent::load_agent_metadata(&config, agent_name).unwrap();
+    let container_id = repaired.container_id.expect("container ID recorded");
+    assert_ne!(container_id, "removed-container-id");
+
+    let docker = DockerClient::new(config.clone()).unwrap();
+    let exists = docker
+        .container_exists(&config.container_name(agent_name))
+        .await
+        .unwrap();
+
+    // Cleanup
+    docker.remove_container(&container_id).await.unwrap();
+
+    assert!(exists);
+}
+
+#[tokio::test]
+#[ignore = "requires Docker"]
+async fn test_repair_prunes_orphaned_container() {
+    let (mut config, _temp_dir) = create_test_config();
+    config.image = "crowdcontrol:latest".to_string();
+    let container_name = "prune-orphan-test";
+
+    // A container with no agent metadata behind it
+    let docker = DockerClient::new(config.clone()).unwrap();
+    let workspace_path = config.workspaces_dir.join("scratch");
+    fs::create_dir_all(&workspace_path).unwrap();
+    let container_id = docker
+        .create_container(container_name, &workspace_path, None, None)
+        .await
+        .unwrap();
+
+    let validator = StateValidator::new(config.clone()).unwrap();
+    let issues = validator.validate_all().await.unwrap();
+    assert!(issues.iter().any(|issue| matches!(
+        issue,
+        StateInconsistency::OrphanedContainer { container_name: name, .. } if name == container_name
+    )));
+
+    let options = RepairOptions {
+        prune_orphans: true,
+        ..Default::default()
+    };
+    validator
+        .repair_inconsistencies(issues, &options)
+        .await
+        .unwrap();
+
+    let exists = docker
+        .container_exists(&config.container_name(container_name))
+        .await
+        .unwrap();
+    if exists {
+        docker.remove_container(&container_id).await.unwrap();
+    }
+
+    assert!(!exists, "orphaned container should have been removed");
+}
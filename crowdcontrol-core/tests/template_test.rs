@@ -0,0 +1,81 @@
+use crowdcontrol_core::{find_template, load_templates, Template};
+use std::fs;
+use tempfile::TempDir;
+
+const TEMPLATES: &str = r#"
+[backend]
+memory = "4g"
+cpus = "2"
+tags = ["backend"]
+env = { RUST_LOG = "debug", DATABASE_URL = "postgres://localhost/dev" }
+
+[frontend]
+memory = "2g"
+"#;
+
+fn write_templates(contents: &str) -> (TempDir, std::path::PathBuf) {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("templates.toml");
+    fs::write(&path, contents).unwrap();
+    (dir, path)
+}
+
+#[test]
+fn test_missing_templates_file_defines_none() {
+    let dir = TempDir::new().unwrap();
+    let templates = load_templates(&dir.path().join("templates.toml")).unwrap();
+    assert!(templates.is_empty());
+}
+
+#[test]
+fn test_template_memory_and_env_are_applied() {
+    let (_dir, path) = write_templates(TEMPLATES);
+    let template = find_template(&path, "backend").unwrap();
+
+    // Nothing given on the command line
+    let resolved = template.with_overrides(&Template::default());
+    assert_eq!(resolved.memory.as_deref(), Some("4g"));
+    assert_eq!(resolved.cpus.as_deref(), Some("2"));
+    assert_eq!(resolved.tags, vec!["backend"]);
+    // Environment variable names keep their case
+    assert_eq!(
+        resolved.env_vars(),
+        vec!["DATABASE_URL=postgres://localhost/dev", "RUST_LOG=debug"]
+    );
+}
+
+#[test]
+fn test_cli_memory_wins_over_template() {
+    let (_dir, path) = write_templates(TEMPLATES);
+    let template = find_template(&path, "backend").unwrap();
+
+    let cli = Template {
+        memory: Some("8g".to_string()),
+        tags: vec!["urgent".to_string(), "backend".to_string()],
+        ..Default::default()
+    };
+    let resolved = template.with_overrides(&cli);
+    assert_eq!(resolved.memory.as_deref(), Some("8g"));
+    assert_eq!(resolved.cpus.as_deref(), Some("2"));
+    // Tags are combined rather than replaced
+    assert_eq!(resolved.tags, vec!["backend", "urgent"]);
+}
+
+#[test]
+fn test_unknown_template_lists_available() {
+    let (_dir, path) = write_templates(TEMPLATES);
+    let err = find_template(&path, "mobile").unwrap_err().to_string();
+    assert!(err.contains("Unknown template 'mobile'"), "{}", err);
+    assert!(err.contains("backend, frontend"), "{}", err);
+}
+
+#[test]
+fn test_invalid_template_values_are_rejected() {
+    let (_dir, path) = write_templates("[broken]\nmemory = \"lots\"\n");
+    let err = find_template(&path, "broken").unwrap_err();
+    assert!(format!("{:#}", err).contains("Invalid template 'broken'"));
+
+    // Typos in option names aren't silently ignored
+    let (_dir, path) = write_templates("[typo]\nmemroy = \"4g\"\n");
+    assert!(load_templates(&path).is_err());
+}
@@ -0,0 +1,75 @@
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use crowdcontrol_core::{parse_duration, parse_time_spec};
+
+fn now() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 6, 1, 12, 0, 0).unwrap()
+}
+
+#[test]
+fn test_parse_relative_durations() {
+    assert_eq!(
+        parse_time_spec("30s", now()).unwrap(),
+        now() - Duration::seconds(30)
+    );
+    assert_eq!(
+        parse_time_spec("10m", now()).unwrap(),
+        now() - Duration::minutes(10)
+    );
+    assert_eq!(
+        parse_time_spec("2h", now()).unwrap(),
+        now() - Duration::hours(2)
+    );
+    assert_eq!(
+        parse_time_spec("1d", now()).unwrap(),
+        now() - Duration::days(1)
+    );
+}
+
+#[test]
+fn test_parse_rfc3339_times() {
+    assert_eq!(
+        parse_time_spec("2024-06-01T09:30:00Z", now()).unwrap(),
+        Utc.with_ymd_and_hms(2024, 6, 1, 9, 30, 0).unwrap()
+    );
+
+    // Offsets are normalised to UTC
+    assert_eq!(
+        parse_time_spec("2024-06-01T11:30:00+02:00", now()).unwrap(),
+        Utc.with_ymd_and_hms(2024, 6, 1, 9, 30, 0).unwrap()
+    );
+}
+
+#[test]
+fn test_parse_rejects_invalid_specs() {
+    for spec in [
+        "",
+        "10",
+        "m",
+        "10x",
+        "-5m",
+        "1.5h",
+        "yesterday",
+        "2024-06-01",
+    ] {
+        let err = parse_time_spec(spec, now()).unwrap_err();
+        assert!(
+            err.to_string().contains("Invalid time"),
+            "unexpected error for {:?}: {}",
+            spec,
+            err
+        );
+    }
+}
+
+#[test]
+fn test_parse_duration() {
+    assert_eq!(parse_duration("45s").unwrap(), Duration::seconds(45));
+    assert_eq!(parse_duration("30m").unwrap(), Duration::minutes(30));
+    assert_eq!(parse_duration("4h").unwrap(), Duration::hours(4));
+    assert_eq!(parse_duration("7d").unwrap(), Duration::days(7));
+
+    for spec in ["", "30", "h", "2w", "-1h", "1.5h"] {
+        let err = parse_duration(spec).unwrap_err();
+        assert!(err.to_string().contains("Invalid duration"));
+    }
+}